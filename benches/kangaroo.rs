@@ -1,19 +1,22 @@
-#![feature(test)]
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use tiny_keccak::{Hasher, KangarooTwelve};
 
-extern crate test;
-
-use test::Bencher;
-use tiny_keccak::{KangarooTwelve, Hasher};
-
-#[bench]
-fn bench_k12(b: &mut Bencher) {
+fn k12(c: &mut Criterion) {
     let data = [0u8; 32];
-    b.bytes = data.len() as u64;
 
-    b.iter(|| {
-        let mut res = [0u8; 32];
-        let mut k12 = KangarooTwelve::new(&[]);
-        k12.update(&data);
-        k12.finalize(&mut res);
+    let mut group = c.benchmark_group("k12");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function(format!("input_{}_bytes", data.len()), |b| {
+        b.iter(|| {
+            let mut res = [0u8; 32];
+            let mut k12 = KangarooTwelve::new(&[]);
+            k12.update(black_box(&data));
+            k12.finalize(&mut res);
+            res
+        })
     });
+    group.finish();
 }
+
+criterion_group!(benches, k12);
+criterion_main!(benches);
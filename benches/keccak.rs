@@ -1,43 +1,216 @@
-#![feature(test)]
+//! Run with `cargo bench --features keccak`, add `avx2` or `neon` to the features to benchmark
+//! the SIMD permutations, `unroll` for the unrolled rounds or `bit-interleaving` for the bit
+//! interleaved permutation of 32-bit targets. The backend is part of the group names, so criterion
+//! keeps separate baselines for each of them.
 
-extern crate test;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use tiny_keccak::{
+    bits_to_rate, keccak256_32, keccak256_64, keccak256_pair, keccak256_x4, keccakf, Hasher,
+    IntoXof, Keccak, KeccakState, Permutation, Xof,
+};
 
-use test::Bencher;
-use tiny_keccak::{keccakf, Keccak, Hasher};
+const WORDS: usize = 25;
 
-#[bench]
-fn bench_keccak_256_input_4096_bytes(b: &mut Bencher) {
-    let data = [254u8; 4096];
-    b.bytes = data.len() as u64;
+const BACKEND: &str = if cfg!(all(feature = "avx2", target_arch = "x86_64")) {
+    "avx2"
+} else if cfg!(all(feature = "neon", target_arch = "aarch64")) {
+    "neon"
+} else if cfg!(all(
+    feature = "bit-interleaving",
+    target_pointer_width = "32"
+)) {
+    "interleaved"
+} else if cfg!(feature = "unroll") {
+    "unrolled"
+} else {
+    "scalar"
+};
 
-    b.iter(|| {
-        let mut res: [u8; 32] = [0; 32];
-        let mut keccak = Keccak::v256();
-        keccak.update(&data);
-        keccak.finalize(&mut res);
+fn keccak256(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("keccak256/{}", BACKEND));
+    for size in [0, 64, 1024, 64 * 1024, 1024 * 1024].iter() {
+        let data = vec![254u8; *size];
+        group.throughput(Throughput::Bytes(*size as u64));
+        group.bench_function(format!("input_{}_bytes", size), |b| {
+            b.iter(|| {
+                let mut res: [u8; 32] = [0; 32];
+                let mut keccak = Keccak::v256();
+                keccak.update(black_box(&data));
+                keccak.finalize(&mut res);
+                res
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Leaves the state untouched, so `absorb` measures only the cost of XORing the input in.
+struct Identity;
+
+impl Permutation for Identity {
+    fn execute(state: &mut [u64; WORDS]) {
+        black_box(state);
+    }
+}
+
+fn absorb(c: &mut Criterion) {
+    let data = vec![254u8; 1024 * 1024];
+
+    let mut group = c.benchmark_group("absorb");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function(format!("input_{}_bytes", data.len()), |b| {
+        b.iter(|| {
+            let mut state = KeccakState::<Identity>::with_rate(bits_to_rate(256), 0x01);
+            state.update(black_box(&data));
+            state
+        })
     });
+    group.finish();
 }
 
-#[bench]
-fn keccakf_u64(b: &mut Bencher) {
-    const WORDS: usize = 25;
-    b.bytes = (WORDS * 8) as u64;
+fn small_updates(c: &mut Criterion) {
+    // 10,000 fields of 1 to 8 bytes, like the fields of a protocol message
+    let data = [0xa5u8; 8];
+    let sizes: Vec<usize> = (0..10_000).map(|i| 1 + i * 7 % 8).collect();
+    let total = sizes.iter().sum::<usize>();
+    let concatenated = vec![0xa5u8; total];
+
+    let mut group = c.benchmark_group(format!("keccak256_small_updates/{}", BACKEND));
+    group.throughput(Throughput::Bytes(total as u64));
+    group.bench_function("small_updates", |b| {
+        b.iter(|| {
+            let mut keccak = Keccak::v256();
+            for size in sizes.iter() {
+                keccak.update(black_box(&data[..*size]));
+            }
+            keccak.finalize_array::<32>()
+        })
+    });
+    group.bench_function("one_update", |b| {
+        b.iter(|| {
+            let mut keccak = Keccak::v256();
+            keccak.update(black_box(&concatenated));
+            keccak.finalize_array::<32>()
+        })
+    });
+    group.finish();
+}
 
-    b.iter(|| {
+fn permutation(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("keccakf/{}", BACKEND));
+    group.throughput(Throughput::Bytes((WORDS * 8) as u64));
+    group.bench_function("keccakf_u64", |b| {
         let mut data = [0u64; WORDS];
-        keccakf(&mut data);
+        b.iter(|| keccakf(black_box(&mut data)))
+    });
+    group.finish();
+}
+
+fn xof(c: &mut Criterion) {
+    let mut output = vec![0u8; 1024 * 1024];
+
+    let mut group = c.benchmark_group(format!("keccak256_xof/{}", BACKEND));
+    group.throughput(Throughput::Bytes(output.len() as u64));
+    group.bench_function(format!("output_{}_bytes", output.len()), |b| {
+        b.iter(|| {
+            let mut xof = Keccak::v256().into_xof();
+            xof.squeeze(black_box(&mut output));
+        })
     });
+    group.finish();
 }
 
-#[bench]
-fn bench_keccak256(b: &mut Bencher) {
-    let data = [0u8; 32];
-    b.bytes = data.len() as u64;
+fn leaves(c: &mut Criterion) {
+    let data = [0u8; 64];
 
-    b.iter(|| {
-        let mut res: [u8; 32] = [0; 32];
+    let mut group = c.benchmark_group(format!("keccak256_4_leaves/{}", BACKEND));
+    group.throughput(Throughput::Bytes(4 * data.len() as u64));
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            let mut res: [[u8; 32]; 4] = [[0; 32]; 4];
+            for res in res.iter_mut() {
+                let mut keccak = Keccak::v256();
+                keccak.update(black_box(&data));
+                keccak.finalize(res);
+            }
+            res
+        })
+    });
+    group.bench_function("x4", |b| {
+        b.iter(|| keccak256_x4(black_box([&data, &data, &data, &data])))
+    });
+    group.finish();
+}
+
+fn fixed_sizes(c: &mut Criterion) {
+    let data = [0xa5u8; 64];
+
+    let mut group = c.benchmark_group(format!("keccak256_fixed/{}", BACKEND));
+    group.bench_function("generic_32", |b| {
+        b.iter(|| tiny_keccak::keccak256(black_box(&data[..32])))
+    });
+    group.bench_function("keccak256_32", |b| {
+        b.iter(|| keccak256_32(black_box(&[0xa5; 32])))
+    });
+    group.bench_function("generic_64", |b| {
+        b.iter(|| tiny_keccak::keccak256(black_box(&data)))
+    });
+    group.bench_function("keccak256_64", |b| {
+        b.iter(|| keccak256_64(black_box(&data)))
+    });
+    group.bench_function("generic_pair", |b| {
+        b.iter(|| {
+            let mut keccak = Keccak::v256();
+            keccak.update(black_box(&[0xa5; 32]));
+            keccak.update(black_box(&[0x5a; 32]));
+            keccak.finalize_array::<32>()
+        })
+    });
+    group.bench_function("keccak256_pair", |b| {
+        b.iter(|| keccak256_pair(black_box(&[0xa5; 32]), black_box(&[0x5a; 32])))
+    });
+    group.finish();
+}
+
+fn checkpoint(c: &mut Criterion) {
+    // a proof-of-work style search: a fixed 1 KiB prefix followed by an 8 byte nonce
+    let prefix = [0xa5u8; 1024];
+
+    let mut group = c.benchmark_group(format!("keccak256_checkpoint/{}", BACKEND));
+    group.bench_function("reabsorb_prefix", |b| {
+        let mut nonce = 0u64;
+        b.iter(|| {
+            nonce += 1;
+            let mut keccak = Keccak::v256();
+            keccak.update(black_box(&prefix));
+            keccak.update(&nonce.to_le_bytes());
+            keccak.finalize_array::<32>()
+        })
+    });
+    group.bench_function("restore_checkpoint", |b| {
         let mut keccak = Keccak::v256();
-        keccak.update(&data);
-        keccak.finalize(&mut res);
+        keccak.update(&prefix);
+        let checkpoint = keccak.checkpoint();
+        let mut nonce = 0u64;
+        b.iter(|| {
+            nonce += 1;
+            keccak.restore_from(black_box(&checkpoint));
+            keccak.update(&nonce.to_le_bytes());
+            keccak.clone().finalize_array::<32>()
+        })
     });
+    group.finish();
 }
+
+criterion_group!(
+    benches,
+    keccak256,
+    absorb,
+    small_updates,
+    permutation,
+    xof,
+    leaves,
+    fixed_sizes,
+    checkpoint
+);
+criterion_main!(benches);
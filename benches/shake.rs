@@ -0,0 +1,21 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use tiny_keccak::{Hasher, Shake, Xof};
+
+fn squeeze(c: &mut Criterion) {
+    let mut output = vec![0u8; 16 * 1024 * 1024];
+
+    let mut group = c.benchmark_group("shake128_squeeze");
+    group.throughput(Throughput::Bytes(output.len() as u64));
+    group.sample_size(10);
+    group.bench_function(format!("output_{}_bytes", output.len()), |b| {
+        b.iter(|| {
+            let mut shake = Shake::v128();
+            shake.update(b"seed");
+            shake.squeeze(black_box(&mut output));
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, squeeze);
+criterion_main!(benches);
@@ -7,13 +7,23 @@
     feature = "tuple_hash",
     feature = "parallel_hash",
     feature = "k12",
+    feature = "kdf",
+    feature = "commitment",
     feature = "fips202",
-    feature = "sp800"
+    feature = "hmac",
+    feature = "keccak_mac",
+    feature = "stream_cipher",
+    feature = "sp800",
+    feature = "permutation",
+    feature = "turboshake",
+    feature = "duplex",
+    feature = "ethereum"
 )))]
 compile_error!(
     "You need to specify at least one hash function you intend to use. \
     Available options:\n\
-    keccak, shake, sha3, cshake, kmac, tuple_hash, parallel_hash, k12, fips202, sp800\n\
+    keccak, shake, sha3, cshake, kmac, tuple_hash, parallel_hash, k12, kdf, commitment, fips202, \
+    hmac, keccak_mac, stream_cipher, sp800, permutation, turboshake, duplex, ethereum\n\
     e.g.\n\
     tiny-keccak = { version = \"2.0.0\", features = [\"sha3\"] }"
 );
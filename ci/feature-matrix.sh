@@ -0,0 +1,15 @@
+#!/bin/sh
+# Builds and tests every algorithm feature on its own, with warnings denied, so a feature that
+# relies on a module gated behind another feature fails here. The features are the ones listed in
+# build.rs, a new algorithm has to be added there anyway.
+set -e
+
+cd "$(dirname "$0")/.."
+
+features=$(sed -n 's/^ *feature = "\([a-z0-9_]*\)",\{0,1\}$/\1/p' build.rs)
+
+for feature in $features; do
+    echo "== $feature"
+    RUSTFLAGS="-D warnings" RUSTDOCFLAGS="-D warnings" \
+        cargo test --no-default-features --features "$feature" "$@"
+done
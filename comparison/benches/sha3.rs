@@ -1,59 +1,42 @@
-#![feature(test)]
-
-extern crate test;
-
-use test::Bencher;
-
-#[bench]
-fn tiny_keccak_sha3_256_input_32_bytes(b: &mut Bencher) {
-    use tiny_keccak::{Sha3, Hasher};
-    let data = vec![254u8; 32];
-    b.bytes = data.len() as u64;
-
-    b.iter(|| {
-        let mut res: [u8; 32] = [0; 32];
-        let mut sha3 = Sha3::v256();
-        sha3.update(&data);
-        sha3.finalize(&mut res);
-    });
-}
-
-#[bench]
-fn tiny_keccak_sha3_256_input_4096_bytes(b: &mut Bencher) {
-    use tiny_keccak::{Sha3, Hasher};
-    let data = vec![254u8; 4096];
-    b.bytes = data.len() as u64;
-
-    b.iter(|| {
-        let mut res: [u8; 32] = [0; 32];
-        let mut sha3 = Sha3::v256();
-        sha3.update(&data);
-        sha3.finalize(&mut res);
-    });
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+fn tiny_keccak_sha3_256(c: &mut Criterion) {
+    use tiny_keccak::{Hasher, Sha3};
+
+    let mut group = c.benchmark_group("tiny_keccak_sha3_256");
+    for size in [32, 4096].iter() {
+        let data = vec![254u8; *size];
+        group.throughput(Throughput::Bytes(*size as u64));
+        group.bench_function(format!("input_{}_bytes", size), |b| {
+            b.iter(|| {
+                let mut res: [u8; 32] = [0; 32];
+                let mut sha3 = Sha3::v256();
+                sha3.update(black_box(&data));
+                sha3.finalize(&mut res);
+                res
+            })
+        });
+    }
+    group.finish();
 }
 
-#[bench]
-fn rust_crypto_sha3_256_input_32_bytes(b: &mut Bencher) {
+fn rust_crypto_sha3_256(c: &mut Criterion) {
     use sha3::{Digest, Sha3_256};
-    let data = vec![254u8; 32];
-    b.bytes = data.len() as u64;
 
-    b.iter(|| {
-        let mut sha3 = Sha3_256::default();
-        sha3.input(&data);
-        sha3.result();
-    });
+    let mut group = c.benchmark_group("rust_crypto_sha3_256");
+    for size in [32, 4096].iter() {
+        let data = vec![254u8; *size];
+        group.throughput(Throughput::Bytes(*size as u64));
+        group.bench_function(format!("input_{}_bytes", size), |b| {
+            b.iter(|| {
+                let mut sha3 = Sha3_256::default();
+                sha3.input(black_box(&data));
+                sha3.result()
+            })
+        });
+    }
+    group.finish();
 }
 
-#[bench]
-fn rust_crypto_sha3_256_input_4096_bytes(b: &mut Bencher) {
-    use sha3::{Digest, Sha3_256};
-    let data = vec![254u8; 4096];
-    b.bytes = data.len() as u64;
-
-    b.iter(|| {
-        let mut sha3 = Sha3_256::default();
-        sha3.input(&data);
-        sha3.result();
-    });
-}
+criterion_group!(benches, tiny_keccak_sha3_256, rust_crypto_sha3_256);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+//! Hashes requests with a small pool of `Keccak-256` hashers, which are reset and reused instead
+//! of being created for every request.
+
+use tiny_keccak::{Hasher, Keccak};
+
+struct Pool {
+    hashers: Vec<Keccak>,
+}
+
+impl Pool {
+    fn new(size: usize) -> Pool {
+        Pool {
+            hashers: vec![Keccak::v256(); size],
+        }
+    }
+
+    fn digest(&mut self, slot: usize, request: &[u8]) -> [u8; 32] {
+        // the slot is left with a reset hasher for the next request
+        let mut keccak = self.hashers[slot].take();
+        keccak.update(request);
+        let mut output = [0u8; 32];
+        keccak.finalize(&mut output);
+        output
+    }
+}
+
+fn main() {
+    let requests: [&[u8]; 4] = [b"GET /", b"GET /index.html", b"POST /login", b"GET /"];
+
+    let mut pool = Pool::new(2);
+    let capacity = pool.hashers.capacity();
+    for (i, request) in requests.iter().enumerate() {
+        let digest = pool.digest(i % 2, request);
+        assert_eq!(digest, tiny_keccak::keccak256(request));
+    }
+    // no hasher was added or reallocated
+    assert_eq!(pool.hashers.capacity(), capacity);
+}
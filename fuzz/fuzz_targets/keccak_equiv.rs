@@ -0,0 +1,41 @@
+//! Compares `Keccak-256` against the RustCrypto implementation, absorbing the input in chunks of
+//! arbitrary sizes.
+//!
+//! The first byte of the input is the number of chunks, followed by a little-endian `u16` size
+//! for every chunk. The rest of the input is the message, whatever is left after the last chunk
+//! is absorbed at once.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sha3::{Digest, Keccak256};
+use tiny_keccak::{Hasher, Keccak};
+
+const MAX_CHUNKS: usize = 16;
+
+fuzz_target!(|data: &[u8]| {
+    let (&chunks, data) = match data.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+
+    let chunks = chunks as usize % MAX_CHUNKS;
+    if data.len() < chunks * 2 {
+        return;
+    }
+
+    let (sizes, message) = data.split_at(chunks * 2);
+    let mut keccak = Keccak::v256();
+    let mut rest = message;
+    for size in sizes.chunks(2) {
+        let size = u16::from_le_bytes([size[0], size[1]]) as usize;
+        let (chunk, tail) = rest.split_at(size.min(rest.len()));
+        keccak.update(chunk);
+        rest = tail;
+    }
+    keccak.update(rest);
+
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+    assert_eq!(output[..], Keccak256::digest(message)[..]);
+});
@@ -0,0 +1,224 @@
+//! `keccak-p[1600]` permutation using AVX2.
+
+use core::arch::x86_64::*;
+
+use crunchy::unroll;
+
+use crate::{PI, RC, RHO, WORDS};
+
+/// Rotation offsets of the rho step, indexed by `x + 5 * y`.
+const RHO_OFFSETS: [u32; WORDS] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Source lane of the pi step, indexed by the destination `x + 5 * y`.
+const PI_SOURCE: [usize; WORDS] = [
+    0, 6, 12, 18, 24, 3, 9, 10, 16, 22, 1, 7, 13, 19, 20, 4, 5, 11, 17, 23, 2, 8, 14, 15, 21,
+];
+
+#[inline(always)]
+unsafe fn load(a: &[u64; WORDS], i0: usize, i1: usize, i2: usize, i3: usize) -> __m256i {
+    _mm256_setr_epi64x(a[i0] as i64, a[i1] as i64, a[i2] as i64, a[i3] as i64)
+}
+
+#[inline(always)]
+unsafe fn rotate_left(v: __m256i, n: __m256i, inverse: __m256i) -> __m256i {
+    // shifting by 64 yields zero, so a rotation by 0 is handled as well
+    _mm256_or_si256(_mm256_sllv_epi64(v, n), _mm256_srlv_epi64(v, inverse))
+}
+
+#[inline(always)]
+unsafe fn rotate_left_1(v: __m256i) -> __m256i {
+    _mm256_or_si256(_mm256_slli_epi64(v, 1), _mm256_srli_epi64(v, 63))
+}
+
+#[inline(always)]
+unsafe fn lane(v: __m256i, x: usize) -> u64 {
+    let mut lanes = [0u64; 4];
+    _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, v);
+    lanes[x]
+}
+
+/// `keccak-p[1600, rounds]` on CPUs supporting AVX2.
+///
+/// Every row of the state is kept as a 256-bit register holding the lanes `x = 0..4` and a
+/// scalar holding the lane `x = 4`.
+///
+/// # Safety
+///
+/// The caller has to make sure that the CPU supports AVX2.
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn keccak_p(a: &mut [u64; WORDS], rounds: usize) {
+    let mut rows = [_mm256_setzero_si256(); 5];
+    let mut last = [0u64; 5];
+    let mut offsets = [_mm256_setzero_si256(); 5];
+    let mut inverse = [_mm256_setzero_si256(); 5];
+    for y in 0..5 {
+        rows[y] = load(a, 5 * y, 5 * y + 1, 5 * y + 2, 5 * y + 3);
+        last[y] = a[5 * y + 4];
+        let o = &RHO_OFFSETS[5 * y..];
+        offsets[y] = _mm256_setr_epi64x(o[0] as i64, o[1] as i64, o[2] as i64, o[3] as i64);
+        inverse[y] = _mm256_sub_epi64(_mm256_set1_epi64x(64), offsets[y]);
+    }
+
+    for rc in &RC[24 - rounds..] {
+        // Theta
+        let c = _mm256_xor_si256(
+            _mm256_xor_si256(
+                _mm256_xor_si256(rows[0], rows[1]),
+                _mm256_xor_si256(rows[2], rows[3]),
+            ),
+            rows[4],
+        );
+        let c4 = last[0] ^ last[1] ^ last[2] ^ last[3] ^ last[4];
+        let c4v = _mm256_set1_epi64x(c4 as i64);
+        // [c4, c0, c1, c2]
+        let previous =
+            _mm256_blend_epi32(_mm256_permute4x64_epi64(c, 0b10_01_00_11), c4v, 0b0000_0011);
+        // [c1, c2, c3, c4]
+        let next = _mm256_blend_epi32(_mm256_permute4x64_epi64(c, 0b00_11_10_01), c4v, 0b1100_0000);
+        let d = _mm256_xor_si256(previous, rotate_left_1(next));
+        let d4 = lane(c, 3) ^ lane(c, 0).rotate_left(1);
+        unroll! {
+            for y in 0..5 {
+                rows[y] = _mm256_xor_si256(rows[y], d);
+                last[y] ^= d4;
+            }
+        }
+
+        // Rho
+        let mut b = [0u64; WORDS];
+        unroll! {
+            for y in 0..5 {
+                let row = rotate_left(rows[y], offsets[y], inverse[y]);
+                _mm256_storeu_si256(b[5 * y..].as_mut_ptr() as *mut __m256i, row);
+                b[5 * y + 4] = last[y].rotate_left(RHO_OFFSETS[5 * y + 4]);
+            }
+        }
+
+        // Pi and chi
+        unroll! {
+            for y in 0..5 {
+                let p = &PI_SOURCE[5 * y..];
+                let row = load(&b, p[0], p[1], p[2], p[3]);
+                let row4 = b[p[4]];
+                let row4v = _mm256_set1_epi64x(row4 as i64);
+                // [b1, b2, b3, b4]
+                let next = _mm256_blend_epi32(
+                    _mm256_permute4x64_epi64(row, 0b00_11_10_01),
+                    row4v,
+                    0b1100_0000,
+                );
+                // [b2, b3, b4, b0]
+                let after_next = _mm256_blend_epi32(
+                    _mm256_permute4x64_epi64(row, 0b00_00_11_10),
+                    row4v,
+                    0b0011_0000,
+                );
+                rows[y] = _mm256_xor_si256(row, _mm256_andnot_si256(next, after_next));
+                last[y] = row4 ^ (!b[p[0]] & b[p[1]]);
+            }
+        }
+
+        // Iota
+        rows[0] = _mm256_xor_si256(rows[0], _mm256_setr_epi64x(*rc as i64, 0, 0, 0));
+    }
+
+    for y in 0..5 {
+        _mm256_storeu_si256(a[5 * y..].as_mut_ptr() as *mut __m256i, rows[y]);
+        a[5 * y + 4] = last[y];
+    }
+}
+
+#[inline(always)]
+unsafe fn rotate_left_x4(v: __m256i, n: i32) -> __m256i {
+    _mm256_or_si256(
+        _mm256_sll_epi64(v, _mm_cvtsi32_si128(n)),
+        _mm256_srl_epi64(v, _mm_cvtsi32_si128(64 - n)),
+    )
+}
+
+/// `keccak-p[1600, rounds]` applied to four independent states at once, on CPUs supporting AVX2.
+///
+/// Lane `w` of the four states is interleaved in a single 256-bit register.
+///
+/// # Safety
+///
+/// The caller has to make sure that the CPU supports AVX2.
+#[allow(unused_assignments)]
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn keccak_p_x4(states: &mut [[u64; WORDS]; 4], rounds: usize) {
+    let mut a = [_mm256_setzero_si256(); WORDS];
+    for (w, lane) in a.iter_mut().enumerate() {
+        *lane = _mm256_setr_epi64x(
+            states[0][w] as i64,
+            states[1][w] as i64,
+            states[2][w] as i64,
+            states[3][w] as i64,
+        );
+    }
+
+    for rc in &RC[24 - rounds..] {
+        // Theta
+        let mut c = [_mm256_setzero_si256(); 5];
+        unroll! {
+            for x in 0..5 {
+                c[x] = _mm256_xor_si256(
+                    _mm256_xor_si256(_mm256_xor_si256(a[x], a[x + 5]), a[x + 10]),
+                    _mm256_xor_si256(a[x + 15], a[x + 20]),
+                );
+            }
+        }
+
+        unroll! {
+            for x in 0..5 {
+                let d = _mm256_xor_si256(c[(x + 4) % 5], rotate_left_1(c[(x + 1) % 5]));
+                unroll! {
+                    for y in 0..5 {
+                        a[x + 5 * y] = _mm256_xor_si256(a[x + 5 * y], d);
+                    }
+                }
+            }
+        }
+
+        // Rho and pi
+        let mut last = a[1];
+        unroll! {
+            for x in 0..24 {
+                let next = a[PI[x]];
+                a[PI[x]] = rotate_left_x4(last, RHO[x] as i32);
+                last = next;
+            }
+        }
+
+        // Chi
+        unroll! {
+            for y in 0..5 {
+                let mut b = [_mm256_setzero_si256(); 5];
+                unroll! {
+                    for x in 0..5 {
+                        b[x] = a[5 * y + x];
+                    }
+                }
+
+                unroll! {
+                    for x in 0..5 {
+                        let not_and = _mm256_andnot_si256(b[(x + 1) % 5], b[(x + 2) % 5]);
+                        a[5 * y + x] = _mm256_xor_si256(b[x], not_and);
+                    }
+                }
+            }
+        }
+
+        // Iota
+        a[0] = _mm256_xor_si256(a[0], _mm256_set1_epi64x(*rc as i64));
+    }
+
+    for (w, lane) in a.iter().enumerate() {
+        let mut words = [0u64; 4];
+        _mm256_storeu_si256(words.as_mut_ptr() as *mut __m256i, *lane);
+        for (state, word) in states.iter_mut().zip(words.iter()) {
+            state[w] = *word;
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{Hasher, Shake};
+
+/// The `k` indices of an item in a Bloom filter of `m` bits, squeezed from `SHAKE256(item)`.
+///
+/// Created by [`bloom_indices_iter`], it doesn't allocate. Every index is uniform in `[0, m)`
+/// and independent of the others, see [`bloom_indices`].
+///
+/// [`bloom_indices_iter`]: fn.bloom_indices_iter.html
+/// [`bloom_indices`]: fn.bloom_indices.html
+#[derive(Clone, Debug)]
+pub struct BloomIndices {
+    shake: Shake,
+    m: u64,
+    remaining: usize,
+}
+
+/// Returns an iterator over the `k` indices of `item` in a Bloom filter of `m` bits, like
+/// [`bloom_indices`] without allocating.
+///
+/// # Panics
+///
+/// Panics if `m` is zero.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::bloom_indices_iter;
+/// let mut filter = [false; 1024];
+/// for index in bloom_indices_iter(b"item", 7, filter.len()) {
+///     filter[index] = true;
+/// }
+/// assert!(bloom_indices_iter(b"item", 7, filter.len()).all(|index| filter[index]));
+/// ```
+///
+/// [`bloom_indices`]: fn.bloom_indices.html
+pub fn bloom_indices_iter(item: &[u8], k: usize, m: usize) -> BloomIndices {
+    assert!(m != 0, "a Bloom filter needs at least one bit");
+    let mut shake = Shake::v256();
+    shake.update(item);
+    BloomIndices {
+        shake,
+        m: m as u64,
+        remaining: k,
+    }
+}
+
+impl Iterator for BloomIndices {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.shake.squeeze_below(self.m) as usize)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for BloomIndices {}
+
+impl core::iter::FusedIterator for BloomIndices {}
+
+/// Computes the `k` indices of `item` in a Bloom filter of `m` bits in one pass over the item.
+///
+/// `SHAKE256` absorbs the item, and every index is squeezed as a little-endian `u64`, rejecting
+/// the candidates that would bias the reduction modulo `m`. The indices are uniform and
+/// independent, unlike double hashing, and the same on every platform. They may repeat, like
+/// the indices of `k` independent hash functions. Requires the `alloc` feature, see
+/// [`bloom_indices_iter`] to avoid allocating.
+///
+/// # Panics
+///
+/// Panics if `m` is zero.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["bloom", "alloc"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::bloom_indices;
+/// let indices = bloom_indices(b"item", 7, 1024);
+/// assert_eq!(indices.len(), 7);
+/// assert!(indices.iter().all(|&index| index < 1024));
+/// ```
+///
+/// [`bloom_indices_iter`]: fn.bloom_indices_iter.html
+#[cfg(feature = "alloc")]
+pub fn bloom_indices(item: &[u8], k: usize, m: usize) -> alloc::vec::Vec<usize> {
+    bloom_indices_iter(item, k, m).collect()
+}
@@ -0,0 +1,95 @@
+use crate::encode::encode_string;
+use crate::{CShake, Hasher};
+
+/// A hash commitment to a value, built on [`cSHAKE256`].
+///
+/// [`commit`] absorbs the randomness and the value, both encoded as in [`SP800-185`], into
+/// `cSHAKE256` customized with `KeccakCommitment`, and squeezes a 32-byte commitment. It is
+/// hiding as long as the randomness is secret and uniformly random, e.g. drawn from the
+/// operating system, and binding by the collision resistance of `cSHAKE256`. The commitment is
+/// opened by revealing the value and the randomness, which [`verify`] checks in constant time.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["commitment"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::KeccakCommitment;
+/// let randomness = [0x5a; 32]; // from a CSPRNG in practice
+/// let (commitment, opening) = KeccakCommitment::commit(b"my bid: 100", randomness);
+///
+/// // publish `commitment.as_bytes()`, later reveal the value and `opening.randomness()`
+/// assert!(commitment.verify(b"my bid: 100", opening.randomness()));
+/// assert!(!commitment.verify(b"my bid: 999", opening.randomness()));
+/// ```
+///
+/// [`cSHAKE256`]: struct.CShake.html#method.v256
+/// [`commit`]: #method.commit
+/// [`verify`]: #method.verify
+/// [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
+#[derive(Clone, Copy, Debug)]
+pub struct KeccakCommitment {
+    digest: [u8; 32],
+}
+
+/// The randomness opening a [`KeccakCommitment`], revealed together with the value.
+///
+/// [`KeccakCommitment`]: struct.KeccakCommitment.html
+#[derive(Clone)]
+pub struct KeccakOpening {
+    randomness: [u8; 32],
+}
+
+impl KeccakCommitment {
+    const CUSTOM_STRING: &'static [u8] = b"KeccakCommitment";
+
+    /// Commits to `value` with `randomness`, returning the commitment and its opening.
+    pub fn commit(value: &[u8], randomness: [u8; 32]) -> (KeccakCommitment, KeccakOpening) {
+        let commitment = KeccakCommitment {
+            digest: Self::digest(value, &randomness),
+        };
+        (commitment, KeccakOpening { randomness })
+    }
+
+    /// Creates a commitment from the bytes returned by [`as_bytes`], e.g. received from the
+    /// committing party.
+    ///
+    /// [`as_bytes`]: #method.as_bytes
+    pub fn from_bytes(digest: [u8; 32]) -> KeccakCommitment {
+        KeccakCommitment { digest }
+    }
+
+    /// The bytes of the commitment, to publish.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.digest
+    }
+
+    /// Recomputes the commitment to `value` with `randomness` and compares it with this one in
+    /// constant time.
+    pub fn verify(&self, value: &[u8], randomness: &[u8; 32]) -> bool {
+        Self::state(value, randomness).verify(&self.digest)
+    }
+
+    fn state(value: &[u8], randomness: &[u8; 32]) -> CShake {
+        let mut state = CShake::v256(b"", Self::CUSTOM_STRING);
+        state.update_vectored(&encode_string(randomness).parts());
+        state.update_vectored(&encode_string(value).parts());
+        state
+    }
+
+    fn digest(value: &[u8], randomness: &[u8; 32]) -> [u8; 32] {
+        Self::state(value, randomness).finalize_array()
+    }
+}
+
+impl KeccakOpening {
+    /// The randomness the value was committed with.
+    pub fn randomness(&self) -> &[u8; 32] {
+        &self.randomness
+    }
+}
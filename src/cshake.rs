@@ -2,10 +2,17 @@
 //!
 //! [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
 
-use crate::{bits_to_rate, keccakf::KeccakF, left_encode, Hasher, KeccakState, Xof};
+use core::mem::MaybeUninit;
+
+use crate::encode::{left_encode, left_encode_bits};
+use crate::{bits_to_rate, Domain, Hasher, KeccakError, KeccakF, KeccakState, Xof, SPONGE_SIZE};
 
 /// The `cSHAKE` extendable-output functions defined in [`SP800-185`].
 ///
+/// The function name and the customization string can have any length, their bit lengths are
+/// encoded with 128-bit integers, so the constructors can't fail on them. [`try_new`] only checks
+/// the security level.
+///
 /// # Usage
 ///
 /// ```toml
@@ -14,13 +21,16 @@ use crate::{bits_to_rate, keccakf::KeccakF, left_encode, Hasher, KeccakState, Xo
 /// ```
 ///
 /// [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
+/// [`try_new`]: #method.try_new
 #[derive(Clone)]
 pub struct CShake {
     state: KeccakState<KeccakF>,
 }
 
+const _: () = assert!(core::mem::size_of::<CShake>() <= SPONGE_SIZE);
+
 impl CShake {
-    const DELIM: u8 = 0x04;
+    const DELIM: u8 = Domain::CShake.delim();
 
     /// Creates  new [`CShake`] hasher with a security level of 128 bits.
     ///
@@ -36,28 +46,69 @@ impl CShake {
         CShake::new(name, custom_string, 256)
     }
 
+    /// Creates a new [`CShake`] hasher with a security level of `bits` bits, failing with
+    /// [`KeccakError::InvalidSecurityLevel`] if it is neither 128 nor 256. Strings of any length
+    /// are encodable, so `name` or `custom_string` never fail.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{CShake, KeccakError};
+    /// assert!(CShake::try_new(b"", b"Email Signature", 128).is_ok());
+    /// assert!(matches!(
+    ///     CShake::try_new(b"", b"Email Signature", 512),
+    ///     Err(KeccakError::InvalidSecurityLevel { bits: 512 })
+    /// ));
+    /// ```
+    ///
+    /// [`CShake`]: struct.CShake.html
+    /// [`KeccakError::InvalidSecurityLevel`]: enum.KeccakError.html#variant.InvalidSecurityLevel
+    pub fn try_new(name: &[u8], custom_string: &[u8], bits: usize) -> Result<CShake, KeccakError> {
+        check_security_level(bits)?;
+        Ok(CShake::new(name, custom_string, bits))
+    }
+
     pub(crate) fn new(name: &[u8], custom_string: &[u8], bits: usize) -> CShake {
         let rate = bits_to_rate(bits);
         // if there is no name and no customization string
         // cSHAKE is SHAKE
         if name.is_empty() && custom_string.is_empty() {
-            let state = KeccakState::new(rate, 0x1f);
+            let state = KeccakState::new(rate, Domain::Shake.delim());
             return CShake { state };
         }
 
         let mut state = KeccakState::new(rate, Self::DELIM);
         state.update(left_encode(rate).value());
-        state.update(left_encode(name.len() * 8).value());
+        state.update(left_encode_bits(name.len()).value());
         state.update(name);
-        state.update(left_encode(custom_string.len() * 8).value());
+        state.update(left_encode_bits(custom_string.len()).value());
         state.update(custom_string);
         state.fill_block();
         CShake { state }
     }
 
+    #[cfg(feature = "kmac")]
     pub(crate) fn fill_block(&mut self) {
         self.state.fill_block();
     }
+
+    #[cfg(any(feature = "kmac", feature = "parallel_hash", feature = "tuple_hash"))]
+    pub(crate) fn into_state(self) -> KeccakState<KeccakF> {
+        self.state
+    }
+
+    #[cfg(any(feature = "kmac", feature = "commitment"))]
+    pub(crate) fn verify(self, expected: &[u8]) -> bool {
+        self.state.verify(expected)
+    }
+}
+
+/// Checks the security level passed to the `try_new` constructors.
+pub(crate) fn check_security_level(bits: usize) -> Result<(), KeccakError> {
+    if bits != 128 && bits != 256 {
+        return Err(KeccakError::InvalidSecurityLevel { bits });
+    }
+    Ok(())
 }
 
 impl Hasher for CShake {
@@ -68,10 +119,34 @@ impl Hasher for CShake {
     fn finalize(self, output: &mut [u8]) {
         self.state.finalize(output);
     }
+
+    fn finalize_uninit(self, output: &mut [MaybeUninit<u8>]) -> &mut [u8] {
+        self.state.finalize_uninit(output)
+    }
+
+    fn finalize_xor(self, output: &mut [u8]) {
+        self.state.finalize_xor(output);
+    }
+
+    fn finalize_scattered(self, outputs: &mut [&mut [u8]]) {
+        self.state.finalize_scattered(outputs);
+    }
 }
 
+#[cfg(feature = "std")]
+impl_write!(CShake);
+
+impl_extend!(CShake);
+
 impl Xof for CShake {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output);
     }
+
+    fn squeeze_xor(&mut self, output: &mut [u8]) {
+        self.state.squeeze_xor(output);
+    }
 }
+
+#[cfg(feature = "digest")]
+impl_digest_xof!(CShake);
@@ -0,0 +1,147 @@
+use crate::{bits_to_rate, Domain, KeccakF, KeccakState, Mode, SPONGE_SIZE};
+
+/// The duplex construction defined in [`Duplexing the sponge`].
+///
+/// Every call to [`duplexing_into`] pads and absorbs an input shorter than the rate, permutes the
+/// state once and squeezes up to a rate of output, the state is kept between the calls. With the
+/// `alloc` feature [`duplexing`] returns the output in a `Vec` instead. It is
+/// the basis of authenticated encryption schemes like `SpongeWrap`, `Keyak` and `Ketje`.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["duplex"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::Duplex;
+/// let mut duplex = Duplex::v256();
+/// let mut output = [0u8; 32];
+/// duplex.duplexing_into(b"key", &mut output);
+/// duplex.duplexing_into(b"nonce", &mut output);
+/// ```
+///
+/// [`Duplexing the sponge`]: https://keccak.team/files/SpongeDuplex.pdf
+/// [`duplexing_into`]: #method.duplexing_into
+/// [`duplexing`]: #method.duplexing
+#[derive(Clone)]
+pub struct Duplex {
+    state: KeccakState<KeccakF>,
+}
+
+const _: () = assert!(core::mem::size_of::<Duplex>() <= SPONGE_SIZE);
+
+impl Duplex {
+    const DELIM: u8 = Domain::Keccak.delim();
+
+    /// Creates  new [`Duplex`] with a security level of 128 bits.
+    ///
+    /// [`Duplex`]: struct.Duplex.html
+    pub fn v128() -> Duplex {
+        Duplex::new(128)
+    }
+
+    /// Creates  new [`Duplex`] with a security level of 256 bits.
+    ///
+    /// [`Duplex`]: struct.Duplex.html
+    pub fn v256() -> Duplex {
+        Duplex::new(256)
+    }
+
+    /// The rate of the duplex in bytes, inputs have to be shorter than the rate and outputs can't
+    /// be longer than it.
+    pub fn rate(&self) -> usize {
+        self.state.rate
+    }
+
+    /// Pads and absorbs `input`, permutes the state and squeezes `output_len` bytes of output.
+    /// Requires the `alloc` feature, [`duplexing_into`] squeezes into a slice instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::Duplex;
+    /// let mut duplex = Duplex::v256();
+    /// duplex.duplexing(b"key", 0);
+    /// let output = duplex.duplexing(b"nonce", 32);
+    /// assert_eq!(output.len(), 32);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` isn't shorter than the rate or `output_len` exceeds the rate.
+    ///
+    /// [`duplexing_into`]: #method.duplexing_into
+    #[cfg(feature = "alloc")]
+    pub fn duplexing(&mut self, input: &[u8], output_len: usize) -> alloc::vec::Vec<u8> {
+        let mut output = alloc::vec![0u8; output_len];
+        self.duplexing_into(input, &mut output);
+        output
+    }
+
+    /// Pads and absorbs `input`, permutes the state and squeezes the output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` isn't shorter than the rate or `output` is longer than the rate.
+    pub fn duplexing_into(&mut self, input: &[u8], output: &mut [u8]) {
+        assert!(
+            input.len() < self.state.rate,
+            "duplex input must be shorter than the rate"
+        );
+        assert!(
+            output.len() <= self.state.rate,
+            "duplex output cannot exceed the rate"
+        );
+
+        self.state.update(input);
+        self.state.pad();
+        self.state.fill_block();
+        self.state.buffer.setout(output, 0, output.len());
+        // the next input is absorbed into the permuted state
+        self.state.mode = Mode::Absorbing;
+    }
+
+    /// Absorbs `input`, pads it and permutes the state, then squeezes `output`, for inputs and
+    /// outputs of any length, e.g. a sponge based generator reseeded on every call. The state is
+    /// kept between the calls, so a sequence of calls is a deterministic stream.
+    ///
+    /// An input shorter than the rate and an output of up to the rate give the same output and
+    /// the same state as [`duplexing_into`]. Longer inputs permute every completed block before the
+    /// padding, longer outputs permute between their blocks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::Duplex;
+    /// let mut prg = Duplex::v256();
+    /// let mut output = [0u8; 300];
+    /// prg.absorb_squeeze(&[0x42; 64], &mut output);
+    /// prg.absorb_squeeze(b"reseed", &mut output);
+    /// ```
+    ///
+    /// [`duplexing_into`]: #method.duplexing_into
+    pub fn absorb_squeeze(&mut self, input: &[u8], output: &mut [u8]) {
+        self.state.update(input);
+        self.state.pad();
+        self.state.fill_block();
+        // the block the output ends in isn't permuted until the next call, like in `duplexing_into`
+        for (i, chunk) in output.chunks_mut(self.state.rate).enumerate() {
+            if i != 0 {
+                self.state.fill_block();
+            }
+            self.state.buffer.setout(chunk, 0, chunk.len());
+        }
+        // the next input is absorbed into the permuted state
+        self.state.mode = Mode::Absorbing;
+    }
+
+    fn new(bits: usize) -> Duplex {
+        Duplex {
+            state: KeccakState::new(bits_to_rate(bits), Self::DELIM),
+        }
+    }
+}
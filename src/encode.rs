@@ -0,0 +1,184 @@
+//! The `left_encode`, `right_encode`, `encode_string` and `bytepad` functions defined in
+//! [`SP800-185`].
+//!
+//! [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
+
+#[cfg(feature = "cshake")]
+use crate::Hasher;
+
+/// An integer encoded by [`left_encode`] or [`right_encode`].
+///
+/// [`left_encode`]: fn.left_encode.html
+/// [`right_encode`]: fn.right_encode.html
+pub struct EncodedLen {
+    pub(crate) offset: usize,
+    pub(crate) buffer: [u8; 17],
+}
+
+impl EncodedLen {
+    /// The encoded bytes.
+    pub fn value(&self) -> &[u8] {
+        &self.buffer[self.offset..]
+    }
+}
+
+/// Encodes `len` as its byte length followed by its big-endian bytes.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::encode::left_encode;
+/// assert_eq!(left_encode(4096).value(), &[2, 16, 0]);
+/// ```
+#[cfg(any(feature = "cshake", feature = "keccak"))]
+pub fn left_encode(len: usize) -> EncodedLen {
+    left_encode_u128(len as u128)
+}
+
+/// Encodes the length in bits of `bytes` bytes like [`left_encode`].
+///
+/// The bit length is computed with 128-bit integers, so it doesn't overflow for inputs longer than
+/// 512 MiB on 32-bit targets.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::encode::left_encode_bits;
+/// assert_eq!(left_encode_bits(512).value(), &[2, 16, 0]);
+/// ```
+///
+/// [`left_encode`]: fn.left_encode.html
+#[cfg(any(feature = "cshake", feature = "keccak"))]
+pub fn left_encode_bits(bytes: usize) -> EncodedLen {
+    left_encode_u128(bytes as u128 * 8)
+}
+
+// The bit length of any slice fits in the 16 bytes encoded here, far below the 255 bytes the
+// length prefix of SP800-185 allows, so every name, customization string and key is encodable.
+#[cfg(any(feature = "cshake", feature = "keccak"))]
+const _: () = assert!(usize::BITS + 3 <= u128::BITS);
+
+#[cfg(any(feature = "cshake", feature = "keccak"))]
+fn left_encode_u128(value: u128) -> EncodedLen {
+    let mut buffer = [0u8; 17];
+    buffer[1..].copy_from_slice(&value.to_be_bytes());
+    let offset = buffer.iter().position(|i| *i != 0).unwrap_or(16);
+    buffer[offset - 1] = 17 - offset as u8;
+
+    EncodedLen {
+        offset: offset - 1,
+        buffer,
+    }
+}
+
+/// Encodes `len` as its big-endian bytes followed by their length.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::encode::right_encode;
+/// assert_eq!(right_encode(4096).value(), &[16, 0, 2]);
+/// ```
+#[cfg(feature = "cshake")]
+pub fn right_encode(len: usize) -> EncodedLen {
+    right_encode_u128(len as u128)
+}
+
+/// Encodes the length in bits of `bytes` bytes like [`right_encode`].
+///
+/// The bit length is computed with 128-bit integers, so it doesn't overflow for outputs longer
+/// than 512 MiB on 32-bit targets.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::encode::right_encode_bits;
+/// assert_eq!(right_encode_bits(512).value(), &[16, 0, 2]);
+/// ```
+///
+/// [`right_encode`]: fn.right_encode.html
+#[cfg(feature = "cshake")]
+pub fn right_encode_bits(bytes: usize) -> EncodedLen {
+    right_encode_u128(bytes as u128 * 8)
+}
+
+#[cfg(feature = "cshake")]
+fn right_encode_u128(value: u128) -> EncodedLen {
+    let mut buffer = [0u8; 17];
+    buffer[..16].copy_from_slice(&value.to_be_bytes());
+    let offset = buffer.iter().position(|i| *i != 0).unwrap_or(15);
+    buffer[16] = 16 - offset as u8;
+    EncodedLen { offset, buffer }
+}
+
+/// A string encoded by [`encode_string`].
+///
+/// [`encode_string`]: fn.encode_string.html
+#[cfg(feature = "cshake")]
+pub struct EncodedString<'a> {
+    len: EncodedLen,
+    string: &'a [u8],
+}
+
+#[cfg(feature = "cshake")]
+impl<'a> EncodedString<'a> {
+    /// The encoded bit length followed by the string, they can be absorbed with
+    /// [`Hasher::update_vectored`].
+    ///
+    /// [`Hasher::update_vectored`]: ../trait.Hasher.html#method.update_vectored
+    pub fn parts(&self) -> [&[u8]; 2] {
+        [self.len.value(), self.string]
+    }
+}
+
+/// Encodes `string` as its bit length, encoded by [`left_encode`], followed by the string.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::encode::encode_string;
+/// assert_eq!(encode_string(b"abc").parts(), [&[1, 24][..], b"abc"]);
+/// ```
+///
+/// [`left_encode`]: fn.left_encode.html
+#[cfg(feature = "cshake")]
+pub fn encode_string(string: &[u8]) -> EncodedString<'_> {
+    EncodedString {
+        len: left_encode_bits(string.len()),
+        string,
+    }
+}
+
+/// Absorbs `bytepad(X, w)`, i.e. `w` encoded by [`left_encode`], followed by the concatenation
+/// `X` of `parts` and zeros up to a multiple of `w` bytes.
+///
+/// # Panics
+///
+/// Panics if `w` is zero.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{encode::{bytepad, encode_string}, CShake, Hasher};
+/// let mut cshake = CShake::v128(b"", b"");
+/// bytepad(&mut cshake, &encode_string(b"key").parts(), 168);
+/// ```
+///
+/// [`left_encode`]: fn.left_encode.html
+#[cfg(feature = "cshake")]
+pub fn bytepad<H: Hasher>(hasher: &mut H, parts: &[&[u8]], w: usize) {
+    assert!(w != 0, "bytepad width must be positive");
+
+    let prefix = left_encode(w);
+    hasher.update(prefix.value());
+    hasher.update_vectored(parts);
+
+    let len = prefix.value().len() + parts.iter().map(|part| part.len()).sum::<usize>();
+    let mut zeros = (w - len % w) % w;
+    let padding = [0u8; 32];
+    while zeros > 0 {
+        let n = zeros.min(padding.len());
+        hasher.update(&padding[..n]);
+        zeros -= n;
+    }
+}
@@ -0,0 +1,88 @@
+//! A narrow `Keccak-256` for the Ethereum ecosystem, [`keccak256`] and the incremental
+//! [`Keccak256`].
+//!
+//! The surface of this module is frozen: it only ever hashes with the original Keccak padding,
+//! the `0x01` delimiter, and a rate of 136 bytes, and its signatures won't change in any 2.x
+//! release, whatever happens to the rest of the crate. Crates that only need Ethereum hashes can
+//! depend on it instead of wrapping [`Keccak`] themselves.
+//!
+//! # Usage
+//!
+//! ```toml
+//! [dependencies]
+//! tiny-keccak = { version = "2.0.0", features = ["ethereum"] }
+//! ```
+//!
+//! [`keccak256`]: fn.keccak256.html
+//! [`Keccak256`]: struct.Keccak256.html
+//! [`Keccak`]: ../struct.Keccak.html
+
+use crate::{KeccakF, KeccakState, SPONGE_SIZE};
+
+/// The rate of `Keccak-256` in bytes.
+const RATE: usize = 136;
+
+/// The delimiter of the original Keccak padding, before the `SHA3` domain separation.
+const DELIM: u8 = 0x01;
+
+/// Computes the `Keccak-256` digest of `input`.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::eth::keccak256;
+/// let digest = keccak256(b"");
+/// assert_eq!(digest[..4], [0xc5, 0xd2, 0x46, 0x01]);
+/// ```
+pub fn keccak256(input: &[u8]) -> [u8; 32] {
+    let mut keccak = Keccak256::new();
+    keccak.update(input);
+    keccak.finalize()
+}
+
+/// The incremental `Keccak-256` of Ethereum.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::eth::{keccak256, Keccak256};
+/// let mut keccak = Keccak256::new();
+/// keccak.update(b"hello ");
+/// keccak.update(b"world");
+/// assert_eq!(keccak.finalize(), keccak256(b"hello world"));
+/// ```
+#[derive(Clone)]
+pub struct Keccak256 {
+    state: KeccakState<KeccakF>,
+}
+
+const _: () = assert!(core::mem::size_of::<Keccak256>() <= SPONGE_SIZE);
+
+impl Keccak256 {
+    /// Creates  new [`Keccak256`] hasher.
+    ///
+    /// [`Keccak256`]: struct.Keccak256.html
+    pub fn new() -> Keccak256 {
+        Keccak256 {
+            state: KeccakState::new(RATE, DELIM),
+        }
+    }
+
+    /// Absorb additional input. Can be called multiple times.
+    pub fn update(&mut self, input: &[u8]) {
+        self.state.update(input);
+    }
+
+    /// Pad and squeeze the state, returning the digest.
+    pub fn finalize(self) -> [u8; 32] {
+        let mut output = [0u8; 32];
+        self.state.finalize(&mut output);
+        output
+    }
+}
+
+impl Default for Keccak256 {
+    fn default() -> Keccak256 {
+        Keccak256::new()
+    }
+}
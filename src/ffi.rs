@@ -0,0 +1,161 @@
+//! A C interface to `Keccak-256` over opaque handles.
+//!
+//! A handle is created by [`keccak_new`], fed with [`keccak_update`], finalized with
+//! [`keccak_finalize`] or [`keccak_finalize_reset`] and released with [`keccak_free`]. The
+//! functions are exported unmangled, a C header declares them as
+//!
+//! ```c
+//! typedef struct KeccakOpaque KeccakOpaque;
+//!
+//! KeccakOpaque *keccak_new(void);
+//! int keccak_update(KeccakOpaque *handle, const uint8_t *data, size_t len);
+//! int keccak_finalize(KeccakOpaque *handle, uint8_t *output, size_t output_len);
+//! int keccak_finalize_reset(KeccakOpaque *handle, uint8_t *output, size_t output_len);
+//! void keccak_free(KeccakOpaque *handle);
+//! ```
+//!
+//! [`keccak_new`]: fn.keccak_new.html
+//! [`keccak_update`]: fn.keccak_update.html
+//! [`keccak_finalize`]: fn.keccak_finalize.html
+//! [`keccak_finalize_reset`]: fn.keccak_finalize_reset.html
+//! [`keccak_free`]: fn.keccak_free.html
+
+use crate::{Hasher, Keccak};
+use alloc::boxed::Box;
+
+/// The call succeeded.
+pub const KECCAK_OK: i32 = 0;
+
+/// The handle, or a buffer of a non-zero length, was null.
+pub const KECCAK_ERR_NULL: i32 = -1;
+
+/// The output was longer than the 32 byte digest.
+pub const KECCAK_ERR_OUTPUT_LEN: i32 = -2;
+
+/// The `Keccak-256` hasher behind a handle, only ever used through a pointer.
+pub struct KeccakOpaque {
+    keccak: Keccak,
+}
+
+/// Creates a new `Keccak-256` hasher and returns its handle, release it with [`keccak_free`].
+///
+/// [`keccak_free`]: fn.keccak_free.html
+#[no_mangle]
+pub extern "C" fn keccak_new() -> *mut KeccakOpaque {
+    Box::into_raw(Box::new(KeccakOpaque {
+        keccak: Keccak::v256(),
+    }))
+}
+
+/// Absorbs `len` bytes from `data`.
+///
+/// Returns [`KECCAK_ERR_NULL`] if `handle` is null, or `data` is null and `len` isn't 0.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [`keccak_new`] and not freed yet. `data` must be null
+/// or valid for reads of `len` bytes.
+///
+/// [`KECCAK_ERR_NULL`]: constant.KECCAK_ERR_NULL.html
+/// [`keccak_new`]: fn.keccak_new.html
+#[no_mangle]
+pub unsafe extern "C" fn keccak_update(
+    handle: *mut KeccakOpaque,
+    data: *const u8,
+    len: usize,
+) -> i32 {
+    let handle = match handle.as_mut() {
+        Some(handle) => handle,
+        None => return KECCAK_ERR_NULL,
+    };
+    if len == 0 {
+        return KECCAK_OK;
+    }
+    if data.is_null() {
+        return KECCAK_ERR_NULL;
+    }
+
+    handle.keccak.update(core::slice::from_raw_parts(data, len));
+    KECCAK_OK
+}
+
+/// Writes the digest of the absorbed input to `output` and resets the hasher, so the handle can
+/// hash another input. An `output_len` shorter than 32 yields the truncated digest.
+///
+/// Returns [`KECCAK_ERR_NULL`] if `handle` is null, or `output` is null and `output_len` isn't 0,
+/// and [`KECCAK_ERR_OUTPUT_LEN`] if `output_len` exceeds 32, the hasher is left untouched then.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [`keccak_new`] and not freed yet. `output` must be null
+/// or valid for writes of `output_len` bytes.
+///
+/// [`KECCAK_ERR_NULL`]: constant.KECCAK_ERR_NULL.html
+/// [`KECCAK_ERR_OUTPUT_LEN`]: constant.KECCAK_ERR_OUTPUT_LEN.html
+/// [`keccak_new`]: fn.keccak_new.html
+#[no_mangle]
+pub unsafe extern "C" fn keccak_finalize(
+    handle: *mut KeccakOpaque,
+    output: *mut u8,
+    output_len: usize,
+) -> i32 {
+    let handle = match handle.as_mut() {
+        Some(handle) => handle,
+        None => return KECCAK_ERR_NULL,
+    };
+    if output_len != 0 && output.is_null() {
+        return KECCAK_ERR_NULL;
+    }
+    if output_len > 32 {
+        return KECCAK_ERR_OUTPUT_LEN;
+    }
+
+    // a null pointer doesn't make a slice, not even an empty one
+    let output: &mut [u8] = if output_len == 0 {
+        &mut []
+    } else {
+        core::slice::from_raw_parts_mut(output, output_len)
+    };
+    handle.keccak.finalize_reset(output);
+    KECCAK_OK
+}
+
+/// Writes the digest of the absorbed input to `output` and resets the hasher in place, keeping
+/// the handle valid for the next input, like the native `finalize_reset`. The same as
+/// [`keccak_finalize`], which resets the hasher too, under a name stating it.
+///
+/// Returns [`KECCAK_ERR_NULL`] if `handle` is null, or `output` is null and `output_len` isn't 0,
+/// and [`KECCAK_ERR_OUTPUT_LEN`] if `output_len` exceeds 32, the hasher is left untouched then.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [`keccak_new`] and not freed yet. `output` must be null
+/// or valid for writes of `output_len` bytes.
+///
+/// [`keccak_finalize`]: fn.keccak_finalize.html
+/// [`KECCAK_ERR_NULL`]: constant.KECCAK_ERR_NULL.html
+/// [`KECCAK_ERR_OUTPUT_LEN`]: constant.KECCAK_ERR_OUTPUT_LEN.html
+/// [`keccak_new`]: fn.keccak_new.html
+#[no_mangle]
+pub unsafe extern "C" fn keccak_finalize_reset(
+    handle: *mut KeccakOpaque,
+    output: *mut u8,
+    output_len: usize,
+) -> i32 {
+    keccak_finalize(handle, output, output_len)
+}
+
+/// Releases a handle, null is ignored.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [`keccak_new`] and not freed yet, it can't be used
+/// afterwards.
+///
+/// [`keccak_new`]: fn.keccak_new.html
+#[no_mangle]
+pub unsafe extern "C" fn keccak_free(handle: *mut KeccakOpaque) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
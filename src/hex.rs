@@ -0,0 +1,24 @@
+//! A minimal lowercase hex encoder for the `finalize_hex` methods.
+
+const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Writes the lowercase hex of `bytes` to the start of `buffer` and returns it as a `str`.
+///
+/// # Panics
+///
+/// Panics if `buffer` is shorter than twice `bytes`.
+pub(crate) fn encode<'a>(bytes: &[u8], buffer: &'a mut [u8]) -> &'a str {
+    let len = bytes.len() * 2;
+    assert!(
+        buffer.len() >= len,
+        "hex buffer must hold at least {} bytes, got {}",
+        len,
+        buffer.len()
+    );
+
+    for (byte, digits) in bytes.iter().zip(buffer.chunks_exact_mut(2)) {
+        digits[0] = DIGITS[usize::from(byte >> 4)];
+        digits[1] = DIGITS[usize::from(byte & 0x0f)];
+    }
+    core::str::from_utf8(&buffer[..len]).expect("hex digits are ASCII")
+}
@@ -0,0 +1,218 @@
+use crate::{bits_to_rate, constant_time_eq, Hasher, Sha3, SPONGE_SIZE};
+
+/// The `HMAC` message authentication codes defined in [`RFC 2104`], instantiated with the
+/// [`SHA3`] hash functions.
+///
+/// Prefer [`KMAC`] for new protocols, `HMAC-SHA3` is provided for interoperability.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["hmac"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{Hasher, HmacSha3};
+/// let mut tag = [0u8; 32];
+/// let mut hmac = HmacSha3::v256(b"key");
+/// hmac.update(b"hello world");
+/// hmac.clone().finalize(&mut tag);
+/// assert!(hmac.verify(&tag));
+/// ```
+///
+/// [`RFC 2104`]: https://www.rfc-editor.org/rfc/rfc2104
+/// [`SHA3`]: struct.Sha3.html
+/// [`KMAC`]: struct.Kmac.html
+#[derive(Clone)]
+pub struct HmacSha3 {
+    inner: Sha3,
+    outer: Sha3,
+    bits: usize,
+}
+
+const _: () =
+    assert!(core::mem::size_of::<HmacSha3>() <= 2 * SPONGE_SIZE + 4 * core::mem::size_of::<u64>());
+
+impl HmacSha3 {
+    /// Creates  new [`HmacSha3`] hasher using `SHA3-224`.
+    ///
+    /// [`HmacSha3`]: struct.HmacSha3.html
+    pub fn v224(key: &[u8]) -> HmacSha3 {
+        HmacSha3::new(key, 224)
+    }
+
+    /// Creates  new [`HmacSha3`] hasher using `SHA3-256`.
+    ///
+    /// [`HmacSha3`]: struct.HmacSha3.html
+    pub fn v256(key: &[u8]) -> HmacSha3 {
+        HmacSha3::new(key, 256)
+    }
+
+    /// Creates  new [`HmacSha3`] hasher using `SHA3-384`.
+    ///
+    /// [`HmacSha3`]: struct.HmacSha3.html
+    pub fn v384(key: &[u8]) -> HmacSha3 {
+        HmacSha3::new(key, 384)
+    }
+
+    /// Creates  new [`HmacSha3`] hasher using `SHA3-512`.
+    ///
+    /// [`HmacSha3`]: struct.HmacSha3.html
+    pub fn v512(key: &[u8]) -> HmacSha3 {
+        HmacSha3::new(key, 512)
+    }
+
+    fn new(key: &[u8], bits: usize) -> HmacSha3 {
+        const IPAD: u8 = 0x36;
+        const OPAD: u8 = 0x5c;
+
+        // the block size of HMAC is the rate of the hash function
+        let block_size = bits_to_rate(bits);
+        let mut block = [0u8; 144];
+        let block = &mut block[..block_size];
+        if key.len() > block_size {
+            Sha3::new(bits).chain(key).finalize(&mut block[..bits / 8]);
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+
+        let mut inner = Sha3::new(bits);
+        let mut outer = Sha3::new(bits);
+        for byte in block.iter_mut() {
+            *byte ^= IPAD;
+        }
+        inner.update(block);
+        for byte in block.iter_mut() {
+            *byte ^= IPAD ^ OPAD;
+        }
+        outer.update(block);
+        for byte in block.iter_mut() {
+            *byte = 0;
+        }
+
+        HmacSha3 { inner, outer, bits }
+    }
+
+    /// Computes the `HMAC` of the absorbed input and compares it with `tag` in constant time.
+    ///
+    /// Returns `false` if `tag` isn't as long as the output of the hash function.
+    pub fn verify(self, tag: &[u8]) -> bool {
+        let len = self.bits / 8;
+        constant_time_eq(&self.into_tag()[..len], tag)
+    }
+
+    /// Computes the full `HMAC`, the first `bits / 8` bytes of the returned block, so truncated
+    /// outputs can copy a prefix of it.
+    fn into_tag(self) -> [u8; 64] {
+        let len = self.bits / 8;
+        let mut tag = [0u8; 64];
+        self.into_outer().finalize(&mut tag[..len]);
+        tag
+    }
+
+    fn check_output_len(&self, len: usize) {
+        assert!(
+            len <= self.bits / 8,
+            "output length exceeds the HMAC, expected at most {} bytes, got {}",
+            self.bits / 8,
+            len
+        );
+    }
+
+    /// Finalizes the inner hash and absorbs it into the outer one, which yields the `HMAC`.
+    fn into_outer(mut self) -> Sha3 {
+        let mut digest = [0u8; 64];
+        let digest = &mut digest[..self.bits / 8];
+        self.inner.finalize(digest);
+        self.outer.update(digest);
+        self.outer
+    }
+}
+
+impl Hasher for HmacSha3 {
+    fn update(&mut self, input: &[u8]) {
+        self.inner.update(input);
+    }
+
+    /// Computes the `HMAC` of the absorbed input, an `output` shorter than the hash function
+    /// yields the truncated `HMAC`.
+    fn finalize(self, output: &mut [u8]) {
+        self.check_output_len(output.len());
+        output.copy_from_slice(&self.into_tag()[..output.len()]);
+    }
+
+    fn finalize_xor(self, output: &mut [u8]) {
+        self.check_output_len(output.len());
+        let tag = self.into_tag();
+        for (byte, tag) in output.iter_mut().zip(tag.iter()) {
+            *byte ^= tag;
+        }
+    }
+
+    fn finalize_scattered(self, outputs: &mut [&mut [u8]]) {
+        self.check_output_len(outputs.iter().map(|output| output.len()).sum());
+        let tag = self.into_tag();
+        let mut tag = &tag[..];
+        for output in outputs.iter_mut() {
+            let (head, rest) = tag.split_at(output.len());
+            output.copy_from_slice(head);
+            tag = rest;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl_write!(HmacSha3);
+
+impl_extend!(HmacSha3);
+
+#[cfg(feature = "digest")]
+fixed_output_mac!(
+    /// `HMAC-SHA3-224` implementing the `digest` [`Mac`] trait. Requires the `digest` feature.
+    ///
+    /// [`Mac`]: https://docs.rs/digest/0.10/digest/trait.Mac.html
+    HmacSha3_224,
+    HmacSha3,
+    HmacSha3::v224,
+    U28,
+    U144
+);
+
+#[cfg(feature = "digest")]
+fixed_output_mac!(
+    /// `HMAC-SHA3-256` implementing the `digest` [`Mac`] trait. Requires the `digest` feature.
+    ///
+    /// [`Mac`]: https://docs.rs/digest/0.10/digest/trait.Mac.html
+    HmacSha3_256,
+    HmacSha3,
+    HmacSha3::v256,
+    U32,
+    U136
+);
+
+#[cfg(feature = "digest")]
+fixed_output_mac!(
+    /// `HMAC-SHA3-384` implementing the `digest` [`Mac`] trait. Requires the `digest` feature.
+    ///
+    /// [`Mac`]: https://docs.rs/digest/0.10/digest/trait.Mac.html
+    HmacSha3_384,
+    HmacSha3,
+    HmacSha3::v384,
+    U48,
+    U104
+);
+
+#[cfg(feature = "digest")]
+fixed_output_mac!(
+    /// `HMAC-SHA3-512` implementing the `digest` [`Mac`] trait. Requires the `digest` feature.
+    ///
+    /// [`Mac`]: https://docs.rs/digest/0.10/digest/trait.Mac.html
+    HmacSha3_512,
+    HmacSha3,
+    HmacSha3::v512,
+    U64,
+    U72
+);
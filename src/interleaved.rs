@@ -0,0 +1,223 @@
+//! `keccak-p[1600]` permutation on bit interleaved lanes, for 32-bit targets.
+//!
+//! Every 64-bit lane is split into two 32-bit words, one holding its even bits and one holding its
+//! odd bits. A rotation of the lane by `2n` rotates both words by `n`, a rotation by `2n + 1`
+//! swaps them and rotates them by `n + 1` and `n`, so the permutation only needs 32-bit
+//! rotations, which 32-bit CPUs have, instead of two shifts and an or of both halves per 64-bit
+//! rotation.
+//!
+//! A sponge absorbing or squeezing whole blocks keeps its state interleaved across all of them,
+//! so only the words of the input and the output are converted between the permutations, see
+//! [`keccak_p_interleaved`]. Everywhere else the state holds plain little-endian lanes, and
+//! [`keccak_p`] converts them around a single permutation.
+
+#![cfg_attr(not(target_pointer_width = "32"), allow(dead_code))]
+
+use crunchy::unroll;
+
+use crate::{PI, RC, RHO, WORDS};
+
+/// A lane split into its even and its odd bits.
+#[derive(Clone, Copy)]
+struct Lane {
+    even: u32,
+    odd: u32,
+}
+
+/// Moves the even bits of `x` to its low half and the odd bits to its high half.
+const fn unshuffle(mut x: u32) -> u32 {
+    let mut t = (x ^ (x >> 1)) & 0x2222_2222;
+    x ^= t ^ (t << 1);
+    t = (x ^ (x >> 2)) & 0x0c0c_0c0c;
+    x ^= t ^ (t << 2);
+    t = (x ^ (x >> 4)) & 0x00f0_00f0;
+    x ^= t ^ (t << 4);
+    t = (x ^ (x >> 8)) & 0x0000_ff00;
+    x ^ t ^ (t << 8)
+}
+
+/// The inverse of [`unshuffle`].
+const fn shuffle(mut x: u32) -> u32 {
+    let mut t = (x ^ (x >> 8)) & 0x0000_ff00;
+    x ^= t ^ (t << 8);
+    t = (x ^ (x >> 4)) & 0x00f0_00f0;
+    x ^= t ^ (t << 4);
+    t = (x ^ (x >> 2)) & 0x0c0c_0c0c;
+    x ^= t ^ (t << 2);
+    t = (x ^ (x >> 1)) & 0x2222_2222;
+    x ^ t ^ (t << 1)
+}
+
+impl Lane {
+    const fn interleave(word: u64) -> Lane {
+        let low = unshuffle(word as u32);
+        let high = unshuffle((word >> 32) as u32);
+        Lane {
+            even: (low & 0xffff) | (high << 16),
+            odd: (low >> 16) | (high & 0xffff_0000),
+        }
+    }
+
+    fn deinterleave(self) -> u64 {
+        let low = shuffle((self.even & 0xffff) | (self.odd << 16));
+        let high = shuffle((self.even >> 16) | (self.odd & 0xffff_0000));
+        u64::from(low) | (u64::from(high) << 32)
+    }
+
+    /// The lane stored in a word, the even bits in the low half and the odd bits in the high
+    /// half.
+    #[inline(always)]
+    fn from_word(word: u64) -> Lane {
+        Lane {
+            even: word as u32,
+            odd: (word >> 32) as u32,
+        }
+    }
+
+    #[inline(always)]
+    fn into_word(self) -> u64 {
+        u64::from(self.even) | (u64::from(self.odd) << 32)
+    }
+
+    #[inline(always)]
+    fn xor(self, other: Lane) -> Lane {
+        Lane {
+            even: self.even ^ other.even,
+            odd: self.odd ^ other.odd,
+        }
+    }
+
+    /// `!self & other`.
+    #[inline(always)]
+    fn and_not(self, other: Lane) -> Lane {
+        Lane {
+            even: !self.even & other.even,
+            odd: !self.odd & other.odd,
+        }
+    }
+
+    /// Rotates the 64-bit lane left by `n` bits. `n` is a constant after unrolling, so only one
+    /// of the branches is compiled.
+    #[inline(always)]
+    fn rotate_left(self, n: u32) -> Lane {
+        if n & 1 == 0 {
+            Lane {
+                even: self.even.rotate_left(n / 2),
+                odd: self.odd.rotate_left(n / 2),
+            }
+        } else {
+            Lane {
+                even: self.odd.rotate_left(n / 2 + 1),
+                odd: self.even.rotate_left(n / 2),
+            }
+        }
+    }
+}
+
+/// The round constants of `keccak-f[1600]`, interleaved at compile time.
+const RC_INTERLEAVED: [Lane; 24] = {
+    let mut constants = [Lane { even: 0, odd: 0 }; 24];
+    let mut i = 0;
+    while i < 24 {
+        constants[i] = Lane::interleave(RC[i]);
+        i += 1;
+    }
+    constants
+};
+
+#[inline(always)]
+fn keccak_round(a: &mut [Lane; WORDS], rc: Lane) {
+    let mut array = [Lane { even: 0, odd: 0 }; 5];
+
+    // Theta
+    unroll! {
+        for x in 0..5 {
+            array[x] = a[x].xor(a[x + 5]).xor(a[x + 10]).xor(a[x + 15]).xor(a[x + 20]);
+        }
+    }
+
+    unroll! {
+        for x in 0..5 {
+            let d = array[(x + 4) % 5].xor(array[(x + 1) % 5].rotate_left(1));
+            unroll! {
+                for y_count in 0..5 {
+                    let y = y_count * 5;
+                    a[y + x] = a[y + x].xor(d);
+                }
+            }
+        }
+    }
+
+    // Rho and pi
+    let mut last = a[1];
+    unroll! {
+        for x in 0..24 {
+            array[0] = a[PI[x]];
+            a[PI[x]] = last.rotate_left(RHO[x]);
+            last = array[0];
+        }
+    }
+
+    // Chi
+    unroll! {
+        for y_step in 0..5 {
+            let y = y_step * 5;
+
+            unroll! {
+                for x in 0..5 {
+                    array[x] = a[y + x];
+                }
+            }
+
+            unroll! {
+                for x in 0..5 {
+                    a[y + x] = array[x].xor(array[(x + 1) % 5].and_not(array[(x + 2) % 5]));
+                }
+            }
+        }
+    }
+
+    // Iota
+    a[0] = a[0].xor(rc);
+}
+
+/// Interleaves a plain lane into a word holding its even bits in the low half and its odd bits
+/// in the high half, the representation of [`keccak_p_interleaved`].
+#[inline]
+pub(crate) fn interleave(word: u64) -> u64 {
+    Lane::interleave(word).into_word()
+}
+
+/// The inverse of [`interleave`].
+#[inline]
+pub(crate) fn deinterleave(word: u64) -> u64 {
+    Lane::from_word(word).deinterleave()
+}
+
+/// `keccak-p[1600, rounds]` computed on words interleaved by [`interleave`].
+pub(crate) fn keccak_p_interleaved(state: &mut [u64; WORDS], rounds: usize) {
+    let mut a = [Lane { even: 0, odd: 0 }; WORDS];
+    for (lane, word) in a.iter_mut().zip(state.iter()) {
+        *lane = Lane::from_word(*word);
+    }
+
+    for rc in &RC_INTERLEAVED[24 - rounds..] {
+        keccak_round(&mut a, *rc);
+    }
+
+    for (word, lane) in state.iter_mut().zip(a.iter()) {
+        *word = lane.into_word();
+    }
+}
+
+/// `keccak-p[1600, rounds]` computed on bit interleaved lanes, converting the plain lanes of
+/// `state` before and after it.
+pub(crate) fn keccak_p(state: &mut [u64; WORDS], rounds: usize) {
+    for word in state.iter_mut() {
+        *word = interleave(*word);
+    }
+    keccak_p_interleaved(state, rounds);
+    for word in state.iter_mut() {
+        *word = deinterleave(*word);
+    }
+}
@@ -2,16 +2,52 @@
 //!
 //! [`here`]: https://eprint.iacr.org/2016/770.pdf
 
-use crate::{bits_to_rate, keccakp::KeccakP, EncodedLen, Hasher, IntoXof, KeccakState, Xof};
+use crate::encode::EncodedLen;
+use crate::{bits_to_rate, Hasher, IntoXof, KeccakP, KeccakState, Xof, SPONGE_SIZE};
 
 fn encode_len(len: usize) -> EncodedLen {
     let len_view = (len as u64).to_be_bytes();
     let offset = len_view.iter().position(|i| *i != 0).unwrap_or(8);
-    let mut buffer = [0u8; 9];
-    buffer[..8].copy_from_slice(&len_view);
-    buffer[8] = 8 - offset as u8;
+    let mut buffer = [0u8; 17];
+    buffer[8..16].copy_from_slice(&len_view);
+    buffer[16] = 8 - offset as u8;
 
-    EncodedLen { offset, buffer }
+    EncodedLen {
+        offset: offset + 8,
+        buffer,
+    }
+}
+
+/// The delimiter of the leaves of the tree, appended to every chunk but the first.
+const LEAF_DELIM: u8 = 0x0b;
+
+/// Computes the chaining value of a `KangarooTwelve` leaf, e.g. to hash the chunks of a custom
+/// tree with the leaves of [`KangarooTwelve`].
+///
+/// It is `TurboSHAKE128(chunk, 0x0B)` truncated to 32 bytes: the chunk is absorbed with a rate of
+/// 168 bytes into `keccak-p[1600, 12]`, padded with the delimiter `0x0B` and a final `0x80`.
+///
+/// [`KangarooTwelve`] splits its input, followed by the customization string and its
+/// `length_encode`, into chunks of 8192 bytes. The first chunk is absorbed as is into the final
+/// node, every other chunk, the last one possibly shorter, is replaced by its chaining value.
+/// With more than one chunk, the first one is followed by `03 00 00 00 00 00 00 00`, the chaining
+/// values by the `length_encode` of their number and `FF FF`, and the final node is padded with
+/// `0x06`. A single chunk is padded with `0x07`.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::k12_chunk_cv;
+/// let chaining_value: [u8; 32] = k12_chunk_cv(&[0u8; 8192]);
+/// ```
+///
+/// [`KangarooTwelve`]: struct.KangarooTwelve.html
+pub fn k12_chunk_cv(chunk: &[u8]) -> [u8; 32] {
+    let mut leaf = KeccakState::<KeccakP<12>>::new(bits_to_rate(128), LEAF_DELIM);
+    leaf.update(chunk);
+    let mut chaining_value = [0u8; 32];
+    leaf.finalize(&mut chaining_value);
+    chaining_value
 }
 
 /// The `KangarooTwelve` hash function defined [`here`].
@@ -26,13 +62,18 @@ fn encode_len(len: usize) -> EncodedLen {
 /// [`here`]: https://eprint.iacr.org/2016/770.pdf
 #[derive(Clone)]
 pub struct KangarooTwelve<T> {
-    state: KeccakState<KeccakP>,
-    current_chunk: KeccakState<KeccakP>,
+    state: KeccakState<KeccakP<12>>,
+    current_chunk: KeccakState<KeccakP<12>>,
     custom_string: Option<T>,
     written: usize,
     chunks: usize,
 }
 
+const _: () = assert!(
+    core::mem::size_of::<KangarooTwelve<&[u8]>>()
+        <= 2 * SPONGE_SIZE + 4 * core::mem::size_of::<u64>()
+);
+
 impl<T> KangarooTwelve<T> {
     const MAX_CHUNK_SIZE: usize = 8192;
 
@@ -43,7 +84,7 @@ impl<T> KangarooTwelve<T> {
         let rate = bits_to_rate(128);
         KangarooTwelve {
             state: KeccakState::new(rate, 0),
-            current_chunk: KeccakState::new(rate, 0x0b),
+            current_chunk: KeccakState::new(rate, LEAF_DELIM),
             custom_string: Some(custom_string),
             written: 0,
             chunks: 0,
@@ -51,6 +92,51 @@ impl<T> KangarooTwelve<T> {
     }
 }
 
+#[cfg(feature = "parallel")]
+impl<T: AsRef<[u8]>> KangarooTwelve<T> {
+    /// Absorb `input` like [`update`], hashing its complete 8 KiB chunks on the [`rayon`] thread
+    /// pool. The chaining values are absorbed in the order of the chunks, so the output is the
+    /// same as the serial one. Requires the `parallel` and `k12` features.
+    ///
+    /// [`update`]: trait.Hasher.html#tymethod.update
+    /// [`rayon`]: https://docs.rs/rayon
+    pub fn update_rayon(&mut self, input: &[u8]) {
+        use rayon::prelude::*;
+
+        // serially up to a full leaf chunk, which the first byte of the next chunk finalizes
+        let head = if self.chunks == 0 {
+            2 * Self::MAX_CHUNK_SIZE - self.written
+        } else {
+            Self::MAX_CHUNK_SIZE - self.written
+        };
+        if input.len() <= head + Self::MAX_CHUNK_SIZE {
+            self.update(input);
+            return;
+        }
+        let (head, rest) = input.split_at(head);
+        self.update(head);
+
+        // the last chunk stays unfinished, more input or the custom string may follow it
+        let complete = (rest.len() - 1) / Self::MAX_CHUNK_SIZE * Self::MAX_CHUNK_SIZE;
+        let (complete, tail) = rest.split_at(complete);
+        let leaves: std::vec::Vec<[u8; 32]> = complete
+            .par_chunks(Self::MAX_CHUNK_SIZE)
+            .map(k12_chunk_cv)
+            .collect();
+
+        let mut chaining_value = [0u8; 32];
+        self.current_chunk.clone().finalize(&mut chaining_value);
+        self.current_chunk.reset();
+        self.state.update(&chaining_value);
+        for chaining_value in leaves.iter() {
+            self.state.update(chaining_value);
+        }
+        self.chunks += 1 + leaves.len();
+        self.written = 0;
+        self.update(tail);
+    }
+}
+
 impl<T: AsRef<[u8]>> Hasher for KangarooTwelve<T> {
     fn update(&mut self, input: &[u8]) {
         let mut to_absorb = input;
@@ -60,14 +146,14 @@ impl<T: AsRef<[u8]>> Hasher for KangarooTwelve<T> {
             self.written += todo;
             to_absorb = &to_absorb[todo..];
 
-            if to_absorb.len() > 0 && self.written == Self::MAX_CHUNK_SIZE {
+            if !to_absorb.is_empty() && self.written == Self::MAX_CHUNK_SIZE {
                 self.state.update(&[0x03, 0, 0, 0, 0, 0, 0, 0]);
                 self.written = 0;
                 self.chunks += 1;
             }
         }
 
-        while to_absorb.len() > 0 {
+        while !to_absorb.is_empty() {
             if self.written == Self::MAX_CHUNK_SIZE {
                 let mut chunk_hash = [0u8; 32];
                 let current_chunk = self.current_chunk.clone();
@@ -89,6 +175,30 @@ impl<T: AsRef<[u8]>> Hasher for KangarooTwelve<T> {
         let mut xof = self.into_xof();
         xof.squeeze(output);
     }
+
+    fn finalize_xor(self, output: &mut [u8]) {
+        let mut xof = self.into_xof();
+        xof.squeeze_xor(output);
+    }
+
+    fn finalize_scattered(self, outputs: &mut [&mut [u8]]) {
+        let mut xof = self.into_xof();
+        for output in outputs.iter_mut() {
+            xof.squeeze(output);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: AsRef<[u8]>> std::io::Write for KangarooTwelve<T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 /// The `KangarooTwelve` extendable-output function defined [`here`].
@@ -122,9 +232,11 @@ impl<T: AsRef<[u8]>> Hasher for KangarooTwelve<T> {
 /// [`KangarooTwelve::IntoXof`]: struct.KangarooTwelve.html#impl-IntoXof
 #[derive(Clone)]
 pub struct KangarooTwelveXof {
-    state: KeccakState<KeccakP>,
+    state: KeccakState<KeccakP<12>>,
 }
 
+const _: () = assert!(core::mem::size_of::<KangarooTwelveXof>() <= SPONGE_SIZE);
+
 impl<T: AsRef<[u8]>> IntoXof for KangarooTwelve<T> {
     type Xof = KangarooTwelveXof;
 
@@ -153,8 +265,24 @@ impl<T: AsRef<[u8]>> IntoXof for KangarooTwelve<T> {
     }
 }
 
+impl KangarooTwelveXof {
+    /// Returns the state of the final node, positioned where the squeezing stopped.
+    ///
+    /// Its delimiter depends on whether the input spanned one chunk or more, so resetting it
+    /// doesn't yield a [`KangarooTwelve`] hasher, which a new message needs.
+    ///
+    /// [`KangarooTwelve`]: struct.KangarooTwelve.html
+    pub fn into_state(self) -> KeccakState<KeccakP<12>> {
+        self.state
+    }
+}
+
 impl Xof for KangarooTwelveXof {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output);
     }
+
+    fn squeeze_xor(&mut self, output: &mut [u8]) {
+        self.state.squeeze_xor(output);
+    }
 }
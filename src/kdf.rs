@@ -0,0 +1,109 @@
+use crate::encode::{encode_string, right_encode_bits};
+use crate::{CShake, Hasher, Xof, SPONGE_SIZE};
+
+/// A key derivation function deriving independent labeled sub-keys from one master key, built
+/// on [`cSHAKE256`].
+///
+/// The master key is absorbed once by [`new`]. Every call to [`derive_key`] continues from a copy
+/// of that state, absorbs the label and the output length, both encoded as in [`SP800-185`], and
+/// squeezes the sub-key. Different labels or output lengths yield unrelated sub-keys, the same
+/// label and length always yield the same one.
+///
+/// For keys from a non uniform secret, e.g. a Diffie-Hellman shared secret, [`extract`] and
+/// [`expand`] split the derivation in two steps like `HKDF`.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["kdf"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::KeccakKdf;
+/// let kdf = KeccakKdf::new(b"a 32 byte master key for the kdf");
+/// let mut encryption_key = [0u8; 32];
+/// let mut mac_key = [0u8; 32];
+/// kdf.derive_key(b"encryption", &mut encryption_key);
+/// kdf.derive_key(b"mac", &mut mac_key);
+/// assert_ne!(encryption_key, mac_key);
+/// ```
+///
+/// [`cSHAKE256`]: struct.CShake.html#method.v256
+/// [`new`]: #method.new
+/// [`derive_key`]: #method.derive_key
+/// [`extract`]: #method.extract
+/// [`expand`]: #method.expand
+/// [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
+#[derive(Clone)]
+pub struct KeccakKdf {
+    state: CShake,
+}
+
+const _: () = assert!(core::mem::size_of::<KeccakKdf>() <= SPONGE_SIZE);
+
+impl KeccakKdf {
+    const CUSTOM_STRING: &'static [u8] = b"KeccakKdf";
+    const EXTRACT_CUSTOM_STRING: &'static [u8] = b"KeccakKdf extract";
+
+    /// The length of the pseudorandom keys returned by [`extract`](#method.extract).
+    pub const PRK_LEN: usize = 64;
+
+    /// Creates  new [`KeccakKdf`] absorbing `master_key`.
+    ///
+    /// [`KeccakKdf`]: struct.KeccakKdf.html
+    pub fn new(master_key: &[u8]) -> KeccakKdf {
+        let mut state = CShake::v256(b"", Self::CUSTOM_STRING);
+        state.update_vectored(&encode_string(master_key).parts());
+        KeccakKdf { state }
+    }
+
+    /// Derives the sub-key labeled `label` with the length of `output`.
+    pub fn derive_key(&self, label: &[u8], output: &mut [u8]) {
+        let mut state = self.state.clone();
+        state.update_vectored(&encode_string(label).parts());
+        state.update(right_encode_bits(output.len()).value());
+        state.squeeze(output);
+    }
+
+    /// Extracts a pseudorandom key from the input keying material `ikm` and an optional, possibly
+    /// public, `salt`, the first step of `HKDF`.
+    ///
+    /// The pseudorandom key is `cSHAKE256` customized with `KeccakKdf extract` of
+    /// `encode_string(salt) || encode_string(ikm)`, [`PRK_LEN`] bytes long.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::KeccakKdf;
+    /// let prk = KeccakKdf::extract(b"salt", b"shared secret");
+    /// let mut okm = [0u8; 42];
+    /// KeccakKdf::expand(&prk, b"session keys", &mut okm);
+    /// ```
+    ///
+    /// [`PRK_LEN`]: #associatedconstant.PRK_LEN
+    pub fn extract(salt: &[u8], ikm: &[u8]) -> [u8; Self::PRK_LEN] {
+        let mut state = CShake::v256(b"", Self::EXTRACT_CUSTOM_STRING);
+        state.update_vectored(&encode_string(salt).parts());
+        state.update_vectored(&encode_string(ikm).parts());
+        let mut prk = [0u8; Self::PRK_LEN];
+        state.squeeze(&mut prk);
+        prk
+    }
+
+    /// Expands the pseudorandom key `prk` into the output keying material labeled `info`, the
+    /// second step of `HKDF`.
+    ///
+    /// It is [`derive_key`] of a [`KeccakKdf`] keyed with `prk`, so the output is bound to `info`
+    /// and to its length, and differs from every output of [`extract`]. An empty `output` is
+    /// left as is.
+    ///
+    /// [`derive_key`]: #method.derive_key
+    /// [`KeccakKdf`]: struct.KeccakKdf.html
+    /// [`extract`]: #method.extract
+    pub fn expand(prk: &[u8], info: &[u8], output: &mut [u8]) {
+        KeccakKdf::new(prk).derive_key(info, output);
+    }
+}
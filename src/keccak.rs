@@ -1,6 +1,11 @@
 //! The `Keccak` hash functions.
 
-use super::{bits_to_rate, keccakf::KeccakF, Hasher, KeccakState};
+use core::mem::MaybeUninit;
+
+use super::{
+    bits_to_rate, is_valid_rate, keccak_p_x4, keccakf_const, Domain, Hasher, IntoXof, KeccakError,
+    KeccakF, KeccakState, Permutation, Xof, SPONGE_SIZE, WORDS,
+};
 
 /// The `Keccak` hash functions defined in [`Keccak SHA3 submission`].
 ///
@@ -18,8 +23,10 @@ pub struct Keccak {
     pub state: KeccakState<KeccakF>,
 }
 
+const _: () = assert!(core::mem::size_of::<Keccak>() <= SPONGE_SIZE);
+
 impl Keccak {
-    const DELIM: u8 = 0x01;
+    const DELIM: u8 = Domain::Keccak.delim();
 
     /// Creates  new [`Keccak`] hasher with a security level of 224 bits.
     ///
@@ -49,6 +56,274 @@ impl Keccak {
         Keccak::new(512)
     }
 
+    /// Creates  new [`Keccak`] hasher with a capacity of `capacity_bits` bits and the domain
+    /// separator `delim`.
+    ///
+    /// The digest is half of the capacity long, e.g. `Keccak::with_bits(512, 0x01)` is the same
+    /// as [`v256`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity_bits` isn't a positive multiple of 64 smaller than 1600.
+    ///
+    /// [`Keccak`]: struct.Keccak.html
+    /// [`v256`]: #method.v256
+    pub fn with_bits(capacity_bits: usize, delim: u8) -> Keccak {
+        assert!(
+            capacity_bits != 0 && capacity_bits & 63 == 0 && capacity_bits < WORDS * 64,
+            "invalid sponge capacity of {} bits",
+            capacity_bits
+        );
+        Keccak {
+            state: KeccakState::with_rate(WORDS * 8 - capacity_bits / 8, delim),
+        }
+    }
+
+    /// Creates  new `Keccak-256` hasher keyed by a block of the rate, 136 bytes, holding `key`
+    /// and zeros after it, absorbed and permuted before any input. The key parameterizes the
+    /// initial state, e.g. to separate the hashes of a fixed context, and the message starts at
+    /// the beginning of a block.
+    ///
+    /// Before the key block is permuted, the length of the key and a marker bit are XORed into
+    /// the capacity, which no input can set. So a keyed digest is not the `Keccak-256` digest of
+    /// any message, and keys differing only in trailing zeros key different hashers.
+    ///
+    /// The key isn't kept: [`reset`], [`finalize_reset`] and [`take`] restore the unkeyed
+    /// initial state, so the hasher they leave is plain `Keccak-256`. Clone a keyed hasher before
+    /// absorbing to reuse it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is longer than 136 bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Keccak};
+    /// let mut keyed = Keccak::keyed(b"context key");
+    /// keyed.update(b"message");
+    /// let mut digest = [0u8; 32];
+    /// keyed.finalize(&mut digest);
+    /// ```
+    ///
+    /// [`reset`]: #method.reset
+    /// [`finalize_reset`]: #method.finalize_reset
+    /// [`take`]: #method.take
+    pub fn keyed(key: &[u8]) -> Keccak {
+        const RATE: usize = bits_to_rate(256);
+        assert!(
+            key.len() <= RATE,
+            "key of {} bytes is longer than the {} byte block",
+            key.len(),
+            RATE
+        );
+
+        let mut block = [0u8; RATE];
+        block[..key.len()].copy_from_slice(key);
+        let mut keccak = Keccak::v256();
+        keccak.state.xor_capacity(1 << 63 | key.len() as u64);
+        keccak.state.update(&block);
+        for byte in block.iter_mut() {
+            // SAFETY: `byte` is a valid, aligned `&mut u8`. The volatile write keeps the compiler
+            // from eliding the zeroing of a block that is never read again.
+            unsafe { core::ptr::write_volatile(byte, 0) };
+        }
+        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+        keccak
+    }
+
+    /// Absorb the first `nbits` bits of `data`, see [`KeccakState::update_bits`]. If `nbits`
+    /// isn't a multiple of 8 no more input can be absorbed afterwards.
+    ///
+    /// [`KeccakState::update_bits`]: struct.KeccakState.html#method.update_bits
+    pub fn update_bits(&mut self, data: &[u8], nbits: usize) {
+        self.state.update_bits(data, nbits);
+    }
+
+    /// Pad and squeeze a copy of the state to the output, so the [`Keccak`] hasher can keep
+    /// absorbing input, e.g. for a rolling digest.
+    ///
+    /// [`Keccak`]: struct.Keccak.html
+    pub fn finalize_into(&self, output: &mut [u8]) {
+        self.check_output_len(output.len());
+        self.state.finalize_into(output);
+    }
+
+    /// Pad and squeeze the state to the output, failing with [`KeccakError::OutputTooLong`]
+    /// instead of panicking if `output` is longer than the digest.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Keccak, KeccakError};
+    /// let mut output = [0u8; 64];
+    /// assert_eq!(
+    ///     Keccak::v256().try_finalize(&mut output),
+    ///     Err(KeccakError::OutputTooLong { max: 32, len: 64 })
+    /// );
+    /// assert_eq!(Keccak::v256().try_finalize(&mut output[..32]), Ok(()));
+    /// ```
+    ///
+    /// [`KeccakError::OutputTooLong`]: enum.KeccakError.html#variant.OutputTooLong
+    pub fn try_finalize(self, output: &mut [u8]) -> Result<(), KeccakError> {
+        let max = self.output_len();
+        if output.len() > max {
+            return Err(KeccakError::OutputTooLong {
+                max,
+                len: output.len(),
+            });
+        }
+        self.state.try_finalize(output)
+    }
+
+    /// Pad and squeeze the state to the output, then reset the [`Keccak`] hasher, so it can be
+    /// reused for another input.
+    ///
+    /// The reset hasher is unkeyed, a [`keyed`] one loses its key.
+    ///
+    /// [`Keccak`]: struct.Keccak.html
+    /// [`keyed`]: #method.keyed
+    pub fn finalize_reset(&mut self, output: &mut [u8]) {
+        self.check_output_len(output.len());
+        self.state.finalize_reset(output);
+    }
+
+    /// Resets the [`Keccak`] hasher to its initial state, so it can be reused for another
+    /// input.
+    ///
+    /// [`Keccak`]: struct.Keccak.html
+    pub fn reset(&mut self) {
+        self.state.reset();
+    }
+
+    /// Snapshots the hasher after a fixed prefix, see [`KeccakState::checkpoint`], e.g. for a
+    /// proof-of-work search varying only a nonce after it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Keccak};
+    /// let mut keccak = Keccak::v256();
+    /// keccak.update(b"block header");
+    /// let checkpoint = keccak.checkpoint();
+    ///
+    /// let mut digest = [0u8; 32];
+    /// for nonce in 0u64..16 {
+    ///     keccak.restore_from(&checkpoint);
+    ///     keccak.update(&nonce.to_le_bytes());
+    ///     keccak.finalize_reset(&mut digest);
+    /// }
+    /// ```
+    ///
+    /// [`KeccakState::checkpoint`]: struct.KeccakState.html#method.checkpoint
+    pub fn checkpoint(&self) -> Keccak {
+        Keccak {
+            state: self.state.checkpoint(),
+        }
+    }
+
+    /// Restores a hasher snapshotted by [`checkpoint`], copying it over this one in place.
+    ///
+    /// [`checkpoint`]: #method.checkpoint
+    pub fn restore_from(&mut self, checkpoint: &Keccak) {
+        self.state.restore_from(&checkpoint.state);
+    }
+
+    /// Returns the hasher with the input absorbed so far, finalizing with the domain separation
+    /// byte `delim` instead of the `Keccak` one, see [`KeccakState::with_delim`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state is padded already.
+    ///
+    /// [`KeccakState::with_delim`]: struct.KeccakState.html#method.with_delim
+    pub fn with_delim(self, delim: u8) -> Keccak {
+        Keccak {
+            state: self.state.with_delim(delim),
+        }
+    }
+
+    /// Converts the hasher into a [`Sha3`] hasher of the same security level, continuing from the
+    /// input absorbed so far. Requires the `sha3` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{sha3_256, Hasher, Keccak};
+    /// let sha3 = Keccak::v256().chain(b"abc").into_sha3();
+    /// assert_eq!(sha3.finalize_array::<32>(), sha3_256(b"abc"));
+    /// ```
+    ///
+    /// [`Sha3`]: struct.Sha3.html
+    #[cfg(feature = "sha3")]
+    pub fn into_sha3(self) -> crate::Sha3 {
+        crate::Sha3::from_state(self.state.with_delim(Domain::Sha3.delim()))
+    }
+
+    /// Takes the hasher out, leaving a reset [`Keccak`] hasher of the same variant in its place.
+    ///
+    /// Unlike [`core::mem::take`], which leaves [`Default`], i.e. `Keccak-256`, behind, a
+    /// `Keccak-512` slot stays a `Keccak-512` slot, so hashers can be pooled without creating new
+    /// ones. The hasher left behind is unkeyed though, a [`keyed`] slot loses its key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Keccak};
+    /// let mut slot = Keccak::v512();
+    /// slot.update(b"first");
+    /// let first = slot.take();
+    ///
+    /// let mut output = [0u8; 64];
+    /// first.finalize(&mut output);
+    /// ```
+    ///
+    /// [`Keccak`]: struct.Keccak.html
+    /// [`core::mem::take`]: https://doc.rust-lang.org/core/mem/fn.take.html
+    /// [`Default`]: #impl-Default
+    /// [`keyed`]: #method.keyed
+    pub fn take(&mut self) -> Keccak {
+        let mut fresh = self.clone();
+        fresh.reset();
+        core::mem::replace(self, fresh)
+    }
+
+    /// Puts `new` in place of the hasher and returns the hasher it replaced.
+    pub fn replace(&mut self, new: Keccak) -> Keccak {
+        core::mem::replace(self, new)
+    }
+
+    /// Pad and squeeze the state, then compare the output with `expected` in constant time.
+    ///
+    /// Returns `false` if the length of `expected` doesn't match the Keccak variant.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Keccak};
+    /// let mut output = [0u8; 32];
+    /// let mut hasher = Keccak::v256();
+    /// hasher.update(b"hello world");
+    /// hasher.clone().finalize(&mut output);
+    /// assert!(hasher.verify(&output));
+    /// ```
+    pub fn verify(self, expected: &[u8]) -> bool {
+        expected.len() == self.output_len() && self.state.verify(expected)
+    }
+
+    fn output_len(&self) -> usize {
+        (WORDS * 8 - self.state.rate) / 2
+    }
+
+    fn check_output_len(&self, len: usize) {
+        debug_assert!(
+            len <= self.output_len(),
+            "output length exceeds the Keccak variant, expected at most {} bytes, got {}",
+            self.output_len(),
+            len
+        );
+    }
+
     fn new(bits: usize) -> Keccak {
         Keccak {
             state: KeccakState::new(bits_to_rate(bits), Self::DELIM),
@@ -56,6 +331,15 @@ impl Keccak {
     }
 }
 
+/// Creates  new [`Keccak`] hasher with a security level of 256 bits, i.e. `Keccak-256`.
+///
+/// [`Keccak`]: struct.Keccak.html
+impl Default for Keccak {
+    fn default() -> Keccak {
+        Keccak::v256()
+    }
+}
+
 impl Hasher for Keccak {
     /// Absorb additional input. Can be called multiple times.
     ///
@@ -70,12 +354,19 @@ impl Hasher for Keccak {
     /// keccak.update(b" world");
     /// # }
     /// ```
+    #[inline]
     fn update(&mut self, input: &[u8]) {
         self.state.update(input);
     }
 
     /// Pad and squeeze the state to the output.
     ///
+    /// A shorter `output` receives the leading bytes of the full digest, it is truncated rather
+    /// than computed differently. Debug builds panic if `output` is longer than the digest of the
+    /// Keccak variant, longer outputs can be squeezed with [`IntoXof`].
+    ///
+    /// [`IntoXof`]: trait.IntoXof.html
+    ///
     /// # Example
     ///
     /// ```
@@ -83,12 +374,626 @@ impl Hasher for Keccak {
     /// #
     /// # fn main() {
     /// # let keccak = Keccak::v256();
-    /// # let mut output = [0u8; 32];
-    /// keccak.finalize(&mut output);
+    /// let mut output = [0u8; 32];
+    /// keccak.clone().finalize(&mut output);
+    ///
+    /// // truncated digests are prefixes of the full one
+    /// let mut truncated = [0u8; 20];
+    /// keccak.finalize(&mut truncated);
+    /// assert_eq!(truncated, output[..20]);
     /// # }
     /// #
     /// ```
     fn finalize(self, output: &mut [u8]) {
+        self.check_output_len(output.len());
         self.state.finalize(output);
     }
+
+    fn finalize_uninit(self, output: &mut [MaybeUninit<u8>]) -> &mut [u8] {
+        self.check_output_len(output.len());
+        self.state.finalize_uninit(output)
+    }
+
+    fn finalize_xor(self, output: &mut [u8]) {
+        self.check_output_len(output.len());
+        self.state.finalize_xor(output);
+    }
+
+    fn finalize_scattered(self, outputs: &mut [&mut [u8]]) {
+        self.check_output_len(outputs.iter().map(|output| output.len()).sum());
+        self.state.finalize_scattered(outputs);
+    }
+
+    fn finalize_array<const N: usize>(self) -> [u8; N] {
+        self.check_output_len(N);
+        let mut output = [0u8; N];
+        self.state.finalize(&mut output);
+        output
+    }
+}
+
+#[cfg(feature = "std")]
+impl_write!(Keccak);
+
+impl_extend!(Keccak);
+
+/// Prints the rate and the offset of the sponge, with the state redacted.
+impl core::fmt::Debug for Keccak {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.state.debug("Keccak", f)
+    }
+}
+
+/// Allows [`Keccak`] to be used as the hasher of a `HashMap`.
+///
+/// `finish` finalizes a copy of the state, so more input can be written afterwards.
+///
+/// With the `std` feature [`Keccak`] implements `io::Write` too, so with both traits in scope
+/// `keccak.write(..)` is ambiguous and has to name the trait:
+///
+/// ```
+/// # use tiny_keccak::Keccak;
+/// let mut keccak = Keccak::v256();
+/// core::hash::Hasher::write(&mut keccak, b"abc");
+/// let hash = core::hash::Hasher::finish(&keccak);
+/// ```
+///
+/// [`Keccak`]: struct.Keccak.html
+impl core::hash::Hasher for Keccak {
+    fn write(&mut self, bytes: &[u8]) {
+        self.state.update(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        let mut output = [0u8; 8];
+        self.state.clone().finalize(&mut output);
+        u64::from_le_bytes(output)
+    }
+}
+
+/// Configures a [`Keccak`] hasher with a custom rate, domain separator or digest length.
+///
+/// Every parameter is optional: the rate defaults to the one of the digest length, i.e. a
+/// capacity of twice the digest, the digest length to half of the capacity, and the domain
+/// separator to the `Keccak` one. Without any parameter the builder builds `Keccak-256`. All of
+/// them are validated by [`build`].
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{Hasher, KeccakBuilder};
+/// // Keccak-224 with the SHA-3 domain separator, i.e. SHA3-224
+/// let sha3_224 = KeccakBuilder::new().output_len(28).delim(0x06).build().unwrap();
+/// let digest: [u8; 28] = sha3_224.chain(b"abc").finalize_array();
+/// ```
+///
+/// [`Keccak`]: struct.Keccak.html
+/// [`build`]: #method.build
+#[derive(Clone, Copy, Default, Debug)]
+pub struct KeccakBuilder {
+    rate: Option<usize>,
+    delim: Option<u8>,
+    output_len: Option<usize>,
+}
+
+impl KeccakBuilder {
+    /// Creates  new [`KeccakBuilder`] without any parameter set, building `Keccak-256`.
+    ///
+    /// [`KeccakBuilder`]: struct.KeccakBuilder.html
+    pub fn new() -> KeccakBuilder {
+        KeccakBuilder::default()
+    }
+
+    /// Sets the rate of the sponge in bytes, see [`is_valid_rate`].
+    ///
+    /// [`is_valid_rate`]: fn.is_valid_rate.html
+    pub fn rate(mut self, rate: usize) -> KeccakBuilder {
+        self.rate = Some(rate);
+        self
+    }
+
+    /// Sets the domain separator appended to the input.
+    pub fn delim(mut self, delim: u8) -> KeccakBuilder {
+        self.delim = Some(delim);
+        self
+    }
+
+    /// Sets the length of the digest in bytes, which picks the rate leaving a capacity of twice
+    /// the length. With a rate set too, the length must be the single one of that rate.
+    pub fn output_len(mut self, output_len: usize) -> KeccakBuilder {
+        self.output_len = Some(output_len);
+        self
+    }
+
+    /// Builds the configured [`Keccak`] hasher.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`KeccakError::InvalidRate`] if the rate isn't valid and with
+    /// [`KeccakError::InvalidOutputLen`] if no valid rate has the digest length. With both set,
+    /// it fails with [`KeccakError::OutputTooLong`] or [`KeccakError::OutputSizeMismatch`] if the
+    /// digest length isn't the one of the rate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakBuilder, KeccakError};
+    /// assert_eq!(
+    ///     KeccakBuilder::new().rate(136).output_len(64).build().err(),
+    ///     Some(KeccakError::OutputTooLong { max: 32, len: 64 })
+    /// );
+    /// assert_eq!(
+    ///     KeccakBuilder::new().output_len(21).build().err(),
+    ///     Some(KeccakError::InvalidOutputLen { len: 21 })
+    /// );
+    /// ```
+    ///
+    /// [`Keccak`]: struct.Keccak.html
+    /// [`KeccakError::InvalidRate`]: enum.KeccakError.html#variant.InvalidRate
+    /// [`KeccakError::InvalidOutputLen`]: enum.KeccakError.html#variant.InvalidOutputLen
+    /// [`KeccakError::OutputTooLong`]: enum.KeccakError.html#variant.OutputTooLong
+    /// [`KeccakError::OutputSizeMismatch`]: enum.KeccakError.html#variant.OutputSizeMismatch
+    pub fn build(self) -> Result<Keccak, KeccakError> {
+        let rate = match (self.rate, self.output_len) {
+            (Some(rate), _) => rate,
+            (None, Some(len)) => {
+                let rate = (WORDS * 8).saturating_sub(len.saturating_mul(2));
+                if !is_valid_rate(rate) {
+                    return Err(KeccakError::InvalidOutputLen { len });
+                }
+                rate
+            }
+            (None, None) => bits_to_rate(256),
+        };
+        let state = KeccakState::try_with_rate(rate, self.delim.unwrap_or(Keccak::DELIM))?;
+        let keccak = Keccak { state };
+        // the digest length of `Keccak` follows from its rate, a different one can't be kept
+        if let Some(len) = self.output_len {
+            let expected = keccak.output_len();
+            if len > expected {
+                return Err(KeccakError::OutputTooLong { max: expected, len });
+            }
+            if len < expected {
+                return Err(KeccakError::OutputSizeMismatch { expected, len });
+            }
+        }
+        Ok(keccak)
+    }
+}
+
+/// Builds `Keccak-256` hashers for a `HashMap`.
+///
+/// # Example
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use tiny_keccak::KeccakBuildHasher;
+/// let mut map = HashMap::with_hasher(KeccakBuildHasher::default());
+/// map.insert("key", "value");
+/// assert_eq!(map.get("key"), Some(&"value"));
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct KeccakBuildHasher;
+
+impl core::hash::BuildHasher for KeccakBuildHasher {
+    type Hasher = Keccak;
+
+    fn build_hasher(&self) -> Keccak {
+        Keccak::v256()
+    }
+}
+
+fixed_output_hasher!(
+    /// `Keccak-224` with the digest length in its type.
+    ///
+    /// With the `digest` feature it implements the `digest` traits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Keccak224};
+    /// let mut hasher = Keccak224::new();
+    /// hasher.update(b"hello world");
+    /// let digest: [u8; 28] = hasher.finalize();
+    /// ```
+    Keccak224,
+    Keccak,
+    v224,
+    28,
+    U28
+);
+
+fixed_output_hasher!(
+    /// `Keccak-256` with the digest length in its type.
+    ///
+    /// With the `digest` feature it implements the `digest` traits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Keccak256};
+    /// let mut hasher = Keccak256::new();
+    /// hasher.update(b"hello world");
+    /// let digest: [u8; 32] = hasher.finalize();
+    /// ```
+    ///
+    /// Digests of the wrong length don't compile:
+    ///
+    /// ```compile_fail
+    /// # use tiny_keccak::Keccak256;
+    /// let digest: [u8; 64] = Keccak256::new().finalize();
+    /// ```
+    Keccak256,
+    Keccak,
+    v256,
+    32,
+    U32
+);
+
+fixed_output_hasher!(
+    /// `Keccak-384` with the digest length in its type.
+    ///
+    /// With the `digest` feature it implements the `digest` traits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Keccak384};
+    /// let mut hasher = Keccak384::new();
+    /// hasher.update(b"hello world");
+    /// let digest: [u8; 48] = hasher.finalize();
+    /// ```
+    Keccak384,
+    Keccak,
+    v384,
+    48,
+    U48
+);
+
+fixed_output_hasher!(
+    /// `Keccak-512` with the digest length in its type.
+    ///
+    /// With the `digest` feature it implements the `digest` traits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Keccak512};
+    /// let mut hasher = Keccak512::new();
+    /// hasher.update(b"hello world");
+    /// let digest: [u8; 64] = hasher.finalize();
+    /// ```
+    Keccak512,
+    Keccak,
+    v512,
+    64,
+    U64
+);
+
+/// The `Keccak` sponge used as an extendable-output function.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{Keccak, Xof, IntoXof, Hasher};
+/// let input = b"hello world";
+/// let mut output = [0u8; 64];
+/// let mut keccak = Keccak::v256();
+/// keccak.update(input);
+/// let mut xof = keccak.into_xof();
+/// xof.squeeze(&mut output[..32]);
+/// xof.squeeze(&mut output[32..]);
+/// ```
+///
+/// ---
+///
+/// [`KeccakXof`] can be created only by using [`Keccak::IntoXof`] interface. It cannot absorb
+/// any more input.
+///
+/// [`KeccakXof`]: struct.KeccakXof.html
+/// [`Keccak::IntoXof`]: struct.Keccak.html#impl-IntoXof
+#[derive(Clone)]
+pub struct KeccakXof {
+    state: KeccakState<KeccakF>,
+}
+
+const _: () = assert!(core::mem::size_of::<KeccakXof>() <= SPONGE_SIZE);
+
+impl IntoXof for Keccak {
+    type Xof = KeccakXof;
+
+    fn into_xof(self) -> KeccakXof {
+        KeccakXof { state: self.state }
+    }
+}
+
+impl KeccakXof {
+    /// Returns the sponge state, positioned where the squeezing stopped.
+    pub fn into_state(self) -> KeccakState<KeccakF> {
+        self.state
+    }
+
+    /// Abandons squeezing and returns the [`Keccak`] hasher reset to its initial state, reusing
+    /// the state of the reader for the next input.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, IntoXof, Keccak, Xof};
+    /// let mut output = [0u8; 64];
+    /// let mut xof = Keccak::v256().chain(b"first message").into_xof();
+    /// xof.squeeze(&mut output);
+    ///
+    /// let mut keccak = xof.reset_to_absorb();
+    /// keccak.update(b"second message");
+    /// keccak.finalize(&mut output[..32]);
+    /// ```
+    ///
+    /// [`Keccak`]: struct.Keccak.html
+    pub fn reset_to_absorb(self) -> Keccak {
+        let mut state = self.state;
+        state.reset();
+        Keccak { state }
+    }
+}
+
+impl Xof for KeccakXof {
+    fn squeeze(&mut self, output: &mut [u8]) {
+        self.state.squeeze(output);
+    }
+
+    fn squeeze_xor(&mut self, output: &mut [u8]) {
+        self.state.squeeze_xor(output);
+    }
+}
+
+hash_function!(
+    /// Computes the `Keccak-224` digest of `input`.
+    keccak224,
+    Keccak,
+    v224,
+    28
+);
+
+hash_function!(
+    /// Computes the `Keccak-256` digest of `input`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::keccak256;
+    /// #
+    /// let digest = keccak256(b"");
+    /// assert_eq!(
+    ///     digest,
+    ///     [
+    ///         0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c,
+    ///         0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    ///         0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b,
+    ///         0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+    ///     ]
+    /// );
+    /// ```
+    keccak256,
+    Keccak,
+    v256,
+    32
+);
+
+hash_function!(
+    /// Computes the `Keccak-384` digest of `input`.
+    keccak384,
+    Keccak,
+    v384,
+    48
+);
+
+hash_function!(
+    /// Computes the `Keccak-512` digest of `input`.
+    keccak512,
+    Keccak,
+    v512,
+    64
+);
+
+/// `Keccak-256` of an input shorter than the rate and a multiple of 8 bytes long, XORed straight
+/// into the state words, padded and permuted once.
+fn keccak256_short<const N: usize>(input: &[u8; N]) -> [u8; 32] {
+    const RATE: usize = 136;
+    debug_assert!(N < RATE && N & 7 == 0);
+
+    let mut state = [0u64; WORDS];
+    for (word, chunk) in state.iter_mut().zip(input.chunks_exact(8)) {
+        let mut le = [0u8; 8];
+        le.copy_from_slice(chunk);
+        *word = u64::from_le_bytes(le);
+    }
+    state[N / 8] ^= u64::from(Keccak::DELIM);
+    state[RATE / 8 - 1] ^= 0x80 << 56;
+    KeccakF::execute(&mut state);
+
+    let mut output = [0u8; 32];
+    for (chunk, word) in output.chunks_exact_mut(8).zip(state.iter()) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    output
+}
+
+/// Computes the `Keccak-256` digest of a 32-byte input, e.g. a storage slot or a hash, without
+/// the bookkeeping of the streaming hasher. The same as [`keccak256`].
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{keccak256, keccak256_32};
+/// assert_eq!(keccak256_32(&[7; 32]), keccak256(&[7; 32]));
+/// ```
+///
+/// [`keccak256`]: fn.keccak256.html
+pub fn keccak256_32(input: &[u8; 32]) -> [u8; 32] {
+    keccak256_short(input)
+}
+
+/// Computes the `Keccak-256` digest of a 64-byte input, e.g. an uncompressed public key to
+/// derive an Ethereum address from, without the bookkeeping of the streaming hasher. The same as
+/// [`keccak256`].
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::keccak256_64;
+/// let public_key = [0x42; 64];
+/// let address = &keccak256_64(&public_key)[12..];
+/// ```
+///
+/// [`keccak256`]: fn.keccak256.html
+pub fn keccak256_64(input: &[u8; 64]) -> [u8; 32] {
+    keccak256_short(input)
+}
+
+/// Computes the `Keccak-256` digest of `input` as a `const fn`, e.g. to bake the hash of a
+/// domain separator into a constant. The same as [`keccak256`], which is much faster at runtime.
+///
+/// The input is absorbed a byte at a time into [`keccakf_const`], so long inputs take a while to
+/// evaluate at compile time.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{keccak256, keccak256_const};
+/// const TAG: [u8; 32] = keccak256_const(b"mydomain");
+/// assert_eq!(TAG, keccak256(b"mydomain"));
+/// ```
+///
+/// [`keccak256`]: fn.keccak256.html
+/// [`keccakf_const`]: fn.keccakf_const.html
+pub const fn keccak256_const(input: &[u8]) -> [u8; 32] {
+    const RATE: usize = bits_to_rate(256);
+
+    let mut state = [0u64; WORDS];
+    let mut i = 0;
+    while i < input.len() {
+        state[i % RATE / 8] ^= (input[i] as u64) << (8 * (i % 8));
+        i += 1;
+        if i % RATE == 0 {
+            state = keccakf_const(state);
+        }
+    }
+    state[i % RATE / 8] ^= (Keccak::DELIM as u64) << (8 * (i % 8));
+    state[RATE / 8 - 1] ^= 0x80 << 56;
+    state = keccakf_const(state);
+
+    let mut output = [0u8; 32];
+    let mut j = 0;
+    while j < 32 {
+        output[j] = (state[j / 8] >> (8 * (j % 8))) as u8;
+        j += 1;
+    }
+    output
+}
+
+/// Computes the `Keccak-256` digest of `left || right`, e.g. an internal node of a Merkle tree
+/// from the hashes of its children. The 64 bytes fit in one block, so the children are hashed
+/// with a single permutation. The same as [`keccak256`] of the concatenation.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{keccak256, keccak256_pair};
+/// let (left, right) = (keccak256(b"left"), keccak256(b"right"));
+/// let node = keccak256_pair(&left, &right);
+/// assert_eq!(node, keccak256(&[left, right].concat()));
+/// ```
+///
+/// [`keccak256`]: fn.keccak256.html
+pub fn keccak256_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut input = [0u8; 64];
+    input[..32].copy_from_slice(left);
+    input[32..].copy_from_slice(right);
+    keccak256_short(&input)
+}
+
+/// Computes the `Keccak-256` digests of four independent inputs at once.
+///
+/// The four sponges are permuted together, which is faster than hashing the inputs one by one
+/// when the `avx2` feature is enabled and supported by the CPU.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::keccak256_x4;
+/// #
+/// let digests = keccak256_x4([b"a", b"b", b"c", b"d"]);
+/// ```
+pub fn keccak256_x4(inputs: [&[u8]; 4]) -> [[u8; 32]; 4] {
+    const RATE: usize = 136;
+
+    let mut states = [[0u64; WORDS]; 4];
+    let mut outputs = [[0u8; 32]; 4];
+    // the last block of every input holds the padding
+    let mut blocks = [0usize; 4];
+    for (blocks, input) in blocks.iter_mut().zip(inputs.iter()) {
+        *blocks = input.len() / RATE + 1;
+    }
+
+    let max_blocks = blocks.iter().copied().max().unwrap_or(0);
+    for block in 0..max_blocks {
+        for i in 0..4 {
+            if block >= blocks[i] {
+                continue;
+            }
+
+            let mut padded = [0u8; RATE];
+            let data = &inputs[i][block * RATE..];
+            if block + 1 < blocks[i] {
+                padded.copy_from_slice(&data[..RATE]);
+            } else {
+                padded[..data.len()].copy_from_slice(data);
+                padded[data.len()] ^= Keccak::DELIM;
+                padded[RATE - 1] ^= 0x80;
+            }
+
+            for (word, bytes) in states[i].iter_mut().zip(padded.chunks(8)) {
+                let mut le = [0u8; 8];
+                le.copy_from_slice(bytes);
+                *word ^= u64::from_le_bytes(le);
+            }
+        }
+
+        keccak_p_x4(&mut states, 24);
+
+        for i in 0..4 {
+            if block + 1 == blocks[i] {
+                for (bytes, word) in outputs[i].chunks_mut(8).zip(states[i].iter()) {
+                    bytes.copy_from_slice(&word.to_le_bytes());
+                }
+            }
+        }
+    }
+
+    outputs
+}
+
+/// Hashes `messages` with `Keccak-256`, prefixing every message with its `left_encode`d length
+/// in bits, and squeezes `output`.
+///
+/// The prefixes make the encoding unambiguous, so `["ab", "c"]`, `["a", "bc"]` and `["abc"]`
+/// hash to different digests. Unlike `TupleHash` there is no customization string and no
+/// cSHAKE framing, the messages are absorbed by the plain `Keccak` sponge. `output` can be of any
+/// length.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::hash_messages;
+/// let mut output = [0u8; 32];
+/// hash_messages(&[b"from", b"to"], &mut output);
+/// ```
+pub fn hash_messages(messages: &[&[u8]], output: &mut [u8]) {
+    let mut keccak = Keccak::v256();
+    for message in messages {
+        keccak.update(crate::encode::left_encode_bits(message.len()).value());
+        keccak.update(message);
+    }
+    keccak.into_xof().squeeze(output);
 }
@@ -0,0 +1,82 @@
+use crate::{Hasher, Keccak, SPONGE_SIZE};
+
+/// A message authentication code computing `Keccak-256(key || message)`.
+///
+/// Unlike Merkle–Damgård hashes such as SHA-2, where `H(key || message)` is broken by length
+/// extension, a sponge never outputs its full state: the capacity, 512 bits for `Keccak-256`, is
+/// never squeezed, so the digest of `key || message` can't be extended to that of
+/// `key || message || suffix` without knowing the key. Prefixing the key is therefore a sound MAC
+/// for Keccak and `HMAC` isn't needed.
+///
+/// Use keys of a fixed length for a given application, otherwise the boundary between the key
+/// and the message is ambiguous. Prefer [`KMAC`], which encodes the key length, when a standard
+/// MAC is required.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["keccak_mac"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{Hasher, KeccakMac};
+/// let mut tag = [0u8; 32];
+/// let mut mac = KeccakMac::new(b"a 32 byte key for keccak mac....");
+/// mac.update(b"hello world");
+/// mac.clone().finalize(&mut tag);
+/// assert!(mac.verify(&tag));
+/// ```
+///
+/// [`KMAC`]: struct.Kmac.html
+#[derive(Clone)]
+pub struct KeccakMac {
+    keccak: Keccak,
+}
+
+const _: () = assert!(core::mem::size_of::<KeccakMac>() <= SPONGE_SIZE);
+
+impl KeccakMac {
+    /// Creates  new [`KeccakMac`] hasher absorbing `key`.
+    ///
+    /// [`KeccakMac`]: struct.KeccakMac.html
+    pub fn new(key: &[u8]) -> KeccakMac {
+        KeccakMac {
+            keccak: Keccak::v256().chain(key),
+        }
+    }
+
+    /// Computes the tag of the absorbed message and compares it with `tag` in constant time.
+    ///
+    /// Returns `false` if `tag` isn't 32 bytes long.
+    pub fn verify(self, tag: &[u8]) -> bool {
+        self.keccak.verify(tag)
+    }
+}
+
+impl Hasher for KeccakMac {
+    fn update(&mut self, input: &[u8]) {
+        self.keccak.update(input);
+    }
+
+    /// Computes the tag of the absorbed message, an `output` shorter than 32 bytes yields the
+    /// truncated tag.
+    fn finalize(self, output: &mut [u8]) {
+        self.keccak.finalize(output);
+    }
+
+    fn finalize_xor(self, output: &mut [u8]) {
+        self.keccak.finalize_xor(output);
+    }
+
+    fn finalize_scattered(self, outputs: &mut [&mut [u8]]) {
+        self.keccak.finalize_scattered(outputs);
+    }
+}
+
+#[cfg(feature = "std")]
+impl_write!(KeccakMac);
+
+impl_extend!(KeccakMac);
@@ -1,40 +1,69 @@
-use crate::{Buffer, Permutation};
+keccak_function!("`keccak-f[1600, 24]`", keccakf, 24);
 
-const ROUNDS: usize = 24;
+/// `keccak-f[1600, 24]` as a `const fn`, taking and returning the state by value, e.g. to
+/// compute a constant at compile time. It is the same permutation as [`keccakf`], written with
+/// `while` loops only, use [`keccakf`] at runtime.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{keccakf, keccakf_const};
+/// const PERMUTED: [u64; 25] = keccakf_const([0; 25]);
+///
+/// let mut state = [0u64; 25];
+/// keccakf(&mut state);
+/// assert_eq!(state, PERMUTED);
+/// ```
+///
+/// [`keccakf`]: fn.keccakf.html
+pub const fn keccakf_const(mut a: [u64; crate::WORDS]) -> [u64; crate::WORDS] {
+    use crate::{PI, RC, RHO};
 
-const RC: [u64; ROUNDS] = [
-    1u64,
-    0x8082u64,
-    0x800000000000808au64,
-    0x8000000080008000u64,
-    0x808bu64,
-    0x80000001u64,
-    0x8000000080008081u64,
-    0x8000000000008009u64,
-    0x8au64,
-    0x88u64,
-    0x80008009u64,
-    0x8000000au64,
-    0x8000808bu64,
-    0x800000000000008bu64,
-    0x8000000000008089u64,
-    0x8000000000008003u64,
-    0x8000000000008002u64,
-    0x8000000000000080u64,
-    0x800au64,
-    0x800000008000000au64,
-    0x8000000080008081u64,
-    0x8000000000008080u64,
-    0x80000001u64,
-    0x8000000080008008u64,
-];
+    let mut round = 0;
+    while round < 24 {
+        // Theta
+        let mut array = [0u64; 5];
+        let mut x = 0;
+        while x < 5 {
+            array[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+            x += 1;
+        }
+        x = 0;
+        while x < 5 {
+            let d = array[(x + 4) % 5] ^ array[(x + 1) % 5].rotate_left(1);
+            let mut y = 0;
+            while y < 25 {
+                a[y + x] ^= d;
+                y += 5;
+            }
+            x += 1;
+        }
 
-keccak_function!("`keccak-f[1600, 24]`", keccakf, ROUNDS, RC);
+        // Rho and pi
+        let mut last = a[1];
+        let mut i = 0;
+        while i < 24 {
+            let next = a[PI[i]];
+            a[PI[i]] = last.rotate_left(RHO[i]);
+            last = next;
+            i += 1;
+        }
 
-pub struct KeccakF;
+        // Chi
+        let mut y = 0;
+        while y < 25 {
+            let row = [a[y], a[y + 1], a[y + 2], a[y + 3], a[y + 4]];
+            x = 0;
+            while x < 5 {
+                a[y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+                x += 1;
+            }
+            y += 5;
+        }
 
-impl Permutation for KeccakF {
-    fn execute(buffer: &mut Buffer) {
-        keccakf(buffer.words());
+        // Iota
+        a[0] ^= RC[round];
+        round += 1;
     }
+    a
 }
@@ -1,4 +1,8 @@
-use crate::{bits_to_rate, left_encode, right_encode, CShake, Hasher, IntoXof, Xof};
+use crate::cshake::check_security_level;
+use crate::encode::{left_encode, left_encode_bits, right_encode, right_encode_bits};
+use crate::{
+    bits_to_rate, CShake, Hasher, IntoXof, KeccakError, KeccakF, KeccakState, Xof, SPONGE_SIZE,
+};
 
 /// The `KMAC` pseudo-random functions defined in [`SP800-185`].
 ///
@@ -27,6 +31,8 @@ pub struct Kmac {
     state: CShake,
 }
 
+const _: () = assert!(core::mem::size_of::<Kmac>() <= SPONGE_SIZE);
+
 impl Kmac {
     /// Creates  new [`Kmac`] hasher with a security level of 128 bits.
     ///
@@ -42,15 +48,80 @@ impl Kmac {
         Kmac::new(key, custom_string, 256)
     }
 
+    /// Creates a new [`Kmac`] hasher with a security level of `bits` bits, failing with
+    /// [`KeccakError::InvalidSecurityLevel`] if it is neither 128 nor 256. Strings of any length
+    /// are encodable, so `key` or `custom_string` never fail.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakError, Kmac};
+    /// assert!(Kmac::try_new(b"key", b"", 256).is_ok());
+    /// assert!(matches!(
+    ///     Kmac::try_new(b"key", b"", 512),
+    ///     Err(KeccakError::InvalidSecurityLevel { bits: 512 })
+    /// ));
+    /// ```
+    ///
+    /// [`Kmac`]: struct.Kmac.html
+    /// [`KeccakError::InvalidSecurityLevel`]: enum.KeccakError.html#variant.InvalidSecurityLevel
+    pub fn try_new(key: &[u8], custom_string: &[u8], bits: usize) -> Result<Kmac, KeccakError> {
+        check_security_level(bits)?;
+        Ok(Kmac::new(key, custom_string, bits))
+    }
+
     fn new(key: &[u8], custom_string: &[u8], bits: usize) -> Kmac {
         let rate = bits_to_rate(bits);
         let mut state = CShake::new(b"KMAC", custom_string, bits);
         state.update(left_encode(rate).value());
-        state.update(left_encode(key.len() * 8).value());
+        state.update(left_encode_bits(key.len()).value());
         state.update(key);
         state.fill_block();
         Kmac { state }
     }
+
+    /// The shortest tag [`verify`] accepts, 128 bits.
+    ///
+    /// [`verify`]: #method.verify
+    pub const MIN_TAG_LEN: usize = 16;
+
+    /// Computes the `KMAC` of the absorbed input with the length of `tag` and compares it with
+    /// `tag` in constant time.
+    ///
+    /// Returns `false` if `tag` is shorter than [`MIN_TAG_LEN`], a forger could guess a short
+    /// tag, or sends an empty one. Use [`verify_min_len`] for another minimum.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Kmac};
+    /// #
+    /// let mut tag = [0u8; 32];
+    /// let mut kmac = Kmac::v256(b"key", b"");
+    /// kmac.update(b"hello world");
+    /// kmac.clone().finalize(&mut tag);
+    /// assert!(kmac.verify(&tag));
+    /// assert!(!kmac.verify(&[]));
+    /// ```
+    ///
+    /// [`MIN_TAG_LEN`]: #associatedconstant.MIN_TAG_LEN
+    /// [`verify_min_len`]: #method.verify_min_len
+    pub fn verify(&self, tag: &[u8]) -> bool {
+        self.verify_min_len(tag, Self::MIN_TAG_LEN)
+    }
+
+    /// Like [`verify`], but rejecting only the tags shorter than `min_len` bytes. Empty tags are
+    /// always rejected.
+    ///
+    /// [`verify`]: #method.verify
+    pub fn verify_min_len(&self, tag: &[u8], min_len: usize) -> bool {
+        if tag.is_empty() || tag.len() < min_len {
+            return false;
+        }
+        let mut state = self.state.clone();
+        state.update(right_encode_bits(tag.len()).value());
+        state.verify(tag)
+    }
 }
 
 impl Hasher for Kmac {
@@ -59,11 +130,27 @@ impl Hasher for Kmac {
     }
 
     fn finalize(mut self, output: &mut [u8]) {
-        self.state.update(right_encode(output.len() * 8).value());
+        self.state.update(right_encode_bits(output.len()).value());
         self.state.finalize(output)
     }
+
+    fn finalize_xor(mut self, output: &mut [u8]) {
+        self.state.update(right_encode_bits(output.len()).value());
+        self.state.finalize_xor(output);
+    }
+
+    fn finalize_scattered(mut self, outputs: &mut [&mut [u8]]) {
+        let len = outputs.iter().map(|output| output.len()).sum();
+        self.state.update(right_encode_bits(len).value());
+        self.state.finalize_scattered(outputs);
+    }
 }
 
+#[cfg(feature = "std")]
+impl_write!(Kmac);
+
+impl_extend!(Kmac);
+
 /// The `KMACXOF` extendable-output functions defined in [`SP800-185`].
 ///
 /// # Usage
@@ -98,6 +185,8 @@ pub struct KmacXof {
     state: CShake,
 }
 
+const _: () = assert!(core::mem::size_of::<KmacXof>() <= SPONGE_SIZE);
+
 impl IntoXof for Kmac {
     type Xof = KmacXof;
 
@@ -107,8 +196,70 @@ impl IntoXof for Kmac {
     }
 }
 
+impl KmacXof {
+    /// Returns the sponge state, positioned where the squeezing stopped.
+    ///
+    /// Resetting the state keeps its rate and delimiter, but not the encoded key prefix, which
+    /// a new [`Kmac`] absorbs again.
+    ///
+    /// [`Kmac`]: struct.Kmac.html
+    pub fn into_state(self) -> KeccakState<KeccakF> {
+        self.state.into_state()
+    }
+}
+
 impl Xof for KmacXof {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output)
     }
+
+    fn squeeze_xor(&mut self, output: &mut [u8]) {
+        self.state.squeeze_xor(output);
+    }
 }
+
+#[cfg(feature = "digest")]
+fixed_output_mac!(
+    /// `KMAC128` with an empty customization string and a 32-byte tag, implementing the `digest`
+    /// [`Mac`] trait. Requires the `digest` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::digest::Mac;
+    /// # use tiny_keccak::Kmac128;
+    /// let mut mac = Kmac128::new_from_slice(b"key").unwrap();
+    /// mac.update(b"hello world");
+    /// let tag = mac.finalize().into_bytes();
+    /// ```
+    ///
+    /// [`Mac`]: https://docs.rs/digest/0.10/digest/trait.Mac.html
+    Kmac128,
+    Kmac,
+    |key| Kmac::v128(key, b""),
+    U32,
+    U32
+);
+
+#[cfg(feature = "digest")]
+fixed_output_mac!(
+    /// `KMAC256` with an empty customization string and a 64-byte tag, implementing the `digest`
+    /// [`Mac`] trait. Requires the `digest` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::digest::Mac;
+    /// # use tiny_keccak::Kmac256;
+    /// let mut mac = Kmac256::new_from_slice(b"key").unwrap();
+    /// mac.update(b"hello world");
+    /// let tag = mac.finalize().into_bytes();
+    /// ```
+    ///
+    /// [`Mac`]: https://docs.rs/digest/0.10/digest/trait.Mac.html
+    Kmac256,
+    Kmac,
+    |key| Kmac::v256(key, b""),
+    U64,
+    U64
+);
@@ -0,0 +1,118 @@
+//! `keccak-f[b]` permutations on narrower lanes.
+
+use core::ops::{BitAnd, BitXor, BitXorAssign, Not};
+
+use crunchy::unroll;
+
+use crate::{PI, RC, RHO, WORDS};
+
+/// A lane of the `keccak-f[25 * w]` state, where `w` is the lane width in bits.
+///
+/// The permutation is the same for every width, only the rotation offsets are reduced modulo
+/// `w`, the round constants are truncated to `w` bits and the number of rounds is
+/// `12 + 2 * log2(w)`.
+pub trait KeccakLane:
+    Copy + BitAnd<Output = Self> + BitXor<Output = Self> + BitXorAssign + Not<Output = Self>
+{
+    /// Number of rounds of `keccak-f` at this width.
+    const ROUNDS: usize;
+
+    /// Rotates the lane left by `n` bits, modulo the lane width.
+    fn rotate_left(self, n: u32) -> Self;
+
+    /// Truncates a round constant of `keccak-f[1600]` to the lane width.
+    fn round_constant(rc: u64) -> Self;
+
+    /// Applies `keccak-f` at this width to the state.
+    #[allow(unused_assignments)]
+    fn keccak_f(a: &mut [Self; WORDS]) {
+        for rc in &RC[..Self::ROUNDS] {
+            // Theta
+            let mut array = [a[0]; 5];
+            unroll! {
+                for x in 0..5 {
+                    array[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+                }
+            }
+
+            unroll! {
+                for x in 0..5 {
+                    let d = array[(x + 4) % 5] ^ array[(x + 1) % 5].rotate_left(1);
+                    unroll! {
+                        for y_count in 0..5 {
+                            a[y_count * 5 + x] ^= d;
+                        }
+                    }
+                }
+            }
+
+            // Rho and pi
+            let mut last = a[1];
+            unroll! {
+                for x in 0..24 {
+                    let next = a[PI[x]];
+                    a[PI[x]] = last.rotate_left(RHO[x]);
+                    last = next;
+                }
+            }
+
+            // Chi
+            unroll! {
+                for y_step in 0..5 {
+                    let y = y_step * 5;
+
+                    unroll! {
+                        for x in 0..5 {
+                            array[x] = a[y + x];
+                        }
+                    }
+
+                    unroll! {
+                        for x in 0..5 {
+                            a[y + x] = array[x] ^ ((!array[(x + 1) % 5]) & (array[(x + 2) % 5]));
+                        }
+                    }
+                }
+            }
+
+            // Iota
+            a[0] ^= Self::round_constant(*rc);
+        }
+    }
+}
+
+macro_rules! impl_lane {
+    ($type: ty, $rounds: expr) => {
+        impl KeccakLane for $type {
+            const ROUNDS: usize = $rounds;
+
+            #[inline(always)]
+            fn rotate_left(self, n: u32) -> Self {
+                <$type>::rotate_left(self, n)
+            }
+
+            #[inline(always)]
+            fn round_constant(rc: u64) -> Self {
+                rc as $type
+            }
+        }
+    };
+}
+
+impl_lane!(u8, 18);
+impl_lane!(u16, 20);
+impl_lane!(u32, 22);
+impl_lane!(u64, 24);
+
+macro_rules! lane_function {
+    ($doc: expr, $name: ident, $type: ty) => {
+        #[doc = $doc]
+        pub fn $name(a: &mut [$type; WORDS]) {
+            <$type as KeccakLane>::keccak_f(a);
+        }
+    };
+}
+
+lane_function!("`keccak-f[800, 22]`", keccakf800, u32);
+lane_function!("`keccak-f[400, 20]`", keccakf400, u16);
+lane_function!("`keccak-f[200, 18]`", keccakf200, u8);
@@ -1,4 +1,5 @@
-//! Keccak derived functions specified in [`FIPS-202`], [`SP800-185`] and [`KangarooTwelve`].
+//! Keccak derived functions specified in [`FIPS-202`], [`SP800-185`], [`KangarooTwelve`] and
+//! [`TurboSHAKE`].
 //!
 //! # Example
 //!
@@ -31,6 +32,7 @@
 //! [`FIPS-202`]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
 //! [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
 //! [`KangarooTwelve`]: https://eprint.iacr.org/2016/770.pdf
+//! [`TurboSHAKE`]: https://www.rfc-editor.org/rfc/rfc9861
 //! [`coruus/keccak-tiny`]: https://github.com/coruus/keccak-tiny
 //! [`mimoo/GoKangarooTwelve`]: https://github.com/mimoo/GoKangarooTwelve
 //! [`@quininer`]: https://github.com/quininer
@@ -40,87 +42,592 @@
 
 #![no_std]
 #![deny(missing_docs)]
-
-const RHO: [u32; 24] = [
+// the sponge is unused if only the `permutation` is enabled
+#![cfg_attr(
+    not(any(
+        feature = "keccak",
+        feature = "shake",
+        feature = "sha3",
+        feature = "cshake",
+        feature = "kmac",
+        feature = "tuple_hash",
+        feature = "parallel_hash",
+        feature = "k12",
+        feature = "turboshake",
+        feature = "duplex",
+        feature = "ethereum"
+    )),
+    allow(dead_code)
+)]
+
+/// The rotation offsets of the `rho` step, in the order the lanes are visited by `pi`.
+pub(crate) const RHO: [u32; 24] = [
     1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
 ];
 
-const PI: [usize; 24] = [
+/// The lane visited after each step of the `pi` permutation, starting from lane 1.
+pub(crate) const PI: [usize; 24] = [
     10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
 ];
 
 const WORDS: usize = 25;
 
-macro_rules! keccak_function {
-    ($doc: expr, $name: ident, $rounds: expr, $rc: expr) => {
-        #[doc = $doc]
-        #[allow(unused_assignments)]
-        #[allow(non_upper_case_globals)]
-        pub fn $name(a: &mut [u64; $crate::WORDS]) {
-            use crunchy::unroll;
-
-            for i in 0..$rounds {
-                let mut array: [u64; 5] = [0; 5];
-
-                // Theta
-                unroll! {
-                    for x in 0..5 {
-                        unroll! {
-                            for y_count in 0..5 {
-                                let y = y_count * 5;
-                                array[x] ^= a[x + y];
-                            }
-                        }
-                    }
+/// The length of the checkpoints written by [`KeccakState::to_bytes`].
+///
+/// [`KeccakState::to_bytes`]: struct.KeccakState.html#method.to_bytes
+pub const CHECKPOINT_LEN: usize = WORDS * 8 + 8 + 8 + 2;
+
+/// The round constants of `keccak-f[1600]`. The `keccak-p[1600, n]` variants use the last `n` of
+/// them and the smaller lanes truncate the first ones, so they are kept in one table.
+pub(crate) const RC: [u64; 24] = [
+    1u64,
+    0x8082u64,
+    0x800000000000808au64,
+    0x8000000080008000u64,
+    0x808bu64,
+    0x80000001u64,
+    0x8000000080008081u64,
+    0x8000000000008009u64,
+    0x8au64,
+    0x88u64,
+    0x80008009u64,
+    0x8000000au64,
+    0x8000808bu64,
+    0x800000000000008bu64,
+    0x8000000000008089u64,
+    0x8000000000008003u64,
+    0x8000000000008002u64,
+    0x8000000000000080u64,
+    0x800au64,
+    0x800000008000000au64,
+    0x8000000080008081u64,
+    0x8000000000008080u64,
+    0x80000001u64,
+    0x8000000080008008u64,
+];
+
+#[cfg(all(feature = "avx2", target_arch = "x86_64"))]
+fn avx2_detected() -> bool {
+    std::is_x86_feature_detected!("avx2")
+}
+
+#[cfg(all(feature = "neon", target_arch = "aarch64"))]
+fn neon_detected() -> bool {
+    std::arch::is_aarch64_feature_detected!("neon")
+}
+
+/// The permutation backend used at runtime, `"avx2"`, `"neon"` or `"scalar"`.
+///
+/// A SIMD backend is used if its feature is enabled and the CPU supports it.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::backend;
+/// println!("keccak backend: {}", backend());
+/// ```
+pub fn backend() -> &'static str {
+    #[cfg(all(feature = "avx2", target_arch = "x86_64"))]
+    {
+        if avx2_detected() {
+            return "avx2";
+        }
+    }
+
+    #[cfg(all(feature = "neon", target_arch = "aarch64"))]
+    {
+        if neon_detected() {
+            return "neon";
+        }
+    }
+
+    "scalar"
+}
+
+fn keccak_p(a: &mut [u64; WORDS], rounds: usize) {
+    #[cfg(all(feature = "avx2", target_arch = "x86_64"))]
+    {
+        if avx2_detected() {
+            // SAFETY: the CPU supports AVX2
+            return unsafe { avx2::keccak_p(a, rounds) };
+        }
+    }
+
+    #[cfg(all(feature = "neon", target_arch = "aarch64"))]
+    {
+        if neon_detected() {
+            // SAFETY: the CPU supports NEON
+            return unsafe { neon::keccak_p(a, rounds) };
+        }
+    }
+
+    keccak_p_scalar(a, rounds);
+}
+
+#[cfg(feature = "keccak")]
+fn keccak_p_x4(states: &mut [[u64; WORDS]; 4], rounds: usize) {
+    #[cfg(all(feature = "avx2", target_arch = "x86_64"))]
+    {
+        if avx2_detected() {
+            // SAFETY: the CPU supports AVX2
+            return unsafe { avx2::keccak_p_x4(states, rounds) };
+        }
+    }
+
+    for state in states.iter_mut() {
+        keccak_p(state, rounds);
+    }
+}
+
+fn keccak_p_scalar(a: &mut [u64; WORDS], rounds: usize) {
+    #[cfg(all(feature = "bit-interleaving", target_pointer_width = "32"))]
+    interleaved::keccak_p(a, rounds);
+
+    #[cfg(all(
+        feature = "unroll",
+        not(all(feature = "bit-interleaving", target_pointer_width = "32"))
+    ))]
+    keccak_p_unrolled(a, rounds);
+
+    #[cfg(not(any(
+        feature = "unroll",
+        all(feature = "bit-interleaving", target_pointer_width = "32")
+    )))]
+    keccak_p_looped(a, rounds);
+}
+
+#[cfg_attr(
+    any(
+        feature = "unroll",
+        all(feature = "bit-interleaving", target_pointer_width = "32")
+    ),
+    allow(dead_code)
+)]
+fn keccak_p_looped(a: &mut [u64; WORDS], rounds: usize) {
+    for rc in &RC[24 - rounds..] {
+        keccak_round(a, *rc);
+    }
+}
+
+/// The rounds of `keccak-f[1600]` and of the 12 round `keccak-p[1600]` used by `KangarooTwelve`
+/// and `TurboSHAKE` are fully unrolled, other round counts are looped.
+///
+/// Unrolling multiplies the code size by the number of rounds, on x86_64 it is slower than the
+/// loop, whose body is unrolled already. The `unroll` feature is there to measure it on other
+/// targets.
+#[cfg(feature = "unroll")]
+#[cfg_attr(
+    all(feature = "bit-interleaving", target_pointer_width = "32"),
+    allow(dead_code)
+)]
+fn keccak_p_unrolled(a: &mut [u64; WORDS], rounds: usize) {
+    use crunchy::unroll;
+
+    match rounds {
+        24 => {
+            unroll! {
+                for i in 0..24 {
+                    keccak_round(a, RC[i]);
+                }
+            }
+        }
+        12 => {
+            unroll! {
+                for i in 0..12 {
+                    keccak_round(a, RC[12 + i]);
                 }
+            }
+        }
+        _ => keccak_p_looped(a, rounds),
+    }
+}
 
-                unroll! {
-                    for x in 0..5 {
-                        unroll! {
-                            for y_count in 0..5 {
-                                let y = y_count * 5;
-                                a[y + x] ^= array[(x + 4) % 5] ^ array[(x + 1) % 5].rotate_left(1);
-                            }
-                        }
-                    }
+/// A single round of `keccak-p[1600]` with the round constant `rc`.
+///
+/// Constant time audit, also valid for the AVX2, NEON and narrower lane permutations:
+///
+/// - the steps only XOR, AND, NOT and rotate the lanes, there is no branch on the state;
+/// - every index is a loop counter or an entry of `PI`, and every rotation amount an entry of
+///   `RHO`, all known at compile time, so the memory accesses don't depend on the state;
+/// - `rc` in iota is selected by the public round number and XORed unconditionally;
+/// - the only branches of the permutation are on the public round count and, with the `avx2`
+///   and `neon` features, on the CPU features detected at runtime.
+///
+/// `tests/timing.rs` checks it empirically with the `timing-test` feature.
+#[allow(unused_assignments)]
+#[allow(non_upper_case_globals)]
+#[inline(always)]
+fn keccak_round(a: &mut [u64; WORDS], rc: u64) {
+    use crunchy::unroll;
+
+    let mut array: [u64; 5] = [0; 5];
+
+    // Theta
+    unroll! {
+        for x in 0..5 {
+            unroll! {
+                for y_count in 0..5 {
+                    let y = y_count * 5;
+                    array[x] ^= a[x + y];
                 }
+            }
+        }
+    }
 
-                // Rho and pi
-                let mut last = a[1];
-                unroll! {
-                    for x in 0..24 {
-                        array[0] = a[$crate::PI[x]];
-                        a[$crate::PI[x]] = last.rotate_left($crate::RHO[x]);
-                        last = array[0];
-                    }
+    unroll! {
+        for x in 0..5 {
+            unroll! {
+                for y_count in 0..5 {
+                    let y = y_count * 5;
+                    a[y + x] ^= array[(x + 4) % 5] ^ array[(x + 1) % 5].rotate_left(1);
                 }
+            }
+        }
+    }
 
-                // Chi
-                unroll! {
-                    for y_step in 0..5 {
-                        let y = y_step * 5;
+    // Rho and pi
+    let mut last = a[1];
+    unroll! {
+        for x in 0..24 {
+            array[0] = a[PI[x]];
+            a[PI[x]] = last.rotate_left(RHO[x]);
+            last = array[0];
+        }
+    }
 
-                        unroll! {
-                            for x in 0..5 {
-                                array[x] = a[y + x];
-                            }
-                        }
+    // Chi
+    unroll! {
+        for y_step in 0..5 {
+            let y = y_step * 5;
 
-                        unroll! {
-                            for x in 0..5 {
-                                a[y + x] = array[x] ^ ((!array[(x + 1) % 5]) & (array[(x + 2) % 5]));
-                            }
-                        }
-                    }
-                };
+            unroll! {
+                for x in 0..5 {
+                    array[x] = a[y + x];
+                }
+            }
 
-                // Iota
-                a[0] ^= $rc[i];
+            unroll! {
+                for x in 0..5 {
+                    a[y + x] = array[x] ^ ((!array[(x + 1) % 5]) & (array[(x + 2) % 5]));
+                }
             }
         }
     }
+
+    // Iota
+    a[0] ^= rc;
+}
+
+#[allow(unused_macros)]
+macro_rules! keccak_function {
+    ($doc: expr, $name: ident, $rounds: expr) => {
+        #[doc = $doc]
+        pub fn $name(a: &mut [u64; $crate::WORDS]) {
+            $crate::keccak_p(a, $rounds);
+        }
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! hash_function {
+    ($(#[$attr: meta])* $name: ident, $hasher: ident, $init: ident, $size: expr) => {
+        $(#[$attr])*
+        pub fn $name(input: &[u8]) -> [u8; $size] {
+            let mut hasher = $hasher::$init();
+            $crate::Hasher::update(&mut hasher, input);
+            $crate::Hasher::finalize_array(hasher)
+        }
+    };
+}
+
+/// Checks an invariant of the sponge with `assert!` if the `debug-invariants` feature is enabled,
+/// and with `debug_assert!` otherwise, so release builds without the feature don't pay for it.
+macro_rules! invariant {
+    ($($arg: tt)*) => {
+        if cfg!(feature = "debug-invariants") {
+            assert!($($arg)*);
+        } else {
+            debug_assert!($($arg)*);
+        }
+    };
+}
+
+/// Implements `Extend<u8>` and `Extend<&u8>` absorbing the bytes like `Hasher::update_iter`.
+#[allow(unused_macros)]
+macro_rules! impl_extend {
+    ($name: ident) => {
+        impl Extend<u8> for $name {
+            fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+                $crate::Hasher::update_iter(self, iter);
+            }
+        }
+
+        impl<'a> Extend<&'a u8> for $name {
+            fn extend<I: IntoIterator<Item = &'a u8>>(&mut self, iter: I) {
+                $crate::Hasher::update_iter(self, iter.into_iter().copied());
+            }
+        }
+    };
+}
+
+#[cfg(feature = "std")]
+#[allow(unused_macros)]
+macro_rules! impl_write {
+    ($name: ident) => {
+        impl std::io::Write for $name {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                $crate::Hasher::update(self, buf);
+                Ok(buf.len())
+            }
+
+            fn write_vectored(&mut self, bufs: &[std::io::IoSlice]) -> std::io::Result<usize> {
+                let mut len = 0;
+                for buf in bufs {
+                    $crate::Hasher::update(self, buf);
+                    len += buf.len();
+                }
+                Ok(len)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! fixed_output_hasher {
+    (
+        $(#[$attr: meta])*
+        $name: ident, $hasher: ident, $init: ident, $size: expr, $digest_size: ident
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug)]
+        #[allow(non_camel_case_types)]
+        pub struct $name($hasher);
+
+        const _: () = assert!(core::mem::size_of::<$name>() <= $crate::SPONGE_SIZE);
+
+        impl $name {
+            /// The length of the digest in bytes.
+            pub const OUTPUT: usize = $size;
+
+            /// The rate of the sponge in bytes, i.e. the block size of the hash function.
+            pub const RATE: usize = $crate::bits_to_rate($size * 8);
+
+            #[doc = concat!("Creates  new [`", stringify!($name), "`] hasher.")]
+            pub fn new() -> $name {
+                $name($hasher::$init())
+            }
+
+            /// Pad and squeeze the state, returning the digest.
+            ///
+            /// This method is called instead of [`Hasher::finalize`] and the `finalize` of the
+            /// `digest` traits, which have to be called by their trait path.
+            ///
+            /// [`Hasher::finalize`]: trait.Hasher.html#tymethod.finalize
+            pub fn finalize(self) -> [u8; $size] {
+                $crate::Hasher::finalize_array(self.0)
+            }
+
+            /// Pad and squeeze the state, writing the lowercase hex of the digest to the start of
+            /// `buffer` and returning it. Requires the `hex` feature, it doesn't allocate.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `buffer` is shorter than twice the digest length.
+            #[cfg(feature = "hex")]
+            pub fn finalize_hex_into(self, buffer: &mut [u8]) -> &str {
+                $crate::hex::encode(&self.finalize(), buffer)
+            }
+
+            /// Pad and squeeze the state, returning the lowercase hex of the digest. Requires the
+            /// `hex` and `alloc` features.
+            #[cfg(all(feature = "hex", feature = "alloc"))]
+            pub fn finalize_hex(self) -> alloc::string::String {
+                let mut buffer = [0u8; $size * 2];
+                self.finalize_hex_into(&mut buffer).into()
+            }
+
+            /// Pad and squeeze the state, returning the digest as a `GenericArray`, for APIs of
+            /// the `generic-array` ecosystem. Requires the `digest` feature.
+            #[cfg(feature = "digest")]
+            pub fn finalize_generic(
+                self,
+            ) -> digest::generic_array::GenericArray<u8, digest::consts::$digest_size> {
+                digest::FixedOutput::finalize_fixed(self)
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name::new()
+            }
+        }
+
+        impl $crate::Hasher for $name {
+            fn update(&mut self, input: &[u8]) {
+                $crate::Hasher::update(&mut self.0, input);
+            }
+
+            fn finalize(self, output: &mut [u8]) {
+                $crate::Hasher::finalize(self.0, output);
+            }
+
+            fn finalize_xor(self, output: &mut [u8]) {
+                $crate::Hasher::finalize_xor(self.0, output);
+            }
+
+            fn finalize_scattered(self, outputs: &mut [&mut [u8]]) {
+                $crate::Hasher::finalize_scattered(self.0, outputs);
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl_write!($name);
+
+        impl_extend!($name);
+
+        #[cfg(feature = "digest")]
+        impl_digest!($name, $digest_size);
+    };
+}
+
+#[cfg(feature = "digest")]
+#[allow(unused_macros)]
+macro_rules! impl_digest {
+    ($name: ident, $size: ident) => {
+        impl digest::HashMarker for $name {}
+
+        impl digest::OutputSizeUser for $name {
+            type OutputSize = digest::consts::$size;
+        }
+
+        impl digest::Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                $crate::Hasher::update(&mut self.0, data);
+            }
+        }
+
+        impl digest::FixedOutput for $name {
+            fn finalize_into(self, out: &mut digest::Output<Self>) {
+                $crate::Hasher::finalize(self.0, out);
+            }
+        }
+
+        impl digest::FixedOutputReset for $name {
+            fn finalize_into_reset(&mut self, out: &mut digest::Output<Self>) {
+                self.0.finalize_reset(out);
+            }
+        }
+
+        impl digest::Reset for $name {
+            fn reset(&mut self) {
+                self.0.reset();
+            }
+        }
+    };
+}
+
+#[cfg(feature = "digest")]
+#[allow(unused_macros)]
+macro_rules! impl_digest_xof {
+    ($name: ident) => {
+        impl digest::Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                $crate::Hasher::update(self, data);
+            }
+        }
+
+        /// The sponge pads on the first squeeze, so the reader is the hasher itself.
+        impl digest::ExtendableOutput for $name {
+            type Reader = $name;
+
+            fn finalize_xof(self) -> $name {
+                self
+            }
+        }
+
+        impl digest::XofReader for $name {
+            fn read(&mut self, buffer: &mut [u8]) {
+                $crate::Xof::squeeze(self, buffer);
+            }
+        }
+    };
+}
+
+#[cfg(feature = "digest")]
+#[allow(unused_macros)]
+macro_rules! fixed_output_mac {
+    (
+        $(#[$attr: meta])*
+        $name: ident, $mac: ident, $init: expr, $output_size: ident, $key_size: ident
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone)]
+        #[allow(non_camel_case_types)]
+        pub struct $name($mac);
+
+        impl digest::MacMarker for $name {}
+
+        impl digest::OutputSizeUser for $name {
+            type OutputSize = digest::consts::$output_size;
+        }
+
+        impl digest::crypto_common::KeySizeUser for $name {
+            type KeySize = digest::consts::$key_size;
+        }
+
+        impl digest::KeyInit for $name {
+            fn new(key: &digest::Key<Self>) -> Self {
+                $name(($init)(key.as_slice()))
+            }
+
+            /// Accepts keys of any length, `KeySize` is only the recommended length.
+            fn new_from_slice(key: &[u8]) -> Result<Self, digest::InvalidLength> {
+                Ok($name(($init)(key)))
+            }
+        }
+
+        impl digest::Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                $crate::Hasher::update(&mut self.0, data);
+            }
+        }
+
+        impl digest::FixedOutput for $name {
+            fn finalize_into(self, out: &mut digest::Output<Self>) {
+                $crate::Hasher::finalize(self.0, out);
+            }
+        }
+    };
 }
 
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "digest")]
+pub use digest;
+
+#[cfg(all(feature = "avx2", target_arch = "x86_64"))]
+mod avx2;
+
+#[cfg(all(feature = "neon", target_arch = "aarch64"))]
+mod neon;
+
+#[cfg(feature = "bit-interleaving")]
+mod interleaved;
+
+#[cfg(any(feature = "cshake", feature = "k12", feature = "keccak"))]
+pub mod encode;
+
+#[cfg(all(feature = "hex", any(feature = "keccak", feature = "sha3")))]
+mod hex;
+
 #[cfg(feature = "k12")]
 mod keccakp;
 
@@ -134,7 +641,8 @@ pub use keccakp::keccakp;
     feature = "cshake",
     feature = "kmac",
     feature = "tuple_hash",
-    feature = "parallel_hash"
+    feature = "parallel_hash",
+    feature = "permutation"
 ))]
 mod keccakf;
 
@@ -145,21 +653,37 @@ mod keccakf;
     feature = "cshake",
     feature = "kmac",
     feature = "tuple_hash",
-    feature = "parallel_hash"
+    feature = "parallel_hash",
+    feature = "permutation"
 ))]
-pub use keccakf::keccakf;
+pub use keccakf::{keccakf, keccakf_const};
+
+#[cfg(feature = "permutation")]
+mod lanes;
+
+#[cfg(feature = "permutation")]
+pub use lanes::{keccakf200, keccakf400, keccakf800, KeccakLane};
 
 #[cfg(feature = "k12")]
 mod k12;
 
 #[cfg(feature = "k12")]
-pub use k12::{KangarooTwelve, KangarooTwelveXof};
+pub use k12::{k12_chunk_cv, KangarooTwelve, KangarooTwelveXof};
 
 #[cfg(feature = "keccak")]
 mod keccak;
 
 #[cfg(feature = "keccak")]
-pub use keccak::Keccak;
+pub use keccak::{
+    keccak224, keccak256, keccak256_32, keccak256_64, keccak256_const, keccak256_pair,
+    keccak256_x4, keccak384, keccak512, Keccak, KeccakBuildHasher, KeccakBuilder, KeccakXof,
+};
+
+#[cfg(feature = "keccak")]
+pub use keccak::{Keccak224, Keccak256, Keccak384, Keccak512};
+
+#[cfg(feature = "keccak")]
+pub use keccak::hash_messages;
 
 #[cfg(feature = "shake")]
 mod shake;
@@ -167,11 +691,17 @@ mod shake;
 #[cfg(feature = "shake")]
 pub use shake::Shake;
 
+#[cfg(all(feature = "shake", feature = "alloc"))]
+pub use shake::{shake128_vec, shake256_vec};
+
 #[cfg(feature = "sha3")]
 mod sha3;
 
 #[cfg(feature = "sha3")]
-pub use sha3::Sha3;
+pub use sha3::{sha3_224, sha3_256, sha3_384, sha3_512, Sha3};
+
+#[cfg(feature = "sha3")]
+pub use sha3::{Sha3_224, Sha3_256, Sha3_384, Sha3_512};
 
 #[cfg(feature = "cshake")]
 mod cshake;
@@ -185,6 +715,9 @@ mod kmac;
 #[cfg(feature = "kmac")]
 pub use kmac::{Kmac, KmacXof};
 
+#[cfg(all(feature = "kmac", feature = "digest"))]
+pub use kmac::{Kmac128, Kmac256};
+
 #[cfg(feature = "tuple_hash")]
 mod tuple_hash;
 
@@ -195,7 +728,102 @@ pub use tuple_hash::{TupleHash, TupleHashXof};
 mod parallel_hash;
 
 #[cfg(feature = "parallel_hash")]
-pub use parallel_hash::{ParallelHash, ParallelHashXof};
+pub use parallel_hash::{ChainingHasher, ParallelHash, ParallelHashXof};
+
+#[cfg(feature = "duplex")]
+mod duplex;
+
+#[cfg(feature = "duplex")]
+pub use duplex::Duplex;
+
+#[cfg(feature = "hmac")]
+mod hmac;
+
+#[cfg(feature = "hmac")]
+pub use hmac::HmacSha3;
+
+#[cfg(all(feature = "hmac", feature = "digest"))]
+pub use hmac::{HmacSha3_224, HmacSha3_256, HmacSha3_384, HmacSha3_512};
+
+#[cfg(feature = "commitment")]
+mod commitment;
+
+#[cfg(feature = "commitment")]
+pub use commitment::{KeccakCommitment, KeccakOpening};
+
+#[cfg(feature = "kdf")]
+mod kdf;
+
+#[cfg(feature = "kdf")]
+pub use kdf::KeccakKdf;
+
+#[cfg(feature = "ethereum")]
+pub mod eth;
+
+#[cfg(feature = "keccak_mac")]
+mod keccak_mac;
+
+#[cfg(feature = "keccak_mac")]
+pub use keccak_mac::KeccakMac;
+
+#[cfg(feature = "stream_cipher")]
+mod stream_cipher;
+
+#[cfg(feature = "stream_cipher")]
+pub use stream_cipher::KeccakStreamCipher;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "nonce")]
+mod nonce;
+
+#[cfg(feature = "nonce")]
+pub use nonce::deterministic_nonce;
+
+#[cfg(feature = "rand")]
+mod rng;
+
+#[cfg(feature = "rand")]
+pub use rng::KeccakRng;
+
+#[cfg(feature = "bloom")]
+mod bloom;
+
+#[cfg(feature = "bloom")]
+pub use bloom::{bloom_indices_iter, BloomIndices};
+
+#[cfg(all(feature = "bloom", feature = "alloc"))]
+pub use bloom::bloom_indices;
+
+#[cfg(feature = "shuffle")]
+mod shuffle;
+
+#[cfg(feature = "shuffle")]
+pub use shuffle::shuffle;
+
+#[cfg(feature = "turboshake")]
+mod turboshake;
+
+#[cfg(feature = "turboshake")]
+pub use turboshake::TurboShake;
+
+#[cfg(feature = "std")]
+mod reader;
+
+#[cfg(feature = "std")]
+pub use reader::XofReader;
+
+mod self_test;
+
+/// Reinterprets `bytes` as initialized.
+///
+/// # Safety
+///
+/// Every byte of `bytes` must be initialized. `MaybeUninit<u8>` has the layout of `u8`.
+unsafe fn assume_init(bytes: &mut [core::mem::MaybeUninit<u8>]) -> &mut [u8] {
+    &mut *(bytes as *mut [core::mem::MaybeUninit<u8>] as *mut [u8])
+}
 
 /// A trait for hashing an arbitrary stream of bytes.
 ///
@@ -217,38 +845,491 @@ pub trait Hasher {
     /// Absorb additional input. Can be called multiple times.
     fn update(&mut self, input: &[u8]);
 
-    /// Pad and squeeze the state to the output.
-    fn finalize(self, output: &mut [u8]);
-}
+    /// Absorb additional input and return the hasher, so absorbs can be chained.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::Hasher;
+    /// #
+    /// # fn foo<H: Hasher>(hasher: H) {
+    /// let mut output = [0u8; 32];
+    /// hasher.chain(b"hello").chain(b" ").chain(b"world").finalize(&mut output);
+    /// # }
+    /// ```
+    fn chain(mut self, input: &[u8]) -> Self
+    where
+        Self: Sized,
+    {
+        self.update(input);
+        self
+    }
 
-/// A trait used to convert [`Hasher`] into it's [`Xof`] counterpart.
-///
-/// # Example
-///
-/// ```
-/// # use tiny_keccak::IntoXof;
-/// #
-/// # fn foo<H: IntoXof>(hasher: H) {
-/// let xof = hasher.into_xof();
-/// # }
-/// ```
-///
-/// [`Hasher`]: trait.Hasher.html
-/// [`Xof`]: trait.Xof.html
-pub trait IntoXof {
-    /// A type implementing [`Xof`], eXtendable-output function interface.
+    /// Absorb the slices in order, as if they were concatenated.
     ///
-    /// [`Xof`]: trait.Xof.html
-    type Xof: Xof;
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::Hasher;
+    /// #
+    /// # fn foo<H: Hasher>(mut hasher: H) {
+    /// hasher.update_vectored(&[b"hello", b" ", b"world"]);
+    /// # }
+    /// ```
+    fn update_vectored(&mut self, slices: &[&[u8]]) {
+        for slice in slices {
+            self.update(slice);
+        }
+    }
 
-    /// A method used to convert type into [`Xof`].
+    /// Absorb anything that can be viewed as bytes, e.g. a `str`, a `Vec<u8>` or a byte array,
+    /// the same as [`update`] with its bytes.
     ///
-    /// [`Xof`]: trait.Xof.html
-    fn into_xof(self) -> Self::Xof;
-}
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, KeccakF, KeccakState};
+    /// let hasher = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    ///
+    /// let mut explicit = hasher.clone();
+    /// explicit.update(b"abc");
+    /// explicit.update(&[1, 2, 3]);
+    /// explicit.update(&[4, 5, 6]);
+    ///
+    /// let mut any = hasher;
+    /// any.update_any("abc");
+    /// any.update_any([1u8, 2, 3]);
+    /// any.update_any(vec![4u8, 5, 6]);
+    ///
+    /// assert_eq!(
+    ///     explicit.finalize_array::<32>(),
+    ///     any.finalize_array::<32>()
+    /// );
+    /// ```
+    ///
+    /// [`update`]: #tymethod.update
+    fn update_any<T: AsRef<[u8]>>(&mut self, input: T)
+    where
+        Self: Sized,
+    {
+        self.update(input.as_ref());
+    }
 
-/// Extendable-output function (`XOF`) is a function on bit strings in which the output can be
-/// extended to any desired length.
+    /// Absorb the bytes of `iter` as they are produced, as if they were collected and passed to
+    /// [`update`]. The bytes are absorbed in small chunks from a buffer on the stack.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::Hasher;
+    /// #
+    /// # fn foo<H: Hasher>(mut hasher: H) {
+    /// hasher.update_iter((0u8..200).map(|i| i ^ 0x5a));
+    /// # }
+    /// ```
+    ///
+    /// [`update`]: #tymethod.update
+    fn update_iter<I: IntoIterator<Item = u8>>(&mut self, iter: I)
+    where
+        Self: Sized,
+    {
+        let mut chunk = [0u8; 64];
+        let mut len = 0;
+        for byte in iter {
+            chunk[len] = byte;
+            len += 1;
+            if len == chunk.len() {
+                self.update(&chunk);
+                len = 0;
+            }
+        }
+        self.update(&chunk[..len]);
+    }
+
+    /// Pad and squeeze the state to the output.
+    fn finalize(self, output: &mut [u8]);
+
+    /// Pad and squeeze the state, returning the output as an array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::Hasher;
+    /// #
+    /// # fn foo<H: Hasher>(hasher: H) {
+    /// let output: [u8; 32] = hasher.finalize_array();
+    /// # }
+    /// ```
+    fn finalize_array<const N: usize>(self) -> [u8; N]
+    where
+        Self: Sized,
+    {
+        let mut output = [0u8; N];
+        self.finalize(&mut output);
+        output
+    }
+
+    /// Pad and squeeze the state to the uninitialized `output`, which is fully initialized
+    /// afterwards and returned as bytes.
+    ///
+    /// The sponge based hashers write the digest straight into `output`, so a freshly allocated
+    /// buffer doesn't have to be zeroed first. Other hashers zero it and call [`finalize`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use core::mem::MaybeUninit;
+    /// # use tiny_keccak::Hasher;
+    /// #
+    /// # fn foo<H: Hasher>(hasher: H) {
+    /// let mut output = [MaybeUninit::<u8>::uninit(); 32];
+    /// let digest: &mut [u8] = hasher.finalize_uninit(&mut output);
+    /// # }
+    /// ```
+    ///
+    /// [`finalize`]: #tymethod.finalize
+    fn finalize_uninit(self, output: &mut [core::mem::MaybeUninit<u8>]) -> &mut [u8]
+    where
+        Self: Sized,
+    {
+        for byte in output.iter_mut() {
+            *byte = core::mem::MaybeUninit::new(0);
+        }
+        // SAFETY: every byte was just initialized
+        let output = unsafe { assume_init(output) };
+        self.finalize(output);
+        output
+    }
+
+    /// Pad and squeeze the state, XORing the output into the existing contents of `output`
+    /// instead of overwriting them, e.g. to mask a buffer without a temporary for the digest.
+    ///
+    /// The hashers of this crate XOR the output straight from the sponge. The default
+    /// implementation finalizes into a buffer on the stack and XORs it into `output`.
+    ///
+    /// # Panics
+    ///
+    /// The default implementation panics if `output` is longer than 200 bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::Hasher;
+    /// #
+    /// # fn foo<H: Hasher>(hasher: H) {
+    /// let mut data = [0x5au8; 32];
+    /// hasher.finalize_xor(&mut data);
+    /// # }
+    /// ```
+    fn finalize_xor(self, output: &mut [u8])
+    where
+        Self: Sized,
+    {
+        let mut digest = [0u8; WORDS * 8];
+        assert!(
+            output.len() <= digest.len(),
+            "the default finalize_xor cannot output more than 200 bytes"
+        );
+        let digest = &mut digest[..output.len()];
+        self.finalize(digest);
+        for (byte, digest) in output.iter_mut().zip(digest.iter()) {
+            *byte ^= digest;
+        }
+    }
+
+    /// Pad and squeeze the state across `outputs` in order, the same as finalizing into one
+    /// buffer of their total length and splitting it, e.g. to write a digest into several
+    /// fields.
+    ///
+    /// The sponge based hashers squeeze every slice in turn. The default implementation
+    /// finalizes into a buffer on the stack and copies it into the slices.
+    ///
+    /// # Panics
+    ///
+    /// The default implementation panics if the slices are longer than 200 bytes in total.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::Hasher;
+    /// #
+    /// # fn foo<H: Hasher>(hasher: H) {
+    /// let mut tag = [0u8; 10];
+    /// let mut key = [0u8; 22];
+    /// hasher.finalize_scattered(&mut [&mut tag, &mut key]);
+    /// # }
+    /// ```
+    fn finalize_scattered(self, outputs: &mut [&mut [u8]])
+    where
+        Self: Sized,
+    {
+        let mut digest = [0u8; WORDS * 8];
+        let len = outputs.iter().map(|output| output.len()).sum::<usize>();
+        assert!(
+            len <= digest.len(),
+            "the default finalize_scattered cannot output more than 200 bytes"
+        );
+        let mut digest = &mut digest[..len];
+        self.finalize(digest);
+        for output in outputs.iter_mut() {
+            let (head, rest) = digest.split_at_mut(output.len());
+            output.copy_from_slice(head);
+            digest = rest;
+        }
+    }
+}
+
+macro_rules! absorb_int {
+    ($($(#[$attr:meta])* $name:ident, $ty:ty, $to_bytes:ident;)*) => {
+        $(
+            $(#[$attr])*
+            fn $name(&mut self, value: $ty) {
+                self.update(&value.$to_bytes());
+            }
+        )*
+    };
+}
+
+/// Absorb helpers that fix the encoding of common types at the call site.
+///
+/// Implemented for every [`Hasher`].
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{Hasher, HasherExt};
+/// #
+/// # fn foo<H: Hasher>(mut hasher: H) {
+/// hasher.absorb_str("tag");
+/// hasher.absorb_u64_le(42);
+/// hasher.absorb_u32_be(7);
+/// # }
+/// ```
+///
+/// [`Hasher`]: trait.Hasher.html
+pub trait HasherExt: Hasher {
+    /// Absorb the UTF-8 bytes of `value`.
+    fn absorb_str(&mut self, value: &str) {
+        self.update(value.as_bytes());
+    }
+
+    /// Absorb the length of `record` as 4 little-endian bytes, followed by `record`.
+    ///
+    /// A fixed width framing for protocols that prefix every record with a `u32` length, unlike
+    /// the variable width big-endian `encode_string` of SP 800-185.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `record` is longer than `u32::MAX` bytes.
+    fn update_framed(&mut self, record: &[u8]) {
+        assert!(
+            record.len() <= u32::MAX as usize,
+            "framed record cannot exceed u32::MAX bytes"
+        );
+        self.absorb_u32_le(record.len() as u32);
+        self.update(record);
+    }
+
+    absorb_int! {
+        /// Absorb `value` as 2 little-endian bytes.
+        absorb_u16_le, u16, to_le_bytes;
+        /// Absorb `value` as 2 big-endian bytes.
+        absorb_u16_be, u16, to_be_bytes;
+        /// Absorb `value` as 4 little-endian bytes.
+        absorb_u32_le, u32, to_le_bytes;
+        /// Absorb `value` as 4 big-endian bytes.
+        absorb_u32_be, u32, to_be_bytes;
+        /// Absorb `value` as 8 little-endian bytes.
+        absorb_u64_le, u64, to_le_bytes;
+        /// Absorb `value` as 8 big-endian bytes.
+        absorb_u64_be, u64, to_be_bytes;
+        /// Absorb `value` as 16 little-endian bytes.
+        absorb_u128_le, u128, to_le_bytes;
+        /// Absorb `value` as 16 big-endian bytes.
+        absorb_u128_be, u128, to_be_bytes;
+    }
+}
+
+impl<H: Hasher + ?Sized> HasherExt for H {}
+
+/// Absorb helpers reading the input through `std::io`. Requires the `std` feature.
+///
+/// Implemented for every [`Hasher`].
+///
+/// [`Hasher`]: trait.Hasher.html
+#[cfg(feature = "std")]
+pub trait HasherIoExt: Hasher {
+    /// Absorb everything `reader` produces until the end of the stream, through an 8 KiB buffer
+    /// on the stack, and return the number of bytes absorbed.
+    ///
+    /// Reads interrupted by a signal are retried, every other error is returned, with the bytes
+    /// read before it already absorbed.
+    ///
+    /// Every read is passed to [`update`] on its own, so a hasher framing each call, like
+    /// [`TupleHash`], absorbs every read as a separate element.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use tiny_keccak::{Hasher, HasherIoExt};
+    /// #
+    /// # fn foo<H: Hasher>(mut hasher: H) -> std::io::Result<()> {
+    /// let mut file = std::fs::File::open("large.bin")?;
+    /// let len = hasher.update_reader(&mut file)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`update`]: trait.Hasher.html#tymethod.update
+    /// [`TupleHash`]: struct.TupleHash.html
+    fn update_reader<R: std::io::Read + ?Sized>(&mut self, reader: &mut R) -> std::io::Result<u64> {
+        let mut buffer = [0u8; 8192];
+        let mut total = 0u64;
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => return Ok(total),
+                Ok(len) => {
+                    self.update(&buffer[..len]);
+                    total += len as u64;
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H: Hasher + ?Sized> HasherIoExt for H {}
+
+/// Absorb helpers encoding their input as in [`SP800-185`], so that a sequence of inputs can't
+/// be confused with another one. Requires the `cshake` feature.
+///
+/// Implemented for every [`Hasher`].
+///
+/// [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
+/// [`Hasher`]: trait.Hasher.html
+#[cfg(feature = "cshake")]
+pub trait EncodeExt: Hasher {
+    /// Absorb every item prefixed by its length, see [`encode_string`], and return the digest.
+    ///
+    /// The prefixes make the encoding unambiguous, so `["ab", "c"]` and `["a", "bc"]` hash to
+    /// different digests.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{CShake, EncodeExt};
+    /// let items = ["hello", "world"];
+    /// let hasher = CShake::v256(b"", b"items");
+    /// let output: [u8; 32] = hasher.absorb_iter(items.iter().map(|x| x.as_bytes()));
+    /// ```
+    ///
+    /// [`encode_string`]: encode/fn.encode_string.html
+    fn absorb_iter<I, const N: usize>(mut self, items: I) -> [u8; N]
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+        Self: Sized,
+    {
+        for item in items {
+            self.update_vectored(&encode::encode_string(item.as_ref()).parts());
+        }
+        self.finalize_array()
+    }
+}
+
+#[cfg(feature = "cshake")]
+impl<H: Hasher + ?Sized> EncodeExt for H {}
+
+/// A [`core::fmt::Write`] adapter absorbing the formatted output into a [`Hasher`], so a value can
+/// be hashed by its `Display` representation without allocating a `String`.
+///
+/// Every piece of the formatted output is passed to [`update`] on its own, so a hasher framing
+/// each call, like [`TupleHash`], absorbs every piece as a separate element.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{HashSink, Hasher};
+/// use core::fmt::Write;
+///
+/// # fn foo<H: Hasher>(mut hasher: H) {
+/// write!(HashSink(&mut hasher), "{}-{}", 1, 2).unwrap();
+/// # }
+/// ```
+///
+/// [`core::fmt::Write`]: https://doc.rust-lang.org/core/fmt/trait.Write.html
+/// [`Hasher`]: trait.Hasher.html
+/// [`update`]: trait.Hasher.html#tymethod.update
+/// [`TupleHash`]: struct.TupleHash.html
+pub struct HashSink<'a, H: ?Sized>(pub &'a mut H);
+
+impl<'a, H: Hasher + ?Sized> core::fmt::Write for HashSink<'a, H> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0.update(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// A trait used to convert [`Hasher`] into it's [`Xof`] counterpart.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::IntoXof;
+/// #
+/// # fn foo<H: IntoXof>(hasher: H) {
+/// let xof = hasher.into_xof();
+/// # }
+/// ```
+///
+/// [`Hasher`]: trait.Hasher.html
+/// [`Xof`]: trait.Xof.html
+pub trait IntoXof {
+    /// A type implementing [`Xof`], eXtendable-output function interface.
+    ///
+    /// [`Xof`]: trait.Xof.html
+    type Xof: Xof;
+
+    /// A method used to convert type into [`Xof`].
+    ///
+    /// [`Xof`]: trait.Xof.html
+    fn into_xof(self) -> Self::Xof;
+
+    /// Convert into [`Xof`], squeeze the first `output.len()` bytes of the stream into `output`
+    /// and return the [`Xof`], which continues the stream from there.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{IntoXof, Xof};
+    /// #
+    /// # fn foo<H: IntoXof>(hasher: H) {
+    /// let mut digest = [0u8; 32];
+    /// let mut xof = hasher.finalize_xof_into(&mut digest);
+    /// let mut more = [0u8; 32];
+    /// xof.squeeze(&mut more);
+    /// # }
+    /// ```
+    ///
+    /// [`Xof`]: trait.Xof.html
+    fn finalize_xof_into(self, output: &mut [u8]) -> Self::Xof
+    where
+        Self: Sized,
+    {
+        let mut xof = self.into_xof();
+        xof.squeeze(output);
+        xof
+    }
+}
+
+/// Extendable-output function (`XOF`) is a function on bit strings in which the output can be
+/// extended to any desired length.
+///
+/// Every `XOF` of this crate implements `Clone`. Cloning forks the stream: the clone and the
+/// original both continue with the same bytes from the current position, independently of each
+/// other, e.g. to snapshot the position of a deterministic generator.
 ///
 /// # Example
 ///
@@ -264,149 +1345,2179 @@ pub trait IntoXof {
 pub trait Xof {
     /// A method used to retrieve another part of hash function output.
     fn squeeze(&mut self, output: &mut [u8]);
+
+    /// Squeeze the next `expected.len()` bytes and compare them with `expected` in constant
+    /// time, e.g. to check a tag of any length without holding the whole stream.
+    ///
+    /// The stream is squeezed in small chunks, and every byte is compared even after a
+    /// difference, so the time taken depends only on the length of `expected`. An empty
+    /// `expected` never verifies, use [`verify_min_len`] to require longer tags.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::Xof;
+    /// #
+    /// # fn foo<X: Xof + Clone>(xof: X, tag: &[u8]) {
+    /// let valid = xof.clone().verify(tag);
+    /// # }
+    /// ```
+    ///
+    /// [`verify_min_len`]: #method.verify_min_len
+    fn verify(&mut self, expected: &[u8]) -> bool {
+        self.verify_min_len(expected, 1)
+    }
+
+    /// Like [`verify`], but returns `false` without squeezing if `expected` is shorter than
+    /// `min_len` bytes, e.g. to reject truncated MAC tags a forger could guess.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::Xof;
+    /// #
+    /// # fn foo<X: Xof + Clone>(xof: X, tag: &[u8]) {
+    /// // tags of at least 128 bits
+    /// let valid = xof.clone().verify_min_len(tag, 16);
+    /// # }
+    /// ```
+    ///
+    /// [`verify`]: #method.verify
+    fn verify_min_len(&mut self, expected: &[u8], min_len: usize) -> bool {
+        if expected.is_empty() || expected.len() < min_len {
+            return false;
+        }
+        let mut diff = 0u8;
+        let mut output = [0u8; 64];
+        for chunk in expected.chunks(output.len()) {
+            let output = &mut output[..chunk.len()];
+            self.squeeze(output);
+            diff = output
+                .iter()
+                .zip(chunk)
+                .fold(diff, |diff, (a, b)| diff | (a ^ b));
+        }
+        core::hint::black_box(diff) == 0
+    }
+
+    /// Squeeze the next `output.len()` bytes and XOR them into the existing contents of
+    /// `output`, e.g. to apply a keystream in place.
+    ///
+    /// The sponges of this crate XOR the output straight from the state, the default
+    /// implementation squeezes small chunks into a buffer on the stack.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::Xof;
+    /// #
+    /// # fn foo<X: Xof>(mut xof: X) {
+    /// let mut data = [0x5au8; 100];
+    /// xof.squeeze_xor(&mut data);
+    /// # }
+    /// ```
+    fn squeeze_xor(&mut self, output: &mut [u8]) {
+        let mut stream = [0u8; 64];
+        for chunk in output.chunks_mut(stream.len()) {
+            let stream = &mut stream[..chunk.len()];
+            self.squeeze(stream);
+            for (byte, stream) in chunk.iter_mut().zip(stream.iter()) {
+                *byte ^= stream;
+            }
+        }
+    }
 }
 
-struct EncodedLen {
-    offset: usize,
-    buffer: [u8; 9],
+/// Allocating helpers for every [`Xof`]. Requires the `alloc` feature.
+///
+/// [`Xof`]: trait.Xof.html
+#[cfg(feature = "alloc")]
+pub trait XofExt: Xof {
+    /// Squeeze the next `len` bytes of output into a `Vec` allocated once with the exact
+    /// capacity.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Xof, XofExt};
+    /// #
+    /// # fn foo<X: Xof>(mut xof: X) {
+    /// let output = xof.squeeze_to_vec(100);
+    /// assert_eq!(output.len(), 100);
+    /// # }
+    /// ```
+    fn squeeze_to_vec(&mut self, len: usize) -> alloc::vec::Vec<u8> {
+        let mut output = alloc::vec![0u8; len];
+        self.squeeze(&mut output);
+        output
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<X: Xof + ?Sized> XofExt for X {}
+
+/// The misuses reported by the `try_*` variants of the panicking methods.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeccakError {
+    /// The output is longer than the digest of the hash function.
+    OutputTooLong {
+        /// The length of the digest in bytes.
+        max: usize,
+        /// The length of the output in bytes.
+        len: usize,
+    },
+    /// The output doesn't match the single digest length of a SHA3 variant, or of a
+    /// [`KeccakBuilder`] given both a rate and a digest length.
+    ///
+    /// [`KeccakBuilder`]: struct.KeccakBuilder.html
+    OutputSizeMismatch {
+        /// The length of the digest in bytes.
+        expected: usize,
+        /// The length of the output in bytes.
+        len: usize,
+    },
+    /// The sponge already squeezed output, so it can't be finalized anymore.
+    AlreadySqueezing,
+    /// The rate isn't valid according to [`is_valid_rate`].
+    ///
+    /// [`is_valid_rate`]: fn.is_valid_rate.html
+    InvalidRate {
+        /// The rejected rate in bytes.
+        rate: usize,
+    },
+    /// The checkpoint decoded by [`KeccakState::from_bytes`] is malformed.
+    ///
+    /// [`KeccakState::from_bytes`]: struct.KeccakState.html#method.from_bytes
+    InvalidCheckpoint,
+    /// A known answer test run by [`self_test`] computed the wrong digest.
+    ///
+    /// [`self_test`]: fn.self_test.html
+    SelfTestFailed {
+        /// The name of the algorithm that failed.
+        algorithm: &'static str,
+    },
+    /// The rate of the sponge can only be switched between blocks, see
+    /// [`KeccakState::switch_rate`].
+    ///
+    /// [`KeccakState::switch_rate`]: struct.KeccakState.html#method.switch_rate
+    NotAtBlockBoundary {
+        /// The offset in the current block.
+        offset: usize,
+    },
+    /// The state passed to [`permute_slice`] isn't 25 words long.
+    ///
+    /// [`permute_slice`]: fn.permute_slice.html
+    InvalidStateLength {
+        /// The length of the state in words.
+        len: usize,
+    },
+    /// No valid rate has a digest of the length passed to [`KeccakBuilder::output_len`], which
+    /// must be a positive multiple of 4 bytes below 100.
+    ///
+    /// [`KeccakBuilder::output_len`]: struct.KeccakBuilder.html#method.output_len
+    InvalidOutputLen {
+        /// The rejected digest length in bytes.
+        len: usize,
+    },
+    /// The security level passed to a `try_new` constructor is neither 128 nor 256 bits.
+    InvalidSecurityLevel {
+        /// The rejected security level in bits.
+        bits: usize,
+    },
+}
+
+impl core::fmt::Display for KeccakError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            KeccakError::OutputTooLong { max, len } => write!(
+                f,
+                "output of {} bytes is longer than the {} byte digest",
+                len, max
+            ),
+            KeccakError::OutputSizeMismatch { expected, len } => write!(
+                f,
+                "output of {} bytes doesn't match the {} byte digest",
+                len, expected
+            ),
+            KeccakError::AlreadySqueezing => f.write_str("the sponge is already squeezing"),
+            KeccakError::InvalidCheckpoint => f.write_str("malformed sponge checkpoint"),
+            KeccakError::InvalidRate { rate } => write!(
+                f,
+                "invalid sponge rate {}, expected a multiple of 8 below 200",
+                rate
+            ),
+            KeccakError::NotAtBlockBoundary { offset } => write!(
+                f,
+                "the rate can only be switched at a block boundary, the offset is {}",
+                offset
+            ),
+            KeccakError::SelfTestFailed { algorithm } => {
+                write!(f, "{} failed its known answer test", algorithm)
+            }
+            KeccakError::InvalidStateLength { len } => {
+                write!(f, "state of {} words, expected {} words", len, WORDS)
+            }
+            KeccakError::InvalidOutputLen { len } => {
+                write!(f, "no sponge rate has a digest of {} bytes", len)
+            }
+            KeccakError::InvalidSecurityLevel { bits } => write!(
+                f,
+                "security level of {} bits, expected 128 or 256 bits",
+                bits
+            ),
+        }
+    }
+}
+
+/// Runs a known answer test of `keccak-f[1600]` and of every enabled hash function.
+///
+/// Lets an application refuse to start when the build is miscompiled or corrupted, every test
+/// compares a digest computed at runtime with a hardcoded one. It has to be called explicitly,
+/// the hashers never run it on their own.
+///
+/// # Example
+///
+/// ```
+/// tiny_keccak::self_test().expect("tiny-keccak is broken");
+/// ```
+///
+/// # Errors
+///
+/// Fails with [`KeccakError::SelfTestFailed`] naming the first algorithm whose digest doesn't
+/// match.
+///
+/// [`KeccakError::SelfTestFailed`]: enum.KeccakError.html#variant.SelfTestFailed
+pub fn self_test() -> Result<(), KeccakError> {
+    self_test::check(self_test::KNOWN_ANSWERS)
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for KeccakError {}
+
+/// Compares two byte strings in constant time.
+///
+/// The time taken depends only on the length of the inputs, not on their contents, so it is
+/// safe to use for comparing digests and MACs. Inputs of different length compare unequal.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::constant_time_eq;
+/// assert!(constant_time_eq(b"tag", b"tag"));
+/// assert!(!constant_time_eq(b"tag", b"taG"));
+/// ```
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let diff = a.iter().zip(b).fold(0u8, |diff, (a, b)| diff | (a ^ b));
+    core::hint::black_box(diff) == 0
 }
 
-impl EncodedLen {
-    fn value(&self) -> &[u8] {
-        &self.buffer[self.offset..]
+#[derive(Default, Clone)]
+#[repr(transparent)]
+struct Buffer([u64; WORDS]);
+
+impl Buffer {
+    /// Views borrowed state words as a buffer.
+    fn from_words_mut(words: &mut [u64; WORDS]) -> &mut Buffer {
+        // SAFETY: `Buffer` is a transparent wrapper around `[u64; WORDS]`.
+        unsafe { &mut *(words as *mut [u64; WORDS] as *mut Buffer) }
+    }
+
+    pub fn words(&mut self) -> &mut [u64; WORDS] {
+        &mut self.0
+    }
+
+    /// Native-endian byte view of the state words.
+    #[allow(dead_code)]
+    fn bytes(&self) -> &[u8; WORDS * 8] {
+        // SAFETY: `[u64; WORDS]` and `[u8; WORDS * 8]` have the same size, `u8` has no alignment
+        // requirement and every bit pattern is a valid `u8`.
+        unsafe { &*(self.0.as_ptr() as *const [u8; WORDS * 8]) }
+    }
+
+    /// Mutable native-endian byte view of the state words.
+    ///
+    /// Keccak lanes are little-endian, so on big-endian targets the affected words have to be
+    /// swapped before and after accessing them, see [`execute`](#method.execute).
+    fn bytes_mut(&mut self) -> &mut [u8; WORDS * 8] {
+        // SAFETY: see `bytes`.
+        unsafe { &mut *(self.0.as_mut_ptr() as *mut [u8; WORDS * 8]) }
+    }
+
+    #[cfg(target_endian = "little")]
+    #[inline]
+    fn execute<F: FnOnce(&mut [u8])>(&mut self, offset: usize, len: usize, f: F) {
+        f(&mut self.bytes_mut()[offset..][..len]);
+    }
+
+    #[cfg(target_endian = "big")]
+    #[inline]
+    fn execute<F: FnOnce(&mut [u8])>(&mut self, offset: usize, len: usize, f: F) {
+        fn swap_endianess(buffer: &mut [u64]) {
+            for item in buffer {
+                *item = item.swap_bytes();
+            }
+        }
+
+        let start = offset / 8;
+        let end = (offset + len + 7) / 8;
+        swap_endianess(&mut self.0[start..end]);
+        f(&mut self.bytes_mut()[offset..][..len]);
+        swap_endianess(&mut self.0[start..end]);
+    }
+
+    fn setout(&mut self, dst: &mut [u8], offset: usize, len: usize) {
+        self.execute(offset, len, |buffer| dst[..len].copy_from_slice(buffer));
+    }
+
+    fn setout_uninit(&mut self, dst: &mut [core::mem::MaybeUninit<u8>], offset: usize, len: usize) {
+        self.execute(offset, len, |buffer| {
+            for (byte, src) in dst[..len].iter_mut().zip(buffer.iter()) {
+                *byte = core::mem::MaybeUninit::new(*src);
+            }
+        });
+    }
+
+    // Reading the state needs no hook: OpenVM only replaces the XOR of input into the state,
+    // `xorin_openvm_opcode`, and the permutation, `keccak_openvm_opcode`. `setout` never went
+    // through a hook either, so the word-wise copies below are exact on OpenVM too.
+
+    /// Copies the leading bytes of the state to `dst`, word by word, so no byte swapping is
+    /// needed on big-endian targets.
+    fn setout_words(&self, dst: &mut [u8]) {
+        for (chunk, word) in dst.chunks_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes()[..chunk.len()]);
+        }
+    }
+
+    /// Copies a block of `dst.len()` bytes, a multiple of the word size, from the first words of
+    /// the state, the counterpart of [`xorin_block`](#method.xorin_block).
+    fn setout_block(&self, dst: &mut [u8]) {
+        debug_assert_eq!(dst.len() & 7, 0);
+        for (chunk, word) in dst.chunks_exact_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+
+    /// XORs `len` bytes of the state from `offset` on into `dst`.
+    fn xorout(&mut self, dst: &mut [u8], offset: usize, len: usize) {
+        self.execute(offset, len, |buffer| {
+            for (byte, src) in dst[..len].iter_mut().zip(buffer.iter()) {
+                *byte ^= src;
+            }
+        });
+    }
+
+    /// The hook every XOR of input into the state goes through, so OpenVM can replace it.
+    ///
+    /// The bytes are XORed 8 at a time in native byte order, which is the same as XORing them one
+    /// by one on any target.
+    fn xorin_openvm_opcode(buffer_ptr: *mut u8, input_ptr: *const u8, len: usize) {
+        let buf_u8: &mut [u8] = unsafe { core::slice::from_raw_parts_mut(buffer_ptr, len) };
+        let input_u8: &[u8] = unsafe { core::slice::from_raw_parts(input_ptr, len) };
+
+        let mut words = buf_u8.chunks_exact_mut(8);
+        let mut inputs = input_u8.chunks_exact(8);
+        for (word, input) in (&mut words).zip(&mut inputs) {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(input);
+            let mut state = [0u8; 8];
+            state.copy_from_slice(word);
+            let value = u64::from_ne_bytes(state) ^ u64::from_ne_bytes(bytes);
+            word.copy_from_slice(&value.to_ne_bytes());
+        }
+        for (byte, input) in words.into_remainder().iter_mut().zip(inputs.remainder()) {
+            *byte ^= *input;
+        }
+    }
+
+    fn xorin(&mut self, src: &[u8], offset: usize, len: usize) {
+        self.execute(offset, len, |dst| {
+            assert!(dst.len() <= src.len());
+            Self::xorin_openvm_opcode(dst.as_mut_ptr(), src.as_ptr(), dst.len());
+        });
+    }
+
+    fn permute<P: Permutation>(&mut self) {
+        let buffer_ptr = self.words().as_mut_ptr() as *mut u8;
+        KeccakState::<P>::keccak_openvm_opcode(buffer_ptr);
+    }
+
+    /// Absorbs `input` into the block from `offset` on, permuting every completed block, and
+    /// returns the offset in the last block.
+    fn absorb<P: Permutation>(&mut self, offset: usize, rate: usize, input: &[u8]) -> usize {
+        let mut input = input;
+
+        // first fill the block absorbed in part by a previous call
+        if offset != 0 {
+            let left = rate - offset;
+            if input.len() < left {
+                self.xorin(input, offset, input.len());
+                return offset + input.len();
+            }
+            let (head, rest) = input.split_at(left);
+            self.xorin(head, offset, left);
+            self.permute::<P>();
+            input = rest;
+        }
+
+        // then the whole blocks, absorbed a word at a time straight from the input
+        let mut blocks = input.chunks_exact(rate);
+        #[cfg(all(feature = "bit-interleaving", target_pointer_width = "32"))]
+        if let (Some(rounds), true) = (P::KECCAK_P_ROUNDS, blocks.len() > 1) {
+            self.absorb_interleaved(&mut blocks, rounds);
+        }
+        for block in &mut blocks {
+            self.xorin_block(block);
+            self.permute::<P>();
+        }
+
+        // and buffer the tail, shorter than a block
+        let tail = blocks.remainder();
+        self.xorin(tail, 0, tail.len());
+        tail.len()
+    }
+
+    /// Squeezes `output` from the block from `offset` on, permuting every exhausted block, and
+    /// returns the offset in the last block.
+    fn squeeze<P: Permutation>(&mut self, offset: usize, rate: usize, output: &mut [u8]) -> usize {
+        let mut output = output;
+
+        // first the rest of the block squeezed in part by a previous call
+        if offset != 0 {
+            let left = rate - offset;
+            if output.len() < left {
+                let len = output.len();
+                self.setout(output, offset, len);
+                return offset + len;
+            }
+            let (head, rest) = output.split_at_mut(left);
+            self.setout(head, offset, left);
+            self.permute::<P>();
+            output = rest;
+        }
+
+        // then the whole blocks, copied out a word at a time
+        let mut blocks = output.chunks_exact_mut(rate);
+        #[cfg(all(feature = "bit-interleaving", target_pointer_width = "32"))]
+        if let (Some(rounds), true) = (P::KECCAK_P_ROUNDS, blocks.len() > 1) {
+            self.squeeze_interleaved(&mut blocks, rounds);
+        }
+        for block in &mut blocks {
+            self.setout_block(block);
+            self.permute::<P>();
+        }
+
+        // and the start of the next block
+        let tail = blocks.into_remainder();
+        let len = tail.len();
+        self.setout(tail, 0, len);
+        len
+    }
+
+    /// Absorbs the whole `blocks` with the state bit interleaved across all of them, so between
+    /// the permutations only the words of the input are converted, not the whole state. An
+    /// interleaved state isn't in the byte layout `xorin_openvm_opcode` works on, so this path
+    /// skips the hook, and `bit-interleaving` should stay off when building for OpenVM.
+    #[cfg(all(feature = "bit-interleaving", target_pointer_width = "32"))]
+    fn absorb_interleaved(&mut self, blocks: &mut core::slice::ChunksExact<'_, u8>, rounds: usize) {
+        self.map_words(interleaved::interleave);
+        for block in blocks {
+            for (word, chunk) in self.0.iter_mut().zip(block.chunks_exact(8)) {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(chunk);
+                *word ^= interleaved::interleave(u64::from_le_bytes(bytes));
+            }
+            interleaved::keccak_p_interleaved(&mut self.0, rounds);
+        }
+        self.map_words(interleaved::deinterleave);
+    }
+
+    /// Squeezes the whole `blocks` with the state bit interleaved across all of them, the
+    /// counterpart of [`absorb_interleaved`](#method.absorb_interleaved).
+    #[cfg(all(feature = "bit-interleaving", target_pointer_width = "32"))]
+    fn squeeze_interleaved(
+        &mut self,
+        blocks: &mut core::slice::ChunksExactMut<'_, u8>,
+        rounds: usize,
+    ) {
+        self.map_words(interleaved::interleave);
+        for block in blocks {
+            for (chunk, word) in block.chunks_exact_mut(8).zip(self.0.iter()) {
+                chunk.copy_from_slice(&interleaved::deinterleave(*word).to_le_bytes());
+            }
+            interleaved::keccak_p_interleaved(&mut self.0, rounds);
+        }
+        self.map_words(interleaved::deinterleave);
+    }
+
+    #[cfg(all(feature = "bit-interleaving", target_pointer_width = "32"))]
+    fn map_words(&mut self, f: fn(u64) -> u64) {
+        for word in self.0.iter_mut() {
+            *word = f(*word);
+        }
+    }
+
+    /// Squeezes the uninitialized `output` like [`squeeze`](#method.squeeze), initializing
+    /// every byte of it.
+    fn squeeze_uninit<P: Permutation>(
+        &mut self,
+        offset: usize,
+        rate: usize,
+        output: &mut [core::mem::MaybeUninit<u8>],
+    ) -> usize {
+        let mut op = 0;
+        let mut l = output.len();
+        let mut offset = offset;
+        let mut left = rate - offset;
+        while l >= left {
+            self.setout_uninit(&mut output[op..], offset, left);
+            self.permute::<P>();
+            op += left;
+            l -= left;
+            left = rate;
+            offset = 0;
+        }
+
+        self.setout_uninit(&mut output[op..], offset, l);
+        offset + l
+    }
+
+    /// Squeezes `output` like [`squeeze`](#method.squeeze), XORing the output into it.
+    fn squeeze_xor<P: Permutation>(
+        &mut self,
+        offset: usize,
+        rate: usize,
+        output: &mut [u8],
+    ) -> usize {
+        let mut op = 0;
+        let mut l = output.len();
+        let mut offset = offset;
+        let mut left = rate - offset;
+        while l >= left {
+            self.xorout(&mut output[op..], offset, left);
+            self.permute::<P>();
+            op += left;
+            l -= left;
+            left = rate;
+            offset = 0;
+        }
+
+        self.xorout(&mut output[op..], offset, l);
+        offset + l
+    }
+
+    /// XORs a block of `src.len()` bytes, a multiple of the word size, into the first words of
+    /// the state, through the same hook as [`xorin`](#method.xorin).
+    fn xorin_block(&mut self, src: &[u8]) {
+        debug_assert_eq!(src.len() & 7, 0);
+        self.xorin(src, 0, src.len());
+    }
+
+    fn pad<D: Padding>(&mut self, offset: usize, delim: u8, rate: usize) {
+        // a full block is permuted as soon as it is absorbed, so input filling the last block
+        // exactly is padded in a block of its own, starting at offset 0. The restoring
+        // constructors reject absorbing states holding a full block.
+        invariant!(
+            offset < rate,
+            "padding at offset {} of a full block of {} bytes",
+            offset,
+            rate
+        );
+        self.execute(0, rate, |block| D::pad(block, offset, rate, delim));
+    }
+
+    fn xor_byte(&mut self, offset: usize, byte: u8) {
+        self.execute(offset, 1, |buff| buff[0] ^= byte);
+    }
+
+    fn wipe(&mut self, len: usize) {
+        self.execute(0, len, |buffer| {
+            for byte in buffer {
+                // SAFETY: `byte` is a valid, aligned `&mut u8`. The volatile write keeps the
+                // compiler from eliding the zeroing.
+                unsafe { core::ptr::write_volatile(byte, 0) };
+            }
+        });
+    }
+
+    #[cfg(feature = "zeroize")]
+    fn zeroize(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
+/// A permutation applied to the sponge state by [`KeccakState`].
+///
+/// [`KeccakState`]: struct.KeccakState.html
+pub trait Permutation {
+    /// Permute the state.
+    fn execute(state: &mut [u64; WORDS]);
+
+    /// The number of rounds of a `keccak-p[1600]` permutation, which the sponge may then run on
+    /// its own representation of the lanes, e.g. with the `bit-interleaving` feature. `None` for
+    /// any other permutation.
+    #[doc(hidden)]
+    const KECCAK_P_ROUNDS: Option<usize> = None;
+}
+
+/// The domain separation of a sponge, i.e. the delimiter byte holding the suffix bits appended
+/// to the input, followed by the first bit of the padding.
+///
+/// Mixing up the domains is a common mistake, e.g. `Keccak-256` and `SHA3-256` differ only in
+/// the delimiter, so the constructors of custom sponges accept a `Domain`, see
+/// [`KeccakState::with_domain`]. Domains are compared by their delimiter byte, so
+/// `Custom(0x06)` equals [`Sha3`].
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::Domain;
+/// assert_eq!(Domain::Sha3.delim(), 0x06);
+/// assert_eq!(Domain::from_delim(0x1f), Domain::Shake);
+/// ```
+///
+/// [`KeccakState::with_domain`]: struct.KeccakState.html#method.with_domain
+/// [`Sha3`]: #variant.Sha3
+#[derive(Clone, Copy, Debug)]
+pub enum Domain {
+    /// `0x01`, no suffix bits, as in the `Keccak` submission and Ethereum.
+    Keccak,
+    /// `0x06`, the `01` suffix of the `SHA3` hash functions.
+    Sha3,
+    /// `0x1f`, the `1111` suffix of the `SHAKE` extendable-output functions.
+    Shake,
+    /// `0x04`, the `00` suffix of `cSHAKE` and the functions built on it.
+    CShake,
+    /// Any other delimiter byte, e.g. the domain separation bytes of `KangarooTwelve`.
+    Custom(u8),
+}
+
+impl Domain {
+    /// The delimiter byte of the domain.
+    pub const fn delim(self) -> u8 {
+        match self {
+            Domain::Keccak => 0x01,
+            Domain::Sha3 => 0x06,
+            Domain::Shake => 0x1f,
+            Domain::CShake => 0x04,
+            Domain::Custom(delim) => delim,
+        }
+    }
+
+    /// The domain with the delimiter byte `delim`, one of the named domains if it matches.
+    pub const fn from_delim(delim: u8) -> Domain {
+        match delim {
+            0x01 => Domain::Keccak,
+            0x06 => Domain::Sha3,
+            0x1f => Domain::Shake,
+            0x04 => Domain::CShake,
+            delim => Domain::Custom(delim),
+        }
+    }
+}
+
+impl PartialEq for Domain {
+    fn eq(&self, other: &Domain) -> bool {
+        self.delim() == other.delim()
+    }
+}
+
+impl Eq for Domain {}
+
+impl core::hash::Hash for Domain {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.delim().hash(state);
+    }
+}
+
+impl From<u8> for Domain {
+    fn from(delim: u8) -> Domain {
+        Domain::from_delim(delim)
+    }
+}
+
+impl From<Domain> for u8 {
+    fn from(domain: Domain) -> u8 {
+        domain.delim()
+    }
+}
+
+/// A padding rule applied by [`KeccakState`] to the last absorbed block.
+///
+/// The hashers of this crate all pad with [`Pad10Star1`], other rules are only needed to
+/// interoperate with legacy constructions, see [`KeccakState::pad_with`].
+///
+/// [`KeccakState`]: struct.KeccakState.html
+/// [`Pad10Star1`]: struct.Pad10Star1.html
+/// [`KeccakState::pad_with`]: struct.KeccakState.html#method.pad_with
+pub trait Padding {
+    /// Pad the block, `offset` is the number of absorbed bytes in it and `delim` the domain
+    /// separator of the sponge.
+    ///
+    /// `block` holds the `rate` bytes of the block in little-endian lane order, the padding is
+    /// XORed into it.
+    fn pad(block: &mut [u8], offset: usize, rate: usize, delim: u8);
+}
+
+/// The multi-rate `pad10*1` padding of FIPS 202, merged with the domain separator.
+///
+/// `delim` is XORed at `offset`, its bits are the domain suffix followed by the first bit of the
+/// padding, and the final bit is XORed into the last byte of the block.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Pad10Star1;
+
+impl Padding for Pad10Star1 {
+    fn pad(block: &mut [u8], offset: usize, rate: usize, delim: u8) {
+        block[offset] ^= delim;
+        block[rate - 1] ^= 0x80;
+    }
+}
+
+/// The `keccak-p[1600, ROUNDS]` permutation.
+///
+/// Reduced-round variants use the last `ROUNDS` round constants of `keccak-f[1600]`, e.g.
+/// `KeccakP<12>` is the permutation used by [`KangarooTwelve`]. Any number of rounds up to 24
+/// is accepted, using more fails to compile.
+///
+/// Below 12 rounds the sponge offers no meaningful security, those variants are meant only for
+/// cryptanalysis, e.g. `KeccakState::<KeccakP<6>>::with_rate(rate, delim)` is a 6 round sponge.
+///
+/// The permutation runs in constant time: it has no branches and no memory accesses that depend
+/// on the state, on every backend. The round constants are looked up by the public round number.
+///
+/// [`KangarooTwelve`]: https://eprint.iacr.org/2016/770.pdf
+pub struct KeccakP<const ROUNDS: usize>;
+
+impl<const ROUNDS: usize> KeccakP<ROUNDS> {
+    const VALID_ROUNDS: () = assert!(ROUNDS <= 24, "keccak-p[1600] has at most 24 rounds");
+}
+
+impl<const ROUNDS: usize> Permutation for KeccakP<ROUNDS> {
+    fn execute(state: &mut [u64; WORDS]) {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_ROUNDS;
+        keccak_p(state, ROUNDS);
+    }
+
+    const KECCAK_P_ROUNDS: Option<usize> = {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_ROUNDS;
+        Some(ROUNDS)
+    };
+}
+
+/// The `keccak-f[1600]` permutation.
+pub type KeccakF = KeccakP<24>;
+
+/// Applies `keccak-f[1600]` to `state`, for constructions composed directly on the 25 words.
+///
+/// Unlike [`keccakf`] it is available with any set of features, and runs on the same backend as
+/// the sponges.
+///
+/// # Example
+///
+/// ```
+/// let mut state = [0u64; 25];
+/// tiny_keccak::permute(&mut state);
+/// assert_eq!(state[0], 0xf1258f7940e1dde7);
+/// ```
+///
+/// [`keccakf`]: fn.keccakf.html
+pub fn permute(state: &mut [u64; WORDS]) {
+    KeccakF::execute(state);
+}
+
+/// Applies `keccak-f[1600]` to a state held in a slice, e.g. a `Vec<u64>`, like [`permute`].
+///
+/// # Errors
+///
+/// Fails with [`KeccakError::InvalidStateLength`], leaving the words untouched, if `state` isn't
+/// 25 words long.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{permute_slice, KeccakError};
+/// let mut state = vec![0u64; 25];
+/// permute_slice(&mut state).unwrap();
+/// assert_eq!(state[0], 0xf1258f7940e1dde7);
+///
+/// assert_eq!(
+///     permute_slice(&mut state[..24]),
+///     Err(KeccakError::InvalidStateLength { len: 24 })
+/// );
+/// ```
+///
+/// [`permute`]: fn.permute.html
+/// [`KeccakError::InvalidStateLength`]: enum.KeccakError.html#variant.InvalidStateLength
+pub fn permute_slice(state: &mut [u64]) -> Result<(), KeccakError> {
+    let len = state.len();
+    let state: &mut [u64; WORDS] = core::convert::TryFrom::try_from(state)
+        .map_err(|_| KeccakError::InvalidStateLength { len })?;
+    permute(state);
+    Ok(())
+}
+
+#[cfg(feature = "unstable-trace")]
+impl<const ROUNDS: usize> KeccakP<ROUNDS> {
+    /// Permute the state like [`execute`], calling `on_round` with the index of every round and
+    /// the state after it, e.g. to compare against the intermediate values published with
+    /// FIPS 202.
+    ///
+    /// The rounds are always computed by the scalar permutation. Requires the `unstable-trace`
+    /// feature, [`execute`] is the same with or without it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::KeccakF;
+    /// let mut state = [0u64; 25];
+    /// KeccakF::execute_traced(&mut state, |round, state| {
+    ///     println!("round {}: {:016x?}", round, state);
+    /// });
+    /// ```
+    ///
+    /// [`execute`]: trait.Permutation.html#tymethod.execute
+    pub fn execute_traced<F: FnMut(usize, &[u64; WORDS])>(
+        state: &mut [u64; WORDS],
+        mut on_round: F,
+    ) {
+        for (round, rc) in RC[24 - ROUNDS..].iter().enumerate() {
+            keccak_round(state, *rc);
+            on_round(round, state);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Mode {
+    Absorbing,
+    Squeezing,
+}
+
+/// The rate is valid and the offset never exceeds it, a larger one would read and write past the
+/// block. The words are a `[u64; WORDS]`, their length is checked by the type.
+///
+/// Every operation of the sponges checks the invariants before and after it runs, with `assert!`
+/// if the `debug-invariants` feature is enabled and with `debug_assert!` otherwise.
+#[inline]
+fn check_invariants(offset: usize, rate: usize) {
+    invariant!(is_valid_rate(rate), "invalid sponge rate {}", rate);
+    invariant!(
+        offset <= rate,
+        "sponge offset {} exceeds the rate {}",
+        offset,
+        rate
+    );
+}
+
+/// Checks a sponge after absorbing or squeezing `len` bytes from the offset `before`: it is in
+/// `expected` mode and the offset moved by `len` bytes within the block, permuting every block it
+/// completed.
+#[inline]
+fn check_advanced(
+    offset: usize,
+    rate: usize,
+    mode: Mode,
+    before: usize,
+    len: usize,
+    expected: Mode,
+) {
+    check_invariants(offset, rate);
+    invariant!(mode == expected, "invalid sponge mode transition");
+    invariant!(
+        offset == (before + len) % rate,
+        "sponge offset {} after {} bytes from the offset {}",
+        offset,
+        len,
+        before
+    );
+}
+
+/// The sponge state shared by all Keccak derived functions.
+///
+/// Input is absorbed with [`update`] and output is squeezed with [`squeeze`]. The first call to
+/// [`squeeze`] pads the absorbed input.
+///
+/// With the `zeroize` feature enabled the state is wiped when it is dropped. Enable it when the
+/// state holds secret material, e.g. when using the hashers for key derivation.
+///
+/// The state is plain data, it and all the hashers built on it are `Send` and `Sync`.
+///
+/// The state implements [`Hasher`] and [`Xof`] for any [`Permutation`], so code generic over
+/// the hashers can be tested with a mock permutation, whose output is predictable:
+///
+/// ```
+/// # use tiny_keccak::{Hasher, KeccakState, Permutation};
+/// struct Identity;
+///
+/// impl Permutation for Identity {
+///     fn execute(_state: &mut [u64; 25]) {}
+/// }
+///
+/// fn digest<H: Hasher>(hasher: H, message: &[u8]) -> [u8; 4] {
+///     hasher.chain(message).finalize_array()
+/// }
+///
+/// // the "digest" is the padded message
+/// let state = KeccakState::<Identity>::with_rate(136, 0x01);
+/// assert_eq!(digest(state, b"abc"), [b'a', b'b', b'c', 0x01]);
+/// ```
+///
+/// [`update`]: #method.update
+/// [`squeeze`]: #method.squeeze
+/// [`Hasher`]: trait.Hasher.html
+/// [`Xof`]: trait.Xof.html
+/// [`Permutation`]: trait.Permutation.html
+pub struct KeccakState<P> {
+    pub(crate) buffer: Buffer,
+    pub(crate) offset: usize,
+    pub(crate) rate: usize,
+    pub(crate) delim: u8,
+    pub(crate) mode: Mode,
+    #[cfg(feature = "count-permutations")]
+    permutations: u64,
+    pub(crate) permutation: core::marker::PhantomData<P>,
+}
+
+/// The most a single sponge may occupy, a fixed budget per target, 8 bytes more with the counter
+/// of the `count-permutations` feature.
+///
+/// It is 216 bytes, the 200 bytes of the state words and 16 of metadata, on targets with pointers
+/// of up to 32 bits. On 64-bit targets the offset and the rate are a `usize` of 8 bytes each and
+/// the delimiter and the mode are padded to the alignment of the words, so the budget there is
+/// 224 bytes; 216 would need narrower offset and rate fields.
+///
+/// The size of [`KeccakState`] and of every hasher is checked against it at compile time, so a
+/// layout regression, e.g. a heap allocated field, fails the build. Hashers holding two sponges
+/// are allowed twice the size and four words of 8 bytes, which also covers the padding of their
+/// fields on 32-bit targets aligning the words to 8 bytes.
+pub(crate) const SPONGE_SIZE: usize = {
+    #[cfg(target_pointer_width = "64")]
+    let budget = 224;
+    #[cfg(not(target_pointer_width = "64"))]
+    let budget = 216;
+    if cfg!(feature = "count-permutations") {
+        budget + 8
+    } else {
+        budget
+    }
+};
+
+const _: () = assert!(core::mem::size_of::<KeccakState<KeccakF>>() <= SPONGE_SIZE);
+
+// the words are stored inline, so a clone can't share them with the original
+const _: () = assert!(core::mem::size_of::<Buffer>() == WORDS * 8);
+
+/// Clones are deep copies: the words of the state are stored inline, without any shared or heap
+/// allocated memory, so the clone and the original absorb and squeeze independently.
+impl<P> Clone for KeccakState<P> {
+    fn clone(&self) -> Self {
+        KeccakState {
+            buffer: self.buffer.clone(),
+            offset: self.offset,
+            rate: self.rate,
+            delim: self.delim,
+            mode: self.mode,
+            #[cfg(feature = "count-permutations")]
+            permutations: self.permutations,
+            permutation: core::marker::PhantomData,
+        }
+    }
+
+    /// Copies `source` into the existing state, word by word, rather than building a new state
+    /// and dropping the old one, e.g. when restoring a snapshot in a loop. With the `zeroize`
+    /// feature this also skips wiping the dropped state.
+    fn clone_from(&mut self, source: &Self) {
+        self.buffer.0 = source.buffer.0;
+        self.offset = source.offset;
+        self.rate = source.rate;
+        self.delim = source.delim;
+        self.mode = source.mode;
+        #[cfg(feature = "count-permutations")]
+        {
+            self.permutations = source.permutations;
+        }
+    }
+}
+
+/// Compares the words, offset, rate, delimiter and mode of two states.
+///
+/// The comparison isn't constant time, it's meant for tests and deduplication, not for secret
+/// material. Compare digests with [`KeccakState::verify`] instead.
+///
+/// [`KeccakState::verify`]: struct.KeccakState.html#method.verify
+impl<P> PartialEq for KeccakState<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.buffer.0 == other.buffer.0
+            && self.offset == other.offset
+            && self.rate == other.rate
+            && self.delim == other.delim
+            && self.mode == other.mode
+    }
+}
+
+impl<P> Eq for KeccakState<P> {}
+
+/// Same as [`KeccakState::from_words`].
+///
+/// [`KeccakState::from_words`]: struct.KeccakState.html#method.from_words
+impl<P: Permutation> From<[u64; WORDS]> for KeccakState<P> {
+    fn from(words: [u64; WORDS]) -> Self {
+        KeccakState::from_words(words)
+    }
+}
+
+impl<P: Permutation> Hasher for KeccakState<P> {
+    fn update(&mut self, input: &[u8]) {
+        KeccakState::update(self, input);
+    }
+
+    fn finalize(self, output: &mut [u8]) {
+        KeccakState::finalize(self, output);
+    }
+
+    fn finalize_uninit(self, output: &mut [core::mem::MaybeUninit<u8>]) -> &mut [u8] {
+        KeccakState::finalize_uninit(self, output)
+    }
+
+    fn finalize_xor(self, output: &mut [u8]) {
+        KeccakState::finalize_xor(self, output);
+    }
+
+    fn finalize_scattered(mut self, outputs: &mut [&mut [u8]]) {
+        // squeezing the slices in turn continues the output stream
+        for output in outputs.iter_mut() {
+            KeccakState::squeeze(&mut self, output);
+        }
+    }
+}
+
+impl<P: Permutation> Xof for KeccakState<P> {
+    fn squeeze(&mut self, output: &mut [u8]) {
+        KeccakState::squeeze(self, output);
+    }
+
+    fn squeeze_xor(&mut self, output: &mut [u8]) {
+        KeccakState::squeeze_xor(self, output);
+    }
+}
+
+/// Decodes a checkpoint written by [`KeccakState::to_bytes`] like [`KeccakState::from_bytes`],
+/// also failing with [`KeccakError::InvalidCheckpoint`] if the slice isn't [`CHECKPOINT_LEN`]
+/// bytes long.
+///
+/// [`KeccakState::to_bytes`]: struct.KeccakState.html#method.to_bytes
+/// [`KeccakState::from_bytes`]: struct.KeccakState.html#method.from_bytes
+/// [`KeccakError::InvalidCheckpoint`]: enum.KeccakError.html#variant.InvalidCheckpoint
+/// [`CHECKPOINT_LEN`]: constant.CHECKPOINT_LEN.html
+impl<P: Permutation> core::convert::TryFrom<&[u8]> for KeccakState<P> {
+    type Error = KeccakError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, KeccakError> {
+        let bytes: &[u8; CHECKPOINT_LEN] =
+            core::convert::TryFrom::try_from(bytes).map_err(|_| KeccakError::InvalidCheckpoint)?;
+        KeccakState::from_bytes(bytes)
+    }
+}
+
+/// Prints the rate and the offset of the sponge, the state itself is redacted unless the
+/// `unstable-debug` feature is enabled, so keys don't leak through `{:?}`.
+impl<P: Permutation> core::fmt::Debug for KeccakState<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.debug("KeccakState", f)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "KeccakState")]
+struct SerializedState {
+    words: [u64; WORDS],
+    offset: usize,
+    rate: usize,
+    delim: u8,
+    mode: Mode,
+}
+
+#[cfg(feature = "serde")]
+impl<P> serde::Serialize for KeccakState<P> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedState {
+            words: self.buffer.0,
+            offset: self.offset,
+            rate: self.rate,
+            delim: self.delim,
+            mode: self.mode,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, P> serde::Deserialize<'de> for KeccakState<P> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let state = SerializedState::deserialize(deserializer)?;
+        if !is_valid_rate(state.rate) {
+            return Err(D::Error::custom(format_args!(
+                "invalid sponge rate {}",
+                state.rate
+            )));
+        }
+        if state.offset > state.rate {
+            return Err(D::Error::custom(format_args!(
+                "offset {} exceeds the sponge rate {}",
+                state.offset, state.rate
+            )));
+        }
+        // an absorbing state permutes a block as soon as it is full
+        if state.offset == state.rate && state.mode == Mode::Absorbing {
+            return Err(D::Error::custom(format_args!(
+                "absorbing state holding a full block of {} bytes",
+                state.rate
+            )));
+        }
+
+        Ok(KeccakState {
+            buffer: Buffer(state.words),
+            offset: state.offset,
+            rate: state.rate,
+            delim: state.delim,
+            mode: state.mode,
+            #[cfg(feature = "count-permutations")]
+            permutations: 0,
+            permutation: core::marker::PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P> Drop for KeccakState<P> {
+    fn drop(&mut self) {
+        self.buffer.zeroize();
+        zeroize::Zeroize::zeroize(&mut self.offset);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P> zeroize::ZeroizeOnDrop for KeccakState<P> {}
+
+impl<P: Permutation> KeccakState<P> {
+    pub(crate) fn new(rate: usize, delim: u8) -> Self {
+        // checked up front, so the sponge never indexes past the state
+        assert!(is_valid_rate(rate), "invalid sponge rate {}", rate);
+        KeccakState {
+            buffer: Buffer::default(),
+            offset: 0,
+            rate,
+            delim,
+            mode: Mode::Absorbing,
+            #[cfg(feature = "count-permutations")]
+            permutations: 0,
+            permutation: core::marker::PhantomData,
+        }
+    }
+
+    /// XORs `value` into the last lane of the state, part of the capacity of every valid rate,
+    /// which the input of a sponge never reaches.
+    #[cfg(feature = "keccak")]
+    pub(crate) fn xor_capacity(&mut self, value: u64) {
+        self.buffer.words()[WORDS - 1] ^= value;
+    }
+
+    /// Creates a new sponge with a rate of `rate` bytes and the domain separator `delim`, for
+    /// parameters not covered by the hashers of this crate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` isn't valid according to [`is_valid_rate`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakF, KeccakState};
+    /// // Keccak[r=1088, c=512]
+    /// let state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    /// assert_eq!(state.capacity_bits(), 512);
+    /// ```
+    ///
+    /// [`is_valid_rate`]: fn.is_valid_rate.html
+    pub fn with_rate(rate: usize, delim: u8) -> Self {
+        KeccakState::new(rate, delim)
+    }
+
+    /// Creates a new sponge with a rate of `rate` bytes in the domain `domain`, the same as
+    /// [`with_rate`] with the delimiter byte of the domain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` isn't valid according to [`is_valid_rate`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Domain, KeccakF, KeccakState};
+    /// // SHA3-256
+    /// let state = KeccakState::<KeccakF>::with_domain(136, Domain::Sha3);
+    /// assert_eq!(state.delim(), 0x06);
+    /// ```
+    ///
+    /// [`with_rate`]: #method.with_rate
+    /// [`is_valid_rate`]: fn.is_valid_rate.html
+    pub fn with_domain(rate: usize, domain: Domain) -> Self {
+        KeccakState::new(rate, domain.delim())
+    }
+
+    /// Creates a new sponge like [`with_rate`], failing with [`KeccakError::InvalidRate`] instead
+    /// of panicking if `rate` isn't valid according to [`is_valid_rate`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakError, KeccakF, KeccakState};
+    /// assert!(KeccakState::<KeccakF>::try_with_rate(136, 0x01).is_ok());
+    /// assert_eq!(
+    ///     KeccakState::<KeccakF>::try_with_rate(208, 0x01).err(),
+    ///     Some(KeccakError::InvalidRate { rate: 208 })
+    /// );
+    /// ```
+    ///
+    /// [`with_rate`]: #method.with_rate
+    /// [`KeccakError::InvalidRate`]: enum.KeccakError.html#variant.InvalidRate
+    /// [`is_valid_rate`]: fn.is_valid_rate.html
+    pub fn try_with_rate(rate: usize, delim: u8) -> Result<Self, KeccakError> {
+        if !is_valid_rate(rate) {
+            return Err(KeccakError::InvalidRate { rate });
+        }
+        Ok(KeccakState::new(rate, delim))
+    }
+
+    /// Reconstructs an absorbing state from the parts returned by [`as_words`], [`offset`],
+    /// [`rate`] and [`delim`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` isn't valid according to [`is_valid_rate`] or `offset` isn't below
+    /// `rate`: an absorbing state permutes a block as soon as it is full.
+    ///
+    /// [`is_valid_rate`]: fn.is_valid_rate.html
+    /// [`as_words`]: #method.as_words
+    /// [`offset`]: #method.offset
+    /// [`rate`]: #method.rate
+    /// [`delim`]: #method.delim
+    pub fn from_parts(words: [u64; WORDS], offset: usize, rate: usize, delim: u8) -> Self {
+        assert!(is_valid_rate(rate), "invalid sponge rate {}", rate);
+        assert!(
+            offset < rate,
+            "offset cannot exceed the rate, nor reach it while absorbing"
+        );
+        KeccakState {
+            buffer: Buffer(words),
+            offset,
+            rate,
+            delim,
+            mode: Mode::Absorbing,
+            #[cfg(feature = "count-permutations")]
+            permutations: 0,
+            permutation: core::marker::PhantomData,
+        }
+    }
+
+    /// Creates an absorbing state holding `words`, with the rate and the domain separator of
+    /// `Keccak-256`. Use [`from_parts`] for other parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakF, KeccakState};
+    /// let state = KeccakState::<KeccakF>::from_words([7; 25]);
+    /// assert_eq!(state.as_words(), &[7; 25]);
+    /// assert_eq!(state.rate(), 136);
+    /// ```
+    ///
+    /// [`from_parts`]: #method.from_parts
+    pub fn from_words(words: [u64; WORDS]) -> Self {
+        KeccakState::from_parts(words, 0, bits_to_rate(256), 0x01)
+    }
+
+    /// The words of the state.
+    pub fn as_words(&self) -> &[u64; WORDS] {
+        &self.buffer.0
+    }
+
+    /// Encodes the state in a fixed binary layout, independent of the target, so a checkpoint
+    /// written on one machine resumes on another.
+    ///
+    /// The [`CHECKPOINT_LEN`] bytes are laid out as
+    ///
+    /// | bytes     | field                                      |
+    /// |-----------|--------------------------------------------|
+    /// | `0..200`  | the 25 state words, little-endian          |
+    /// | `200..208`| the offset, a little-endian `u64`          |
+    /// | `208..216`| the rate, a little-endian `u64`            |
+    /// | `216`     | the domain separator                       |
+    /// | `217`     | the mode, `0` absorbing and `1` squeezing  |
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakF, KeccakState};
+    /// let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    /// state.update(b"hello");
+    /// let checkpoint = state.to_bytes();
+    /// assert!(KeccakState::<KeccakF>::from_bytes(&checkpoint).unwrap() == state);
+    /// ```
+    ///
+    /// [`CHECKPOINT_LEN`]: constant.CHECKPOINT_LEN.html
+    pub fn to_bytes(&self) -> [u8; CHECKPOINT_LEN] {
+        let mut bytes = [0u8; CHECKPOINT_LEN];
+        for (chunk, word) in bytes[..WORDS * 8]
+            .chunks_exact_mut(8)
+            .zip(self.buffer.0.iter())
+        {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes[200..208].copy_from_slice(&(self.offset as u64).to_le_bytes());
+        bytes[208..216].copy_from_slice(&(self.rate as u64).to_le_bytes());
+        bytes[216] = self.delim;
+        bytes[217] = match self.mode {
+            Mode::Absorbing => 0,
+            Mode::Squeezing => 1,
+        };
+        bytes
+    }
+
+    /// Decodes a state encoded by [`to_bytes`].
+    ///
+    /// Fails with [`KeccakError::InvalidRate`] if the rate isn't valid according to
+    /// [`is_valid_rate`] and with [`KeccakError::InvalidCheckpoint`] if the mode is unknown, or
+    /// if the offset exceeds the rate, or reaches it in an absorbing state, which permutes a
+    /// block as soon as it is full.
+    ///
+    /// [`to_bytes`]: #method.to_bytes
+    /// [`KeccakError::InvalidRate`]: enum.KeccakError.html#variant.InvalidRate
+    /// [`KeccakError::InvalidCheckpoint`]: enum.KeccakError.html#variant.InvalidCheckpoint
+    /// [`is_valid_rate`]: fn.is_valid_rate.html
+    pub fn from_bytes(bytes: &[u8; CHECKPOINT_LEN]) -> Result<Self, KeccakError> {
+        fn read_u64(bytes: &[u8]) -> usize {
+            let mut le = [0u8; 8];
+            le.copy_from_slice(bytes);
+            // a value that doesn't fit is rejected by the checks below
+            core::convert::TryFrom::try_from(u64::from_le_bytes(le)).unwrap_or(usize::MAX)
+        }
+
+        let offset = read_u64(&bytes[200..208]);
+        let rate = read_u64(&bytes[208..216]);
+        if !is_valid_rate(rate) {
+            return Err(KeccakError::InvalidRate { rate });
+        }
+        let mode = match bytes[217] {
+            0 => Mode::Absorbing,
+            1 => Mode::Squeezing,
+            _ => return Err(KeccakError::InvalidCheckpoint),
+        };
+        if offset > rate || (offset == rate && mode == Mode::Absorbing) {
+            return Err(KeccakError::InvalidCheckpoint);
+        }
+
+        let mut words = [0u64; WORDS];
+        for (word, chunk) in words.iter_mut().zip(bytes[..WORDS * 8].chunks_exact(8)) {
+            let mut le = [0u8; 8];
+            le.copy_from_slice(chunk);
+            *word = u64::from_le_bytes(le);
+        }
+
+        Ok(KeccakState {
+            buffer: Buffer(words),
+            offset,
+            rate,
+            delim: bytes[216],
+            mode,
+            #[cfg(feature = "count-permutations")]
+            permutations: 0,
+            permutation: core::marker::PhantomData,
+        })
+    }
+
+    /// Copies the capacity of the state, the `200 - rate` bytes after the rate, to `output` as
+    /// little-endian bytes.
+    ///
+    /// The capacity is what makes the sponge secure, it is never absorbed into or squeezed from
+    /// by the hashers. This is an escape hatch for constructions built on the raw state, e.g.
+    /// commitments, it isn't needed for hashing. Requires the `unstable-capacity` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output` isn't as long as the capacity.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakF, KeccakState};
+    /// let state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    /// let mut capacity = [0u8; 64];
+    /// state.capacity_bytes(&mut capacity);
+    /// ```
+    #[cfg(feature = "unstable-capacity")]
+    pub fn capacity_bytes(&self, output: &mut [u8]) {
+        assert_eq!(
+            output.len(),
+            WORDS * 8 - self.rate,
+            "output must be as long as the capacity"
+        );
+        // the words are read directly, the byte view of the buffer is native-endian
+        for (i, byte) in output.iter_mut().enumerate() {
+            let position = self.rate + i;
+            *byte = (self.buffer.0[position / 8] >> (8 * (position % 8))) as u8;
+        }
+    }
+
+    /// The number of bytes absorbed into, or squeezed from, the current block.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The rate of the sponge in bytes.
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    /// The capacity of the sponge in bits.
+    pub fn capacity_bits(&self) -> usize {
+        rate_to_capacity_bits(self.rate)
+    }
+
+    /// The domain separation byte appended to the input when padding.
+    pub fn delim(&self) -> u8 {
+        self.delim
+    }
+
+    /// The domain separation of the sponge, see [`Domain`].
+    ///
+    /// [`Domain`]: enum.Domain.html
+    pub fn domain(&self) -> Domain {
+        Domain::from_delim(self.delim)
+    }
+
+    /// The number of times the permutation ran since the sponge was created or [`reset`], e.g.
+    /// to charge a hash per permutation. Absorbing permutes every completed block, squeezing
+    /// every exhausted one, and padding permutes once before the first output. Requires the
+    /// `count-permutations` feature.
+    ///
+    /// A clone keeps the count, a state restored from a checkpoint starts from zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakF, KeccakState};
+    /// let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    /// state.update(&[0u8; 300]);
+    /// assert_eq!(state.permutation_count(), 2);
+    /// state.squeeze(&mut [0u8; 32]);
+    /// assert_eq!(state.permutation_count(), 3);
+    /// ```
+    ///
+    /// [`reset`]: #method.reset
+    #[cfg(feature = "count-permutations")]
+    pub fn permutation_count(&self) -> u64 {
+        self.permutations
+    }
+
+    fn keccak_openvm_opcode(buffer_ptr: *mut u8) {
+        let words: &mut [u64; WORDS] = unsafe { &mut *(buffer_ptr as *mut [u64; WORDS]) };
+        P::execute(words);
+    }
+
+    fn keccak(&mut self) {
+        self.buffer.permute::<P>();
+        self.count_permutations(1);
+    }
+
+    /// Counts `n` runs of the permutation with the `count-permutations` feature.
+    #[inline]
+    fn count_permutations(&mut self, n: usize) {
+        #[cfg(feature = "count-permutations")]
+        {
+            self.permutations += n as u64;
+        }
+        #[cfg(not(feature = "count-permutations"))]
+        let _ = n;
+    }
+
+    /// Absorb additional input. Can be called multiple times.
+    ///
+    /// An empty `input` is a no-op in either mode: it neither moves the offset nor permutes, so
+    /// empty updates anywhere in a sequence leave the digest unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if output was squeezed already, the input would be silently XORed into the output
+    /// stream. [`reset`] the sponge to absorb a new input, or use [`try_update`] to handle it as an
+    /// error.
+    ///
+    /// [`reset`]: #method.reset
+    /// [`try_update`]: #method.try_update
+    #[inline]
+    pub fn update(&mut self, input: &[u8]) {
+        self.check_invariants();
+        if input.is_empty() {
+            return;
+        }
+        assert!(
+            matches!(self.mode, Mode::Absorbing),
+            "cannot absorb after squeezing, reset the sponge first"
+        );
+
+        // a small field of a message that fits in the rest of the block skips the block
+        // handling of `absorb`
+        if input.len() <= 8 && input.len() < self.rate - self.offset {
+            self.buffer.xorin(input, self.offset, input.len());
+            self.offset += input.len();
+            self.check_advanced(self.offset - input.len(), input.len(), Mode::Absorbing);
+            return;
+        }
+
+        self.absorb_blocks(input);
+    }
+
+    // out of line, so the small input path of `update` stays small enough to be inlined
+    #[inline(never)]
+    fn absorb_blocks(&mut self, input: &[u8]) {
+        let before = self.offset;
+        self.offset = self.buffer.absorb::<P>(self.offset, self.rate, input);
+        // every block completed from the offset on was permuted
+        self.count_permutations((before + input.len()) / self.rate);
+        self.check_advanced(before, input.len(), Mode::Absorbing);
+    }
+
+    /// Absorb additional input like [`update`], failing with [`KeccakError::AlreadySqueezing`]
+    /// instead of panicking if output was squeezed already.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakError, KeccakF, KeccakState};
+    /// let mut state = KeccakState::<KeccakF>::with_rate(168, 0x1f);
+    /// state.try_update(b"seed").unwrap();
+    /// state.squeeze(&mut [0u8; 32]);
+    /// assert_eq!(state.try_update(b"more"), Err(KeccakError::AlreadySqueezing));
+    /// ```
+    ///
+    /// [`update`]: #method.update
+    /// [`KeccakError::AlreadySqueezing`]: enum.KeccakError.html#variant.AlreadySqueezing
+    pub fn try_update(&mut self, input: &[u8]) -> Result<(), KeccakError> {
+        if let Mode::Squeezing = self.mode {
+            if !input.is_empty() {
+                return Err(KeccakError::AlreadySqueezing);
+            }
+        }
+        self.update(input);
+        Ok(())
+    }
+
+    #[inline]
+    fn check_invariants(&self) {
+        check_invariants(self.offset, self.rate);
+    }
+
+    #[inline]
+    fn check_advanced(&self, before: usize, len: usize, mode: Mode) {
+        check_advanced(self.offset, self.rate, self.mode, before, len, mode);
+    }
+
+    /// Absorb the first `nbits` bits of `data`, for inputs whose length isn't a multiple of 8
+    /// bits. The bits of a byte are absorbed from the least significant one, the bits of the last
+    /// byte past `nbits` are ignored.
+    ///
+    /// If `nbits` isn't a multiple of 8 the input ends with the partial byte: the state is
+    /// padded, with the domain separation bits following the last input bit, and the output can
+    /// be squeezed, but no more input can be absorbed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is shorter than `nbits` bits, or if `nbits` isn't zero and output was
+    /// squeezed already, which includes any call after a partial byte.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{bits_to_rate, KeccakF, KeccakState};
+    /// // SHA3-256 of the 5 bits `11001`
+    /// let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x06);
+    /// state.update_bits(&[0b10011], 5);
+    /// let mut output = [0u8; 32];
+    /// state.squeeze(&mut output);
+    /// assert_eq!(output[..4], [0x7b, 0x00, 0x47, 0xcf]);
+    /// ```
+    pub fn update_bits(&mut self, data: &[u8], nbits: usize) {
+        assert!(
+            nbits <= data.len() * 8,
+            "data is shorter than {} bits",
+            nbits
+        );
+        if nbits == 0 {
+            return;
+        }
+        // without whole bytes `update` gets an empty slice and checks nothing
+        assert!(
+            matches!(self.mode, Mode::Absorbing),
+            "cannot absorb after squeezing, reset the sponge first"
+        );
+        self.update(&data[..nbits / 8]);
+
+        let partial = nbits & 7;
+        if partial == 0 {
+            return;
+        }
+
+        // the partial byte is followed by the delimiter bits, including the first padding bit
+        let bits = u16::from(data[nbits / 8]) & ((1 << partial) - 1);
+        let padded = bits | (u16::from(self.delim) << partial);
+        // the bit position of the first padding bit in the block
+        let mut first = self.offset * 8 + 15 - padded.leading_zeros() as usize;
+
+        self.buffer.xor_byte(self.offset, padded as u8);
+        let high = (padded >> 8) as u8;
+        if high != 0 {
+            if self.offset + 1 < self.rate {
+                self.buffer.xor_byte(self.offset + 1, high);
+            } else {
+                self.keccak();
+                self.buffer.xor_byte(0, high);
+                first -= self.rate * 8;
+            }
+        }
+
+        // the last padding bit needs a block of its own if the first one ends the block
+        if first == self.rate * 8 - 1 {
+            self.keccak();
+        }
+        self.buffer.xor_byte(self.rate - 1, 0x80);
+        self.mode = Mode::Squeezing;
+        self.offset = self.rate;
+        self.check_invariants();
+    }
+
+    /// Absorb additional input, the same as [`update`].
+    ///
+    /// [`update`]: #method.update
+    pub fn absorb(&mut self, input: &[u8]) {
+        self.update(input);
+    }
+
+    /// Pad the absorbed input with the domain separator and the final bit of `pad10*1`, and
+    /// switch to squeezing.
+    ///
+    /// The padded block is permuted by the next [`squeeze`], so the state can be inspected in
+    /// between. Padding a squeezing state does nothing.
+    ///
+    /// [`squeeze`]: #method.squeeze
+    pub fn pad(&mut self) {
+        self.pad_with::<Pad10Star1>();
+    }
+
+    /// Pad the absorbed input with the padding rule `D` instead of `pad10*1`, and switch to
+    /// squeezing.
+    ///
+    /// The following [`squeeze`] calls continue from the padded block, so a custom padding is
+    /// applied by calling `pad_with` before squeezing. Padding a squeezing state does nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakF, KeccakState, Padding};
+    /// // a legacy padding appending a single marker byte
+    /// struct Marker;
+    ///
+    /// impl Padding for Marker {
+    ///     fn pad(block: &mut [u8], offset: usize, _rate: usize, _delim: u8) {
+    ///         block[offset] ^= 0x80;
+    ///     }
+    /// }
+    ///
+    /// let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    /// state.update(b"legacy");
+    /// state.pad_with::<Marker>();
+    /// let mut output = [0u8; 32];
+    /// state.squeeze(&mut output);
+    /// ```
+    ///
+    /// [`squeeze`]: #method.squeeze
+    pub fn pad_with<D: Padding>(&mut self) {
+        self.check_invariants();
+        if let Mode::Absorbing = self.mode {
+            self.buffer.pad::<D>(self.offset, self.delim, self.rate);
+            self.mode = Mode::Squeezing;
+            // the padded block is exhausted, the first squeeze permutes it
+            self.offset = self.rate;
+        }
+        invariant!(
+            self.mode == Mode::Squeezing,
+            "invalid sponge mode transition"
+        );
+    }
+
+    /// Squeeze the next part of the output.
+    ///
+    /// The first call pads the absorbed input. Subsequent calls continue the output stream, so
+    /// squeezing `a` and then `b` bytes is the same as squeezing `a + b` bytes at once. Squeezing
+    /// an empty output does nothing, not even padding.
+    pub fn squeeze(&mut self, output: &mut [u8]) {
+        self.check_invariants();
+        if output.is_empty() {
+            return;
+        }
+
+        self.pad();
+
+        let before = self.offset;
+        self.offset = self.buffer.squeeze::<P>(self.offset, self.rate, output);
+        self.count_permutations((before + output.len()) / self.rate);
+        self.check_advanced(before, output.len(), Mode::Squeezing);
+    }
+
+    /// Squeeze the next block of the output, exactly `RATE` bytes, and permute, for consumers
+    /// working a block at a time, e.g. a generator refilling a pool of buffers. The blocks
+    /// continue the output stream, `n` blocks are the same as squeezing `n * RATE` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `RATE` isn't the rate of the sponge, or if the output squeezed so far ends
+    /// within a block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakF, KeccakState};
+    /// // SHAKE128
+    /// let mut state = KeccakState::<KeccakF>::with_rate(168, 0x1f);
+    /// state.update(b"seed");
+    /// let first: [u8; 168] = state.squeeze_block();
+    /// let second: [u8; 168] = state.squeeze_block();
+    /// ```
+    pub fn squeeze_block<const RATE: usize>(&mut self) -> [u8; RATE] {
+        self.check_invariants();
+        assert_eq!(
+            RATE, self.rate,
+            "the block of {} bytes doesn't match the rate",
+            RATE
+        );
+        self.pad();
+        assert!(
+            self.offset == 0 || self.offset == self.rate,
+            "the output is within a block, the offset is {}",
+            self.offset
+        );
+
+        let before = self.offset;
+        if self.offset == self.rate {
+            self.keccak();
+        }
+        let mut block = [0u8; RATE];
+        self.buffer.setout_block(&mut block);
+        // like `squeeze`, an exhausted block is permuted right away
+        self.keccak();
+        self.offset = 0;
+        self.check_advanced(before, RATE, Mode::Squeezing);
+        block
     }
-}
 
-fn left_encode(len: usize) -> EncodedLen {
-    let mut buffer = [0u8; 9];
-    buffer[1..].copy_from_slice(&(len as u64).to_be_bytes());
-    let offset = buffer.iter().position(|i| *i != 0).unwrap_or(8);
-    buffer[offset - 1] = 9 - offset as u8;
+    /// Pad and squeeze the state to the output.
+    ///
+    /// An output of up to a rate, e.g. every `Keccak` and `SHA3` digest, is copied straight from
+    /// the words after the final permutation, longer outputs go through [`squeeze`].
+    ///
+    /// [`squeeze`]: #method.squeeze
+    pub fn finalize(mut self, output: &mut [u8]) {
+        self.check_invariants();
+        if let Mode::Absorbing = self.mode {
+            if !output.is_empty() && output.len() <= self.rate {
+                self.buffer
+                    .pad::<Pad10Star1>(self.offset, self.delim, self.rate);
+                self.keccak();
+                self.buffer.setout_words(output);
+                return;
+            }
+        }
+        self.squeeze(output);
+    }
 
-    EncodedLen {
-        offset: offset - 1,
-        buffer,
+    /// Pad and squeeze the state to the uninitialized `output` like [`finalize`], returning it
+    /// initialized. The output doesn't have to be zeroed first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use core::mem::MaybeUninit;
+    /// # use tiny_keccak::{KeccakF, KeccakState};
+    /// let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    /// state.update(b"hello");
+    /// let mut output = [MaybeUninit::uninit(); 32];
+    /// let digest = state.finalize_uninit(&mut output);
+    /// assert_eq!(digest.len(), 32);
+    /// ```
+    ///
+    /// [`finalize`]: #method.finalize
+    pub fn finalize_uninit(mut self, output: &mut [core::mem::MaybeUninit<u8>]) -> &mut [u8] {
+        self.check_invariants();
+        if !output.is_empty() {
+            self.pad();
+            let before = self.offset;
+            self.offset = self
+                .buffer
+                .squeeze_uninit::<P>(self.offset, self.rate, output);
+            self.count_permutations((before + output.len()) / self.rate);
+            self.check_advanced(before, output.len(), Mode::Squeezing);
+        }
+        // SAFETY: `squeeze_uninit` initialized every byte of `output`
+        unsafe { assume_init(output) }
     }
-}
 
-fn right_encode(len: usize) -> EncodedLen {
-    let mut buffer = [0u8; 9];
-    buffer[..8].copy_from_slice(&(len as u64).to_be_bytes());
-    let offset = buffer.iter().position(|i| *i != 0).unwrap_or(7);
-    buffer[8] = 8 - offset as u8;
-    EncodedLen { offset, buffer }
-}
+    /// Squeeze the next part of the output like [`squeeze`], XORing it into the existing
+    /// contents of `output`.
+    ///
+    /// [`squeeze`]: #method.squeeze
+    pub fn squeeze_xor(&mut self, output: &mut [u8]) {
+        self.check_invariants();
+        if output.is_empty() {
+            return;
+        }
 
-#[derive(Default, Clone)]
-struct Buffer([u64; WORDS]);
+        self.pad();
 
-impl Buffer {
-    pub fn words(&mut self) -> &mut [u64; WORDS] {
-        &mut self.0
+        let before = self.offset;
+        self.offset = self.buffer.squeeze_xor::<P>(self.offset, self.rate, output);
+        self.count_permutations((before + output.len()) / self.rate);
+        self.check_advanced(before, output.len(), Mode::Squeezing);
     }
 
-    #[cfg(target_endian = "little")]
-    #[inline]
-    fn execute<F: FnOnce(&mut [u8])>(&mut self, offset: usize, len: usize, f: F) {
-        let buffer: &mut [u8; WORDS * 8] = unsafe { core::mem::transmute(&mut self.0) };
-        f(&mut buffer[offset..][..len]);
+    /// Pad and squeeze the state like [`finalize`], XORing the output into the existing contents
+    /// of `output` instead of overwriting them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakF, KeccakState};
+    /// let state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    /// let mut digest = [0u8; 32];
+    /// state.clone().finalize(&mut digest);
+    ///
+    /// let mut data = [0xffu8; 32];
+    /// state.finalize_xor(&mut data);
+    /// assert_eq!(data[0], !digest[0]);
+    /// ```
+    ///
+    /// [`finalize`]: #method.finalize
+    pub fn finalize_xor(mut self, output: &mut [u8]) {
+        self.squeeze_xor(output);
     }
 
-    #[cfg(target_endian = "big")]
-    #[inline]
-    fn execute<F: FnOnce(&mut [u8])>(&mut self, offset: usize, len: usize, f: F) {
-        fn swap_endianess(buffer: &mut [u64]) {
-            for item in buffer {
-                *item = item.swap_bytes();
-            }
+    /// Pad and squeeze the state to the output like [`finalize`], failing with
+    /// [`KeccakError::AlreadySqueezing`] if output was squeezed already, where [`finalize`] would
+    /// keep squeezing.
+    ///
+    /// [`finalize`]: #method.finalize
+    /// [`KeccakError::AlreadySqueezing`]: enum.KeccakError.html#variant.AlreadySqueezing
+    pub fn try_finalize(self, output: &mut [u8]) -> Result<(), KeccakError> {
+        if let Mode::Squeezing = self.mode {
+            return Err(KeccakError::AlreadySqueezing);
         }
+        self.finalize(output);
+        Ok(())
+    }
 
-        let start = offset / 8;
-        let end = (offset + len + 7) / 8;
-        swap_endianess(&mut self.0[start..end]);
-        let buffer: &mut [u8; WORDS * 8] = unsafe { core::mem::transmute(&mut self.0) };
-        f(&mut buffer[offset..][..len]);
-        swap_endianess(&mut self.0[start..end]);
+    /// Pad and squeeze a copy of the state to the output, leaving the state itself untouched,
+    /// so more input can be absorbed afterwards.
+    ///
+    /// Only the copy is padded and squeezed, so a panic while squeezing, e.g. in a custom
+    /// [`Permutation`], leaves the state as it was and it can still be used.
+    ///
+    /// [`Permutation`]: trait.Permutation.html
+    pub fn finalize_into(&self, output: &mut [u8]) {
+        self.clone().squeeze(output);
     }
 
-    fn setout(&mut self, dst: &mut [u8], offset: usize, len: usize) {
-        self.execute(offset, len, |buffer| dst[..len].copy_from_slice(buffer));
+    /// Writes the digest of the input absorbed so far to `output`, absorption can continue
+    /// afterwards.
+    ///
+    /// The cost is a copy of the 200-byte state plus one permutation for every `rate` bytes of
+    /// output, the absorbed input is never processed again. Padding has to go through the
+    /// permutation, so there's no cheaper way to get an intermediate digest out of a sponge.
+    /// Like [`finalize_into`], it never modifies the state, even if squeezing panics.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{bits_to_rate, KeccakF, KeccakState};
+    /// let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    /// let mut digest = [0u8; 32];
+    /// state.update(b"first");
+    /// state.peek_digest(&mut digest);
+    /// state.update(b"second");
+    /// state.peek_digest(&mut digest);
+    /// ```
+    ///
+    /// [`finalize_into`]: #method.finalize_into
+    pub fn peek_digest(&self, output: &mut [u8]) {
+        self.finalize_into(output);
     }
 
-    fn xorin_openvm_opcode(&mut self, mut buffer_ptr: *mut u8, input_ptr: *const u8, len: usize) {
-        let buf_u8: &mut [u8; 200] = unsafe { &mut *(buffer_ptr as *mut [u8; 200]) };
-        let input_u8: &[u8] = unsafe { core::slice::from_raw_parts(input_ptr as *const u8, len) };
+    /// Pad and squeeze the state to the output, then reset it.
+    pub fn finalize_reset(&mut self, output: &mut [u8]) {
+        self.squeeze(output);
+        self.reset();
+    }
 
-        for i in 0..len {
-            buf_u8[i] ^= input_u8[i];
-        }
+    pub(crate) fn debug(&self, name: &str, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let mut debug = f.debug_struct(name);
+        debug
+            .field("rate", &self.rate)
+            .field("offset", &self.offset);
+        #[cfg(feature = "unstable-debug")]
+        debug.field("state", &self.buffer.0);
+        #[cfg(not(feature = "unstable-debug"))]
+        debug.field("state", &format_args!("<redacted>"));
+        debug.finish()
     }
 
-    fn xorin(&mut self, src: &[u8], offset: usize, len: usize) {
-        let buffer_ptr = unsafe { (self.0.as_mut_ptr() as *mut u8).add(offset) };
-        let input_ptr = src.as_ptr();
-        self.xorin_openvm_opcode(buffer_ptr, input_ptr, len);
+    /// Permute the state and start a new block.
+    pub fn fill_block(&mut self) {
+        self.check_invariants();
+        self.keccak();
+        self.offset = 0;
+    }
 
-        /* 
-        self.execute(offset, len, |dst| {
-            assert!(dst.len() <= src.len());
-            let len = dst.len();
-            let mut dst_ptr = dst.as_mut_ptr();
-            let mut src_ptr = src.as_ptr();
-            for _ in 0..len {
-                unsafe {
-                    *dst_ptr ^= *src_ptr;
-                    src_ptr = src_ptr.offset(1);
-                    dst_ptr = dst_ptr.offset(1);
-                }
-            }
-        });
-        */
+    /// Squeeze as many bytes as `expected` holds and compare them with `expected` in constant
+    /// time, see [`Xof::verify`]. An empty `expected` never verifies.
+    ///
+    /// [`Xof::verify`]: trait.Xof.html#method.verify
+    pub fn verify(mut self, expected: &[u8]) -> bool {
+        Xof::verify(&mut self, expected)
     }
 
-    fn pad(&mut self, offset: usize, delim: u8, rate: usize) {
-        self.execute(offset, 1, |buff| buff[0] ^= delim);
-        self.execute(rate - 1, 1, |buff| buff[0] ^= 0x80);
+    /// Overwrite the first `offset` bytes of the state, i.e. the bytes absorbed into, or
+    /// squeezed from, the current block, with zeros.
+    ///
+    /// The writes are volatile, so they aren't optimized away even if the state is never read
+    /// again. It is a lighter alternative to the `zeroize` feature, which wipes the whole state on
+    /// drop. The state doesn't match its input anymore, [`reset`] it before using it again.
+    ///
+    /// [`reset`]: #method.reset
+    pub fn wipe(&mut self) {
+        self.buffer.wipe(self.offset);
     }
-}
 
-trait Permutation {
-    fn execute(a: &mut Buffer);
-}
+    /// Snapshots the state, e.g. after absorbing the fixed prefix of a proof-of-work search, to
+    /// [`restore_from`] it before every nonce instead of absorbing the prefix again.
+    ///
+    /// The checkpoint is a copy of the 200 byte state and its position, a few nanoseconds, while
+    /// absorbing the prefix again costs a permutation for every block of it. After a 1 KiB
+    /// prefix, hashing a nonce from a checkpoint is about 8 times faster on x86_64.
+    ///
+    /// This checkpoint is a plain clone kept in memory. [`to_bytes`] writes the same state as a
+    /// serialized checkpoint of [`CHECKPOINT_LEN`] bytes instead, to store it or send it
+    /// elsewhere, and [`from_bytes`] reads it back.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakF, KeccakState};
+    /// let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    /// state.update(&[0u8; 1024]);
+    /// let checkpoint = state.checkpoint();
+    ///
+    /// let mut digest = [0u8; 32];
+    /// for nonce in 0u64.. {
+    ///     state.restore_from(&checkpoint);
+    ///     state.update(&nonce.to_le_bytes());
+    ///     state.squeeze(&mut digest);
+    ///     if digest[0] == 0 {
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`restore_from`]: #method.restore_from
+    /// [`to_bytes`]: #method.to_bytes
+    /// [`from_bytes`]: #method.from_bytes
+    /// [`CHECKPOINT_LEN`]: constant.CHECKPOINT_LEN.html
+    pub fn checkpoint(&self) -> KeccakState<P> {
+        self.clone()
+    }
 
-#[derive(Clone, Copy)]
-enum Mode {
-    Absorbing,
-    Squeezing,
-}
+    /// Restores a state snapshotted by [`checkpoint`], copying it over this one in place.
+    ///
+    /// [`checkpoint`]: #method.checkpoint
+    pub fn restore_from(&mut self, checkpoint: &KeccakState<P>) {
+        self.clone_from(checkpoint);
+    }
 
-struct KeccakState<P> {
-    pub buffer: Buffer,
-    pub offset: usize,
-    pub rate: usize,
-    pub delim: u8,
-    pub mode: Mode,
-    pub permutation: core::marker::PhantomData<P>,
-}
+    /// Reset the state to its initial, absorbing, state. The permutation count of the
+    /// `count-permutations` feature starts from zero again.
+    pub fn reset(&mut self) {
+        self.buffer = Buffer::default();
+        self.offset = 0;
+        self.mode = Mode::Absorbing;
+        #[cfg(feature = "count-permutations")]
+        {
+            self.permutations = 0;
+        }
+    }
 
-impl<P> Clone for KeccakState<P> {
-    fn clone(&self) -> Self {
-        KeccakState {
-            buffer: self.buffer.clone(),
-            offset: self.offset,
-            rate: self.rate,
-            delim: self.delim,
-            mode: self.mode,
-            permutation: core::marker::PhantomData,
+    /// Returns the absorbed state tagged with the domain separation byte `delim`, which is only
+    /// used when padding, so the same input can be finalized in several domains without
+    /// absorbing it again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state is padded or squeezing already, its delimiter was absorbed then.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{bits_to_rate, Domain, KeccakF, KeccakState};
+    /// let mut keccak = KeccakState::<KeccakF>::with_domain(bits_to_rate(256), Domain::Keccak);
+    /// keccak.update(b"abc");
+    /// let sha3 = keccak.clone().with_delim(Domain::Sha3.delim());
+    /// assert_eq!(sha3.domain(), Domain::Sha3);
+    /// ```
+    pub fn with_delim(mut self, delim: u8) -> Self {
+        assert!(
+            matches!(self.mode, Mode::Absorbing),
+            "cannot change the delimiter after padding"
+        );
+        self.delim = delim;
+        self
+    }
+
+    /// Reset the state like [`reset`] and switch it to a sponge with a different `rate` and
+    /// domain separation byte `delim`, e.g. to reuse it for another variant.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` isn't valid according to [`is_valid_rate`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{bits_to_rate, KeccakF, KeccakState};
+    /// let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    /// // SHAKE128
+    /// state.reset_with(bits_to_rate(128), 0x1f);
+    /// assert_eq!(state.rate(), 168);
+    /// ```
+    ///
+    /// [`reset`]: #method.reset
+    /// [`is_valid_rate`]: fn.is_valid_rate.html
+    pub fn reset_with(&mut self, rate: usize, delim: u8) {
+        assert!(is_valid_rate(rate), "invalid sponge rate {}", rate);
+        self.reset();
+        self.rate = rate;
+        self.delim = delim;
+    }
+
+    /// Switch the sponge to a rate of `new_rate` bytes without resetting it, e.g. to absorb at
+    /// one rate and squeeze at another.
+    ///
+    /// The rate can only change between blocks: while absorbing before the first byte of a
+    /// block, and while squeezing before the first byte of a block or once it is exhausted, which
+    /// includes right after [`pad`]. Elsewhere the bytes of the block already absorbed or
+    /// squeezed would be reinterpreted at the new rate.
+    ///
+    /// Switching is only meaningful for research constructions and multi-rate sponges designed
+    /// around it, none of the standard functions switch. The security of the stream is bounded by
+    /// the smallest capacity it used.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`KeccakError::InvalidRate`] if `new_rate` isn't valid according to
+    /// [`is_valid_rate`], and with [`KeccakError::NotAtBlockBoundary`] if the sponge is in the
+    /// middle of a block. The state is left untouched on errors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{KeccakF, KeccakState};
+    /// let mut state = KeccakState::<KeccakF>::with_rate(168, 0x1f);
+    /// state.update(&[0u8; 168]);
+    /// state.switch_rate(136).unwrap();
+    /// state.update(b"tail");
+    /// assert!(state.switch_rate(168).is_err());
+    /// ```
+    ///
+    /// [`pad`]: #method.pad
+    /// [`KeccakError::InvalidRate`]: enum.KeccakError.html#variant.InvalidRate
+    /// [`KeccakError::NotAtBlockBoundary`]: enum.KeccakError.html#variant.NotAtBlockBoundary
+    /// [`is_valid_rate`]: fn.is_valid_rate.html
+    pub fn switch_rate(&mut self, new_rate: usize) -> Result<(), KeccakError> {
+        if !is_valid_rate(new_rate) {
+            return Err(KeccakError::InvalidRate { rate: new_rate });
         }
+        match self.mode {
+            _ if self.offset == 0 => {}
+            // the exhausted block is permuted by the next squeeze, whatever the rate
+            Mode::Squeezing if self.offset == self.rate => self.offset = new_rate,
+            _ => {
+                return Err(KeccakError::NotAtBlockBoundary {
+                    offset: self.offset,
+                })
+            }
+        }
+        self.rate = new_rate;
+        self.check_invariants();
+        Ok(())
     }
 }
 
-impl<P: Permutation> KeccakState<P> {
-    fn new(rate: usize, delim: u8) -> Self {
-        assert!(rate != 0, "rate cannot be equal 0");
-        KeccakState {
-            buffer: Buffer::default(),
+/// A sponge like [`KeccakState`] working on state words borrowed from the caller.
+///
+/// The state lives in memory the caller owns, e.g. a static or a stack buffer of a memory
+/// constrained target, and the sponge never copies it. The words are left as they are when the
+/// sponge is dropped, overwrite them if they held secret material.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{bits_to_rate, KeccakF, KeccakStateRef};
+/// let mut scratch = [0u64; 25];
+/// let mut state = KeccakStateRef::<KeccakF>::new(&mut scratch, bits_to_rate(256), 0x01);
+/// state.update(b"hello world");
+/// let mut output = [0u8; 32];
+/// state.finalize(&mut output);
+/// ```
+///
+/// [`KeccakState`]: struct.KeccakState.html
+pub struct KeccakStateRef<'a, P> {
+    buffer: &'a mut Buffer,
+    offset: usize,
+    rate: usize,
+    delim: u8,
+    mode: Mode,
+    permutation: core::marker::PhantomData<P>,
+}
+
+impl<'a, P: Permutation> KeccakStateRef<'a, P> {
+    /// Creates a new sponge over `scratch` with a rate of `rate` bytes and the domain separator
+    /// `delim`. The words are zeroed first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` isn't valid according to [`is_valid_rate`].
+    ///
+    /// [`is_valid_rate`]: fn.is_valid_rate.html
+    pub fn new(scratch: &'a mut [u64; WORDS], rate: usize, delim: u8) -> Self {
+        assert!(is_valid_rate(rate), "invalid sponge rate {}", rate);
+        *scratch = [0; WORDS];
+        KeccakStateRef {
+            buffer: Buffer::from_words_mut(scratch),
             offset: 0,
             rate,
             delim,
@@ -415,96 +3526,300 @@ impl<P: Permutation> KeccakState<P> {
         }
     }
 
-    fn keccak_openvm_opcode(&mut self, mut buffer_ptr: *mut u8) {
-        let buffer: &mut Buffer = unsafe { &mut *(buffer_ptr as *mut Buffer) };
-        keccakf(buffer.words());
+    /// Absorb additional input, see [`KeccakState::update`].
+    ///
+    /// [`KeccakState::update`]: struct.KeccakState.html#method.update
+    pub fn update(&mut self, input: &[u8]) {
+        check_invariants(self.offset, self.rate);
+        if input.is_empty() {
+            return;
+        }
+        assert!(
+            matches!(self.mode, Mode::Absorbing),
+            "cannot absorb after squeezing, reset the sponge first"
+        );
+        let before = self.offset;
+        self.offset = self.buffer.absorb::<P>(self.offset, self.rate, input);
+        check_advanced(
+            self.offset,
+            self.rate,
+            self.mode,
+            before,
+            input.len(),
+            Mode::Absorbing,
+        );
     }
 
-    fn keccak(&mut self) {
-        // P::execute(&mut self.buffer);
-        let buffer_ptr = unsafe { self.buffer.0.as_mut_ptr() as *mut u8 };
-        self.keccak_openvm_opcode(buffer_ptr);
+    /// Squeeze the next part of the output, see [`KeccakState::squeeze`].
+    ///
+    /// [`KeccakState::squeeze`]: struct.KeccakState.html#method.squeeze
+    pub fn squeeze(&mut self, output: &mut [u8]) {
+        check_invariants(self.offset, self.rate);
+        if output.is_empty() {
+            return;
+        }
+
+        if let Mode::Absorbing = self.mode {
+            self.buffer
+                .pad::<Pad10Star1>(self.offset, self.delim, self.rate);
+            self.mode = Mode::Squeezing;
+            self.offset = self.rate;
+        }
+        let before = self.offset;
+        self.offset = self.buffer.squeeze::<P>(self.offset, self.rate, output);
+        check_advanced(
+            self.offset,
+            self.rate,
+            self.mode,
+            before,
+            output.len(),
+            Mode::Squeezing,
+        );
     }
 
-    pub fn update(&mut self, input: &[u8]) {
-        if let Mode::Squeezing = self.mode {
-            self.mode = Mode::Absorbing;
-            self.fill_block();
+    /// Pad and squeeze the state to the output.
+    pub fn finalize(mut self, output: &mut [u8]) {
+        self.squeeze(output);
+    }
+}
+
+/// The rate in bytes of a sponge with a security level of `bits` bits, i.e. a capacity of
+/// `2 * bits` bits.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::bits_to_rate;
+/// assert_eq!(bits_to_rate(256), 136);
+/// ```
+pub const fn bits_to_rate(bits: usize) -> usize {
+    200 - bits / 4
+}
+
+/// The capacity in bits of a sponge with a rate of `rate` bytes.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::rate_to_capacity_bits;
+/// assert_eq!(rate_to_capacity_bits(136), 512);
+/// ```
+pub fn rate_to_capacity_bits(rate: usize) -> usize {
+    (WORDS * 8 - rate) * 8
+}
+
+/// Checks that `rate` is a positive multiple of the lane size, which leaves room for the
+/// capacity of a `keccak-f[1600]` sponge.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::is_valid_rate;
+/// assert!(is_valid_rate(136));
+/// assert!(!is_valid_rate(200));
+/// ```
+pub fn is_valid_rate(rate: usize) -> bool {
+    // a whole number of 8-byte lanes
+    rate != 0 && rate & 7 == 0 && rate < WORDS * 8
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Buffer, PI, RC, RHO};
+
+    #[cfg(feature = "cshake")]
+    use crate::encode::{
+        encode_string, left_encode, left_encode_bits, right_encode, right_encode_bits,
+    };
+
+    /// Runs `backend` and `reference` 16 times in a row from the same state, for every number of
+    /// `rounds`, and checks that they agree after each run.
+    #[cfg(any(
+        all(feature = "avx2", target_arch = "x86_64"),
+        all(feature = "neon", target_arch = "aarch64")
+    ))]
+    fn assert_matches(
+        backend: fn(&mut [u64; crate::WORDS], usize),
+        reference: fn(&mut [u64; crate::WORDS], usize),
+        rounds: &[usize],
+    ) {
+        let mut state = [0u64; crate::WORDS];
+        for (i, word) in state.iter_mut().enumerate() {
+            *word = (i as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
         }
 
-        //first foldp
-        let mut ip = 0;
-        let mut l = input.len();
-        let mut rate = self.rate - self.offset;
-        let mut offset = self.offset;
-        while l >= rate {
-            self.buffer.xorin(&input[ip..], offset, rate);
-            self.keccak();
-            ip += rate;
-            l -= rate;
-            rate = self.rate;
-            offset = 0;
+        for &rounds in rounds {
+            let mut expected = state;
+            let mut output = state;
+            for _ in 0..16 {
+                reference(&mut expected, rounds);
+                backend(&mut output, rounds);
+                assert_eq!(expected, output, "{} rounds", rounds);
+            }
+        }
+    }
+
+    #[cfg(all(feature = "avx2", target_arch = "x86_64"))]
+    #[test]
+    fn test_avx2_matches_scalar() {
+        if !std::is_x86_feature_detected!("avx2") {
+            return;
         }
 
-        self.buffer.xorin(&input[ip..], offset, l);
-        self.offset = offset + l;
+        assert_matches(
+            // SAFETY: the CPU supports AVX2
+            |a, rounds| unsafe { crate::avx2::keccak_p(a, rounds) },
+            crate::keccak_p_scalar,
+            &[1, 12, 24],
+        );
     }
 
-    pub fn pad(&mut self) {
-        self.buffer.pad(self.offset, self.delim, self.rate);
+    #[cfg(all(feature = "neon", target_arch = "aarch64"))]
+    #[test]
+    fn test_neon_matches_scalar() {
+        if !std::arch::is_aarch64_feature_detected!("neon") {
+            return;
+        }
+
+        assert_matches(
+            // SAFETY: the CPU supports NEON
+            |a, rounds| unsafe { crate::neon::keccak_p(a, rounds) },
+            crate::keccak_p_scalar,
+            &[1, 12, 24],
+        );
     }
 
-    fn squeeze(&mut self, output: &mut [u8]) {
-        if let Mode::Absorbing = self.mode {
-            self.mode = Mode::Squeezing;
-            self.pad();
-            self.fill_block();
+    #[cfg(feature = "unroll")]
+    #[test]
+    fn test_unrolled_matches_looped() {
+        let mut state = [0u64; crate::WORDS];
+        for (i, word) in state.iter_mut().enumerate() {
+            *word = (i as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
         }
 
-        // second foldp
-        let mut op = 0;
-        let mut l = output.len();
-        let mut rate = self.rate - self.offset;
-        let mut offset = self.offset;
-        while l >= rate {
-            self.buffer.setout(&mut output[op..], offset, rate);
-            self.keccak();
-            op += rate;
-            l -= rate;
-            rate = self.rate;
-            offset = 0;
+        for &rounds in &[1, 6, 12, 24] {
+            let mut looped = state;
+            let mut unrolled = state;
+            for _ in 0..16 {
+                crate::keccak_p_looped(&mut looped, rounds);
+                crate::keccak_p_unrolled(&mut unrolled, rounds);
+                assert_eq!(looped, unrolled);
+            }
+        }
+    }
+
+    #[cfg(feature = "bit-interleaving")]
+    #[test]
+    fn test_interleaved_matches_looped() {
+        let mut state = [0u64; crate::WORDS];
+        crate::interleaved::keccak_p(&mut state, 24);
+        assert_eq!(state[0], 0xf1258f7940e1dde7);
+        assert_eq!(state[24], 0xeaf1ff7b5ceca249);
+
+        for (i, word) in state.iter_mut().enumerate() {
+            *word = (i as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
         }
 
-        self.buffer.setout(&mut output[op..], offset, l);
-        self.offset = offset + l;
+        for &rounds in &[1, 6, 12, 24] {
+            let mut looped = state;
+            let mut interleaved = state;
+            for _ in 0..16 {
+                crate::keccak_p_looped(&mut looped, rounds);
+                crate::interleaved::keccak_p(&mut interleaved, rounds);
+                assert_eq!(looped, interleaved);
+            }
+        }
     }
 
-    pub fn finalize(mut self, output: &mut [u8]) {
-        self.squeeze(output);
-        // extern crate std;
-        // std::println!("buffer: {:?}", self.buffer.words());
+    #[cfg(all(feature = "bit-interleaving", target_pointer_width = "32"))]
+    #[test]
+    fn test_interleaved_blocks_match_single_blocks() {
+        let mut input = [0u8; 5 * 136];
+        for (i, byte) in input.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        // a block per call goes through the plain lanes, several blocks through the interleaved
+        let mut single = Buffer::default();
+        for block in input.chunks(136) {
+            assert_eq!(single.absorb::<crate::KeccakF>(0, 136, block), 0);
+        }
+        let mut blocks = Buffer::default();
+        assert_eq!(blocks.absorb::<crate::KeccakF>(0, 136, &input), 0);
+        assert_eq!(single.0, blocks.0);
+
+        let mut expected = [0u8; 5 * 136];
+        for block in expected.chunks_mut(136) {
+            single.squeeze::<crate::KeccakF>(0, 136, block);
+        }
+        let mut output = [0u8; 5 * 136];
+        blocks.squeeze::<crate::KeccakF>(0, 136, &mut output);
+        assert_eq!(single.0, blocks.0);
+        assert_eq!(output[..], expected[..]);
     }
 
-    pub fn fill_block(&mut self) {
-        self.keccak();
-        self.offset = 0;
+    #[test]
+    fn test_round_constants() {
+        // the `rc(t)` linear feedback shift register of FIPS 202, section 3.2.5
+        let mut lfsr = 1u8;
+        let mut rc_bit = || {
+            let bit = lfsr & 1;
+            lfsr = if lfsr & 0x80 != 0 {
+                (lfsr << 1) ^ 0x71
+            } else {
+                lfsr << 1
+            };
+            bit as u64
+        };
+
+        for rc in RC.iter() {
+            let mut expected = 0u64;
+            for j in 0..7 {
+                expected |= rc_bit() << ((1 << j) - 1);
+            }
+            assert_eq!(*rc, expected);
+        }
     }
 
-    fn reset(&mut self) {
-        self.buffer = Buffer::default();
-        self.offset = 0;
-        self.mode = Mode::Absorbing;
+    #[test]
+    fn test_rho_pi_offsets() {
+        // FIPS 202, section 3.2.2 and 3.2.3, lanes are indexed as `x + 5 * y`
+        let (mut x, mut y) = (1, 0);
+        for t in 0..24 {
+            let (next_x, next_y) = (y, (2 * x + 3 * y) % 5);
+            assert_eq!(PI[t], next_x + 5 * next_y);
+            assert_eq!(RHO[t] as usize, ((t + 1) * (t + 2) / 2) % 64);
+            x = next_x;
+            y = next_y;
+        }
     }
-}
 
-fn bits_to_rate(bits: usize) -> usize {
-    200 - bits / 4
-}
+    #[test]
+    fn test_buffer_bytes_are_little_endian() {
+        let mut input = [0u8; 24];
+        for (i, byte) in input.iter_mut().enumerate() {
+            *byte = i as u8 + 1;
+        }
 
-#[cfg(test)]
-mod tests {
-    use crate::{left_encode, right_encode};
+        let mut buffer = Buffer::default();
+        buffer.execute(5, 19, |bytes| bytes.copy_from_slice(&input[5..]));
+        assert_eq!(buffer.0[0], u64::from_le_bytes([0, 0, 0, 0, 0, 6, 7, 8]));
+        assert_eq!(
+            buffer.0[1],
+            u64::from_le_bytes([9, 10, 11, 12, 13, 14, 15, 16])
+        );
+        assert_eq!(
+            buffer.0[2],
+            u64::from_le_bytes([17, 18, 19, 20, 21, 22, 23, 24])
+        );
+        assert!(buffer.0[3..].iter().all(|word| *word == 0));
+
+        let mut output = [0u8; 19];
+        buffer.setout(&mut output, 5, 19);
+        assert_eq!(output, input[5..]);
+    }
 
+    #[cfg(feature = "cshake")]
     #[test]
     fn test_left_encode() {
         assert_eq!(left_encode(0).value(), &[1, 0]);
@@ -514,6 +3829,7 @@ mod tests {
         assert_eq!(left_encode(54321).value(), &[2, 212, 49]);
     }
 
+    #[cfg(feature = "cshake")]
     #[test]
     fn test_right_encode() {
         assert_eq!(right_encode(0).value(), &[0, 1]);
@@ -522,4 +3838,119 @@ mod tests {
         assert_eq!(right_encode(4096).value(), &[16, 0, 2]);
         assert_eq!(right_encode(54321).value(), &[212, 49, 2]);
     }
+
+    #[cfg(feature = "cshake")]
+    #[test]
+    fn test_encode_bits() {
+        // 600 MiB is 0x12c00000 bytes, its bit length overflows a 32-bit `usize`
+        assert_eq!(left_encode_bits(629_145_600).value(), &[5, 1, 44, 0, 0, 0]);
+        assert_eq!(right_encode_bits(629_145_600).value(), &[1, 44, 0, 0, 0, 5]);
+
+        #[cfg(target_pointer_width = "32")]
+        {
+            assert_eq!(
+                left_encode_bits(usize::MAX).value(),
+                &[5, 0x07, 0xff, 0xff, 0xff, 0xf8]
+            );
+            assert_eq!(
+                right_encode_bits(usize::MAX).value(),
+                &[0x07, 0xff, 0xff, 0xff, 0xf8, 5]
+            );
+        }
+        #[cfg(target_pointer_width = "64")]
+        {
+            assert_eq!(
+                left_encode_bits(usize::MAX).value(),
+                &[9, 0x07, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xf8]
+            );
+            assert_eq!(
+                right_encode_bits(usize::MAX).value(),
+                &[0x07, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xf8, 9]
+            );
+        }
+    }
+
+    #[cfg(feature = "cshake")]
+    #[test]
+    fn test_encode_string() {
+        assert_eq!(encode_string(b"").parts(), [&[1, 0][..], b""]);
+        assert_eq!(
+            encode_string(b"Email Signature").parts(),
+            [&[1, 120][..], b"Email Signature"]
+        );
+    }
+
+    /// Asserts the invariants of a freshly constructed or reset sponge: all-zero words, nothing
+    /// absorbed yet, and absorbing.
+    fn assert_zeroed_state<P>(state: &crate::KeccakState<P>) {
+        assert_eq!(
+            state.buffer.0,
+            [0u64; crate::WORDS],
+            "state words aren't zeroed"
+        );
+        assert_eq!(state.offset, 0, "state offset isn't zero");
+        assert!(
+            matches!(state.mode, crate::Mode::Absorbing),
+            "state isn't absorbing"
+        );
+    }
+
+    #[test]
+    fn test_new_state_is_zeroed() {
+        use crate::{KeccakF, KeccakState, KeccakStateRef, WORDS};
+
+        for rate in (8..WORDS * 8).step_by(8) {
+            assert_zeroed_state(&KeccakState::<KeccakF>::new(rate, 0x01));
+            assert_zeroed_state(&KeccakState::<KeccakF>::with_rate(rate, 0x06));
+            assert_zeroed_state(&KeccakState::<KeccakF>::try_with_rate(rate, 0x1f).unwrap());
+        }
+        assert_zeroed_state(&KeccakState::<KeccakF>::from_words([0; WORDS]));
+
+        let mut state = KeccakState::<KeccakF>::new(136, 0x01);
+        state.update(&[0xa5; 300]);
+        state.squeeze(&mut [0u8; 300]);
+        state.reset();
+        assert_zeroed_state(&state);
+
+        state.update(&[0xa5; 300]);
+        state.reset_with(168, 0x1f);
+        assert_zeroed_state(&state);
+
+        // the scratch words are zeroed, whatever they held before
+        let mut scratch = [u64::MAX; WORDS];
+        let state = KeccakStateRef::<KeccakF>::new(&mut scratch, 136, 0x01);
+        assert_eq!(state.buffer.0, [0u64; WORDS]);
+        assert_eq!(state.offset, 0);
+
+        #[cfg(feature = "keccak")]
+        assert_zeroed_state(&crate::Keccak::v256().state);
+    }
+
+    #[test]
+    fn test_self_test() {
+        assert_eq!(crate::self_test(), Ok(()));
+    }
+
+    #[test]
+    fn test_self_test_detects_corruption() {
+        use crate::self_test::{check, KnownAnswer, KNOWN_ANSWERS};
+
+        // the keccak-f[1600] answer with the last bit flipped
+        let known_answers = [KnownAnswer {
+            algorithm: "corrupted",
+            compute: KNOWN_ANSWERS[0].compute,
+            expected: b"\
+                \xe7\xdd\xe1\x40\x79\x8f\x25\xf1\x8a\x47\xc0\x33\xf9\xcc\xd5\x84\
+                \xee\xa9\x5a\xa6\x1e\x26\x98\xd5\x4d\x49\x80\x6f\x30\x47\x15\x3d\
+            ",
+        }];
+        assert_ne!(known_answers[0].expected, KNOWN_ANSWERS[0].expected);
+        assert_eq!(check(&KNOWN_ANSWERS[..1]), Ok(()));
+        assert_eq!(
+            check(&known_answers),
+            Err(crate::KeccakError::SelfTestFailed {
+                algorithm: "corrupted"
+            })
+        );
+    }
 }
@@ -0,0 +1,89 @@
+//! `keccak-p[1600]` permutation using NEON.
+
+use core::arch::aarch64::*;
+
+use crunchy::unroll;
+
+use crate::{PI, RC, RHO, WORDS};
+
+#[inline(always)]
+unsafe fn rotate_left_1(v: uint64x2_t) -> uint64x2_t {
+    vorrq_u64(vshlq_n_u64::<1>(v), vshrq_n_u64::<63>(v))
+}
+
+/// `keccak-p[1600, rounds]` on CPUs supporting NEON.
+///
+/// Columns `x = 0, 1` and `x = 2, 3` of every row are processed as pairs in 128-bit registers
+/// during theta and chi, the lane `x = 4` is processed as a scalar.
+///
+/// # Safety
+///
+/// The caller has to make sure that the CPU supports NEON.
+#[allow(unused_assignments)]
+#[target_feature(enable = "neon")]
+pub(crate) unsafe fn keccak_p(a: &mut [u64; WORDS], rounds: usize) {
+    for rc in &RC[24 - rounds..] {
+        // Theta
+        let mut c01 = vdupq_n_u64(0);
+        let mut c23 = vdupq_n_u64(0);
+        let mut c4 = 0;
+        unroll! {
+            for y in 0..5 {
+                c01 = veorq_u64(c01, vld1q_u64(a[5 * y..].as_ptr()));
+                c23 = veorq_u64(c23, vld1q_u64(a[5 * y + 2..].as_ptr()));
+                c4 ^= a[5 * y + 4];
+            }
+        }
+
+        let c44 = vdupq_n_u64(c4);
+        // [c4, c0] ^ rotate_left([c1, c2], 1)
+        let d01 = veorq_u64(
+            vextq_u64::<1>(c44, c01),
+            rotate_left_1(vextq_u64::<1>(c01, c23)),
+        );
+        // [c1, c2] ^ rotate_left([c3, c4], 1)
+        let d23 = veorq_u64(
+            vextq_u64::<1>(c01, c23),
+            rotate_left_1(vextq_u64::<1>(c23, c44)),
+        );
+        let d4 = vgetq_lane_u64::<1>(c23) ^ vgetq_lane_u64::<0>(c01).rotate_left(1);
+        unroll! {
+            for y in 0..5 {
+                let row = a[5 * y..].as_mut_ptr();
+                vst1q_u64(row, veorq_u64(vld1q_u64(row), d01));
+                vst1q_u64(row.add(2), veorq_u64(vld1q_u64(row.add(2)), d23));
+                a[5 * y + 4] ^= d4;
+            }
+        }
+
+        // Rho and pi
+        let mut last = a[1];
+        unroll! {
+            for x in 0..24 {
+                let next = a[PI[x]];
+                a[PI[x]] = last.rotate_left(RHO[x]);
+                last = next;
+            }
+        }
+
+        // Chi
+        unroll! {
+            for y in 0..5 {
+                let row = a[5 * y..].as_mut_ptr();
+                let b01 = vld1q_u64(row);
+                let b23 = vld1q_u64(row.add(2));
+                let b4 = a[5 * y + 4];
+                let b44 = vdupq_n_u64(b4);
+                let b12 = vextq_u64::<1>(b01, b23);
+                let b34 = vextq_u64::<1>(b23, b44);
+                let b40 = vextq_u64::<1>(b44, b01);
+                vst1q_u64(row, veorq_u64(b01, vbicq_u64(b23, b12)));
+                vst1q_u64(row.add(2), veorq_u64(b23, vbicq_u64(b40, b34)));
+                a[5 * y + 4] = b4 ^ (!vgetq_lane_u64::<0>(b01) & vgetq_lane_u64::<1>(b01));
+            }
+        }
+
+        // Iota
+        a[0] ^= rc;
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{Hasher, Shake, Xof};
+
+/// Generates a deterministic nonce of `bits` bits for a signature, in the style of [`RFC 6979`]
+/// but squeezed from `SHAKE256` instead of `HMAC-DRBG`.
+///
+/// `SHAKE256` absorbs `private_key || message_hash`. Pass fixed-length encodings of both, like
+/// the `int2octets` and `bits2octets` encodings of RFC 6979, so the concatenation is
+/// unambiguous. Candidates of `nonce.len()` bytes are squeezed as big-endian integers, with the
+/// excess high bits of the first byte cleared, until `accept` returns `true` for one, e.g. when
+/// it is non-zero and below the group order. Every rejected candidate is followed by the
+/// next bytes of the stream, so the nonce is fully determined by the inputs.
+///
+/// # Panics
+///
+/// Panics if `nonce` isn't `bits` bits long, rounded up to whole bytes.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["nonce"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::deterministic_nonce;
+/// let private_key = [0x11u8; 32];
+/// let message_hash = [0x22u8; 32];
+/// let mut nonce = [0u8; 32];
+/// // a 255-bit nonce, which mustn't be zero
+/// let non_zero = |k: &[u8]| k.iter().any(|&b| b != 0);
+/// deterministic_nonce(&private_key, &message_hash, 255, &mut nonce, non_zero);
+/// assert!(nonce[0] < 0x80);
+/// ```
+///
+/// [`RFC 6979`]: https://tools.ietf.org/html/rfc6979
+pub fn deterministic_nonce<F>(
+    private_key: &[u8],
+    message_hash: &[u8],
+    bits: usize,
+    nonce: &mut [u8],
+    mut accept: F,
+) where
+    F: FnMut(&[u8]) -> bool,
+{
+    let len = bits / 8 + usize::from(bits & 7 != 0);
+    assert_eq!(nonce.len(), len, "nonce must be {} bits long", bits);
+
+    let mut shake = Shake::v256();
+    shake.update(private_key);
+    shake.update(message_hash);
+    loop {
+        shake.squeeze(nonce);
+        if bits & 7 != 0 {
+            nonce[0] &= 0xff >> (8 - (bits & 7));
+        }
+        if accept(nonce) {
+            return;
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{left_encode, right_encode, CShake, Hasher, IntoXof, Xof};
+use crate::encode::{left_encode, right_encode, right_encode_bits};
+use crate::{CShake, Hasher, IntoXof, KeccakF, KeccakState, Xof, SPONGE_SIZE};
 
 #[derive(Clone)]
 struct UnfinishedState {
@@ -44,7 +45,11 @@ impl Suboutout {
 /// tiny-keccak = { version = "2.0.0", features = ["parallel_hash"] }
 /// ```
 ///
+/// With the `parallel` feature the blocks of every `update` are hashed on the [`rayon`] thread
+/// pool, the output is the same as the serial one.
+///
 /// [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
+/// [`rayon`]: https://docs.rs/rayon
 /// [`128-bit`]: struct.ParallelHash.html#method.v128
 /// [`256-bit`]: struct.ParallelHash.html#method.v256
 #[derive(Clone)]
@@ -56,6 +61,10 @@ pub struct ParallelHash {
     unfinished: Option<UnfinishedState>,
 }
 
+const _: () = assert!(
+    core::mem::size_of::<ParallelHash>() <= 2 * SPONGE_SIZE + 4 * core::mem::size_of::<u64>()
+);
+
 impl ParallelHash {
     /// Creates  new [`ParallelHash`] hasher with a security level of 128 bits.
     ///
@@ -82,6 +91,29 @@ impl ParallelHash {
             unfinished: None,
         }
     }
+
+    /// Hashes the last, partial block and absorbs the number of blocks, what's left to absorb
+    /// before the output length.
+    fn finish_blocks(&mut self) {
+        if let Some(unfinished) = self.unfinished.take() {
+            let mut suboutput = Suboutout::security(self.bits);
+            unfinished.state.finalize(suboutput.as_bytes_mut());
+            self.state.update(suboutput.as_bytes());
+            self.blocks += 1;
+        }
+
+        self.state.update(right_encode(self.blocks).value());
+    }
+
+    /// Absorb `input` like [`update`], which already hashes the blocks on the [`rayon`] thread
+    /// pool with the `parallel` feature. Named like the parallel update of other tree hashers.
+    ///
+    /// [`update`]: trait.Hasher.html#tymethod.update
+    /// [`rayon`]: https://docs.rs/rayon
+    #[cfg(feature = "parallel")]
+    pub fn update_rayon(&mut self, input: &[u8]) {
+        self.update(input);
+    }
 }
 
 impl Hasher for ParallelHash {
@@ -108,13 +140,25 @@ impl Hasher for ParallelHash {
         let input_blocks_end = input.len() / self.block_size * self.block_size;
         let input_blocks = &input[..input_blocks_end];
         let input_end = &input[input_blocks_end..];
-        let parts = input_blocks.chunks(self.block_size).map(|chunk| {
+        let hash_block = |chunk: &[u8]| {
             let mut state = CShake::new(b"", b"", bits);
             state.update(chunk);
             let mut suboutput = Suboutout::security(bits);
             state.finalize(suboutput.as_bytes_mut());
             suboutput
-        });
+        };
+
+        // the blocks are hashed across threads, their digests are still absorbed in order
+        #[cfg(feature = "parallel")]
+        let parts: std::vec::Vec<_> = {
+            use rayon::prelude::*;
+            input_blocks
+                .par_chunks(self.block_size)
+                .map(hash_block)
+                .collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let parts = input_blocks.chunks(self.block_size).map(hash_block);
 
         for part in parts {
             self.state.update(part.as_bytes());
@@ -133,17 +177,119 @@ impl Hasher for ParallelHash {
     }
 
     fn finalize(mut self, output: &mut [u8]) {
-        if let Some(unfinished) = self.unfinished.take() {
-            let mut suboutput = Suboutout::security(self.bits);
-            unfinished.state.finalize(suboutput.as_bytes_mut());
-            self.state.update(suboutput.as_bytes());
-            self.blocks += 1;
-        }
-
-        self.state.update(right_encode(self.blocks).value());
-        self.state.update(right_encode(output.len() * 8).value());
+        self.finish_blocks();
+        self.state.update(right_encode_bits(output.len()).value());
         self.state.finalize(output);
     }
+
+    fn finalize_xor(mut self, output: &mut [u8]) {
+        self.finish_blocks();
+        self.state.update(right_encode_bits(output.len()).value());
+        self.state.finalize_xor(output);
+    }
+
+    fn finalize_scattered(mut self, outputs: &mut [&mut [u8]]) {
+        self.finish_blocks();
+        let len = outputs.iter().map(|output| output.len()).sum();
+        self.state.update(right_encode_bits(len).value());
+        self.state.finalize_scattered(outputs);
+    }
+}
+
+#[cfg(feature = "std")]
+impl_write!(ParallelHash);
+
+impl_extend!(ParallelHash);
+
+/// The tree of [`ParallelHash`] made explicit: the chunks of the input are hashed to chaining
+/// values independently, e.g. on different threads or machines, and [`combine`] absorbs them
+/// into the root digest.
+///
+/// `N` is the length of the chaining values, 32 bytes for [`v128`] and 64 bytes for [`v256`].
+/// Every chunk but the last has to be exactly `block_size` bytes long for the root to match
+/// [`ParallelHash`] with the same parameters.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::{ChainingHasher, Hasher, ParallelHash};
+/// let input = [7u8; 20];
+/// let tree = ChainingHasher::v128(b"", 8);
+/// let chaining_values: Vec<_> = input.chunks(8).map(|chunk| tree.chaining_value(chunk)).collect();
+/// let mut root = [0u8; 32];
+/// tree.combine(&chaining_values, &mut root);
+///
+/// let mut expected = [0u8; 32];
+/// ParallelHash::v128(b"", 8).chain(&input).finalize(&mut expected);
+/// assert_eq!(expected, root);
+/// ```
+///
+/// [`ParallelHash`]: struct.ParallelHash.html
+/// [`combine`]: #method.combine
+/// [`v128`]: #method.v128
+/// [`v256`]: #method.v256
+#[derive(Clone)]
+pub struct ChainingHasher<const N: usize> {
+    root: CShake,
+    block_size: usize,
+}
+
+impl ChainingHasher<32> {
+    /// Creates  new [`ChainingHasher`] with a security level of 128 bits.
+    ///
+    /// [`ChainingHasher`]: struct.ChainingHasher.html
+    pub fn v128(custom_string: &[u8], block_size: usize) -> ChainingHasher<32> {
+        ChainingHasher::new(custom_string, block_size)
+    }
+}
+
+impl ChainingHasher<64> {
+    /// Creates  new [`ChainingHasher`] with a security level of 256 bits.
+    ///
+    /// [`ChainingHasher`]: struct.ChainingHasher.html
+    pub fn v256(custom_string: &[u8], block_size: usize) -> ChainingHasher<64> {
+        ChainingHasher::new(custom_string, block_size)
+    }
+}
+
+impl<const N: usize> ChainingHasher<N> {
+    // 32 => 128, 64 => 256
+    const BITS: usize = N * 4;
+
+    fn new(custom_string: &[u8], block_size: usize) -> ChainingHasher<N> {
+        let mut root = CShake::new(b"ParallelHash", custom_string, Self::BITS);
+        root.update(left_encode(block_size).value());
+        ChainingHasher { root, block_size }
+    }
+
+    /// Hashes one chunk of the input to its chaining value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk` is longer than the block size.
+    pub fn chaining_value(&self, chunk: &[u8]) -> [u8; N] {
+        assert!(
+            chunk.len() <= self.block_size,
+            "chunk is longer than the block size"
+        );
+        let mut state = CShake::new(b"", b"", Self::BITS);
+        state.update(chunk);
+        let mut chaining_value = [0u8; N];
+        state.finalize(&mut chaining_value);
+        chaining_value
+    }
+
+    /// Absorbs the chaining values of the chunks, in the order of the chunks, and squeezes the
+    /// root digest with the length of `output`.
+    pub fn combine(&self, chaining_values: &[[u8; N]], output: &mut [u8]) {
+        let mut root = self.root.clone();
+        for chaining_value in chaining_values {
+            root.update(chaining_value);
+        }
+        root.update(right_encode(chaining_values.len()).value());
+        root.update(right_encode_bits(output.len()).value());
+        root.finalize(output);
+    }
 }
 
 /// The `ParallelHashXOF` extendable-output functions defined in [`SP800-185`].
@@ -181,26 +327,37 @@ pub struct ParallelHashXof {
     state: CShake,
 }
 
+const _: () = assert!(core::mem::size_of::<ParallelHashXof>() <= SPONGE_SIZE);
+
 impl IntoXof for ParallelHash {
     type Xof = ParallelHashXof;
 
     fn into_xof(mut self) -> Self::Xof {
-        if let Some(unfinished) = self.unfinished.take() {
-            let mut suboutput = Suboutout::security(self.bits);
-            unfinished.state.finalize(suboutput.as_bytes_mut());
-            self.state.update(suboutput.as_bytes());
-            self.blocks += 1;
-        }
-
-        self.state.update(right_encode(self.blocks).value());
+        self.finish_blocks();
         self.state.update(right_encode(0).value());
 
         ParallelHashXof { state: self.state }
     }
 }
 
+impl ParallelHashXof {
+    /// Returns the sponge state, positioned where the squeezing stopped.
+    ///
+    /// Resetting the state keeps its rate and delimiter, but not the encoded customization
+    /// prefix, which a new [`ParallelHash`] absorbs again.
+    ///
+    /// [`ParallelHash`]: struct.ParallelHash.html
+    pub fn into_state(self) -> KeccakState<KeccakF> {
+        self.state.into_state()
+    }
+}
+
 impl Xof for ParallelHashXof {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output);
     }
+
+    fn squeeze_xor(&mut self, output: &mut [u8]) {
+        self.state.squeeze_xor(output);
+    }
 }
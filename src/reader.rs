@@ -0,0 +1,67 @@
+use crate::Xof;
+
+/// Reads the output stream of an [`Xof`] through [`std::io::Read`], e.g. to copy a
+/// deterministic stream into a file with [`std::io::copy`].
+///
+/// The stream is endless, every `read` fills the whole buffer. Since `read_to_end` would never
+/// return, bound the reader with [`std::io::Read::take`] first.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["std", "shake"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "shake")]
+/// # {
+/// # use std::io::{self, Read};
+/// # use tiny_keccak::{Hasher, Shake, XofReader};
+/// let mut shake = Shake::v256();
+/// shake.update(b"seed");
+/// let mut reader = XofReader::new(shake).take(1000);
+/// let mut stream = Vec::new();
+/// io::copy(&mut reader, &mut stream).unwrap();
+/// assert_eq!(stream.len(), 1000);
+/// # }
+/// ```
+///
+/// [`Xof`]: trait.Xof.html
+/// [`std::io::Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
+/// [`std::io::copy`]: https://doc.rust-lang.org/std/io/fn.copy.html
+/// [`std::io::Read::take`]: https://doc.rust-lang.org/std/io/trait.Read.html#method.take
+#[derive(Clone, Debug)]
+pub struct XofReader<X> {
+    xof: X,
+}
+
+impl<X: Xof> XofReader<X> {
+    /// Creates  new [`XofReader`] reading the output of `xof` from its current position on.
+    ///
+    /// [`XofReader`]: struct.XofReader.html
+    pub fn new(xof: X) -> XofReader<X> {
+        XofReader { xof }
+    }
+
+    /// Returns the [`Xof`], positioned after the bytes read so far.
+    ///
+    /// [`Xof`]: trait.Xof.html
+    pub fn into_inner(self) -> X {
+        self.xof
+    }
+}
+
+impl<X: Xof> std::io::Read for XofReader<X> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.xof.squeeze(buf);
+        Ok(buf.len())
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
+        self.xof.squeeze(buf);
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{Hasher, Shake, Xof, SPONGE_SIZE};
+use rand_core::{impls, RngCore, SeedableRng};
+
+/// A deterministic random number generator squeezing its output from a `SHAKE256` sponge
+/// absorbing the seed.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["rand"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::KeccakRng;
+/// use rand_core::{RngCore, SeedableRng};
+///
+/// let mut rng = KeccakRng::from_seed([7u8; 32]);
+/// let mut nonce = [0u8; 24];
+/// rng.fill_bytes(&mut nonce);
+/// ```
+///
+/// It is not a replacement for the operating system random number generator, the output is
+/// only as unpredictable as the seed.
+#[derive(Clone)]
+pub struct KeccakRng {
+    shake: Shake,
+}
+
+const _: () = assert!(core::mem::size_of::<KeccakRng>() <= SPONGE_SIZE);
+
+impl KeccakRng {
+    /// Creates  new [`KeccakRng`] from a seed of any length.
+    ///
+    /// [`KeccakRng`]: struct.KeccakRng.html
+    pub fn from_seed_bytes(seed: &[u8]) -> KeccakRng {
+        let mut shake = Shake::v256();
+        shake.update(seed);
+        KeccakRng { shake }
+    }
+}
+
+impl RngCore for KeccakRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.shake.squeeze(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for KeccakRng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> KeccakRng {
+        KeccakRng::from_seed_bytes(&seed)
+    }
+}
@@ -0,0 +1,224 @@
+//! Known answer tests run at runtime by [`self_test`](../fn.self_test.html).
+
+use crate::{KeccakError, KeccakF, Permutation, WORDS};
+
+#[cfg(any(
+    feature = "cshake",
+    feature = "k12",
+    feature = "keccak",
+    feature = "sha3",
+    feature = "shake",
+    feature = "turboshake"
+))]
+use crate::Hasher;
+
+/// A digest computed at runtime and the digest it has to match.
+pub(crate) struct KnownAnswer {
+    pub(crate) algorithm: &'static str,
+    pub(crate) compute: fn(&mut [u8]),
+    pub(crate) expected: &'static [u8],
+}
+
+/// The first four words of `keccak-f[1600]` applied to the all-zero state.
+fn keccakf_zero(output: &mut [u8]) {
+    let mut state = [0u64; WORDS];
+    KeccakF::execute(&mut state);
+    for (chunk, word) in output.chunks_exact_mut(8).zip(state.iter()) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+}
+
+#[cfg(feature = "kmac")]
+const NIST_KEY: &[u8] = b"\
+    \x40\x41\x42\x43\x44\x45\x46\x47\x48\x49\x4A\x4B\x4C\x4D\x4E\x4F\
+    \x50\x51\x52\x53\x54\x55\x56\x57\x58\x59\x5A\x5B\x5C\x5D\x5E\x5F\
+";
+
+pub(crate) const KNOWN_ANSWERS: &[KnownAnswer] = &[
+    KnownAnswer {
+        algorithm: "keccak-f[1600]",
+        compute: keccakf_zero,
+        expected: b"\
+            \xe7\xdd\xe1\x40\x79\x8f\x25\xf1\x8a\x47\xc0\x33\xf9\xcc\xd5\x84\
+            \xee\xa9\x5a\xa6\x1e\x26\x98\xd5\x4d\x49\x80\x6f\x30\x47\x15\xbd\
+        ",
+    },
+    #[cfg(feature = "keccak")]
+    KnownAnswer {
+        algorithm: "Keccak-256",
+        compute: |output| crate::Keccak::v256().chain(b"abc").finalize(output),
+        expected: b"\
+            \x4e\x03\x65\x7a\xea\x45\xa9\x4f\xc7\xd4\x7b\xa8\x26\xc8\xd6\x67\
+            \xc0\xd1\xe6\xe3\x3a\x64\xa0\x36\xec\x44\xf5\x8f\xa1\x2d\x6c\x45\
+        ",
+    },
+    #[cfg(feature = "sha3")]
+    KnownAnswer {
+        algorithm: "SHA3-256",
+        compute: |output| crate::Sha3::v256().chain(b"abc").finalize(output),
+        expected: b"\
+            \x3a\x98\x5d\xa7\x4f\xe2\x25\xb2\x04\x5c\x17\x2d\x6b\xd3\x90\xbd\
+            \x85\x5f\x08\x6e\x3e\x9d\x52\x5b\x46\xbf\xe2\x45\x11\x43\x15\x32\
+        ",
+    },
+    #[cfg(feature = "shake")]
+    KnownAnswer {
+        algorithm: "SHAKE128",
+        compute: |output| crate::Shake::v128().chain(b"abc").finalize(output),
+        expected: b"\
+            \x58\x81\x09\x2d\xd8\x18\xbf\x5c\xf8\xa3\xdd\xb7\x93\xfb\xcb\xa7\
+        ",
+    },
+    #[cfg(feature = "cshake")]
+    KnownAnswer {
+        algorithm: "cSHAKE128",
+        compute: |output| {
+            crate::CShake::v128(b"", b"Email Signature")
+                .chain(b"\x00\x01\x02\x03")
+                .finalize(output)
+        },
+        expected: b"\
+            \xC1\xC3\x69\x25\xB6\x40\x9A\x04\xF1\xB5\x04\xFC\xBC\xA9\xD8\x2B\
+            \x40\x17\x27\x7C\xB5\xED\x2B\x20\x65\xFC\x1D\x38\x14\xD5\xAA\xF5\
+        ",
+    },
+    #[cfg(feature = "kmac")]
+    KnownAnswer {
+        algorithm: "KMAC128",
+        compute: |output| {
+            crate::Kmac::v128(NIST_KEY, b"")
+                .chain(b"\x00\x01\x02\x03")
+                .finalize(output)
+        },
+        expected: b"\
+            \xE5\x78\x0B\x0D\x3E\xA6\xF7\xD3\xA4\x29\xC5\x70\x6A\xA4\x3A\x00\
+            \xFA\xDB\xD7\xD4\x96\x28\x83\x9E\x31\x87\x24\x3F\x45\x6E\xE1\x4E\
+        ",
+    },
+    #[cfg(feature = "tuple_hash")]
+    KnownAnswer {
+        algorithm: "TupleHash128",
+        compute: |output| {
+            crate::TupleHash::v128(b"")
+                .chain(b"\x00\x01\x02")
+                .chain(b"\x10\x11\x12\x13\x14\x15")
+                .finalize(output)
+        },
+        expected: b"\
+            \xC5\xD8\x78\x6C\x1A\xFB\x9B\x82\x11\x1A\xB3\x4B\x65\xB2\xC0\x04\
+            \x8F\xA6\x4E\x6D\x48\xE2\x63\x26\x4C\xE1\x70\x7D\x3F\xFC\x8E\xD1\
+        ",
+    },
+    #[cfg(feature = "parallel_hash")]
+    KnownAnswer {
+        algorithm: "ParallelHash128",
+        compute: |output| {
+            crate::ParallelHash::v128(b"", 8)
+                .chain(b"\x00\x01\x02\x03\x04\x05\x06\x07\x10\x11\x12\x13")
+                .chain(b"\x14\x15\x16\x17\x20\x21\x22\x23\x24\x25\x26\x27")
+                .finalize(output)
+        },
+        expected: b"\
+            \xBA\x8D\xC1\xD1\xD9\x79\x33\x1D\x3F\x81\x36\x03\xC6\x7F\x72\x60\
+            \x9A\xB5\xE4\x4B\x94\xA0\xB8\xF9\xAF\x46\x51\x44\x54\xA2\xB4\xF5\
+        ",
+    },
+    #[cfg(feature = "k12")]
+    KnownAnswer {
+        algorithm: "KangarooTwelve",
+        compute: |output| crate::KangarooTwelve::new(b"").finalize(output),
+        expected: b"\
+            \x1a\xc2\xd4\x50\xfc\x3b\x42\x05\xd1\x9d\xa7\xbf\xca\x1b\x37\x51\
+            \x3c\x08\x03\x57\x7a\xc7\x16\x7f\x06\xfe\x2c\xe1\xf0\xef\x39\xe5\
+        ",
+    },
+    #[cfg(feature = "turboshake")]
+    KnownAnswer {
+        algorithm: "TurboSHAKE128",
+        compute: |output| crate::TurboShake::v128(0x1f).finalize(output),
+        expected: b"\
+            \x1e\x41\x5f\x1c\x59\x83\xaf\xf2\x16\x92\x17\x27\x7d\x17\xbb\x53\
+            \x8c\xd9\x45\xa3\x97\xdd\xec\x54\x1f\x1c\xe4\x1a\xf2\xc1\xb7\x4c\
+        ",
+    },
+    #[cfg(feature = "hmac")]
+    KnownAnswer {
+        algorithm: "HMAC-SHA3-256",
+        compute: |output| crate::HmacSha3::v256(b"key").chain(b"abc").finalize(output),
+        expected: b"\
+            \x09\xb6\xdb\xab\x8d\x11\x79\x5c\xa7\xc8\xd8\x2f\x1c\xf9\x16\x82\
+            \x01\x3c\x7c\xb9\x80\xab\xbb\x25\x47\x3b\xe4\xae\x7f\x7b\x56\x83\
+        ",
+    },
+    #[cfg(feature = "keccak_mac")]
+    KnownAnswer {
+        algorithm: "KeccakMac",
+        compute: |output| crate::KeccakMac::new(b"key").chain(b"abc").finalize(output),
+        expected: b"\
+            \x60\x68\xb5\x42\x3c\xe0\x90\x08\x59\xe5\xc1\xb7\x07\x6e\x0d\xa9\
+            \x0d\x5a\x4d\xe7\xe5\x46\xcc\x94\x1f\x09\x74\xfa\x6e\xb8\xf4\xeb\
+        ",
+    },
+    #[cfg(feature = "kdf")]
+    KnownAnswer {
+        algorithm: "KeccakKdf",
+        compute: |output| crate::KeccakKdf::new(b"master key").derive_key(b"label", output),
+        expected: b"\
+            \xf9\xdd\xae\x29\x92\xc1\x3f\x1d\xd7\x70\xb4\x49\xb6\x78\x28\xc9\
+            \x7c\x75\xa5\x53\x7e\xde\x8e\x76\x2f\x47\x0e\x31\x4a\x1d\xe6\x9a\
+        ",
+    },
+    #[cfg(feature = "commitment")]
+    KnownAnswer {
+        algorithm: "KeccakCommitment",
+        compute: |output| {
+            let (commitment, _) = crate::KeccakCommitment::commit(b"abc", [0u8; 32]);
+            output.copy_from_slice(commitment.as_bytes());
+        },
+        expected: b"\
+            \xe2\x18\xe7\xac\xa1\xc9\x87\xd3\x56\xf3\xa4\xd9\xe0\x8b\x96\x10\
+            \xec\x11\xfd\xd7\x7e\x96\x0a\x43\xf4\x29\xd8\x16\xea\x44\xe1\xb1\
+        ",
+    },
+    #[cfg(feature = "duplex")]
+    KnownAnswer {
+        algorithm: "Duplex",
+        compute: |output| {
+            let mut duplex = crate::Duplex::v256();
+            duplex.duplexing_into(b"key", output);
+            duplex.duplexing_into(b"abc", output);
+        },
+        expected: b"\
+            \xc7\x9e\x56\x01\x64\x57\xed\x67\x6a\x9d\x2c\x8f\x49\xf0\x61\xf3\
+            \x8c\x2d\xc9\x51\xd2\x9a\x92\x28\x99\x3c\x41\xa6\xf5\x43\xd5\x63\
+        ",
+    },
+    #[cfg(feature = "stream_cipher")]
+    KnownAnswer {
+        algorithm: "KeccakStreamCipher",
+        compute: |output| {
+            let mut data = [0u8; 16];
+            data[..3].copy_from_slice(b"abc");
+            crate::KeccakStreamCipher::new(b"key", b"nonce").apply_keystream(&mut data);
+            output.copy_from_slice(&data);
+        },
+        expected: b"\
+            \x79\x64\xe9\x14\x0f\x4c\x0f\x1f\x85\xf1\x7d\xa1\x3b\x04\x91\x55\
+        ",
+    },
+];
+
+/// Computes every digest of `known_answers` and compares it with the expected one.
+pub(crate) fn check(known_answers: &[KnownAnswer]) -> Result<(), KeccakError> {
+    let mut output = [0u8; 32];
+    for known_answer in known_answers {
+        let output = &mut output[..known_answer.expected.len()];
+        (known_answer.compute)(output);
+        if output != known_answer.expected {
+            return Err(KeccakError::SelfTestFailed {
+                algorithm: known_answer.algorithm,
+            });
+        }
+    }
+    Ok(())
+}
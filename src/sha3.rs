@@ -1,4 +1,6 @@
-use crate::{bits_to_rate, keccakf::KeccakF, Hasher, KeccakState};
+use core::mem::MaybeUninit;
+
+use crate::{bits_to_rate, Domain, Hasher, KeccakError, KeccakF, KeccakState, SPONGE_SIZE, WORDS};
 
 /// The `SHA3` hash functions defined in [`FIPS-202`].
 ///
@@ -34,8 +36,10 @@ pub struct Sha3 {
     state: KeccakState<KeccakF>,
 }
 
+const _: () = assert!(core::mem::size_of::<Sha3>() <= SPONGE_SIZE);
+
 impl Sha3 {
-    const DELIM: u8 = 0x06;
+    const DELIM: u8 = Domain::Sha3.delim();
 
     /// Creates  new [`Sha3`] hasher with a security level of 224 bits.
     ///
@@ -65,11 +69,142 @@ impl Sha3 {
         Sha3::new(512)
     }
 
-    fn new(bits: usize) -> Sha3 {
+    /// Absorb the first `nbits` bits of `data`, see [`KeccakState::update_bits`]. If `nbits`
+    /// isn't a multiple of 8 no more input can be absorbed afterwards.
+    ///
+    /// [`KeccakState::update_bits`]: struct.KeccakState.html#method.update_bits
+    pub fn update_bits(&mut self, data: &[u8], nbits: usize) {
+        self.state.update_bits(data, nbits);
+    }
+
+    /// Pad and squeeze a copy of the state to the output, so the [`Sha3`] hasher can keep
+    /// absorbing input, e.g. for a rolling digest.
+    ///
+    /// [`Sha3`]: struct.Sha3.html
+    pub fn finalize_into(&self, output: &mut [u8]) {
+        self.check_output_len(output.len());
+        self.state.finalize_into(output);
+    }
+
+    /// Pad and squeeze the state to the output, failing with
+    /// [`KeccakError::OutputSizeMismatch`] if `output` isn't exactly as long as the digest.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Sha3, KeccakError};
+    /// let mut output = [0u8; 64];
+    /// assert_eq!(
+    ///     Sha3::v256().try_finalize(&mut output),
+    ///     Err(KeccakError::OutputSizeMismatch { expected: 32, len: 64 })
+    /// );
+    /// assert_eq!(Sha3::v256().try_finalize(&mut output[..32]), Ok(()));
+    /// ```
+    ///
+    /// [`KeccakError::OutputSizeMismatch`]: enum.KeccakError.html#variant.OutputSizeMismatch
+    pub fn try_finalize(self, output: &mut [u8]) -> Result<(), KeccakError> {
+        let expected = self.output_len();
+        if output.len() != expected {
+            return Err(KeccakError::OutputSizeMismatch {
+                expected,
+                len: output.len(),
+            });
+        }
+        self.state.try_finalize(output)
+    }
+
+    /// Pad and squeeze the state to the output, then reset the [`Sha3`] hasher, so it can be
+    /// reused for another input.
+    ///
+    /// [`Sha3`]: struct.Sha3.html
+    pub fn finalize_reset(&mut self, output: &mut [u8]) {
+        self.check_output_len(output.len());
+        self.state.finalize_reset(output);
+    }
+
+    /// Resets the [`Sha3`] hasher to its initial state, so it can be reused for another
+    /// input.
+    ///
+    /// [`Sha3`]: struct.Sha3.html
+    pub fn reset(&mut self) {
+        self.state.reset();
+    }
+
+    /// Pad and squeeze the state, then compare the output with `expected` in constant time.
+    ///
+    /// Returns `false` if the length of `expected` doesn't match the SHA3 variant.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Sha3};
+    /// let mut output = [0u8; 32];
+    /// let mut hasher = Sha3::v256();
+    /// hasher.update(b"hello world");
+    /// hasher.clone().finalize(&mut output);
+    /// assert!(hasher.verify(&output));
+    /// ```
+    pub fn verify(self, expected: &[u8]) -> bool {
+        expected.len() == self.output_len() && self.state.verify(expected)
+    }
+
+    fn output_len(&self) -> usize {
+        (WORDS * 8 - self.state.rate) / 2
+    }
+
+    fn check_output_len(&self, len: usize) {
+        debug_assert!(
+            len <= self.output_len(),
+            "output length exceeds the SHA3 variant, expected at most {} bytes, got {}",
+            self.output_len(),
+            len
+        );
+    }
+
+    /// Returns the hasher with the input absorbed so far, finalizing with the domain separation
+    /// byte `delim` instead of the `SHA3` one, see [`KeccakState::with_delim`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state is padded already.
+    ///
+    /// [`KeccakState::with_delim`]: struct.KeccakState.html#method.with_delim
+    pub fn with_delim(self, delim: u8) -> Sha3 {
+        Sha3 {
+            state: self.state.with_delim(delim),
+        }
+    }
+
+    /// Converts the hasher into a [`Keccak`] hasher of the same security level, continuing from
+    /// the input absorbed so far. Requires the `keccak` feature.
+    ///
+    /// [`Keccak`]: struct.Keccak.html
+    #[cfg(feature = "keccak")]
+    pub fn into_keccak(self) -> crate::Keccak {
+        crate::Keccak {
+            state: self.state.with_delim(Domain::Keccak.delim()),
+        }
+    }
+
+    pub(crate) fn new(bits: usize) -> Sha3 {
         Sha3 {
             state: KeccakState::new(bits_to_rate(bits), Self::DELIM),
         }
     }
+
+    #[cfg(feature = "keccak")]
+    pub(crate) fn from_state(state: KeccakState<KeccakF>) -> Sha3 {
+        Sha3 { state }
+    }
+}
+
+/// Creates  new [`Sha3`] hasher with a security level of 256 bits, i.e. `SHA3-256`.
+///
+/// [`Sha3`]: struct.Sha3.html
+impl Default for Sha3 {
+    fn default() -> Sha3 {
+        Sha3::v256()
+    }
 }
 
 impl Hasher for Sha3 {
@@ -77,7 +212,185 @@ impl Hasher for Sha3 {
         self.state.update(input);
     }
 
+    /// Pad and squeeze the state to the output.
+    ///
+    /// A shorter `output` receives the leading bytes of the full digest. Debug builds panic if
+    /// `output` is longer than the digest of the SHA3 variant. SHA3 is specified for a single
+    /// output length, 28, 32, 48 or 64 bytes, use [`try_finalize`] to require exactly that one.
+    ///
+    /// [`try_finalize`]: struct.Sha3.html#method.try_finalize
     fn finalize(self, output: &mut [u8]) {
+        self.check_output_len(output.len());
         self.state.finalize(output);
     }
+
+    fn finalize_uninit(self, output: &mut [MaybeUninit<u8>]) -> &mut [u8] {
+        self.check_output_len(output.len());
+        self.state.finalize_uninit(output)
+    }
+
+    fn finalize_xor(self, output: &mut [u8]) {
+        self.check_output_len(output.len());
+        self.state.finalize_xor(output);
+    }
+
+    fn finalize_scattered(self, outputs: &mut [&mut [u8]]) {
+        self.check_output_len(outputs.iter().map(|output| output.len()).sum());
+        self.state.finalize_scattered(outputs);
+    }
+
+    fn finalize_array<const N: usize>(self) -> [u8; N] {
+        self.check_output_len(N);
+        let mut output = [0u8; N];
+        self.state.finalize(&mut output);
+        output
+    }
 }
+
+#[cfg(feature = "std")]
+impl_write!(Sha3);
+
+impl_extend!(Sha3);
+
+/// Prints the rate and the offset of the sponge, with the state redacted.
+impl core::fmt::Debug for Sha3 {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.state.debug("Sha3", f)
+    }
+}
+
+hash_function!(
+    /// Computes the `SHA3-224` digest of `input`.
+    sha3_224,
+    Sha3,
+    v224,
+    28
+);
+
+hash_function!(
+    /// Computes the `SHA3-256` digest of `input`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::sha3_256;
+    /// #
+    /// let digest = sha3_256(b"");
+    /// assert_eq!(
+    ///     digest,
+    ///     [
+    ///         0xa7, 0xff, 0xc6, 0xf8, 0xbf, 0x1e, 0xd7, 0x66,
+    ///         0x51, 0xc1, 0x47, 0x56, 0xa0, 0x61, 0xd6, 0x62,
+    ///         0xf5, 0x80, 0xff, 0x4d, 0xe4, 0x3b, 0x49, 0xfa,
+    ///         0x82, 0xd8, 0x0a, 0x4b, 0x80, 0xf8, 0x43, 0x4a,
+    ///     ]
+    /// );
+    /// ```
+    sha3_256,
+    Sha3,
+    v256,
+    32
+);
+
+hash_function!(
+    /// Computes the `SHA3-384` digest of `input`.
+    sha3_384,
+    Sha3,
+    v384,
+    48
+);
+
+hash_function!(
+    /// Computes the `SHA3-512` digest of `input`.
+    sha3_512,
+    Sha3,
+    v512,
+    64
+);
+
+fixed_output_hasher!(
+    /// `SHA3-224` with the digest length in its type.
+    ///
+    /// With the `digest` feature it implements the `digest` traits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Sha3_224};
+    /// let mut hasher = Sha3_224::new();
+    /// hasher.update(b"hello world");
+    /// let digest: [u8; 28] = hasher.finalize();
+    /// ```
+    Sha3_224,
+    Sha3,
+    v224,
+    28,
+    U28
+);
+
+fixed_output_hasher!(
+    /// `SHA3-256` with the digest length in its type.
+    ///
+    /// With the `digest` feature it implements the `digest` traits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Sha3_256};
+    /// let mut hasher = Sha3_256::new();
+    /// hasher.update(b"hello world");
+    /// let digest: [u8; 32] = hasher.finalize();
+    /// ```
+    ///
+    /// Digests of the wrong length don't compile:
+    ///
+    /// ```compile_fail
+    /// # use tiny_keccak::Sha3_256;
+    /// let digest: [u8; 64] = Sha3_256::new().finalize();
+    /// ```
+    Sha3_256,
+    Sha3,
+    v256,
+    32,
+    U32
+);
+
+fixed_output_hasher!(
+    /// `SHA3-384` with the digest length in its type.
+    ///
+    /// With the `digest` feature it implements the `digest` traits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Sha3_384};
+    /// let mut hasher = Sha3_384::new();
+    /// hasher.update(b"hello world");
+    /// let digest: [u8; 48] = hasher.finalize();
+    /// ```
+    Sha3_384,
+    Sha3,
+    v384,
+    48,
+    U48
+);
+
+fixed_output_hasher!(
+    /// `SHA3-512` with the digest length in its type.
+    ///
+    /// With the `digest` feature it implements the `digest` traits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Sha3_512};
+    /// let mut hasher = Sha3_512::new();
+    /// hasher.update(b"hello world");
+    /// let digest: [u8; 64] = hasher.finalize();
+    /// ```
+    Sha3_512,
+    Sha3,
+    v512,
+    64,
+    U64
+);
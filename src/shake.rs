@@ -1,4 +1,6 @@
-use crate::{bits_to_rate, keccakf::KeccakF, Hasher, KeccakState, Xof};
+use core::mem::MaybeUninit;
+
+use crate::{bits_to_rate, Domain, Hasher, KeccakF, KeccakState, Xof, SPONGE_SIZE};
 
 /// The `SHAKE` extendable-output functions defined in [`FIPS-202`].
 ///
@@ -15,8 +17,10 @@ pub struct Shake {
     state: KeccakState<KeccakF>,
 }
 
+const _: () = assert!(core::mem::size_of::<Shake>() <= SPONGE_SIZE);
+
 impl Shake {
-    const DELIM: u8 = 0x1f;
+    const DELIM: u8 = Domain::Shake.delim();
 
     /// Creates  new [`Shake`] hasher with a security level of 128 bits.
     ///
@@ -32,6 +36,104 @@ impl Shake {
         Shake::new(256)
     }
 
+    /// Creates  new `SHAKE256` hasher seeded with the next `n` bytes squeezed from `reader`,
+    /// ready to absorb more input, e.g. to derive a child context from the output of a parent
+    /// in a key ladder.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Shake, Xof};
+    /// let mut parent = Shake::v256();
+    /// parent.update(b"root key");
+    /// let mut child = Shake::seed_from(&mut parent, 32);
+    /// child.update(b"child label");
+    /// let mut child_key = [0u8; 32];
+    /// child.squeeze(&mut child_key);
+    /// ```
+    pub fn seed_from<X: Xof + ?Sized>(reader: &mut X, n: usize) -> Shake {
+        let mut shake = Shake::v256();
+        let mut seed = [0u8; 64];
+        let mut left = n;
+        while left > 0 {
+            let chunk = &mut seed[..left.min(64)];
+            reader.squeeze(chunk);
+            shake.update(chunk);
+            left -= chunk.len();
+        }
+        for byte in seed.iter_mut() {
+            *byte = 0;
+        }
+        shake
+    }
+
+    /// Squeezes a uniform integer below `bound`. The candidates are squeezed as little-endian
+    /// `u64`, the `2^64 % bound` smallest ones are rejected so the reduction modulo `bound`
+    /// isn't biased, a power of two never rejects.
+    #[cfg(any(feature = "shuffle", feature = "bloom"))]
+    pub(crate) fn squeeze_below(&mut self, bound: u64) -> u64 {
+        let threshold = 0u64.wrapping_sub(bound) % bound;
+        loop {
+            let mut bytes = [0u8; 8];
+            self.squeeze(&mut bytes);
+            let candidate = u64::from_le_bytes(bytes);
+            if candidate >= threshold {
+                return candidate % bound;
+            }
+        }
+    }
+
+    /// Absorb the first `nbits` bits of `data`, see [`KeccakState::update_bits`]. If `nbits`
+    /// isn't a multiple of 8 no more input can be absorbed afterwards.
+    ///
+    /// [`KeccakState::update_bits`]: struct.KeccakState.html#method.update_bits
+    pub fn update_bits(&mut self, data: &[u8], nbits: usize) {
+        self.state.update_bits(data, nbits);
+    }
+
+    /// Pad and squeeze a copy of the state to the output, so the [`Shake`] hasher can keep
+    /// absorbing input, e.g. for a rolling digest.
+    ///
+    /// [`Shake`]: struct.Shake.html
+    pub fn finalize_into(&self, output: &mut [u8]) {
+        self.state.finalize_into(output);
+    }
+
+    /// Pad and squeeze the state to the output, then reset the [`Shake`] hasher, so it can be
+    /// reused for another input.
+    ///
+    /// [`Shake`]: struct.Shake.html
+    pub fn finalize_reset(&mut self, output: &mut [u8]) {
+        self.state.finalize_reset(output);
+    }
+
+    /// Resets the [`Shake`] hasher to its initial state, so it can be reused for another
+    /// input.
+    ///
+    /// [`Shake`]: struct.Shake.html
+    pub fn reset(&mut self) {
+        self.state.reset();
+    }
+
+    /// Pad and squeeze the state, then compare the output with `expected` in constant time.
+    ///
+    /// Returns `false` if `expected` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tiny_keccak::{Hasher, Shake};
+    /// let mut output = [0u8; 32];
+    /// let mut hasher = Shake::v128();
+    /// hasher.update(b"hello world");
+    /// hasher.clone().finalize(&mut output);
+    /// assert!(hasher.clone().verify(&output));
+    /// assert!(!hasher.verify(&[]));
+    /// ```
+    pub fn verify(self, expected: &[u8]) -> bool {
+        self.state.verify(expected)
+    }
+
     pub(crate) fn new(bits: usize) -> Shake {
         Shake {
             state: KeccakState::new(bits_to_rate(bits), Self::DELIM),
@@ -39,6 +141,15 @@ impl Shake {
     }
 }
 
+/// Creates  new [`Shake`] hasher with a security level of 128 bits, i.e. `SHAKE128`.
+///
+/// [`Shake`]: struct.Shake.html
+impl Default for Shake {
+    fn default() -> Shake {
+        Shake::v128()
+    }
+}
+
 impl Hasher for Shake {
     fn update(&mut self, input: &[u8]) {
         self.state.update(input);
@@ -47,10 +158,87 @@ impl Hasher for Shake {
     fn finalize(self, output: &mut [u8]) {
         self.state.finalize(output);
     }
+
+    fn finalize_uninit(self, output: &mut [MaybeUninit<u8>]) -> &mut [u8] {
+        self.state.finalize_uninit(output)
+    }
+
+    fn finalize_xor(self, output: &mut [u8]) {
+        self.state.finalize_xor(output);
+    }
+
+    fn finalize_scattered(self, outputs: &mut [&mut [u8]]) {
+        self.state.finalize_scattered(outputs);
+    }
+}
+
+#[cfg(feature = "std")]
+impl_write!(Shake);
+
+impl_extend!(Shake);
+
+/// Prints the rate and the offset of the sponge, with the state redacted.
+impl core::fmt::Debug for Shake {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.state.debug("Shake", f)
+    }
 }
 
 impl Xof for Shake {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output)
     }
+
+    fn squeeze_xor(&mut self, output: &mut [u8]) {
+        self.state.squeeze_xor(output);
+    }
+}
+
+#[cfg(feature = "digest")]
+impl_digest_xof!(Shake);
+
+#[cfg(feature = "digest")]
+impl digest::Reset for Shake {
+    fn reset(&mut self) {
+        self.state.reset();
+    }
+}
+
+#[cfg(feature = "digest")]
+impl digest::ExtendableOutputReset for Shake {
+    fn finalize_xof_reset(&mut self) -> Shake {
+        let reader = self.clone();
+        self.state.reset();
+        reader
+    }
+}
+
+/// Computes `out_len` bytes of the `SHAKE128` output for `input`.
+///
+/// Requires the `alloc` feature.
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::shake128_vec;
+/// let output = shake128_vec(b"hello world", 100);
+/// assert_eq!(output.len(), 100);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn shake128_vec(input: &[u8], out_len: usize) -> alloc::vec::Vec<u8> {
+    shake_vec(Shake::v128(), input, out_len)
+}
+
+/// Computes `out_len` bytes of the `SHAKE256` output for `input`.
+///
+/// Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+pub fn shake256_vec(input: &[u8], out_len: usize) -> alloc::vec::Vec<u8> {
+    shake_vec(Shake::v256(), input, out_len)
+}
+
+#[cfg(feature = "alloc")]
+fn shake_vec(mut shake: Shake, input: &[u8], out_len: usize) -> alloc::vec::Vec<u8> {
+    shake.update(input);
+    crate::XofExt::squeeze_to_vec(&mut shake, out_len)
 }
@@ -0,0 +1,37 @@
+use crate::{Hasher, Shake};
+
+/// Shuffles `items` in place with a Fisher–Yates shuffle driven by `SHAKE256(seed)`, e.g. to
+/// sample reproducibly in randomized tests.
+///
+/// The item at position `i`, from the last position down to the second one, is swapped with the
+/// one at position `j`, a uniform index up to `i`. The candidates for `j` are squeezed as
+/// little-endian `u64`, and the ones that would bias the reduction modulo `i + 1` are rejected,
+/// so every permutation is equally likely. The stream doesn't depend on the width of `usize` or
+/// on the endianness, the same seed shuffles the same way on every platform.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["shuffle"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::shuffle;
+/// let mut items = [1, 2, 3, 4, 5];
+/// shuffle(b"seed", &mut items);
+///
+/// let mut again = [1, 2, 3, 4, 5];
+/// shuffle(b"seed", &mut again);
+/// assert_eq!(items, again);
+/// ```
+pub fn shuffle<T>(seed: &[u8], items: &mut [T]) {
+    let mut shake = Shake::v256();
+    shake.update(seed);
+    for i in (1..items.len()).rev() {
+        let j = shake.squeeze_below(i as u64 + 1);
+        items.swap(i, j as usize);
+    }
+}
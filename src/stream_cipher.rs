@@ -0,0 +1,67 @@
+use crate::{Hasher, Shake, Xof, SPONGE_SIZE};
+
+/// A stream cipher XORing the output of a `SHAKE256` sponge absorbing `key || nonce` into the
+/// data.
+///
+/// The keystream position advances across calls to [`apply_keystream`], so processing the data
+/// in chunks gives the same result as processing it at once, and applying the keystream again
+/// with the same key and nonce decrypts it.
+///
+/// The cipher provides confidentiality only, authenticate the ciphertext separately, for instance
+/// with [`KMAC`]. Never reuse a nonce with the same key, and use keys and nonces of a fixed length
+/// for a given application, otherwise the boundary between them is ambiguous.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["stream_cipher"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tiny_keccak::KeccakStreamCipher;
+/// let key = [7u8; 32];
+/// let nonce = [1u8; 16];
+/// let mut data = *b"attack at dawn";
+///
+/// KeccakStreamCipher::new(&key, &nonce).apply_keystream(&mut data);
+/// assert_ne!(&data, b"attack at dawn");
+///
+/// KeccakStreamCipher::new(&key, &nonce).apply_keystream(&mut data);
+/// assert_eq!(&data, b"attack at dawn");
+/// ```
+///
+/// [`apply_keystream`]: #method.apply_keystream
+/// [`KMAC`]: struct.Kmac.html
+#[derive(Clone)]
+pub struct KeccakStreamCipher {
+    shake: Shake,
+}
+
+const _: () = assert!(core::mem::size_of::<KeccakStreamCipher>() <= SPONGE_SIZE);
+
+impl KeccakStreamCipher {
+    /// Creates  new [`KeccakStreamCipher`] absorbing `key` followed by `nonce`.
+    ///
+    /// [`KeccakStreamCipher`]: struct.KeccakStreamCipher.html
+    pub fn new(key: &[u8], nonce: &[u8]) -> KeccakStreamCipher {
+        let mut shake = Shake::v256();
+        shake.update(key);
+        shake.update(nonce);
+        KeccakStreamCipher { shake }
+    }
+
+    /// XORs the next `data.len()` bytes of the keystream into `data`.
+    pub fn apply_keystream(&mut self, data: &mut [u8]) {
+        let mut keystream = [0u8; 64];
+        for chunk in data.chunks_mut(keystream.len()) {
+            let keystream = &mut keystream[..chunk.len()];
+            self.shake.squeeze(keystream);
+            for (byte, key) in chunk.iter_mut().zip(keystream.iter()) {
+                *byte ^= key;
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{left_encode, right_encode, CShake, Hasher, IntoXof, Xof};
+use crate::encode::{left_encode_bits, right_encode, right_encode_bits};
+use crate::{CShake, Hasher, IntoXof, KeccakF, KeccakState, Xof, SPONGE_SIZE};
 
 /// The `TupleHash` hash functions defined in [`SP800-185`].
 ///
@@ -21,6 +22,8 @@ pub struct TupleHash {
     state: CShake,
 }
 
+const _: () = assert!(core::mem::size_of::<TupleHash>() <= SPONGE_SIZE);
+
 impl TupleHash {
     /// Creates  new [`TupleHash`] hasher with a security level of 128 bits.
     ///
@@ -45,16 +48,58 @@ impl TupleHash {
 
 impl Hasher for TupleHash {
     fn update(&mut self, input: &[u8]) {
-        self.state.update(left_encode(input.len() * 8).value());
+        self.state.update(left_encode_bits(input.len()).value());
         self.state.update(input)
     }
 
+    // the slices are one element of the tuple, as if they were concatenated
+    fn update_vectored(&mut self, slices: &[&[u8]]) {
+        let len = slices.iter().map(|slice| slice.len()).sum();
+        self.state.update(left_encode_bits(len).value());
+        for slice in slices {
+            self.state.update(slice);
+        }
+    }
+
+    // the bytes are one element of the tuple, so its length has to be known before they are
+    // absorbed: from an exact size hint, or by collecting them
+    fn update_iter<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        match iter.size_hint() {
+            (len, Some(upper)) if len == upper => {
+                self.state.update(left_encode_bits(len).value());
+                let mut absorbed = 0;
+                self.state.update_iter(iter.inspect(|_| absorbed += 1));
+                assert_eq!(absorbed, len, "the iterator broke its size hint");
+            }
+            #[cfg(feature = "alloc")]
+            _ => self.update(&iter.collect::<alloc::vec::Vec<u8>>()),
+            #[cfg(not(feature = "alloc"))]
+            _ => panic!("TupleHash needs an iterator of known length without the alloc feature"),
+        }
+    }
+
     fn finalize(mut self, output: &mut [u8]) {
-        self.state.update(right_encode(output.len() * 8).value());
+        self.state.update(right_encode_bits(output.len()).value());
         self.state.finalize(output)
     }
+
+    fn finalize_xor(mut self, output: &mut [u8]) {
+        self.state.update(right_encode_bits(output.len()).value());
+        self.state.finalize_xor(output);
+    }
+
+    fn finalize_scattered(mut self, outputs: &mut [&mut [u8]]) {
+        let len = outputs.iter().map(|output| output.len()).sum();
+        self.state.update(right_encode_bits(len).value());
+        self.state.finalize_scattered(outputs);
+    }
 }
 
+// every `write` absorbs one element of the tuple, like `update`
+#[cfg(feature = "std")]
+impl_write!(TupleHash);
+
 /// The `TupleHashXOF` extendable-output functions defined in [`SP800-185`].
 ///
 /// # Usage
@@ -90,6 +135,8 @@ pub struct TupleHashXof {
     state: CShake,
 }
 
+const _: () = assert!(core::mem::size_of::<TupleHashXof>() <= SPONGE_SIZE);
+
 impl IntoXof for TupleHash {
     type Xof = TupleHashXof;
 
@@ -99,8 +146,24 @@ impl IntoXof for TupleHash {
     }
 }
 
+impl TupleHashXof {
+    /// Returns the sponge state, positioned where the squeezing stopped.
+    ///
+    /// Resetting the state keeps its rate and delimiter, but not the encoded customization
+    /// prefix, which a new [`TupleHash`] absorbs again.
+    ///
+    /// [`TupleHash`]: struct.TupleHash.html
+    pub fn into_state(self) -> KeccakState<KeccakF> {
+        self.state.into_state()
+    }
+}
+
 impl Xof for TupleHashXof {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output)
     }
+
+    fn squeeze_xor(&mut self, output: &mut [u8]) {
+        self.state.squeeze_xor(output);
+    }
 }
@@ -0,0 +1,99 @@
+use core::mem::MaybeUninit;
+
+use crate::{bits_to_rate, Hasher, KeccakP, KeccakState, Xof, SPONGE_SIZE};
+
+/// The `TurboSHAKE` extendable-output functions defined in [`RFC 9861`].
+///
+/// `TurboSHAKE` is `SHAKE` with the number of rounds reduced to 12 and a selectable domain
+/// separation byte, it is the sponge underlying [`KangarooTwelve`].
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["turboshake"] }
+/// ```
+///
+/// [`RFC 9861`]: https://www.rfc-editor.org/rfc/rfc9861
+/// [`KangarooTwelve`]: https://eprint.iacr.org/2016/770.pdf
+#[derive(Clone)]
+pub struct TurboShake {
+    state: KeccakState<KeccakP<12>>,
+}
+
+const _: () = assert!(core::mem::size_of::<TurboShake>() <= SPONGE_SIZE);
+
+impl TurboShake {
+    /// Domain separation byte used when the caller has no need for one.
+    pub const DEFAULT_DOMAIN: u8 = 0x1f;
+
+    /// Creates  new [`TurboShake`] hasher with a security level of 128 bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `domain` is not in the `0x01..=0x7f` range.
+    ///
+    /// [`TurboShake`]: struct.TurboShake.html
+    pub fn v128(domain: u8) -> TurboShake {
+        TurboShake::new(domain, 128)
+    }
+
+    /// Creates  new [`TurboShake`] hasher with a security level of 256 bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `domain` is not in the `0x01..=0x7f` range.
+    ///
+    /// [`TurboShake`]: struct.TurboShake.html
+    pub fn v256(domain: u8) -> TurboShake {
+        TurboShake::new(domain, 256)
+    }
+
+    fn new(domain: u8, bits: usize) -> TurboShake {
+        assert!(
+            (0x01..=0x7f).contains(&domain),
+            "TurboSHAKE domain separation byte must be in the 0x01..=0x7f range, got {:#04x}",
+            domain
+        );
+        TurboShake {
+            state: KeccakState::new(bits_to_rate(bits), domain),
+        }
+    }
+}
+
+impl Hasher for TurboShake {
+    fn update(&mut self, input: &[u8]) {
+        self.state.update(input);
+    }
+
+    fn finalize(self, output: &mut [u8]) {
+        self.state.finalize(output);
+    }
+
+    fn finalize_uninit(self, output: &mut [MaybeUninit<u8>]) -> &mut [u8] {
+        self.state.finalize_uninit(output)
+    }
+
+    fn finalize_xor(self, output: &mut [u8]) {
+        self.state.finalize_xor(output);
+    }
+
+    fn finalize_scattered(self, outputs: &mut [&mut [u8]]) {
+        self.state.finalize_scattered(outputs);
+    }
+}
+
+#[cfg(feature = "std")]
+impl_write!(TurboShake);
+
+impl_extend!(TurboShake);
+
+impl Xof for TurboShake {
+    fn squeeze(&mut self, output: &mut [u8]) {
+        self.state.squeeze(output)
+    }
+
+    fn squeeze_xor(&mut self, output: &mut [u8]) {
+        self.state.squeeze_xor(output);
+    }
+}
@@ -0,0 +1,73 @@
+use tiny_keccak::{bloom_indices, bloom_indices_iter, Hasher, Shake, Xof};
+
+#[test]
+fn bloom_indices_are_in_range_and_reproducible() {
+    for &m in &[1, 2, 3, 1000, 1 << 20] {
+        let indices = bloom_indices(b"item", 16, m);
+        assert_eq!(indices.len(), 16);
+        assert!(indices.iter().all(|&index| index < m));
+        assert_eq!(indices, bloom_indices(b"item", 16, m));
+        assert_eq!(
+            indices,
+            bloom_indices_iter(b"item", 16, m).collect::<Vec<_>>()
+        );
+    }
+    assert!(bloom_indices(b"item", 0, 1000).is_empty());
+
+    // a power of two never rejects, since `2^64 % m` is zero, so the indices are the squeezed
+    // words reduced modulo m, computed independently from the SHAKE256 stream of the item
+    assert_eq!(bloom_indices(b"item", 3, 1024), [130, 712, 668]);
+    let mut shake = Shake::v256();
+    shake.update(b"item");
+    let mut stream = [0u8; 24];
+    shake.squeeze(&mut stream);
+    let expected: Vec<usize> = stream
+        .chunks(8)
+        .map(|word| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(word);
+            (u64::from_le_bytes(bytes) % 1024) as usize
+        })
+        .collect();
+    assert_eq!(bloom_indices(b"item", 3, 1024), expected);
+
+    // fewer indices are a prefix of more
+    assert_eq!(
+        bloom_indices(b"item", 3, 1000)[..],
+        bloom_indices(b"item", 7, 1000)[..3]
+    );
+}
+
+#[test]
+fn bloom_indices_differ_between_items() {
+    let items: Vec<Vec<u8>> = (0..100u32).map(|i| i.to_le_bytes().to_vec()).collect();
+    let sets: Vec<Vec<usize>> = items
+        .iter()
+        .map(|item| {
+            let mut set = bloom_indices(item, 7, 1 << 16);
+            set.sort_unstable();
+            set
+        })
+        .collect();
+    for (i, first) in sets.iter().enumerate() {
+        for second in sets[i + 1..].iter() {
+            assert_ne!(first, second);
+        }
+    }
+}
+
+#[test]
+fn bloom_indices_iter_is_exact_size() {
+    let mut indices = bloom_indices_iter(b"item", 5, 64);
+    assert_eq!(indices.len(), 5);
+    indices.next();
+    assert_eq!(indices.size_hint(), (4, Some(4)));
+    assert_eq!(indices.by_ref().count(), 4);
+    assert_eq!(indices.next(), None);
+}
+
+#[test]
+#[should_panic(expected = "at least one bit")]
+fn bloom_indices_need_a_bit() {
+    bloom_indices_iter(b"item", 1, 0);
+}
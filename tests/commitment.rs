@@ -0,0 +1,46 @@
+use tiny_keccak::KeccakCommitment;
+
+const RANDOMNESS: [u8; 32] = [0x5a; 32];
+
+#[test]
+fn commitment_opens() {
+    let (commitment, opening) = KeccakCommitment::commit(b"value", RANDOMNESS);
+    assert_eq!(opening.randomness(), &RANDOMNESS);
+    assert!(commitment.verify(b"value", opening.randomness()));
+
+    // a commitment received as bytes opens the same way
+    let received = KeccakCommitment::from_bytes(*commitment.as_bytes());
+    assert!(received.verify(b"value", &RANDOMNESS));
+}
+
+#[test]
+fn commitment_rejects_wrong_openings() {
+    let (commitment, _) = KeccakCommitment::commit(b"value", RANDOMNESS);
+    assert!(!commitment.verify(b"valuf", &RANDOMNESS));
+    assert!(!commitment.verify(b"", &RANDOMNESS));
+    assert!(!commitment.verify(b"value", &[0x5b; 32]));
+}
+
+#[test]
+fn commitment_distinct_inputs_differ() {
+    let (a, _) = KeccakCommitment::commit(b"value", RANDOMNESS);
+    let (b, _) = KeccakCommitment::commit(b"other", RANDOMNESS);
+    assert_ne!(a.as_bytes(), b.as_bytes());
+
+    let (c, _) = KeccakCommitment::commit(b"value", [0xa5; 32]);
+    assert_ne!(a.as_bytes(), c.as_bytes());
+}
+
+#[test]
+fn commitment_known_answer() {
+    // cSHAKE256 customized with `KeccakCommitment` of
+    // `encode_string(RANDOMNESS) || encode_string("value")`, computed with an implementation
+    // independent of this crate
+    let expected = b"\
+        \xD8\xE5\x5B\x36\x5B\xC8\x69\x3F\x3F\x44\x15\x52\x45\xEB\xAE\xAA\
+        \xEE\x77\x96\x1E\x10\x79\x80\xD8\x1D\x19\x7F\xA9\xB2\x92\xE9\xCE\
+    ";
+
+    let (commitment, _) = KeccakCommitment::commit(b"value", RANDOMNESS);
+    assert_eq!(commitment.as_bytes(), expected);
+}
@@ -0,0 +1,13 @@
+use tiny_keccak::Hasher;
+
+/// Collects the absorbed bytes.
+#[derive(Default)]
+pub struct Collect(pub Vec<u8>);
+
+impl Hasher for Collect {
+    fn update(&mut self, input: &[u8]) {
+        self.0.extend_from_slice(input);
+    }
+
+    fn finalize(self, _output: &mut [u8]) {}
+}
@@ -1,4 +1,24 @@
-use tiny_keccak::{CShake, Hasher, Xof};
+use tiny_keccak::encode::{
+    bytepad, encode_string, left_encode, left_encode_bits, right_encode_bits,
+};
+use tiny_keccak::{CShake, Hasher, KeccakError, KeccakF, KeccakState, Xof};
+
+mod common;
+
+use common::Collect;
+
+#[test]
+fn test_cshake_empty_is_shake() {
+    // without a name and a customization string cSHAKE is SHAKE
+    let mut output = [0u8; 32];
+    let expected = b"\
+        \x7f\x9c\x2b\xa4\xe8\x8f\x82\x7d\x61\x60\x45\x50\x76\x05\x85\x3e\
+        \xd7\x3b\x80\x93\xf6\xef\xbc\x88\xeb\x1a\x6e\xac\xfa\x66\xef\x26\
+    ";
+
+    CShake::v128(b"", b"").finalize(&mut output);
+    assert_eq!(expected, &output);
+}
 
 #[test]
 fn test_cshake128_one() {
@@ -114,3 +134,88 @@ fn test_cshake_as_shake() {
 
     assert_eq!(expected, &output);
 }
+
+#[test]
+fn test_cshake_long_custom_string() {
+    // a customization string longer than the rate, absorbed by hand
+    let custom_string = [0xa3u8; 200];
+    let mut expected = KeccakState::<KeccakF>::with_rate(168, 0x04);
+    let name = encode_string(b"");
+    let custom = encode_string(&custom_string);
+    let parts = [name.parts(), custom.parts()].concat();
+    assert_eq!(parts[2], &[2, 0x06, 0x40][..]);
+    bytepad(&mut expected, &parts, 168);
+    expected.update(b"input");
+
+    let mut cshake = CShake::v128(b"", &custom_string);
+    cshake.update(b"input");
+    let mut output = [0u8; 64];
+    cshake.squeeze(&mut output);
+    let mut reference = [0u8; 64];
+    expected.squeeze(&mut reference);
+    assert_eq!(output, reference);
+}
+
+#[test]
+fn test_cshake_length_encoding_boundaries() {
+    assert_eq!(left_encode(0).value(), &[1, 0]);
+    assert_eq!(left_encode(255).value(), &[1, 255]);
+    assert_eq!(left_encode(256).value(), &[2, 1, 0]);
+
+    // the bit length of the longest slice doesn't overflow
+    let bits = (usize::MAX as u128 * 8).to_be_bytes();
+    let start = bits.iter().position(|byte| *byte != 0).unwrap();
+    let len = (bits.len() - start) as u8;
+    assert_eq!(left_encode_bits(usize::MAX).value()[0], len);
+    assert_eq!(&left_encode_bits(usize::MAX).value()[1..], &bits[start..]);
+    assert_eq!(
+        &right_encode_bits(usize::MAX).value()[..len as usize],
+        &bits[start..]
+    );
+    assert_eq!(right_encode_bits(usize::MAX).value()[len as usize], len);
+}
+
+#[test]
+fn test_bytepad() {
+    let mut collect = Collect::default();
+    bytepad(&mut collect, &[b"ab", b"c"], 8);
+    assert_eq!(collect.0, [1, 8, b'a', b'b', b'c', 0, 0, 0]);
+
+    // the padding is empty if the input fills the last block
+    let mut collect = Collect::default();
+    bytepad(&mut collect, &[b"abcdef"], 8);
+    assert_eq!(collect.0, [1, 8, b'a', b'b', b'c', b'd', b'e', b'f']);
+
+    let mut collect = Collect::default();
+    let string = encode_string(b"Email Signature");
+    bytepad(&mut collect, &string.parts(), 168);
+    assert_eq!(collect.0.len(), 168);
+    assert_eq!(collect.0[..4], [1, 168, 1, 120]);
+    assert_eq!(collect.0[4..19], b"Email Signature"[..]);
+    assert!(collect.0[19..168].iter().all(|byte| *byte == 0));
+}
+
+#[test]
+fn test_cshake_try_new() {
+    let mut expected = [0u8; 64];
+    CShake::v256(b"name", b"custom")
+        .chain(b"input")
+        .squeeze(&mut expected);
+    let mut output = [0u8; 64];
+    CShake::try_new(b"name", b"custom", 256)
+        .unwrap()
+        .chain(b"input")
+        .squeeze(&mut output);
+    assert_eq!(output, expected);
+
+    for bits in [0, 127, 224, 512] {
+        assert_eq!(
+            CShake::try_new(b"name", b"custom", bits).err(),
+            Some(KeccakError::InvalidSecurityLevel { bits })
+        );
+    }
+    assert_eq!(
+        KeccakError::InvalidSecurityLevel { bits: 512 }.to_string(),
+        "security level of 512 bits, expected 128 or 256 bits"
+    );
+}
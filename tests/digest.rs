@@ -0,0 +1,69 @@
+use tiny_keccak::digest::{Digest, FixedOutputReset, Update};
+use tiny_keccak::{Hasher, Keccak, Keccak256, Keccak512, Sha3, Sha3_224, Sha3_256, Sha3_384};
+
+fn native<H: Hasher>(mut hasher: H, input: &[u8], output: &mut [u8]) {
+    hasher.update(input);
+    hasher.finalize(output);
+}
+
+#[test]
+fn digest_keccak() {
+    let mut expected = [0u8; 32];
+    native(Keccak::v256(), b"abc", &mut expected);
+    assert_eq!(&expected[..], &Keccak256::digest(b"abc")[..]);
+
+    let mut expected = [0u8; 64];
+    native(Keccak::v512(), b"abc", &mut expected);
+    assert_eq!(&expected[..], &Keccak512::digest(b"abc")[..]);
+}
+
+#[test]
+fn digest_sha3() {
+    let mut expected = [0u8; 28];
+    native(Sha3::v224(), b"abc", &mut expected);
+    assert_eq!(&expected[..], &Sha3_224::digest(b"abc")[..]);
+
+    let mut expected = [0u8; 32];
+    native(Sha3::v256(), b"abc", &mut expected);
+    assert_eq!(&expected[..], &Sha3_256::digest(b"abc")[..]);
+
+    let mut expected = [0u8; 48];
+    native(Sha3::v384(), b"abc", &mut expected);
+    assert_eq!(&expected[..], &Sha3_384::digest(b"abc")[..]);
+}
+
+#[test]
+fn digest_output_size() {
+    assert_eq!(<Keccak256 as Digest>::output_size(), 32);
+    assert_eq!(<Sha3_224 as Digest>::output_size(), 28);
+    assert_eq!(<Sha3_384 as Digest>::output_size(), 48);
+}
+
+#[test]
+fn digest_reset() {
+    let mut hasher = Sha3_256::new();
+    Update::update(&mut hasher, b"abc");
+    let first = hasher.finalize_fixed_reset();
+    Update::update(&mut hasher, b"abc");
+    let second = hasher.finalize_fixed_reset();
+    assert_eq!(first, second);
+    assert_eq!(first, Sha3_256::digest(b"abc"));
+}
+
+#[test]
+fn finalize_generic_matches_finalize() {
+    let mut keccak = Keccak256::new();
+    Hasher::update(&mut keccak, b"hello world");
+    let generic: tiny_keccak::digest::generic_array::GenericArray<u8, _> =
+        keccak.clone().finalize_generic();
+    let array: [u8; 32] = keccak.finalize();
+    assert_eq!(generic.len(), 32);
+    assert_eq!(generic.as_slice(), &array);
+
+    let mut sha3 = Sha3_384::new();
+    Hasher::update(&mut sha3, b"abc");
+    assert_eq!(
+        sha3.clone().finalize_generic().as_slice(),
+        &sha3.finalize()[..]
+    );
+}
@@ -0,0 +1,54 @@
+use tiny_keccak::digest::{ExtendableOutput, ExtendableOutputReset, Update, XofReader};
+use tiny_keccak::{CShake, Hasher, Shake, Xof};
+
+fn generic_xof<X: ExtendableOutput>(mut xof: X, input: &[u8], output: &mut [u8]) {
+    xof.update(input);
+    let mut reader = xof.finalize_xof();
+    let (first, rest) = output.split_at_mut(10);
+    reader.read(first);
+    reader.read(rest);
+}
+
+#[test]
+fn digest_shake256_matches_native() {
+    let mut expected = [0u8; 64];
+    let mut shake = Shake::v256();
+    Hasher::update(&mut shake, b"abc");
+    shake.squeeze(&mut expected);
+
+    let mut output = [0u8; 64];
+    generic_xof(Shake::v256(), b"abc", &mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn digest_cshake_matches_native() {
+    let mut expected = [0u8; 64];
+    let mut cshake = CShake::v128(b"", b"Email Signature");
+    Hasher::update(&mut cshake, &[0, 1, 2, 3]);
+    cshake.squeeze(&mut expected);
+
+    let mut output = [0u8; 64];
+    generic_xof(
+        CShake::v128(b"", b"Email Signature"),
+        &[0, 1, 2, 3],
+        &mut output,
+    );
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn digest_shake_finalize_xof_reset() {
+    let mut shake = Shake::v128();
+    Update::update(&mut shake, b"abc");
+    let mut first = [0u8; 32];
+    shake.finalize_xof_reset().read(&mut first);
+    Update::update(&mut shake, b"abc");
+    let mut second = [0u8; 32];
+    shake.finalize_xof_reset().read(&mut second);
+    assert_eq!(first, second);
+
+    let mut expected = [0u8; 32];
+    Shake::digest_xof(b"abc", &mut expected);
+    assert_eq!(expected, first);
+}
@@ -0,0 +1,164 @@
+use tiny_keccak::{keccak256, Duplex};
+
+#[test]
+fn duplex_first_call_is_keccak() {
+    let mut duplex = Duplex::v256();
+    let mut output = [0u8; 32];
+    duplex.duplexing_into(b"key", &mut output);
+    assert_eq!(keccak256(b"key"), output);
+}
+
+#[test]
+fn duplex_v256() {
+    let input: Vec<u8> = (0..135).collect();
+    let expected = b"\
+        \x07\x85\x5b\x46\xa6\x23\xa8\xec\xab\xac\x76\xed\x69\x7a\xa4\xe1\
+        \x36\x31\xe3\xb6\x71\x8c\x8a\x0d\x34\x28\x60\xc1\x3c\x30\xd2\xfc\
+        \x67\x04\x25\x0e\x25\x7f\x1a\xa7\x4d\x31\xe1\xd5\x6b\xf8\x60\x84\
+        \x5e\xbf\x90\xdf\x4c\x15\x54\x95\x9e\xa6\xd5\x0b\x91\xa1\x37\x55\
+        \xc9\xdf\x74\xd6\x55\x83\x4a\xa8\x50\x8a\x07\x57\x8b\xe8\x79\x19\
+        \xdb\x34\xac\x5a\x46\x77\x2b\xd7\x4a\x2f\xb1\xf5\x76\xb7\xc5\x2f\
+        \x95\x84\xbb\x8a\x23\xbd\x88\x77\x4e\x63\x56\x1b\xd6\xe4\x6d\x3a\
+        \x07\x21\xcb\xd2\x22\xfa\xa1\xe9\x5a\x70\xb3\x36\x35\x15\x98\x3e\
+        \x3b\xb8\x9e\xdc\x4b\xad\x3f\x46\x7e\xca\xc6\x19\xea\x5a\x59\xa6\
+        \xa7\xa9\xab\x45\x6a\x42\x15\x4f\x2d\x58\x55\x28\xec\x4e\x2c\xf8\
+        \x51\xa7\xb6\xb1\xb1\x04\x9a\x5d\x1f\xfd\xe6\x20\x75\x5d\xc5\x0d\
+        \xb8\xd1\x25\xf5\x5f\x7f\x0d\x6b\
+    ";
+
+    let mut duplex = Duplex::v256();
+    let mut output = [0u8; 32 + 16 + 136];
+    duplex.duplexing_into(b"key", &mut output[..32]);
+    duplex.duplexing_into(b"", &mut output[32..48]);
+    duplex.duplexing_into(&input, &mut output[48..]);
+    assert_eq!(expected[..], output[..]);
+}
+
+#[test]
+fn duplex_v128() {
+    let input: Vec<u8> = (0..167).collect();
+    let expected = b"\
+        \xf1\xcb\x91\x0d\xe0\x08\xe8\x72\x5d\x4b\xf1\x32\x4c\xcf\x51\xbc\
+        \xb7\xa7\x05\x0a\xe8\xf9\x4a\xb6\x8b\x44\xfc\x4e\x1c\xec\x2e\x03\
+        \x62\xaa\x1e\xbc\x66\xf0\xfe\x1e\xd0\xc6\x2f\xa1\x39\x54\xc3\x8b\
+        \xef\x82\x8d\x80\x18\x54\x31\x2a\xfa\x85\xc8\xa3\xc3\xec\xfc\x89\
+        \xc0\x0d\xcf\x4a\x79\xa9\x6a\xfb\x95\x06\x9c\x8d\x5a\xa4\xde\x06\
+        \x22\xf9\x31\x47\x13\xf5\xb1\x1e\xd5\x4d\xba\x5c\x30\x9f\x4f\xfa\
+        \x0a\x54\x9b\xd9\x0d\x64\x49\x59\xee\x14\xac\x5c\x92\x51\x55\xd8\
+        \x50\x34\x82\x61\xca\x93\x13\x84\x6d\xe4\x7b\x03\x17\xc5\x58\xb3\
+        \x53\x7b\xe1\x0c\xfe\x09\xd0\xfe\x10\x34\x25\xcf\x1f\xc7\x1f\xaa\
+        \x8e\xc4\xeb\x6e\x43\x63\xca\x45\x53\x90\x37\xc3\x1d\xca\xba\x0f\
+        \x85\x31\xd7\x8b\x7a\xc2\xb7\xe4\
+    ";
+
+    let mut duplex = Duplex::v128();
+    let mut output = [0u8; 168];
+    duplex.duplexing_into(&input, &mut output);
+    assert_eq!(expected[..], output[..]);
+}
+
+#[test]
+#[should_panic(expected = "duplex input must be shorter than the rate")]
+fn duplex_input_too_long() {
+    Duplex::v256().duplexing_into(&[0; 136], &mut []);
+}
+
+#[test]
+#[should_panic(expected = "duplex output cannot exceed the rate")]
+fn duplex_output_too_long() {
+    Duplex::v256().duplexing_into(b"", &mut [0; 137]);
+}
+
+/// A simplified `SpongeWrap`, the keystream of every block is squeezed while absorbing the
+/// previous plaintext block and the tag is squeezed while absorbing the last one.
+fn wrap(key: &[u8], plaintext: &[u8], decrypt: bool) -> (Vec<u8>, [u8; 16]) {
+    let mut duplex = Duplex::v256();
+    let block = duplex.rate() - 1;
+    let mut output = Vec::with_capacity(plaintext.len());
+    let mut keystream = vec![0u8; block];
+
+    duplex.duplexing_into(key, &mut []);
+    let mut previous: Vec<u8> = Vec::new();
+    for chunk in plaintext.chunks(block) {
+        let keystream = &mut keystream[..chunk.len()];
+        duplex.duplexing_into(&previous, keystream);
+        let out: Vec<u8> = chunk
+            .iter()
+            .zip(keystream.iter())
+            .map(|(a, b)| a ^ b)
+            .collect();
+        previous = if decrypt { out.clone() } else { chunk.to_vec() };
+        output.extend_from_slice(&out);
+    }
+
+    let mut tag = [0u8; 16];
+    duplex.duplexing_into(&previous, &mut tag);
+    (output, tag)
+}
+
+#[test]
+fn duplex_wrap_round_trip() {
+    let plaintext: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+    let (ciphertext, tag) = wrap(b"secret key", &plaintext, false);
+    assert_ne!(ciphertext, plaintext);
+
+    let (decrypted, decrypted_tag) = wrap(b"secret key", &ciphertext, true);
+    assert_eq!(decrypted, plaintext);
+    assert_eq!(decrypted_tag, tag);
+
+    let (_, wrong_tag) = wrap(b"wrong key", &ciphertext, true);
+    assert_ne!(wrong_tag, tag);
+}
+
+#[test]
+fn duplex_absorb_squeeze_is_a_deterministic_stream() {
+    let calls: [(&[u8], usize); 4] = [(&[1; 200], 300), (b"", 0), (b"abc", 136), (&[2; 136], 1)];
+    let run = || {
+        let mut duplex = Duplex::v256();
+        let mut outputs = Vec::new();
+        for (input, len) in calls.iter() {
+            let mut output = vec![0u8; *len];
+            duplex.absorb_squeeze(input, &mut output);
+            outputs.push(output);
+        }
+        outputs
+    };
+    assert_eq!(run(), run());
+
+    // the same call sequence diverges after a different input
+    let mut first = Duplex::v256();
+    let mut second = Duplex::v256();
+    let (mut a, mut b) = ([0u8; 64], [0u8; 64]);
+    first.absorb_squeeze(b"same", &mut a);
+    second.absorb_squeeze(b"same", &mut b);
+    assert_eq!(a, b);
+    first.absorb_squeeze(b"one", &mut a);
+    second.absorb_squeeze(b"two", &mut b);
+    assert_ne!(a, b);
+}
+
+#[test]
+fn duplex_absorb_squeeze_matches_duplexing_into() {
+    let mut fused = Duplex::v128();
+    let mut duplex = Duplex::v128();
+    for (i, input) in [&b"key"[..], b"", &[7; 167], b"nonce"].iter().enumerate() {
+        let mut expected = [0u8; 168];
+        let mut output = [0u8; 168];
+        let len = [168, 0, 32, 1][i];
+        duplex.duplexing_into(input, &mut expected[..len]);
+        fused.absorb_squeeze(input, &mut output[..len]);
+        assert_eq!(expected, output);
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn duplex_duplexing_returns_the_output() {
+    let mut duplex = Duplex::v256();
+    let mut expected = Duplex::v256();
+    let mut output = [0u8; 136];
+    for (input, len) in [(&b"key"[..], 32), (b"", 0), (&[7; 135], 136)].iter() {
+        expected.duplexing_into(input, &mut output[..*len]);
+        assert_eq!(duplex.duplexing(input, *len), &output[..*len]);
+    }
+}
@@ -0,0 +1,39 @@
+//! Pins the contract of the `eth` module, these vectors must never change.
+
+use tiny_keccak::eth::{keccak256, Keccak256};
+
+const EMPTY: [u8; 32] = *b"\
+    \xc5\xd2\x46\x01\x86\xf7\x23\x3c\x92\x7e\x7d\xb2\xdc\xc7\x03\xc0\
+    \xe5\x00\xb6\x53\xca\x82\x27\x3b\x7b\xfa\xd8\x04\x5d\x85\xa4\x70\
+";
+
+const ABC: [u8; 32] = *b"\
+    \x4e\x03\x65\x7a\xea\x45\xa9\x4f\xc7\xd4\x7b\xa8\x26\xc8\xd6\x67\
+    \xc0\xd1\xe6\xe3\x3a\x64\xa0\x36\xec\x44\xf5\x8f\xa1\x2d\x6c\x45\
+";
+
+#[test]
+fn eth_keccak256_vectors() {
+    assert_eq!(keccak256(b""), EMPTY);
+    assert_eq!(keccak256(b"abc"), ABC);
+}
+
+#[test]
+fn eth_keccak256_incremental() {
+    assert_eq!(Keccak256::new().finalize(), EMPTY);
+    assert_eq!(Keccak256::default().finalize(), EMPTY);
+
+    let mut keccak = Keccak256::new();
+    keccak.update(b"a");
+    keccak.update(b"");
+    keccak.update(b"bc");
+    assert_eq!(keccak.clone().finalize(), ABC);
+
+    // input across the 136 byte rate
+    let input = [0x61u8; 300];
+    let mut keccak = Keccak256::new();
+    for chunk in input.chunks(135) {
+        keccak.update(chunk);
+    }
+    assert_eq!(keccak.finalize(), keccak256(&input));
+}
@@ -0,0 +1,133 @@
+use std::ptr;
+use tiny_keccak::ffi::{
+    keccak_finalize, keccak_finalize_reset, keccak_free, keccak_new, keccak_update,
+    KECCAK_ERR_NULL, KECCAK_ERR_OUTPUT_LEN, KECCAK_OK,
+};
+use tiny_keccak::{keccak256, Hasher, Keccak};
+
+#[test]
+fn ffi_matches_native_digest() {
+    let input = b"The quick brown fox jumps over the lazy dog";
+    let handle = keccak_new();
+    assert!(!handle.is_null());
+
+    unsafe {
+        for chunk in input.chunks(5) {
+            assert_eq!(
+                keccak_update(handle, chunk.as_ptr(), chunk.len()),
+                KECCAK_OK
+            );
+        }
+        assert_eq!(keccak_update(handle, ptr::null(), 0), KECCAK_OK);
+
+        let mut output = [0u8; 32];
+        assert_eq!(
+            keccak_finalize(handle, output.as_mut_ptr(), output.len()),
+            KECCAK_OK
+        );
+        assert_eq!(output, keccak256(input));
+
+        // finalizing resets the handle
+        assert_eq!(keccak_update(handle, b"abc".as_ptr(), 3), KECCAK_OK);
+        let mut truncated = [0u8; 20];
+        assert_eq!(
+            keccak_finalize(handle, truncated.as_mut_ptr(), truncated.len()),
+            KECCAK_OK
+        );
+        assert_eq!(truncated, keccak256(b"abc")[..20]);
+
+        keccak_free(handle);
+    }
+}
+
+#[test]
+fn ffi_rejects_invalid_arguments() {
+    let handle = keccak_new();
+    let mut output = [0u8; 33];
+
+    unsafe {
+        assert_eq!(
+            keccak_update(ptr::null_mut(), b"a".as_ptr(), 1),
+            KECCAK_ERR_NULL
+        );
+        assert_eq!(keccak_update(handle, ptr::null(), 1), KECCAK_ERR_NULL);
+        assert_eq!(
+            keccak_finalize(ptr::null_mut(), output.as_mut_ptr(), 32),
+            KECCAK_ERR_NULL
+        );
+        assert_eq!(
+            keccak_finalize(handle, ptr::null_mut(), 32),
+            KECCAK_ERR_NULL
+        );
+        assert_eq!(
+            keccak_finalize(handle, output.as_mut_ptr(), output.len()),
+            KECCAK_ERR_OUTPUT_LEN
+        );
+
+        // the failed calls left the hasher untouched
+        assert_eq!(keccak_finalize(handle, output.as_mut_ptr(), 32), KECCAK_OK);
+        assert_eq!(output[..32], keccak256(b""));
+
+        keccak_free(handle);
+        keccak_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn ffi_finalize_reset_keeps_the_handle() {
+    let handle = keccak_new();
+    let mut first = [0u8; 32];
+    let mut second = [0u8; 32];
+
+    unsafe {
+        assert_eq!(keccak_update(handle, b"first".as_ptr(), 5), KECCAK_OK);
+        assert_eq!(
+            keccak_finalize_reset(handle, first.as_mut_ptr(), first.len()),
+            KECCAK_OK
+        );
+        assert_eq!(keccak_update(handle, b"second".as_ptr(), 6), KECCAK_OK);
+        assert_eq!(
+            keccak_finalize_reset(handle, second.as_mut_ptr(), second.len()),
+            KECCAK_OK
+        );
+
+        assert_eq!(
+            keccak_finalize_reset(ptr::null_mut(), first.as_mut_ptr(), 32),
+            KECCAK_ERR_NULL
+        );
+        assert_eq!(
+            keccak_finalize_reset(handle, ptr::null_mut(), 32),
+            KECCAK_ERR_NULL
+        );
+        assert_eq!(
+            keccak_finalize_reset(handle, first.as_mut_ptr(), 33),
+            KECCAK_ERR_OUTPUT_LEN
+        );
+
+        keccak_free(handle);
+    }
+
+    let mut expected = [0u8; 32];
+    Keccak::v256().chain(b"first").finalize(&mut expected);
+    assert_eq!(first, expected);
+    Keccak::v256().chain(b"second").finalize(&mut expected);
+    assert_eq!(second, expected);
+}
+
+#[test]
+fn ffi_finalize_accepts_a_null_empty_output() {
+    let handle = keccak_new();
+    let mut output = [0u8; 32];
+
+    unsafe {
+        assert_eq!(keccak_update(handle, b"dropped".as_ptr(), 7), KECCAK_OK);
+        assert_eq!(keccak_finalize(handle, ptr::null_mut(), 0), KECCAK_OK);
+        assert_eq!(keccak_finalize_reset(handle, ptr::null_mut(), 0), KECCAK_OK);
+
+        // the empty finalize still reset the hasher
+        assert_eq!(keccak_finalize(handle, output.as_mut_ptr(), 32), KECCAK_OK);
+        assert_eq!(output, keccak256(b""));
+
+        keccak_free(handle);
+    }
+}
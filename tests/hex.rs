@@ -0,0 +1,37 @@
+use tiny_keccak::{Hasher, Keccak256, Keccak512, Sha3_256};
+
+#[test]
+fn keccak256_empty_hex() {
+    assert_eq!(
+        Keccak256::new().finalize_hex(),
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+}
+
+#[test]
+fn sha3_256_abc_hex_into() {
+    let mut hasher = Sha3_256::new();
+    hasher.update(b"abc");
+    let mut buffer = [0xffu8; 80];
+    assert_eq!(
+        hasher.finalize_hex_into(&mut buffer),
+        "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+    );
+    // only the hex digits are written
+    assert_eq!(buffer[64..], [0xff; 16]);
+}
+
+#[test]
+fn keccak512_hex_matches_digest() {
+    let mut hasher = Keccak512::new();
+    hasher.update(b"hello world");
+    let digest = hasher.clone().finalize();
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    assert_eq!(hasher.finalize_hex(), hex);
+}
+
+#[test]
+#[should_panic(expected = "hex buffer must hold at least 64 bytes, got 63")]
+fn finalize_hex_into_short_buffer() {
+    Keccak256::new().finalize_hex_into(&mut [0u8; 63]);
+}
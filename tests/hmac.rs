@@ -0,0 +1,216 @@
+use tiny_keccak::{Hasher, HmacSha3};
+
+fn key(len: usize) -> Vec<u8> {
+    (0..len).map(|i| i as u8).collect()
+}
+
+fn test_hmac(hmac: HmacSha3, message: &[u8], expected: &[u8]) {
+    let mut output = vec![0u8; expected.len()];
+    hmac.clone().chain(message).finalize(&mut output);
+    assert_eq!(&output[..], expected);
+    assert!(hmac.chain(message).verify(expected));
+}
+
+#[test]
+fn hmac_sha3_224_key_short() {
+    let expected = b"\
+        \x33\x2c\xfd\x59\x34\x7f\xdb\x8e\x57\x6e\x77\x26\x0b\xe4\xab\xa2\
+        \xd6\xdc\x53\x11\x7b\x3b\xfb\x52\xc6\xd1\x8c\x04\
+    ";
+    test_hmac(
+        HmacSha3::v224(&key(28)),
+        b"Sample message for keylen<blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_224_key_block() {
+    let expected = b"\
+        \xd8\xb7\x33\xbc\xf6\x6c\x64\x4a\x12\x32\x3d\x56\x4e\x24\xdc\xf3\
+        \xfc\x75\xf2\x31\xf3\xb6\x79\x68\x35\x91\x00\xc7\
+    ";
+    test_hmac(
+        HmacSha3::v224(&key(144)),
+        b"Sample message for keylen=blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_224_key_long() {
+    let expected = b"\
+        \x07\x86\x95\xee\xcc\x22\x7c\x63\x6a\xd3\x1d\x06\x3a\x15\xdd\x05\
+        \xa7\xe8\x19\xa6\x6e\xc6\xd8\xde\x1e\x19\x3e\x59\
+    ";
+    test_hmac(
+        HmacSha3::v224(&key(172)),
+        b"Sample message for keylen>blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_256_key_short() {
+    let expected = b"\
+        \x4f\xe8\xe2\x02\xc4\xf0\x58\xe8\xdd\xdc\x23\xd8\xc3\x4e\x46\x73\
+        \x43\xe2\x35\x55\xe2\x4f\xc2\xf0\x25\xd5\x98\xf5\x58\xf6\x72\x05\
+    ";
+    test_hmac(
+        HmacSha3::v256(&key(32)),
+        b"Sample message for keylen<blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_256_key_block() {
+    let expected = b"\
+        \x68\xb9\x4e\x2e\x53\x8a\x9b\xe4\x10\x3b\xeb\xb5\xaa\x01\x6d\x47\
+        \x96\x1d\x4d\x1a\xa9\x06\x06\x13\x13\xb5\x57\xf8\xaf\x2c\x3f\xaa\
+    ";
+    test_hmac(
+        HmacSha3::v256(&key(136)),
+        b"Sample message for keylen=blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_256_key_long() {
+    let expected = b"\
+        \x48\x0d\x03\x02\xa2\x70\x31\x19\x25\xdd\x43\xb7\x5f\x25\x7b\x81\
+        \xd1\xdf\x6b\xfd\xb6\x48\xe2\xec\x5c\x61\xf1\x48\x98\x6a\x53\x86\
+    ";
+    test_hmac(
+        HmacSha3::v256(&key(164)),
+        b"Sample message for keylen>blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_384_key_short() {
+    let expected = b"\
+        \xd5\x88\xa3\xc5\x1f\x3f\x2d\x90\x6e\x82\x98\xc1\x19\x9a\xa8\xff\
+        \x62\x96\x21\x81\x27\xf6\xb3\x8a\x90\xb6\xaf\xe2\xc5\x61\x77\x25\
+        \xbc\x99\x98\x7f\x79\xb2\x2a\x55\x7b\x65\x20\xdb\x71\x0b\x7f\x42\
+    ";
+    test_hmac(
+        HmacSha3::v384(&key(48)),
+        b"Sample message for keylen<blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_384_key_block() {
+    let expected = b"\
+        \xa2\x7d\x24\xb5\x92\xe8\xc8\xcb\xf6\xd4\xce\x6f\xc5\xbf\x62\xd8\
+        \xfc\x98\xbf\x2d\x48\x66\x40\xd9\xeb\x80\x99\xe2\x40\x47\x83\x7f\
+        \x5f\x3b\xff\xbe\x92\xdc\xce\x90\xb4\xed\x5b\x1e\x7e\x44\xfa\x90\
+    ";
+    test_hmac(
+        HmacSha3::v384(&key(104)),
+        b"Sample message for keylen=blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_384_key_long() {
+    let expected = b"\
+        \xa0\xc9\x28\x73\xea\x6e\xcd\x9d\x43\x26\x53\x0b\xd6\xf5\x5c\xba\
+        \xe0\xdc\x9f\xf6\x92\xcf\x91\x9d\x0e\xdd\x79\xe0\x4f\xd4\xfa\xa5\
+        \x6c\xc4\x28\x69\x5c\x3e\x2c\x50\xe7\x43\x72\xd2\x2b\x5e\x06\x94\
+    ";
+    test_hmac(
+        HmacSha3::v384(&key(132)),
+        b"Sample message for keylen>blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_512_key_short() {
+    let expected = b"\
+        \x4e\xfd\x62\x9d\x6c\x71\xbf\x86\x16\x26\x58\xf2\x99\x43\xb1\xc3\
+        \x08\xce\x27\xcd\xfa\x6d\xb0\xd9\xc3\xce\x81\x76\x3f\x9c\xbc\xe5\
+        \xf7\xeb\xe9\x86\x80\x31\xdb\x1a\x8f\x8e\xb7\xb6\xb9\x5e\x5c\x5e\
+        \x3f\x65\x7a\x89\x96\xc8\x6a\x2f\x65\x27\xe3\x07\xf0\x21\x31\x96\
+    ";
+    test_hmac(
+        HmacSha3::v512(&key(64)),
+        b"Sample message for keylen<blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_512_key_block() {
+    let expected = b"\
+        \x54\x4e\x25\x7e\xa2\xa3\xe5\xea\x19\xa5\x90\xe6\xa2\x4b\x72\x4c\
+        \xe6\x32\x77\x57\x72\x3f\xe2\x75\x1b\x75\xbf\x00\x7d\x80\xf6\xb3\
+        \x60\x74\x4b\xf1\xb7\xa8\x8e\xa5\x85\xf9\x76\x5b\x47\x91\x19\x76\
+        \xd3\x19\x1c\xf8\x3c\x03\x9f\x5f\xfa\xb0\xd2\x9c\xc9\xd9\xb6\xda\
+    ";
+    test_hmac(
+        HmacSha3::v512(&key(72)),
+        b"Sample message for keylen=blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_512_key_long() {
+    let expected = b"\
+        \xb3\xac\x5a\x87\xdb\x1d\xde\xc6\x8c\x83\x25\xa9\x09\x6e\x69\x67\
+        \xd4\x9b\x9b\x5d\x8f\x78\xf7\xa5\x33\x17\x46\x95\xc1\xef\x4d\x46\
+        \x00\xf7\x4d\x29\xf7\x1e\xa4\x96\x71\xf3\xc9\x49\xb6\xde\x10\x11\
+        \x99\x45\x10\x95\xd5\x29\xfc\x8c\x9f\x15\x7c\xc7\xd3\x74\xe4\xc7\
+    ";
+    test_hmac(
+        HmacSha3::v512(&key(100)),
+        b"Sample message for keylen>blocklen",
+        expected,
+    );
+}
+
+#[test]
+fn hmac_sha3_verify_near_miss() {
+    let mut tag = [0u8; 32];
+    let hmac = HmacSha3::v256(b"key").chain(b"message");
+    hmac.clone().finalize(&mut tag);
+    assert!(hmac.clone().verify(&tag));
+    assert!(!hmac.clone().verify(&tag[..16]));
+
+    for i in 0..tag.len() {
+        let mut near_miss = tag;
+        near_miss[i] ^= 0x80;
+        assert!(!hmac.clone().verify(&near_miss));
+    }
+}
+
+#[test]
+fn hmac_sha3_finalize_truncated() {
+    let hmac = HmacSha3::v256(b"key").chain(b"message");
+    let mut tag = [0u8; 32];
+    hmac.clone().finalize(&mut tag);
+
+    let mut truncated = [0u8; 16];
+    hmac.finalize(&mut truncated);
+    assert_eq!(truncated, tag[..16]);
+}
+
+#[test]
+fn hmac_sha3_finalize_xor_truncated() {
+    let hmac = HmacSha3::v256(b"key").chain(b"message");
+    let mut tag = [0u8; 32];
+    hmac.clone().finalize(&mut tag);
+
+    let mut output = [0xa5u8; 16];
+    hmac.finalize_xor(&mut output);
+    for (byte, tag) in output.iter().zip(tag.iter()) {
+        assert_eq!(*byte, tag ^ 0xa5);
+    }
+}
@@ -0,0 +1,37 @@
+use tiny_keccak::{KeccakF, KeccakState, KeccakStateRef};
+
+// the lengths straddle the words and the blocks of every rate
+const LENGTHS: [usize; 10] = [0, 1, 7, 8, 9, 71, 72, 135, 136, 337];
+
+#[test]
+fn invariants_hold_through_every_operation() {
+    let input = [0xa5u8; 337];
+    let mut output = [0u8; 337];
+    for rate in [8, 72, 136, 168, 192] {
+        for absorbed in LENGTHS {
+            let mut state = KeccakState::<KeccakF>::with_rate(rate, 0x1f);
+            state.update(&input[..absorbed]);
+            state.update(&input[..absorbed % 9]);
+            let _ = state.clone().switch_rate(72);
+            state.clone().finalize(&mut output[..absorbed]);
+            state.clone().finalize_xor(&mut output[..absorbed]);
+            state.peek_digest(&mut output[..absorbed]);
+
+            state.pad();
+            for squeezed in LENGTHS {
+                state.squeeze(&mut output[..squeezed]);
+                state.squeeze_xor(&mut output[..squeezed]);
+            }
+            state.reset();
+            state.update_bits(&input, absorbed % 8 + 1);
+            state.squeeze(&mut output);
+
+            let mut words = [0u64; 25];
+            let mut borrowed = KeccakStateRef::<KeccakF>::new(&mut words, rate, 0x1f);
+            borrowed.update(&input[..absorbed]);
+            for squeezed in LENGTHS {
+                borrowed.squeeze(&mut output[..squeezed]);
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use tiny_keccak::{Hasher, KangarooTwelve};
+use tiny_keccak::{
+    k12_chunk_cv, keccakp, Hasher, IntoXof, KangarooTwelve, KeccakP, KeccakState, Xof,
+};
 
 fn pattern(len: usize) -> Vec<u8> {
     (0..len).map(|j| (j % 251) as u8).collect()
@@ -26,6 +28,27 @@ fn empty_kangaroo_twelve() {
     test_kangaroo_twelve("", "", 32, expected);
 }
 
+// with a Keccak hasher enabled every sponge once ran the 24 rounds of keccak-f[1600], K12 included
+#[cfg(feature = "keccak")]
+#[test]
+fn kangaroo_twelve_next_to_keccak() {
+    let mut keccak = [0u8; 32];
+    tiny_keccak::Keccak::v256().finalize(&mut keccak);
+    let expected = b"\
+        \xc5\xd2\x46\x01\x86\xf7\x23\x3c\x92\x7e\x7d\xb2\xdc\xc7\x03\xc0\
+        \xe5\x00\xb6\x53\xca\x82\x27\x3b\x7b\xfa\xd8\x04\x5d\x85\xa4\x70\
+    ";
+    assert_eq!(&keccak, expected);
+
+    let mut k12 = [0u8; 32];
+    KangarooTwelve::new(b"").finalize(&mut k12);
+    let expected = b"\
+        \x1a\xc2\xd4\x50\xfc\x3b\x42\x05\xd1\x9d\xa7\xbf\xca\x1b\x37\x51\
+        \x3c\x08\x03\x57\x7a\xc7\x16\x7f\x06\xfe\x2c\xe1\xf0\xef\x39\xe5\
+    ";
+    assert_eq!(&k12, expected);
+}
+
 #[test]
 fn kangaroo_twelve_long() {
     let expected = b"\
@@ -53,6 +76,61 @@ fn kangaroo_twelve_with_message2() {
     test_kangaroo_twelve("", pattern(17), 32, expected);
 }
 
+#[test]
+fn kangaroo_twelve_with_message_17_pow_2() {
+    let expected = b"\
+        \x0c\x31\x5e\xbc\xde\xdb\xf6\x14\x26\xde\x7d\xcf\x8f\xb7\x25\xd1\
+        \xe7\x46\x75\xd7\xf5\x32\x7a\x50\x67\xf3\x67\xb1\x08\xec\xb6\x7c\
+    ";
+    test_kangaroo_twelve("", pattern(289), 32, expected);
+}
+
+#[test]
+fn kangaroo_twelve_with_message_17_pow_3() {
+    let expected = b"\
+        \xcb\x55\x2e\x2e\xc7\x7d\x99\x10\x70\x1d\x57\x8b\x45\x7d\xdf\x77\
+        \x2c\x12\xe3\x22\xe4\xee\x7f\xe4\x17\xf9\x2c\x75\x8f\x0d\x59\xd0\
+    ";
+    test_kangaroo_twelve("", pattern(4913), 32, expected);
+}
+
+#[test]
+fn kangaroo_twelve_with_message_17_pow_4() {
+    let expected = b"\
+        \x87\x01\x04\x5e\x22\x20\x53\x45\xff\x4d\xda\x05\x55\x5c\xbb\x5c\
+        \x3a\xf1\xa7\x71\xc2\xb8\x9b\xae\xf3\x7d\xb4\x3d\x99\x98\xb9\xfe\
+    ";
+    test_kangaroo_twelve("", pattern(83521), 32, expected);
+}
+
+#[test]
+fn kangaroo_twelve_with_message_17_pow_5() {
+    let expected = b"\
+        \x84\x4d\x61\x09\x33\xb1\xb9\x96\x3c\xbd\xeb\x5a\xe3\xb6\xb0\x5c\
+        \xc7\xcb\xd6\x7c\xee\xdf\x88\x3e\xb6\x78\xa0\xa8\xe0\x37\x16\x82\
+    ";
+    test_kangaroo_twelve("", pattern(1419857), 32, expected);
+}
+
+#[test]
+fn kangaroo_twelve_incremental_update() {
+    let message = pattern(83521);
+    let mut expected = [0u8; 32];
+    let mut kangaroo = KangarooTwelve::new(b"");
+    kangaroo.update(&message);
+    kangaroo.finalize(&mut expected);
+
+    for &chunk_size in &[1000, 8191, 8192, 8193] {
+        let mut output = [0u8; 32];
+        let mut kangaroo = KangarooTwelve::new(b"");
+        for chunk in message.chunks(chunk_size) {
+            kangaroo.update(chunk);
+        }
+        kangaroo.finalize(&mut output);
+        assert_eq!(expected, output);
+    }
+}
+
 #[test]
 fn kangaroo_twelve_with_custom_string() {
     let expected = b"\
@@ -68,7 +146,7 @@ fn kangaroo_twelve_with_custom_string_and_message() {
         \xd8\x48\xc5\x06\x8c\xed\x73\x6f\x44\x62\x15\x9b\x98\x67\xfd\x4c\
         \x20\xb8\x08\xac\xc3\xd5\xbc\x48\xe0\xb0\x6b\xa0\xa3\x76\x2e\xc4\
     ";
-    test_kangaroo_twelve(pattern(41), &[0xff], 32, expected);
+    test_kangaroo_twelve(pattern(41), [0xff], 32, expected);
 }
 
 #[test]
@@ -79,8 +157,122 @@ fn kangaroo_twelve_with_custom_string_and_message2() {
     ";
     test_kangaroo_twelve(
         pattern(68921),
-        &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
         32,
         expected,
     );
 }
+
+#[test]
+fn keccakp_zero_state() {
+    let mut state = [0u64; 25];
+    let expected = [
+        0x8e5e5438b9a78617,
+        0xd9cd6a50f259d01e,
+        0x87b8e7c652a91f35,
+        0x1093e067cde4e0c5,
+        0xb033ab90f2d95a45,
+        0xe0a72f72a8dd1a45,
+        0xc53780aa14672f9c,
+        0x3edd47f50051071d,
+        0xb3a31d310c178acc,
+        0x79b586a59257aaa0,
+        0xbc4a7c3db3b1f99b,
+        0x68874063e68a6793,
+        0x5c6c03332e0e2566,
+        0x9caa1202b9f030da,
+        0x5f3b9a782bcf7a9f,
+        0xe536c1e061ae7923,
+        0x6de9b618b73c87ec,
+        0x2abed1f170918ac2,
+        0x6aabbd53daed24b7,
+        0xbfc1416a2c2ee15a,
+        0xc6cfe036b90952af,
+        0x45503617dc7060d7,
+        0x625611b2c29f7ae4,
+        0xd43671db2c30647a,
+        0xcffd0d76222ca01c,
+    ];
+
+    keccakp(&mut state);
+    assert_eq!(expected, state);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn update_rayon_matches_update() {
+    let input = pattern(16 * 1024 * 1024 + 123);
+
+    let mut expected = [0u8; 64];
+    let mut k12 = KangarooTwelve::new(b"custom");
+    k12.update(&input);
+    k12.finalize(&mut expected);
+
+    let mut output = [0u8; 64];
+    let mut k12 = KangarooTwelve::new(b"custom");
+    k12.update_rayon(&input);
+    k12.finalize(&mut output);
+    assert_eq!(expected, output);
+
+    // starting inside the first chunk, at chunk boundaries and with short inputs
+    for &(first, len) in &[
+        (1, 40000),
+        (8192, 3 * 8192),
+        (8192, 8192 + 1),
+        (100, 5),
+        (0, 0),
+    ] {
+        let input = &input[..first + len];
+
+        let mut expected = [0u8; 32];
+        let mut k12 = KangarooTwelve::new(b"");
+        k12.update(input);
+        k12.finalize(&mut expected);
+
+        let mut output = [0u8; 32];
+        let mut k12 = KangarooTwelve::new(b"");
+        k12.update(&input[..first]);
+        k12.update_rayon(&input[first..]);
+        k12.finalize(&mut output);
+        assert_eq!(expected, output, "{} then {} bytes", first, len);
+    }
+}
+
+#[test]
+fn k12_chunk_cv_matches_the_tree() {
+    // two chunks, the second one ends with the empty customization string and its length 0
+    let message = pattern(8192 + 100);
+    let mut second = message[8192..].to_vec();
+    second.push(0);
+
+    let mut node = KeccakState::<KeccakP<12>>::with_rate(168, 0x06);
+    node.update(&message[..8192]);
+    node.update(&[0x03, 0, 0, 0, 0, 0, 0, 0]);
+    node.update(&k12_chunk_cv(&second));
+    node.update(&[0x01, 0x01, 0xff, 0xff]);
+    let mut expected = [0u8; 32];
+    node.finalize(&mut expected);
+
+    let mut k12 = KangarooTwelve::new(b"");
+    k12.update(&message);
+    let mut output = [0u8; 32];
+    k12.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn kangaroo_twelve_xof_into_state() {
+    let mut drained = [0u8; 300];
+    let mut xof = KangarooTwelve::new(b"custom");
+    xof.update(b"message");
+    let mut xof = xof.into_xof();
+    let mut expected = [0u8; 332];
+    xof.clone().squeeze(&mut expected);
+    xof.squeeze(&mut drained);
+
+    // the state keeps squeezing where the reader stopped
+    let mut state = xof.into_state();
+    let mut next = [0u8; 32];
+    state.squeeze(&mut next);
+    assert_eq!(expected[300..], next);
+}
@@ -0,0 +1,166 @@
+//! Runs the known answer tests bundled in `tests/kat`, files in the `.rsp` format of the NIST
+//! CAVP test vectors for `FIPS-202`, see `tests/kat/README.md` for where they come from.
+
+use std::collections::HashMap;
+use tiny_keccak::{Hasher, Sha3, Shake, Xof};
+
+/// A known answer test, the fields of one record of an `.rsp` file.
+struct Vector {
+    fields: HashMap<String, String>,
+}
+
+impl Vector {
+    fn hex(&self, key: &str) -> Vec<u8> {
+        let value = &self.fields[key];
+        assert_eq!(value.len() & 1, 0, "odd length hex in {} = {}", key, value);
+        (0..value.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&value[i..i + 2], 16).expect("invalid hex"))
+            .collect()
+    }
+
+    fn bits(&self, key: &str) -> Option<usize> {
+        self.fields
+            .get(key)
+            .map(|value| value.parse().expect("invalid length"))
+    }
+
+    /// The message and its length in bits, `Len = 0` comes with a placeholder `Msg = 00`. A
+    /// bit-oriented message ends with a partial byte.
+    fn msg(&self) -> (Vec<u8>, usize) {
+        let msg = self.hex("Msg");
+        match self.bits("Len") {
+            Some(0) => (Vec::new(), 0),
+            Some(len) => {
+                assert_eq!(
+                    msg.len(),
+                    len.div_ceil(8),
+                    "Msg doesn't match Len = {}",
+                    len
+                );
+                (msg, len)
+            }
+            None => {
+                let len = msg.len() * 8;
+                (msg, len)
+            }
+        }
+    }
+}
+
+/// Splits an `.rsp` file into its records, skipping comments and the `[...]` section headers.
+/// A record ends with its `MD` or `Output` field.
+fn parse(rsp: &str) -> Vec<Vector> {
+    let mut vectors = Vec::new();
+    let mut fields = HashMap::new();
+    for line in rsp.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+            continue;
+        }
+
+        let (key, value) = line.split_at(line.find('=').expect("expected `key = value`"));
+        let key = key.trim();
+        fields.insert(key.to_owned(), value[1..].trim().to_owned());
+        if key == "MD" || key == "Output" {
+            vectors.push(Vector {
+                fields: std::mem::take(&mut fields),
+            });
+        }
+    }
+    assert!(fields.is_empty(), "unterminated record");
+    vectors
+}
+
+macro_rules! kat {
+    ($file: expr) => {
+        ($file, include_str!(concat!("kat/", $file)))
+    };
+}
+
+#[test]
+fn sha3_kat() {
+    let files = [
+        (224, kat!("sha3_224_short.rsp")),
+        (224, kat!("sha3_224_long.rsp")),
+        (224, kat!("sha3_224_bits.rsp")),
+        (256, kat!("sha3_256_short.rsp")),
+        (256, kat!("sha3_256_long.rsp")),
+        (256, kat!("sha3_256_bits.rsp")),
+        (384, kat!("sha3_384_short.rsp")),
+        (384, kat!("sha3_384_long.rsp")),
+        (384, kat!("sha3_384_bits.rsp")),
+        (512, kat!("sha3_512_short.rsp")),
+        (512, kat!("sha3_512_long.rsp")),
+        (512, kat!("sha3_512_bits.rsp")),
+    ];
+
+    for (bits, (file, rsp)) in files.iter() {
+        let vectors = parse(rsp);
+        assert!(!vectors.is_empty(), "{} holds no vectors", file);
+
+        for (i, vector) in vectors.iter().enumerate() {
+            let expected = vector.hex("MD");
+            assert_eq!(expected.len() * 8, *bits, "{} #{}: MD length", file, i);
+
+            let mut sha3 = match bits {
+                224 => Sha3::v224(),
+                256 => Sha3::v256(),
+                384 => Sha3::v384(),
+                _ => Sha3::v512(),
+            };
+            let (msg, len) = vector.msg();
+            sha3.update_bits(&msg, len);
+            let mut output = vec![0u8; expected.len()];
+            sha3.finalize(&mut output);
+            assert_eq!(
+                expected,
+                output,
+                "{} #{}: Len = {:?}",
+                file,
+                i,
+                vector.bits("Len")
+            );
+        }
+    }
+}
+
+#[test]
+fn shake_kat() {
+    let files = [
+        (128, kat!("shake128_short.rsp")),
+        (128, kat!("shake128_variable_out.rsp")),
+        (128, kat!("shake128_bits.rsp")),
+        (256, kat!("shake256_short.rsp")),
+        (256, kat!("shake256_variable_out.rsp")),
+        (256, kat!("shake256_bits.rsp")),
+    ];
+
+    for (bits, (file, rsp)) in files.iter() {
+        let vectors = parse(rsp);
+        assert!(!vectors.is_empty(), "{} holds no vectors", file);
+
+        for (i, vector) in vectors.iter().enumerate() {
+            let expected = vector.hex("Output");
+            if let Some(len) = vector.bits("Outputlen") {
+                assert_eq!(expected.len() * 8, len, "{} #{}: Output length", file, i);
+            }
+
+            let mut shake = match bits {
+                128 => Shake::v128(),
+                _ => Shake::v256(),
+            };
+            let (msg, len) = vector.msg();
+            shake.update_bits(&msg, len);
+            let mut output = vec![0u8; expected.len()];
+            shake.squeeze(&mut output);
+            assert_eq!(
+                expected,
+                output,
+                "{} #{}: Len = {:?}",
+                file,
+                i,
+                vector.bits("Len")
+            );
+        }
+    }
+}
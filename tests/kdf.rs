@@ -0,0 +1,84 @@
+use tiny_keccak::KeccakKdf;
+
+const MASTER_KEY: &[u8] = b"a 32 byte master key for the kdf";
+
+#[test]
+fn kdf_labels_are_independent() {
+    let kdf = KeccakKdf::new(MASTER_KEY);
+    let mut encryption = [0u8; 32];
+    let mut mac = [0u8; 32];
+    kdf.derive_key(b"encryption", &mut encryption);
+    kdf.derive_key(b"mac", &mut mac);
+    assert_ne!(encryption, mac);
+
+    // the same label is reproducible, from the same or a new instance
+    let mut again = [0u8; 32];
+    kdf.derive_key(b"encryption", &mut again);
+    assert_eq!(encryption, again);
+    KeccakKdf::new(MASTER_KEY).derive_key(b"encryption", &mut again);
+    assert_eq!(encryption, again);
+
+    // the output length is absorbed too, a shorter key isn't a prefix of a longer one
+    let mut short = [0u8; 16];
+    kdf.derive_key(b"encryption", &mut short);
+    assert_ne!(encryption[..16], short);
+
+    // the label boundary is encoded
+    let mut split = [0u8; 32];
+    KeccakKdf::new(b"a 32 byte master key for the").derive_key(b" kdfencryption", &mut split);
+    assert_ne!(encryption, split);
+}
+
+#[test]
+fn kdf_known_answer() {
+    // cSHAKE256 customized with `KeccakKdf` of
+    // `encode_string(MASTER_KEY) || encode_string("label") || right_encode(512)`, computed with
+    // an implementation independent of this crate
+    let expected = b"\
+        \x83\xDF\xBF\x1B\xEA\x87\x24\xF7\x70\xFA\xBF\xF6\xE4\xCC\x32\x97\
+        \xC6\xB6\x26\x91\x18\xF0\x14\x2D\x72\x7C\x4C\x8C\xF0\x4C\x35\x5A\
+        \xCB\x9F\x86\x2F\x60\x8B\x0F\xA2\x0F\xA4\xCC\x6E\xBF\x5D\xF1\x20\
+        \xBB\xA4\xAD\x3C\x6C\xE3\xBE\xA7\x50\xE6\x22\x81\x1D\xD3\x39\xD5\
+    ";
+
+    let mut output = [0u8; 64];
+    KeccakKdf::new(MASTER_KEY).derive_key(b"label", &mut output);
+    assert_eq!(expected[..], output[..]);
+}
+
+#[test]
+fn kdf_extract_then_expand() {
+    let prk = KeccakKdf::extract(b"salt", b"input keying material");
+    assert_eq!(prk, KeccakKdf::extract(b"salt", b"input keying material"));
+    assert_ne!(prk, KeccakKdf::extract(b"", b"input keying material"));
+    assert_ne!(prk, KeccakKdf::extract(b"saltinput", b" keying material"));
+
+    // cSHAKE256 customized with `KeccakKdf extract` of
+    // `encode_string("salt") || encode_string("input keying material")`, computed with an
+    // implementation independent of this crate
+    let expected = b"\
+        \x51\x46\xCE\x2B\x8B\xD4\x47\xEC\xB1\x17\x77\x4A\x56\xC8\x38\x8F\
+        \xFD\xCA\x19\x6D\x26\x63\xF7\xF5\x3A\xDD\xD1\x94\x45\xFD\x03\xF4\
+        \xE0\x9E\xC4\xD5\x2C\x35\x52\xF1\xB8\xE5\xED\x01\xC5\x40\xEA\x0A\
+        \x61\x93\xD3\x81\xB7\xBB\xFC\x5B\x93\x69\x98\xA2\x1F\x81\xC2\xE5\
+    ";
+    assert_eq!(prk[..], expected[..]);
+
+    // the same inputs reproduce the output, a different info is independent
+    let mut okm = [0u8; 100];
+    let mut again = [0u8; 100];
+    let mut other = [0u8; 100];
+    KeccakKdf::expand(&prk, b"info", &mut okm);
+    KeccakKdf::expand(&prk, b"info", &mut again);
+    KeccakKdf::expand(&prk, b"other info", &mut other);
+    assert_eq!(okm[..], again[..]);
+    assert_ne!(okm[..], other[..]);
+
+    let mut derived = [0u8; 100];
+    KeccakKdf::new(&prk).derive_key(b"info", &mut derived);
+    assert_eq!(okm[..], derived[..]);
+
+    // an empty output is a no-op
+    let mut empty = [0u8; 0];
+    KeccakKdf::expand(&prk, b"info", &mut empty);
+}
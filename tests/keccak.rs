@@ -1,4 +1,16 @@
-use tiny_keccak::{Hasher, Keccak};
+use std::mem::MaybeUninit;
+
+use tiny_keccak::{
+    bits_to_rate, is_valid_rate, keccak224, keccak256, keccak256_32, keccak256_64, keccak256_const,
+    keccak256_pair, keccak256_x4, keccak384, keccak512, keccakf, keccakf_const, permute,
+    permute_slice, rate_to_capacity_bits, Domain, HashSink, Hasher, HasherExt, IntoXof, Keccak,
+    Keccak224, Keccak256, Keccak384, Keccak512, KeccakBuilder, KeccakError, KeccakF, KeccakP,
+    KeccakState, Pad10Star1, Padding, Permutation, Xof, CHECKPOINT_LEN,
+};
+
+mod common;
+
+use common::Collect;
 
 #[test]
 fn empty_keccak() {
@@ -13,6 +25,62 @@ fn empty_keccak() {
     assert_eq!(expected, &output);
 }
 
+#[test]
+fn empty_keccak_224() {
+    let keccak = Keccak::v224();
+    let mut output = [0; 28];
+    let expected = b"\
+        \xf7\x18\x37\x50\x2b\xa8\xe1\x08\x37\xbd\xd8\xd3\x65\xad\xb8\x55\
+        \x91\x89\x56\x02\xfc\x55\x2b\x48\xb7\x39\x0a\xbd\
+    ";
+
+    keccak.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn empty_keccak_384() {
+    let keccak = Keccak::v384();
+    let mut output = [0; 48];
+    let expected = b"\
+        \x2c\x23\x14\x6a\x63\xa2\x9a\xcf\x99\xe7\x3b\x88\xf8\xc2\x4e\xaa\
+        \x7d\xc6\x0a\xa7\x71\x78\x0c\xcc\x00\x6a\xfb\xfa\x8f\xe2\x47\x9b\
+        \x2d\xd2\xb2\x13\x62\x33\x74\x41\xac\x12\xb5\x15\x91\x19\x57\xff\
+    ";
+
+    keccak.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn empty_keccak_512() {
+    let keccak = Keccak::v512();
+    let mut output = [0; 64];
+    let expected = b"\
+        \x0e\xab\x42\xde\x4c\x3c\xeb\x92\x35\xfc\x91\xac\xff\xe7\x46\xb2\
+        \x9c\x29\xa8\xc3\x66\xb7\xc6\x0e\x4e\x67\xc4\x66\xf3\x6a\x43\x04\
+        \xc0\x0f\xa9\xca\xf9\xd8\x79\x76\xba\x46\x9b\xcb\xe0\x67\x13\xb4\
+        \x35\xf0\x91\xef\x27\x69\xfb\x16\x0c\xda\xb3\x3d\x36\x70\x68\x0e\
+    ";
+
+    keccak.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn abc_keccak_256() {
+    let mut keccak = Keccak::v256();
+    let mut output = [0; 32];
+    let expected = b"\
+        \x4e\x03\x65\x7a\xea\x45\xa9\x4f\xc7\xd4\x7b\xa8\x26\xc8\xd6\x67\
+        \xc0\xd1\xe6\xe3\x3a\x64\xa0\x36\xec\x44\xf5\x8f\xa1\x2d\x6c\x45\
+    ";
+
+    keccak.update(b"abc");
+    keccak.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
 #[test]
 fn string_keccak_256() {
     let mut keccak = Keccak::v256();
@@ -28,3 +96,2486 @@ fn string_keccak_256() {
     keccak.finalize(&mut in_and_out);
     assert_eq!(expected, &in_and_out);
 }
+
+#[test]
+fn keccak_xof_matches_finalize() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"hello world");
+    let mut expected = [0u8; 32];
+    keccak.clone().finalize(&mut expected);
+
+    let mut output = [0u8; 32];
+    let mut xof = keccak.into_xof();
+    xof.squeeze(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn keccak_xof_squeeze_in_parts() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"hello world");
+
+    let mut expected = [0u8; 40];
+    keccak.clone().into_xof().squeeze(&mut expected);
+
+    let mut output = [0u8; 40];
+    let mut xof = keccak.clone().into_xof();
+    xof.squeeze(&mut output[..20]);
+    xof.squeeze(&mut output[20..]);
+    assert_eq!(expected, output);
+
+    // 7 bytes at a time crosses the 136 bytes rate block boundary unaligned
+    let mut expected = [0u8; 497];
+    keccak.clone().into_xof().squeeze(&mut expected);
+
+    let mut output = [0u8; 497];
+    let mut xof = keccak.into_xof();
+    for chunk in output.chunks_mut(7) {
+        xof.squeeze(chunk);
+    }
+    assert_eq!(&expected[..], &output[..]);
+}
+
+#[test]
+fn keccak_reset() {
+    let mut expected = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(b"abc");
+    keccak.clone().finalize(&mut expected);
+
+    keccak.reset();
+    keccak.update(b"abc");
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn keccak_finalize_reset() {
+    let mut keccak = Keccak::v256();
+    for message in [&b"hello"[..], &[0xa3; 200][..], b""].iter() {
+        let mut expected = [0u8; 32];
+        let mut fresh = Keccak::v256();
+        fresh.update(message);
+        fresh.finalize(&mut expected);
+
+        let mut output = [0u8; 32];
+        keccak.update(message);
+        keccak.finalize_reset(&mut output);
+        assert_eq!(expected, output);
+    }
+}
+
+#[test]
+fn keccak_finalize_into() {
+    let mut keccak = Keccak::v256();
+    let mut absorbed = Vec::new();
+    for message in [&b"hello"[..], &[0xa3; 200][..]].iter() {
+        keccak.update(message);
+        absorbed.extend_from_slice(message);
+
+        let mut expected = [0u8; 32];
+        let mut fresh = Keccak::v256();
+        fresh.update(&absorbed);
+        fresh.finalize(&mut expected);
+
+        let mut output = [0u8; 32];
+        keccak.finalize_into(&mut output);
+        assert_eq!(expected, output);
+
+        // the state is untouched, so the snapshot can be taken again
+        keccak.state.finalize_into(&mut output);
+        assert_eq!(expected, output);
+    }
+}
+
+#[test]
+fn keccak_finalize_array() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"abc");
+    let mut expected = [0u8; 32];
+    keccak.clone().finalize(&mut expected);
+
+    let output: [u8; 32] = keccak.finalize_array();
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn keccak_finalize_uninit() {
+    let mut output = [MaybeUninit::uninit(); 32];
+    let digest = Keccak::v256().chain(b"abc").finalize_uninit(&mut output);
+    assert_eq!(digest, keccak256(b"abc"));
+
+    // outputs across blocks, from an absorbing or a squeezing state
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    state.update(&[0x5a; 300]);
+    let mut expected = [0u8; 600];
+    state.clone().finalize(&mut expected);
+    for &len in &[0, 1, 136, 137, 600] {
+        let mut output = [MaybeUninit::uninit(); 600];
+        let output = state.clone().finalize_uninit(&mut output[..len]);
+        assert_eq!(output, &expected[..len], "{} bytes", len);
+    }
+
+    let mut squeezing = state.clone();
+    squeezing.squeeze(&mut [0u8; 7]);
+    let mut output = [MaybeUninit::uninit(); 100];
+    assert_eq!(squeezing.finalize_uninit(&mut output), &expected[7..107]);
+
+    // hashers without a sponge of their own zero the output before finalizing it
+    let mut output = [MaybeUninit::new(0xff); 16];
+    assert_eq!(Collect::default().finalize_uninit(&mut output), [0u8; 16]);
+}
+
+#[test]
+fn keccak_finalize_xor() {
+    let mut output = [0u8; 32];
+    Keccak::v256().chain(b"abc").finalize_xor(&mut output);
+    assert_eq!(output, keccak256(b"abc"));
+
+    // outputs across blocks, XORed into nonzero data
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    state.update(&[0x5a; 300]);
+    let mut expected = [0u8; 600];
+    state.clone().finalize(&mut expected);
+    let data: Vec<u8> = (0..600).map(|i| (i * 7) as u8).collect();
+    for &len in &[0, 1, 136, 137, 600] {
+        let mut output = data[..len].to_vec();
+        state.clone().finalize_xor(&mut output);
+        for i in 0..len {
+            assert_eq!(output[i], data[i] ^ expected[i], "{} bytes", len);
+        }
+    }
+
+    // squeezing goes on from the current offset
+    let mut squeezing = state.clone();
+    squeezing.squeeze(&mut [0u8; 7]);
+    let mut output = [0xffu8; 100];
+    squeezing.squeeze_xor(&mut output[..50]);
+    squeezing.finalize_xor(&mut output[50..]);
+    for i in 0..100 {
+        assert_eq!(output[i], !expected[7 + i]);
+    }
+
+    // hashers without a sponge of their own XOR a digest finalized on the stack
+    let mut output = [0xa5u8; 16];
+    Collect::default().finalize_xor(&mut output);
+    assert_eq!(output, [0xa5u8; 16]);
+}
+
+#[test]
+fn keccak_take_and_replace() {
+    // one slot reused for every message, each digest independent of the previous messages
+    let mut slot = Keccak::v256();
+    let messages: [&[u8]; 4] = [b"", b"abc", &[0x5a; 300], b"abc"];
+    for message in messages.iter() {
+        slot.update(message);
+        let mut output = [0u8; 32];
+        slot.take().finalize(&mut output);
+        assert_eq!(output, keccak256(message));
+    }
+
+    // the slot keeps its variant
+    let mut slot = Keccak::v512();
+    slot.update(b"abc");
+    let taken = slot.take();
+    let mut output = [0u8; 64];
+    taken.finalize(&mut output);
+    assert_eq!(output, keccak512(b"abc"));
+    slot.finalize(&mut output);
+    assert_eq!(output, keccak512(b""));
+
+    let mut slot = Keccak::v256().chain(b"abc");
+    let old = slot.replace(Keccak::v384());
+    assert_eq!(old.finalize_array::<32>(), keccak256(b"abc"));
+    assert_eq!(slot.finalize_array::<48>(), keccak384(b""));
+}
+
+#[test]
+fn keccak256_x4_matches_serial() {
+    let data = [0xa5u8; 1000];
+    let lengths = [
+        [0, 0, 0, 0],
+        [64, 64, 64, 64],
+        [0, 135, 136, 137],
+        [1000, 3, 272, 271],
+    ];
+
+    for lengths in lengths.iter() {
+        let inputs = [
+            &data[..lengths[0]],
+            &data[..lengths[1]],
+            &data[..lengths[2]],
+            &data[..lengths[3]],
+        ];
+        let outputs = keccak256_x4(inputs);
+
+        for (input, output) in inputs.iter().zip(outputs.iter()) {
+            let mut keccak = Keccak::v256();
+            keccak.update(input);
+            let mut expected = [0u8; 32];
+            keccak.finalize(&mut expected);
+            assert_eq!(&expected, output);
+        }
+    }
+}
+
+#[test]
+fn keccak_core_hasher() {
+    use std::hash::Hasher as _;
+
+    let mut keccak = Keccak::v256();
+    keccak.write(b"abc");
+    let mut digest = [0u8; 32];
+    keccak.clone().finalize(&mut digest);
+    let mut expected = [0u8; 8];
+    expected.copy_from_slice(&digest[..8]);
+    assert_eq!(keccak.finish(), u64::from_le_bytes(expected));
+    // finish doesn't consume the state
+    assert_eq!(keccak.finish(), u64::from_le_bytes(expected));
+}
+
+#[test]
+fn keccak_hash_map() {
+    use std::collections::HashMap;
+    use tiny_keccak::KeccakBuildHasher;
+
+    let mut map = HashMap::with_hasher(KeccakBuildHasher);
+    for i in 0..1000u32 {
+        map.insert(i, i * 2);
+    }
+    for i in 0..1000u32 {
+        assert_eq!(map.get(&i), Some(&(i * 2)));
+    }
+    assert_eq!(map.get(&1000), None);
+}
+
+#[test]
+fn keccak_state_parameters() {
+    let levels = [
+        (Keccak::v224(), 144, 448),
+        (Keccak::v256(), 136, 512),
+        (Keccak::v384(), 104, 768),
+        (Keccak::v512(), 72, 1024),
+    ];
+
+    for (keccak, rate, capacity_bits) in levels.iter() {
+        assert_eq!(keccak.state.rate(), *rate);
+        assert_eq!(keccak.state.capacity_bits(), *capacity_bits);
+        assert_eq!(keccak.state.delim(), 0x01);
+    }
+}
+
+#[test]
+fn keccak_default_is_v256() {
+    let mut expected = [0u8; 32];
+    Keccak::v256().chain(b"abc").finalize(&mut expected);
+    let mut output = [0u8; 32];
+    Keccak::default().chain(b"abc").finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn keccak_state_domains() {
+    let domains = [
+        (
+            Domain::Keccak,
+            0x01,
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+        ),
+        (
+            Domain::Sha3,
+            0x06,
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+        ),
+        (
+            Domain::Shake,
+            0x1f,
+            "483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37e78b5739",
+        ),
+        (
+            Domain::CShake,
+            0x04,
+            "f4e4a2d747910716f38c8ec58a5a50f6b0ea4ebd1e4c92a19e9b36ae640580f1",
+        ),
+        (
+            Domain::Custom(0x0b),
+            0x0b,
+            "e905aa909dd92936ce668507e1b9987cfd8c5215d79ec556d663832926efc572",
+        ),
+    ];
+    for &(domain, delim, expected) in domains.iter() {
+        assert_eq!(domain.delim(), delim);
+        assert_eq!(u8::from(domain), delim);
+        assert_eq!(Domain::from(delim), domain);
+
+        let mut state = KeccakState::<KeccakF>::with_domain(bits_to_rate(256), domain);
+        assert_eq!(state.delim(), delim);
+        assert_eq!(state.domain(), domain);
+        state.update(b"abc");
+        let mut output = [0u8; 32];
+        state.finalize(&mut output);
+        let output: String = output.iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_eq!(output, expected, "{:?}", domain);
+    }
+
+    // a custom byte matching a named domain is that domain
+    assert_eq!(Domain::from_delim(0x06), Domain::Sha3);
+    assert_eq!(Domain::Custom(0x06).delim(), Domain::Sha3.delim());
+    assert_eq!(Domain::Custom(0x06), Domain::Sha3);
+    assert_ne!(Domain::Custom(0x0b), Domain::Sha3);
+}
+
+#[test]
+fn keccak_state_from_parts() {
+    let message = [0xc3u8; 700];
+    let mut expected = [0u8; 32];
+    Keccak::v256().chain(&message).finalize(&mut expected);
+
+    let mut keccak = Keccak::v256();
+    keccak.update(&message[..333]);
+    let words = *keccak.state.as_words();
+    let offset = keccak.state.offset();
+    let rate = keccak.state.rate();
+    let delim = keccak.state.delim();
+    // the state only implements `Drop` with the `zeroize` feature
+    #[allow(clippy::drop_non_drop)]
+    drop(keccak);
+
+    let mut keccak = Keccak {
+        state: KeccakState::from_parts(words, offset, rate, delim),
+    };
+    keccak.update(&message[333..]);
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+#[should_panic(expected = "offset cannot exceed the rate")]
+fn keccak_state_from_parts_invalid_offset() {
+    KeccakState::<KeccakF>::from_parts([0; 25], 137, 136, 0x01);
+}
+
+#[test]
+fn keccak_state_finalize_block_boundaries() {
+    let input = [0x5au8; 300];
+    for &bits in &[224, 256, 384, 512] {
+        let rate = bits_to_rate(bits);
+        let mut state = KeccakState::<KeccakF>::with_rate(rate, 0x01);
+        state.update(&input[..bits / 3]);
+
+        let mut expected = [0u8; 600];
+        state.clone().squeeze(&mut expected);
+
+        // the digest itself, the whole first block, and outputs crossing into the next blocks
+        for &len in &[bits / 8, rate - 1, rate, rate + 1, 2 * rate + 5] {
+            let mut output = [0u8; 600];
+            state.clone().finalize(&mut output[..len]);
+            assert_eq!(
+                output[..len],
+                expected[..len],
+                "{} bits, {} bytes",
+                bits,
+                len
+            );
+        }
+
+        // a squeezing state continues the stream rather than taking the single block path
+        let mut squeezing = state.clone();
+        let mut first = [0u8; 7];
+        squeezing.squeeze(&mut first);
+        let mut output = [0u8; 32];
+        squeezing.finalize(&mut output);
+        assert_eq!(output, expected[7..39]);
+    }
+
+    let mut output = [0u8; 32];
+    Keccak::v256().chain(b"abc").finalize(&mut output);
+    assert_eq!(output, keccak256(b"abc"));
+}
+
+#[test]
+fn keccak_state_reduced_rounds() {
+    let mut full = KeccakState::<KeccakP<24>>::with_rate(bits_to_rate(256), 0x01);
+    full.update(b"abc");
+    let mut output = [0u8; 32];
+    full.finalize(&mut output);
+    assert_eq!(output, keccak256(b"abc"));
+
+    // a single round maps the zero state to the last round constant, the first one is 0x01
+    let mut state = [0u64; 25];
+    KeccakP::<1>::execute(&mut state);
+    assert_eq!(state[0], 0x8000000080008008);
+    assert!(state[1..].iter().all(|&word| word == 0));
+
+    let mut six = KeccakState::<KeccakP<6>>::with_rate(bits_to_rate(256), 0x01);
+    six.update(b"abc");
+    six.finalize(&mut output);
+    assert_ne!(output, keccak256(b"abc"));
+}
+
+#[test]
+fn keccak_large_single_update() {
+    let input: Vec<u8> = (0..10 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+    // starting at a block boundary and in the middle of a block
+    for &prefix in &[0, 3] {
+        let mut expected = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(&input[..prefix]);
+        for chunk in input[prefix..].chunks(4096) {
+            keccak.update(chunk);
+        }
+        keccak.finalize(&mut expected);
+
+        let mut output = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(&input[..prefix]);
+        keccak.update(&input[prefix..]);
+        keccak.finalize(&mut output);
+        assert_eq!(output, expected, "prefix of {} bytes", prefix);
+    }
+}
+
+#[test]
+fn keccak256_fixed_sizes() {
+    fn unhex(hex: &str) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (byte, pair) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap();
+        }
+        bytes
+    }
+
+    let mut counting32 = [0u8; 32];
+    let mut counting64 = [0u8; 64];
+    for (i, byte) in counting64.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    counting32.copy_from_slice(&counting64[..32]);
+
+    let vectors32 = [
+        (
+            [0u8; 32],
+            "290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563",
+        ),
+        (
+            counting32,
+            "8ae1aa597fa146ebd3aa2ceddf360668dea5e526567e92b0321816a4e895bd2d",
+        ),
+    ];
+    for (input, expected) in vectors32.iter() {
+        assert_eq!(keccak256_32(input), unhex(expected));
+        assert_eq!(keccak256_32(input), keccak256(input));
+    }
+
+    let vectors64 = [
+        (
+            [0u8; 64],
+            "ad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5",
+        ),
+        (
+            counting64,
+            "002030bde3d4cf89919649775cd71875c4d0ab1708a380e03fefc3a28aa24831",
+        ),
+    ];
+    for (input, expected) in vectors64.iter() {
+        assert_eq!(keccak256_64(input), unhex(expected));
+        assert_eq!(keccak256_64(input), keccak256(input));
+    }
+
+    let input = [0xa5u8; 64];
+    assert_eq!(keccak256_64(&input), keccak256(&input));
+}
+
+#[test]
+fn permute_zero_state() {
+    // the first permutation of the all-zero state, from the Keccak team's intermediate values
+    let expected = [
+        0xf1258f7940e1dde7,
+        0x84d5ccf933c0478a,
+        0xd598261ea65aa9ee,
+        0xbd1547306f80494d,
+        0x8b284e056253d057,
+        0xff97a42d7f8e6fd4,
+        0x90fee5a0a44647c4,
+        0x8c5bda0cd6192e76,
+        0xad30a6f71b19059c,
+        0x30935ab7d08ffc64,
+        0xeb5aa93f2317d635,
+        0xa9a6e6260d712103,
+        0x81a57c16dbcf555f,
+        0x43b831cd0347c826,
+        0x01f22f1a11a5569f,
+        0x05e5635a21d9ae61,
+        0x64befef28cc970f2,
+        0x613670957bc46611,
+        0xb87c5a554fd00ecb,
+        0x8c3ee88a1ccf32c8,
+        0x940c7922ae3a2614,
+        0x1841f924a2c509e4,
+        0x16f53526e70465c2,
+        0x75f644e97f30a13b,
+        0xeaf1ff7b5ceca249,
+    ];
+    let mut state = [0u64; 25];
+    permute(&mut state);
+    assert_eq!(state, expected);
+}
+
+#[test]
+fn permute_slice_matches_permute() {
+    let mut words: Vec<u64> = (0..25u64)
+        .map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+        .collect();
+    let mut expected = [0u64; 25];
+    expected.copy_from_slice(&words);
+    permute(&mut expected);
+    permute(&mut expected);
+
+    assert_eq!(permute_slice(&mut words), Ok(()));
+    assert_eq!(permute_slice(&mut words[..]), Ok(()));
+    assert_eq!(words[..], expected[..]);
+}
+
+#[test]
+fn permute_slice_rejects_other_lengths() {
+    let mut words = vec![7u64; 26];
+    for &len in &[0, 1, 24, 26] {
+        assert_eq!(
+            permute_slice(&mut words[..len]),
+            Err(KeccakError::InvalidStateLength { len })
+        );
+    }
+    assert!(words.iter().all(|&word| word == 7));
+    assert_eq!(
+        KeccakError::InvalidStateLength { len: 24 }.to_string(),
+        "state of 24 words, expected 25 words"
+    );
+}
+
+#[test]
+fn keccak_state_from_words() {
+    let mut words = [0u64; 25];
+    for (i, word) in words.iter_mut().enumerate() {
+        *word = i as u64 * 0x0101_0101_0101_0101;
+    }
+
+    let state = KeccakState::<KeccakF>::from_words(words);
+    assert_eq!(state.as_words(), &words);
+    assert_eq!(state.rate(), bits_to_rate(256));
+    assert_eq!(state.delim(), 0x01);
+    assert_eq!(state.offset(), 0);
+    assert!(KeccakState::<KeccakF>::from(words) == state);
+}
+
+#[test]
+fn keccak_state_clone_from() {
+    let mut snapshot = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    snapshot.update(b"common prefix");
+
+    // restore a state that absorbed, squeezed, and changed its rate in between
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(128), 0x1f);
+    for suffix in [&b"a"[..], b"bc", &[0x5a; 300]].iter() {
+        let mut expected = [0u8; 32];
+        let mut clone = snapshot.clone();
+        clone.update(suffix);
+        clone.finalize(&mut expected);
+
+        state.clone_from(&snapshot);
+        assert!(state == snapshot);
+        state.update(suffix);
+        let mut output = [0u8; 32];
+        state.squeeze(&mut output);
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn keccak_clone_is_independent() {
+    let mut original = Keccak::v256();
+    original.update(b"shared prefix, ");
+    let mut clone = original.clone();
+
+    // more than a block each, so both permute their own copy of the state
+    original.update(&[0xa5; 200]);
+    clone.update(b"clone input");
+    assert_eq!(
+        original.finalize_array::<32>(),
+        keccak256(&[&b"shared prefix, "[..], &[0xa5; 200]].concat())
+    );
+    assert_eq!(
+        clone.finalize_array::<32>(),
+        keccak256(b"shared prefix, clone input")
+    );
+
+    // a clone taken while squeezing continues the stream on its own
+    let mut state = KeccakState::<KeccakF>::with_rate(136, 0x1f);
+    state.update(b"seed");
+    let mut expected = [0u8; 400];
+    state.clone().squeeze(&mut expected);
+    state.squeeze(&mut [0u8; 100]);
+    let mut fork = state.clone();
+    let mut output = [0u8; 300];
+    state.squeeze(&mut output);
+    assert_eq!(output[..], expected[100..]);
+    fork.squeeze(&mut output);
+    assert_eq!(output[..], expected[100..]);
+}
+
+#[test]
+fn keccak_state_empty_update_is_noop() {
+    let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    state.update(&[0x11; 136]);
+    let before = state.clone();
+    state.update(&[]);
+    assert!(state == before);
+
+    // not even while squeezing, the output stream continues
+    let mut expected = [0u8; 64];
+    state.clone().squeeze(&mut expected);
+    let mut output = [0u8; 64];
+    state.squeeze(&mut output[..20]);
+    let squeezing = state.clone();
+    state.update(&[]);
+    assert!(state == squeezing);
+    state.squeeze(&mut output[20..]);
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn keccak_state_squeeze_straddling_blocks() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"squeeze me");
+    let mut expected = [0u8; 1000];
+    keccak.state.clone().squeeze(&mut expected);
+
+    // rate is 136 bytes, the reads below start and end inside different blocks
+    let sizes = [1, 134, 2, 137, 0, 135, 136, 271, 184];
+    let mut output = [0u8; 1000];
+    let mut state = keccak.state;
+    let mut offset = 0;
+    for &size in sizes.iter() {
+        state.squeeze(&mut output[offset..offset + size]);
+        offset += size;
+    }
+    assert_eq!(offset, output.len());
+    assert_eq!(&expected[..], &output[..]);
+}
+
+struct Identity;
+
+impl Permutation for Identity {
+    fn execute(_state: &mut [u64; 25]) {}
+}
+
+/// Counts the permutation calls in the first word, so every block squeezed is distinguishable.
+struct Counter;
+
+impl Permutation for Counter {
+    fn execute(state: &mut [u64; 25]) {
+        state[0] = state[0].wrapping_add(1);
+    }
+}
+
+/// Stands in for downstream code, generic over the hasher.
+fn fingerprint<H: Hasher>(hasher: H, message: &[u8]) -> [u8; 8] {
+    hasher.chain(message).finalize_array()
+}
+
+#[test]
+fn keccak_state_mock_permutation() {
+    let state = KeccakState::<Counter>::with_rate(bits_to_rate(256), 0x01);
+    assert_eq!(
+        fingerprint(state, b"abc"),
+        [b'a' + 1, b'b', b'c', 0x01, 0, 0, 0, 0]
+    );
+
+    // a whole block permutes once more before the last one is padded
+    let state = KeccakState::<Counter>::with_rate(bits_to_rate(256), 0x01);
+    let mut message = [0u8; 137];
+    message[136] = 0x42;
+    assert_eq!(
+        fingerprint(state, &message),
+        [(1 ^ 0x42) + 1, 0x01, 0, 0, 0, 0, 0, 0]
+    );
+
+    // the real permutation still hashes through the same trait
+    let state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    assert_eq!(fingerprint(state, b"abc"), keccak256(b"abc")[..8]);
+
+    // squeezing permutes between the blocks
+    let mut state = KeccakState::<Counter>::with_rate(8, 0x01);
+    let mut output = [0u8; 24];
+    Xof::squeeze(&mut state, &mut output);
+    assert_eq!(output[..8], [0x02, 0, 0, 0, 0, 0, 0, 0x80]);
+    assert_eq!(output[8..16], [0x03, 0, 0, 0, 0, 0, 0, 0x80]);
+}
+
+/// Appends a single `0x80` marker byte, like some pre-FIPS 202 constructions.
+struct Marker;
+
+impl Padding for Marker {
+    fn pad(block: &mut [u8], offset: usize, _rate: usize, _delim: u8) {
+        block[offset] ^= 0x80;
+    }
+}
+
+#[test]
+fn keccak_state_default_padding() {
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    state.update(b"hello");
+    state.pad_with::<Pad10Star1>();
+    let mut output = [0u8; 32];
+    state.squeeze(&mut output);
+    assert_eq!(output, keccak256(b"hello"));
+}
+
+#[test]
+fn keccak_state_custom_padding() {
+    let mut state = KeccakState::<Identity>::with_rate(136, 0x01);
+    state.update(b"abc");
+    state.pad_with::<Marker>();
+    let mut output = [0u8; 136];
+    state.squeeze(&mut output);
+
+    let mut expected = [0u8; 136];
+    expected[..4].copy_from_slice(b"abc\x80");
+    assert_eq!(output[..], expected[..]);
+
+    // padding once more does nothing
+    state.pad();
+    state.squeeze(&mut output);
+    assert_eq!(output[..], expected[..]);
+}
+
+#[test]
+fn keccak_state_custom_permutation() {
+    let mut state = KeccakState::<Identity>::from_parts([0; 25], 0, 136, 0x06);
+    state.update(b"abc");
+    let mut output = [0u8; 272];
+    state.squeeze(&mut output);
+
+    let mut expected = [0u8; 136];
+    expected[..3].copy_from_slice(b"abc");
+    expected[3] = 0x06;
+    expected[135] = 0x80;
+    // the identity permutation squeezes the padded block over and over
+    assert_eq!(&output[..136], &expected[..]);
+    assert_eq!(&output[136..], &expected[..]);
+}
+
+#[test]
+fn keccak_one_shot() {
+    let input = b"hello world";
+
+    let mut hasher = Keccak::v224();
+    hasher.update(input);
+    assert_eq!(keccak224(input), hasher.finalize_array::<28>());
+
+    let mut hasher = Keccak::v256();
+    hasher.update(input);
+    assert_eq!(keccak256(input), hasher.finalize_array::<32>());
+
+    let mut hasher = Keccak::v384();
+    hasher.update(input);
+    assert_eq!(keccak384(input)[..], hasher.finalize_array::<48>()[..]);
+
+    let mut hasher = Keccak::v512();
+    hasher.update(input);
+    assert_eq!(keccak512(input)[..], hasher.finalize_array::<64>()[..]);
+}
+
+#[cfg(not(feature = "unstable-debug"))]
+#[test]
+fn keccak_debug_is_redacted() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"secret key");
+    let words = keccak.state.as_words();
+
+    let debug = format!("{:?}", keccak);
+    assert_eq!(debug, "Keccak { rate: 136, offset: 10, state: <redacted> }");
+    assert_eq!(
+        format!("{:?}", keccak.state),
+        "KeccakState { rate: 136, offset: 10, state: <redacted> }"
+    );
+    for word in words.iter().filter(|word| **word != 0) {
+        assert!(!debug.contains(&word.to_string()));
+        assert!(!debug.contains(&format!("{:x}", word)));
+    }
+}
+
+#[cfg(feature = "unstable-debug")]
+#[test]
+fn keccak_debug_shows_state() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"secret key");
+    let words = keccak.state.as_words();
+
+    let debug = format!("{:?}", keccak);
+    assert!(!debug.contains("redacted"));
+    assert!(debug.contains(&words[0].to_string()));
+}
+
+#[test]
+fn keccak_finalize_exact_output() {
+    let mut output = [0u8; 64];
+    Keccak::v512().finalize(&mut output);
+
+    // longer outputs are squeezed from the xof
+    let mut xof = Keccak::v256().into_xof();
+    xof.squeeze(&mut output);
+    let mut digest = [0u8; 32];
+    Keccak::v256().finalize(&mut digest);
+    assert_eq!(digest, output[..32]);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(
+    expected = "output length exceeds the Keccak variant, expected at most 32 bytes, got 64"
+)]
+fn keccak_finalize_wrong_size() {
+    Keccak::v256().finalize(&mut [0u8; 64]);
+}
+
+#[test]
+fn rate_helpers() {
+    for &(bits, rate) in [(224, 144), (256, 136), (384, 104), (512, 72)].iter() {
+        assert_eq!(bits_to_rate(bits), rate);
+        assert_eq!(rate_to_capacity_bits(rate), 2 * bits);
+        assert!(is_valid_rate(rate));
+    }
+
+    assert!(is_valid_rate(168));
+    assert!(!is_valid_rate(0));
+    assert!(!is_valid_rate(100));
+    assert!(!is_valid_rate(200));
+    assert!(!is_valid_rate(208));
+}
+
+#[test]
+#[should_panic(expected = "invalid sponge rate 100")]
+fn keccak_state_from_parts_invalid_rate() {
+    KeccakState::<KeccakF>::from_parts([0; 25], 0, 100, 0x01);
+}
+
+#[test]
+fn keccak_state_absorb_pad_squeeze() {
+    let mut expected = [0u8; 64];
+    let mut keccak = Keccak::v256();
+    keccak.update(b"hello world");
+    keccak.into_xof().squeeze(&mut expected);
+
+    let mut state = Keccak::v256().state;
+    state.absorb(b"hello");
+    state.absorb(b" world");
+    let absorbed = *state.as_words();
+    state.pad();
+
+    // padding appends the delimiter after the input and sets the last bit of the block
+    let padded = *state.as_words();
+    assert_eq!(padded[1] ^ absorbed[1], 0x01 << 24);
+    assert_eq!(padded[16] ^ absorbed[16], 0x80 << 56);
+    // padding twice does nothing
+    state.pad();
+    assert_eq!(&padded, state.as_words());
+
+    let mut output = [0u8; 64];
+    state.squeeze(&mut output[..10]);
+    state.squeeze(&mut output[10..]);
+    assert_eq!(expected[..], output[..]);
+}
+
+#[test]
+fn keccak_state_eq() {
+    let mut state = Keccak::v256().state;
+    state.update(b"hello");
+    let snapshot = state.clone();
+    assert_eq!(snapshot, state);
+
+    state.update(b" world");
+    assert_ne!(snapshot, state);
+
+    // same words and offset, but a different delimiter
+    let (words, offset) = (*snapshot.as_words(), snapshot.offset());
+    assert_ne!(
+        snapshot,
+        KeccakState::from_parts(words, offset, snapshot.rate(), 0x06)
+    );
+    assert_eq!(
+        snapshot,
+        KeccakState::from_parts(words, offset, snapshot.rate(), 0x01)
+    );
+}
+
+#[test]
+fn keccak_with_bits() {
+    let mut expected = [0u8; 32];
+    Keccak::v256().chain(b"hello world").finalize(&mut expected);
+    let mut output = [0u8; 32];
+    Keccak::with_bits(512, 0x01)
+        .chain(b"hello world")
+        .finalize(&mut output);
+    assert_eq!(expected, output);
+
+    // Keccak[r=1536, c=64]
+    let expected = b"\x44\xac\x0c\x1d";
+    let mut output = [0u8; 4];
+    Keccak::with_bits(64, 0x01)
+        .chain(b"hello world")
+        .finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn keccak_state_with_rate() {
+    let expected = b"\
+        \x49\x16\x86\xbd\x91\x3b\xa3\x87\x1d\x12\x7e\x55\xad\x95\x89\x50\
+        \xff\x9d\x46\x11\x0a\x6a\x2b\xef\x3d\x3a\x46\x66\xa7\x4a\xd0\x9f\
+        \xf8\xb6\x2c\xb9\
+    ";
+
+    let mut state = KeccakState::<KeccakF>::with_rate(128, 0x06);
+    assert_eq!(state.capacity_bits(), 576);
+    for _ in 0..20 {
+        state.update(b"hello world");
+    }
+    let mut output = [0u8; 36];
+    state.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+#[should_panic(expected = "invalid sponge capacity of 1600 bits")]
+fn keccak_with_bits_without_rate() {
+    Keccak::with_bits(1600, 0x01);
+}
+
+#[test]
+#[should_panic(expected = "invalid sponge capacity of 100 bits")]
+fn keccak_with_bits_partial_lane() {
+    Keccak::with_bits(100, 0x01);
+}
+
+#[test]
+#[should_panic(expected = "invalid sponge rate 0")]
+fn keccak_state_with_rate_zero() {
+    KeccakState::<KeccakF>::with_rate(0, 0x01);
+}
+
+#[cfg(feature = "cshake")]
+#[test]
+fn keccak_absorb_iter_is_unambiguous() {
+    use tiny_keccak::encode::encode_string;
+    use tiny_keccak::EncodeExt;
+
+    let ab_c: [u8; 32] = Keccak::v256().absorb_iter(["ab", "c"].iter().map(|x| x.as_bytes()));
+    let a_bc: [u8; 32] = Keccak::v256().absorb_iter(["a", "bc"].iter().map(|x| x.as_bytes()));
+    assert_ne!(ab_c, a_bc);
+
+    let mut keccak = Keccak::v256();
+    keccak.update_vectored(&encode_string(b"ab").parts());
+    keccak.update_vectored(&encode_string(b"c").parts());
+    assert_eq!(ab_c, keccak.finalize_array::<32>());
+
+    let empty: [u8; 32] = Keccak::v256().absorb_iter(core::iter::empty::<&[u8]>());
+    assert_eq!(empty, keccak256(b""));
+}
+
+#[test]
+fn keccak_hash_messages_is_unambiguous() {
+    use tiny_keccak::hash_messages;
+
+    let mut ab_c = [0u8; 32];
+    let mut a_bc = [0u8; 32];
+    let mut abc = [0u8; 32];
+    hash_messages(&[b"ab", b"c"], &mut ab_c);
+    hash_messages(&[b"a", b"bc"], &mut a_bc);
+    hash_messages(&[b"abc"], &mut abc);
+    assert_ne!(ab_c, a_bc);
+    assert_ne!(abc, ab_c);
+    assert_ne!(abc, a_bc);
+
+    // the same encoding as absorb_iter, with output of any length
+    #[cfg(feature = "cshake")]
+    {
+        use tiny_keccak::EncodeExt;
+        let expected: [u8; 32] =
+            Keccak::v256().absorb_iter(["ab", "c"].iter().map(|x| x.as_bytes()));
+        assert_eq!(ab_c, expected);
+    }
+
+    let mut long = [0u8; 200];
+    hash_messages(&[b"ab", b"c"], &mut long);
+    assert_eq!(long[..32], ab_c);
+}
+
+#[test]
+fn keccak_extend() {
+    let mut keccak = Keccak::v256();
+    keccak.extend(b"abc".iter().copied());
+    assert_eq!(keccak.finalize_array::<32>(), keccak256(b"abc"));
+
+    let input: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+    let mut keccak = Keccak::v256();
+    keccak.extend(&input[..10]);
+    keccak.extend(input[10..].iter());
+    assert_eq!(keccak.finalize_array::<32>(), keccak256(&input));
+
+    fn sink<E: Extend<u8>>(sink: &mut E) {
+        sink.extend(b"from a generic sink".iter().copied());
+    }
+    let mut keccak256_sink = Keccak256::new();
+    sink(&mut keccak256_sink);
+    assert_eq!(keccak256_sink.finalize(), keccak256(b"from a generic sink"));
+}
+
+#[test]
+fn hasher_ext_encodings() {
+    let mut collect = Collect::default();
+    collect.absorb_u64_le(1);
+    assert_eq!(collect.0, [1, 0, 0, 0, 0, 0, 0, 0]);
+
+    let mut collect = Collect::default();
+    collect.absorb_str("tag");
+    collect.absorb_u16_be(0x0102);
+    collect.absorb_u32_be(7);
+    collect.absorb_u32_le(7);
+    collect.absorb_u64_be(1);
+    assert_eq!(
+        collect.0,
+        [b't', b'a', b'g', 1, 2, 0, 0, 0, 7, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]
+    );
+
+    let mut collect = Collect::default();
+    collect.update_framed(b"abc");
+    collect.update_framed(b"");
+    assert_eq!(collect.0, [3, 0, 0, 0, b'a', b'b', b'c', 0, 0, 0, 0]);
+
+    let mut keccak = Keccak::v256();
+    keccak.absorb_u64_le(42);
+    assert_eq!(
+        keccak.finalize_array::<32>(),
+        keccak256(&42u64.to_le_bytes())
+    );
+}
+
+#[test]
+fn keccak_state_peek_digest() {
+    let chunks: [&[u8]; 3] = [b"first chunk", &[0xa5; 200], b"third"];
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    let mut absorbed = Vec::new();
+    for chunk in chunks.iter() {
+        state.update(chunk);
+        absorbed.extend_from_slice(chunk);
+
+        let mut digest = [0u8; 32];
+        state.peek_digest(&mut digest);
+        assert_eq!(digest, keccak256(&absorbed));
+    }
+
+    let mut digest = [0u8; 32];
+    state.finalize(&mut digest);
+    assert_eq!(digest, keccak256(&absorbed));
+}
+
+#[test]
+fn keccak_state_wipe() {
+    fn bytes(state: &KeccakState<KeccakF>) -> Vec<u8> {
+        state
+            .as_words()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    state.update(&[0xa5; 150]);
+    let offset = state.offset();
+    assert_eq!(offset, 14);
+
+    let before = bytes(&state);
+    state.wipe();
+    let after = bytes(&state);
+    assert!(after[..offset].iter().all(|byte| *byte == 0));
+    assert_eq!(after[offset..], before[offset..]);
+}
+
+#[test]
+fn keccak_typed_output() {
+    let mut hasher = Keccak256::new();
+    hasher.update(b"hello");
+    hasher.update(b" world");
+    let digest: [u8; 32] = hasher.finalize();
+    assert_eq!(digest, keccak256(b"hello world"));
+    assert_eq!(Keccak256::OUTPUT, 32);
+
+    assert_eq!(Keccak224::new().finalize(), keccak224(b""));
+    assert_eq!(Keccak384::new().finalize(), keccak384(b""));
+    assert_eq!(Keccak512::new().finalize(), keccak512(b""));
+    assert_eq!(Keccak512::OUTPUT, 64);
+
+    // the typed hashers work with generic `Hasher` code too
+    fn generic<H: Hasher>(hasher: H) -> [u8; 32] {
+        hasher.chain(b"hello world").finalize_array()
+    }
+    assert_eq!(generic(Keccak256::default()), digest);
+}
+
+#[test]
+fn keccak_typed_constants_are_const() {
+    const _: () = assert!(Keccak256::RATE == 136 && Keccak256::OUTPUT == 32);
+    const BLOCK: [u8; Keccak256::RATE] = [0; Keccak256::RATE];
+    const DIGEST: [u8; Keccak256::OUTPUT] = [0; Keccak256::OUTPUT];
+
+    assert_eq!(BLOCK.len(), bits_to_rate(256));
+    assert_eq!(DIGEST.len(), Keccak256::new().finalize().len());
+    assert_eq!(Keccak224::RATE, 144);
+    assert_eq!(Keccak384::RATE, 104);
+    assert_eq!(Keccak512::RATE, 72);
+}
+
+#[test]
+fn keccak_state_bulk_absorb_matches_bytewise() {
+    let input: Vec<u8> = (0..1000u32).map(|i| (i * 7 + 3) as u8).collect();
+    for &rate in [bits_to_rate(128), bits_to_rate(256), bits_to_rate(512)].iter() {
+        for &prefix in [0, 1, 5, 8, rate - 1].iter() {
+            for &len in [0, 1, rate - 1, rate, rate + 1, 2 * rate + 3, 999 - prefix].iter() {
+                let input = &input[..prefix + len];
+
+                // single bytes never fill a whole block at once
+                let mut bytewise = KeccakState::<KeccakF>::with_rate(rate, 0x01);
+                for byte in input {
+                    bytewise.update(core::slice::from_ref(byte));
+                }
+
+                let mut bulk = KeccakState::<KeccakF>::with_rate(rate, 0x01);
+                bulk.update(&input[..prefix]);
+                bulk.update(&input[prefix..]);
+                assert!(
+                    bulk == bytewise,
+                    "rate {} prefix {} len {}",
+                    rate,
+                    prefix,
+                    len
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn keccak_is_send_sync() {
+    fn _assert_send_sync<T: Send + Sync>() {}
+    _assert_send_sync::<KeccakState<KeccakF>>();
+    _assert_send_sync::<Keccak>();
+    _assert_send_sync::<Keccak256>();
+    _assert_send_sync::<tiny_keccak::KeccakXof>();
+}
+
+#[test]
+fn keccak_finalize_truncated() {
+    let mut full = [0u8; 32];
+    Keccak::v256().chain(b"hello world").finalize(&mut full);
+
+    let mut truncated = [0u8; 20];
+    Keccak::v256()
+        .chain(b"hello world")
+        .finalize(&mut truncated);
+    assert_eq!(truncated, full[..20]);
+
+    let truncated: [u8; 20] = Keccak::v256().chain(b"hello world").finalize_array();
+    assert_eq!(truncated, full[..20]);
+}
+
+#[test]
+fn keccak_update_iter() {
+    // 200 bytes cross the block boundary, 100 bytes fit in a single block
+    for &len in [0u8, 1, 100, 200].iter() {
+        let mut expected = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(&(0u8..len).collect::<Vec<_>>());
+        keccak.finalize(&mut expected);
+
+        let mut output = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update_iter(0u8..len);
+        keccak.finalize(&mut output);
+        assert_eq!(expected, output);
+    }
+}
+
+#[test]
+fn keccak_state_reset_with() {
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    state.update(b"the Keccak-256 phase");
+    let mut output = [0u8; 32];
+    state.squeeze(&mut output);
+
+    // SHAKE128 of "abc"
+    state.reset_with(bits_to_rate(128), 0x1f);
+    assert!(state == KeccakState::<KeccakF>::with_rate(bits_to_rate(128), 0x1f));
+    state.update(b"abc");
+    state.squeeze(&mut output);
+    let expected = b"\
+        \x58\x81\x09\x2d\xd8\x18\xbf\x5c\xf8\xa3\xdd\xb7\x93\xfb\xcb\xa7\
+        \x40\x97\xd5\xc5\x26\xa6\xd3\x5f\x97\xb8\x33\x51\x94\x0f\x2c\xc8\
+    ";
+    assert_eq!(expected, &output);
+}
+
+#[test]
+#[should_panic(expected = "invalid sponge rate 0")]
+fn keccak_state_reset_with_invalid_rate() {
+    KeccakState::<KeccakF>::with_rate(136, 0x01).reset_with(0, 0x01);
+}
+
+#[test]
+#[should_panic(expected = "nor reach it while absorbing")]
+fn keccak_state_from_parts_full_block() {
+    // an absorbing state permutes a full block right away, it never holds one
+    KeccakState::<KeccakF>::from_parts([0; 25], 136, 136, 0x01);
+}
+
+#[test]
+fn keccak_state_from_parts_last_offset() {
+    // the last byte of the block is the last offset padding can start at
+    let mut state = KeccakState::<KeccakF>::from_parts([0; 25], 135, 136, 0x01);
+    let mut output = [0u8; 32];
+    state.clone().finalize(&mut output);
+    state.update(b"abc");
+    assert_eq!(state.offset(), 2);
+}
+
+#[test]
+fn keccak_state_offset_stays_within_rate() {
+    // every update and squeeze checks the offset in debug builds
+    let input = [0x5au8; 1000];
+    let mut output = [0u8; 1000];
+    for &rate in [8, 72, 136, 168, 192].iter() {
+        let mut state = KeccakState::<KeccakF>::with_rate(rate, 0x06);
+        for len in 0..300 {
+            state.update(&input[..len]);
+            assert!(state.offset() <= rate);
+            if len % 7 == 0 {
+                state.squeeze(&mut output[..len * 3]);
+                assert!(state.offset() <= rate);
+                // a new input starts from a reset sponge
+                state.reset();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "unstable-capacity")]
+#[test]
+fn keccak_state_capacity_bytes() {
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    state.update(b"abc");
+    let mut output = [0u8; 32];
+    state.squeeze(&mut output);
+    assert_eq!(output, keccak256(b"abc"));
+
+    // the tail of keccak-f[1600] applied to the padded block of "abc"
+    let expected = b"\
+        \xf1\xdd\xce\xdc\xd8\x78\xc4\xd6\x8c\x8d\xe5\xd6\x0b\xc1\xb1\x2e\
+        \x99\xd1\xc7\xae\xcb\x5d\xd6\x93\x04\x03\x47\x9d\xfa\x29\x0a\x61\
+        \xd2\xc8\xd5\x14\xf1\x77\xa9\x7e\xd5\x40\xe8\x76\x9f\x6f\x86\x50\
+        \xce\xb7\xa9\xe7\xc9\x6d\x1c\x89\x9a\xbd\x41\xcd\x6d\x5e\xff\xda\
+    ";
+    let mut capacity = [0u8; 64];
+    state.capacity_bytes(&mut capacity);
+    assert_eq!(expected, &capacity);
+
+    // squeezing within the block doesn't permute
+    state.squeeze(&mut output);
+    state.capacity_bytes(&mut capacity);
+    assert_eq!(expected, &capacity);
+}
+
+#[cfg(feature = "unstable-capacity")]
+#[test]
+#[should_panic(expected = "output must be as long as the capacity")]
+fn keccak_state_capacity_bytes_wrong_length() {
+    KeccakState::<KeccakF>::with_rate(136, 0x01).capacity_bytes(&mut [0u8; 32]);
+}
+
+#[test]
+fn keccak_try_finalize() {
+    let mut output = [0u8; 32];
+    assert_eq!(
+        Keccak::v256().chain(b"abc").try_finalize(&mut output),
+        Ok(())
+    );
+    assert_eq!(output, keccak256(b"abc"));
+
+    let mut truncated = [0u8; 20];
+    assert_eq!(Keccak::v256().try_finalize(&mut truncated), Ok(()));
+    assert_eq!(truncated, keccak256(b"")[..20]);
+
+    let error = Keccak::v256().try_finalize(&mut [0u8; 33]).unwrap_err();
+    assert_eq!(error, KeccakError::OutputTooLong { max: 32, len: 33 });
+    assert_eq!(
+        error.to_string(),
+        "output of 33 bytes is longer than the 32 byte digest"
+    );
+}
+
+#[test]
+fn keccak_state_absorb_then_finalize() {
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    assert_eq!(state.try_update(b"a"), Ok(()));
+    state.update(b"bc");
+    let mut output = [0u8; 32];
+    state.finalize(&mut output);
+    assert_eq!(output, keccak256(b"abc"));
+}
+
+#[test]
+fn keccak_state_try_update_after_squeeze() {
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    state.update(b"abc");
+    let mut output = [0u8; 32];
+    state.squeeze(&mut output[..16]);
+    assert_eq!(
+        state.try_update(b"more"),
+        Err(KeccakError::AlreadySqueezing)
+    );
+    // an empty input is still a no-op, and the output stream is untouched
+    assert_eq!(state.try_update(b""), Ok(()));
+    state.squeeze(&mut output[16..]);
+    assert_eq!(output, keccak256(b"abc"));
+
+    state.reset();
+    assert_eq!(state.try_update(b"abc"), Ok(()));
+}
+
+#[test]
+#[should_panic(expected = "cannot absorb after squeezing")]
+fn keccak_state_update_after_squeeze() {
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    state.update(b"abc");
+    state.squeeze(&mut [0u8; 32]);
+    state.update(b"more");
+}
+
+#[test]
+fn keccak_state_try_finalize_after_squeeze() {
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    state.update(b"abc");
+    let mut output = [0u8; 32];
+    state.clone().try_finalize(&mut output).unwrap();
+    assert_eq!(output, keccak256(b"abc"));
+
+    state.squeeze(&mut output);
+    assert_eq!(
+        state.try_finalize(&mut output),
+        Err(KeccakError::AlreadySqueezing)
+    );
+}
+
+#[test]
+fn keccak_state_update_bits() {
+    // `(rate, delim, nbits, output)`, computed by a bit-level reference implementation
+    let vectors: [(usize, u8, usize, [u8; 8]); 42] = [
+        (
+            136,
+            0x06,
+            0,
+            [0xa7, 0xff, 0xc6, 0xf8, 0xbf, 0x1e, 0xd7, 0x66],
+        ),
+        (
+            136,
+            0x06,
+            5,
+            [0x91, 0x7b, 0x27, 0x71, 0xf4, 0x33, 0x93, 0x8a],
+        ),
+        (
+            136,
+            0x06,
+            8,
+            [0x96, 0x2f, 0x84, 0x20, 0x91, 0x7d, 0x7f, 0xa5],
+        ),
+        (
+            136,
+            0x06,
+            13,
+            [0x78, 0x32, 0x17, 0xb2, 0xb8, 0x1e, 0xd3, 0xbc],
+        ),
+        (
+            136,
+            0x06,
+            1081,
+            [0xe1, 0xaa, 0x1d, 0x72, 0x84, 0x09, 0xba, 0x59],
+        ),
+        (
+            136,
+            0x06,
+            1082,
+            [0xf9, 0xf0, 0x26, 0xeb, 0x82, 0x27, 0x5e, 0xe7],
+        ),
+        (
+            136,
+            0x06,
+            1083,
+            [0xe0, 0xe8, 0x31, 0x7c, 0x63, 0x9b, 0xad, 0x97],
+        ),
+        (
+            136,
+            0x06,
+            1084,
+            [0x03, 0xcb, 0x17, 0xc5, 0x9d, 0xbb, 0x47, 0xd3],
+        ),
+        (
+            136,
+            0x06,
+            1085,
+            [0x43, 0x3f, 0x70, 0x0e, 0x06, 0xae, 0x0c, 0x12],
+        ),
+        (
+            136,
+            0x06,
+            1086,
+            [0xb8, 0x71, 0x7c, 0x6e, 0x76, 0x05, 0xca, 0x3b],
+        ),
+        (
+            136,
+            0x06,
+            1087,
+            [0x25, 0x8f, 0xff, 0xf6, 0xa0, 0x80, 0x50, 0x33],
+        ),
+        (
+            136,
+            0x06,
+            1088,
+            [0x9f, 0x06, 0x57, 0x22, 0x98, 0x3c, 0x1b, 0x64],
+        ),
+        (
+            136,
+            0x06,
+            1091,
+            [0xb0, 0xc2, 0x2d, 0x1d, 0xaa, 0x8c, 0xc1, 0x14],
+        ),
+        (
+            136,
+            0x06,
+            2175,
+            [0xbb, 0xa4, 0x5f, 0x54, 0x26, 0x9a, 0x7d, 0x46],
+        ),
+        (
+            168,
+            0x1f,
+            0,
+            [0x7f, 0x9c, 0x2b, 0xa4, 0xe8, 0x8f, 0x82, 0x7d],
+        ),
+        (
+            168,
+            0x1f,
+            5,
+            [0xf5, 0x88, 0xd3, 0x9c, 0x81, 0xf8, 0x85, 0x28],
+        ),
+        (
+            168,
+            0x1f,
+            8,
+            [0x96, 0x5e, 0x60, 0x70, 0xbf, 0x20, 0xc4, 0xf4],
+        ),
+        (
+            168,
+            0x1f,
+            13,
+            [0x25, 0xd3, 0x0c, 0xbe, 0xd9, 0x7a, 0xef, 0xbb],
+        ),
+        (
+            168,
+            0x1f,
+            1337,
+            [0x15, 0xbc, 0x99, 0xe0, 0xda, 0x83, 0xe7, 0x32],
+        ),
+        (
+            168,
+            0x1f,
+            1338,
+            [0x98, 0x15, 0x55, 0x89, 0x94, 0x17, 0xe8, 0x8a],
+        ),
+        (
+            168,
+            0x1f,
+            1339,
+            [0x03, 0x08, 0x19, 0x68, 0x2b, 0x84, 0xab, 0x2c],
+        ),
+        (
+            168,
+            0x1f,
+            1340,
+            [0x3d, 0x49, 0x24, 0x90, 0x69, 0x34, 0x7f, 0x0f],
+        ),
+        (
+            168,
+            0x1f,
+            1341,
+            [0x17, 0x17, 0xf4, 0xaa, 0x8b, 0x56, 0xdc, 0x74],
+        ),
+        (
+            168,
+            0x1f,
+            1342,
+            [0x02, 0x90, 0x15, 0x77, 0xdb, 0xd5, 0xf3, 0x1d],
+        ),
+        (
+            168,
+            0x1f,
+            1343,
+            [0x5e, 0x39, 0x15, 0x5d, 0xac, 0xe7, 0xc1, 0xf1],
+        ),
+        (
+            168,
+            0x1f,
+            1344,
+            [0xa9, 0x46, 0x3d, 0x8b, 0x6c, 0x70, 0x7a, 0x86],
+        ),
+        (
+            168,
+            0x1f,
+            1347,
+            [0x9e, 0x05, 0x30, 0xce, 0xe1, 0x45, 0x43, 0x33],
+        ),
+        (
+            168,
+            0x1f,
+            2687,
+            [0xe6, 0xe4, 0xa9, 0x67, 0xef, 0xc7, 0xac, 0x26],
+        ),
+        (
+            136,
+            0x01,
+            0,
+            [0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c],
+        ),
+        (
+            136,
+            0x01,
+            5,
+            [0xab, 0x63, 0xdb, 0xc3, 0xbf, 0x07, 0x2c, 0x92],
+        ),
+        (
+            136,
+            0x01,
+            8,
+            [0x60, 0x81, 0x18, 0x57, 0xdd, 0x56, 0x68, 0x89],
+        ),
+        (
+            136,
+            0x01,
+            13,
+            [0x85, 0x70, 0xe4, 0x3e, 0x25, 0x53, 0x97, 0x1e],
+        ),
+        (
+            136,
+            0x01,
+            1081,
+            [0x10, 0xa3, 0xcc, 0xa2, 0xda, 0x57, 0xa0, 0xba],
+        ),
+        (
+            136,
+            0x01,
+            1082,
+            [0x3a, 0xa8, 0x1a, 0x5b, 0x23, 0x3c, 0xe7, 0x53],
+        ),
+        (
+            136,
+            0x01,
+            1083,
+            [0x5d, 0x12, 0x1e, 0xf3, 0x67, 0x48, 0xc2, 0xd2],
+        ),
+        (
+            136,
+            0x01,
+            1084,
+            [0xf3, 0xe7, 0x88, 0x65, 0xb9, 0x3e, 0x61, 0xa0],
+        ),
+        (
+            136,
+            0x01,
+            1085,
+            [0x5a, 0x48, 0x01, 0x71, 0xe5, 0x1a, 0x4c, 0x3d],
+        ),
+        (
+            136,
+            0x01,
+            1086,
+            [0xfd, 0x48, 0xfb, 0x93, 0x03, 0x48, 0x93, 0x25],
+        ),
+        (
+            136,
+            0x01,
+            1087,
+            [0xed, 0x05, 0x92, 0x32, 0x5f, 0x74, 0x58, 0x60],
+        ),
+        (
+            136,
+            0x01,
+            1088,
+            [0xb8, 0x71, 0x7c, 0x6e, 0x76, 0x05, 0xca, 0x3b],
+        ),
+        (
+            136,
+            0x01,
+            1091,
+            [0x78, 0xfb, 0x85, 0x9c, 0x98, 0x23, 0xa5, 0x5d],
+        ),
+        (
+            136,
+            0x01,
+            2175,
+            [0x76, 0x6b, 0x47, 0x69, 0xf4, 0xbe, 0xf3, 0x27],
+        ),
+    ];
+    let data: Vec<u8> = (0..400u32).map(|i| (i * 37 + 11) as u8).collect();
+
+    for &(rate, delim, nbits, expected) in vectors.iter() {
+        let mut input = data[..nbits / 8 + usize::from(nbits & 7 != 0)].to_vec();
+        if nbits & 7 != 0 {
+            // the bits past `nbits` are ignored
+            *input.last_mut().unwrap() |= 0xff << (nbits & 7);
+        }
+
+        let mut state = KeccakState::<KeccakF>::with_rate(rate, delim);
+        state.update_bits(&input, nbits);
+        let mut output = [0u8; 8];
+        state.squeeze(&mut output);
+        assert_eq!(output, expected, "rate {} nbits {}", rate, nbits);
+    }
+}
+
+#[test]
+fn sha3_update_bits_nist() {
+    // SHA3-256 of the 5-bit message `11001` from the NIST examples
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x06);
+    state.update_bits(&[0x13], 5);
+    let mut output = [0u8; 32];
+    state.squeeze(&mut output);
+    let expected = b"\
+        \x7b\x00\x47\xcf\x5a\x45\x68\x82\x36\x3c\xbf\x0f\xb0\x53\x22\xcf\
+        \x65\xf4\xb7\x05\x9a\x46\x36\x5e\x83\x01\x32\xe3\xb5\xd9\x57\xaf\
+    ";
+    assert_eq!(expected, &output);
+
+    // whole bytes are absorbed like `update`
+    let mut keccak = Keccak::v256();
+    keccak.update_bits(b"abcd", 24);
+    assert_eq!(keccak.finalize_array::<32>(), keccak256(b"abc"));
+}
+
+#[test]
+#[should_panic(expected = "cannot absorb after squeezing")]
+fn keccak_state_update_bits_after_squeeze() {
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x06);
+    let mut output = [0u8; 32];
+    state.squeeze(&mut output);
+    state.update_bits(&[0x13], 5);
+}
+
+#[test]
+#[should_panic(expected = "cannot absorb after squeezing")]
+fn keccak_state_update_bits_two_partial_bytes() {
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x06);
+    state.update_bits(&[0x13], 5);
+    state.update_bits(&[0x13], 5);
+}
+
+#[test]
+fn keccak_finalize_xof_into_continues_the_stream() {
+    let keccak = Keccak::v256().chain(b"give me 32 bytes now, maybe more later");
+
+    let mut expected = [0u8; 64];
+    keccak.clone().into_xof().squeeze(&mut expected);
+
+    let mut output = [0u8; 64];
+    let (first, second) = output.split_at_mut(32);
+    let mut xof = keccak.finalize_xof_into(first);
+    xof.squeeze(second);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn keccak_state_try_with_rate_rejects_oversized_rate() {
+    let error = KeccakState::<KeccakF>::try_with_rate(208, 0x01).err();
+    assert_eq!(error, Some(KeccakError::InvalidRate { rate: 208 }));
+    assert_eq!(
+        error.unwrap().to_string(),
+        "invalid sponge rate 208, expected a multiple of 8 below 200"
+    );
+    assert!(KeccakState::<KeccakF>::try_with_rate(100, 0x01).is_err());
+
+    let mut state = KeccakState::<KeccakF>::try_with_rate(bits_to_rate(256), 0x01).unwrap();
+    state.update(b"abc");
+    let mut output = [0u8; 32];
+    state.finalize(&mut output);
+    assert_eq!(output, keccak256(b"abc"));
+}
+
+#[test]
+#[should_panic(expected = "invalid sponge rate 208")]
+fn keccak_state_with_rate_rejects_oversized_rate() {
+    KeccakState::<KeccakF>::with_rate(208, 0x01);
+}
+
+#[test]
+fn keccak_xof_recovers_the_hasher() {
+    let mut drained = [0u8; 500];
+    let mut xof = Keccak::v256().chain(b"first message").into_xof();
+    xof.squeeze(&mut drained);
+
+    // the state keeps squeezing where the reader stopped
+    let mut expected = [0u8; 532];
+    Keccak::v256()
+        .chain(b"first message")
+        .into_xof()
+        .squeeze(&mut expected);
+    let mut state = xof.clone().into_state();
+    let mut next = [0u8; 32];
+    state.squeeze(&mut next);
+    assert_eq!(expected[500..], next);
+
+    state.reset();
+    state.update(b"second message");
+    let mut output = [0u8; 32];
+    state.finalize(&mut output);
+    assert_eq!(output, keccak256(b"second message"));
+
+    let mut keccak = xof.reset_to_absorb();
+    keccak.update(b"second message");
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+    assert_eq!(output, keccak256(b"second message"));
+}
+
+#[test]
+fn keccak_state_checkpoint_bytes_round_trip() {
+    let input: Vec<u8> = (0..500u32).map(|i| (i * 13) as u8).collect();
+    let mut expected = [0u8; 32];
+    Keccak::v256().chain(&input).finalize(&mut expected);
+
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    state.update(&input[..300]);
+    let checkpoint = state.to_bytes();
+    assert_eq!(checkpoint.len(), CHECKPOINT_LEN);
+    assert_eq!(checkpoint[..8], state.as_words()[0].to_le_bytes());
+    assert_eq!(checkpoint[200..208], (300u64 % 136).to_le_bytes());
+    assert_eq!(checkpoint[208..216], 136u64.to_le_bytes());
+    assert_eq!(checkpoint[216..], [0x01, 0]);
+
+    let mut resumed = KeccakState::<KeccakF>::from_bytes(&checkpoint).unwrap();
+    resumed.update(&input[300..]);
+    let mut output = [0u8; 32];
+    resumed.finalize(&mut output);
+    assert_eq!(expected, output);
+
+    // a squeezing state keeps squeezing where it stopped
+    let mut stream = [0u8; 200];
+    Keccak::v256().chain(&input).into_xof().squeeze(&mut stream);
+    let mut state = KeccakState::<KeccakF>::with_rate(bits_to_rate(256), 0x01);
+    state.update(&input);
+    state.squeeze(&mut output);
+    let mut resumed = KeccakState::<KeccakF>::from_bytes(&state.to_bytes()).unwrap();
+    let mut rest = [0u8; 168];
+    resumed.squeeze(&mut rest);
+    assert_eq!(stream[32..], rest[..]);
+}
+
+#[test]
+fn keccak_state_checkpoint_bytes_validation() {
+    let checkpoint = KeccakState::<KeccakF>::with_rate(136, 0x01).to_bytes();
+
+    let mut invalid = checkpoint;
+    invalid[208..216].copy_from_slice(&208u64.to_le_bytes());
+    assert_eq!(
+        KeccakState::<KeccakF>::from_bytes(&invalid).err(),
+        Some(KeccakError::InvalidRate { rate: 208 })
+    );
+
+    let mut invalid = checkpoint;
+    invalid[200..208].copy_from_slice(&137u64.to_le_bytes());
+    assert_eq!(
+        KeccakState::<KeccakF>::from_bytes(&invalid).err(),
+        Some(KeccakError::InvalidCheckpoint)
+    );
+
+    let mut invalid = checkpoint;
+    invalid[217] = 2;
+    assert_eq!(
+        KeccakState::<KeccakF>::from_bytes(&invalid).err(),
+        Some(KeccakError::InvalidCheckpoint)
+    );
+
+    // a full block is valid only while squeezing, an absorbing state would fail to pad it
+    let mut full = checkpoint;
+    full[200..208].copy_from_slice(&136u64.to_le_bytes());
+    assert_eq!(
+        KeccakState::<KeccakF>::from_bytes(&full).err(),
+        Some(KeccakError::InvalidCheckpoint)
+    );
+    full[217] = 1;
+    let mut output = [0u8; 32];
+    KeccakState::<KeccakF>::from_bytes(&full)
+        .unwrap()
+        .finalize(&mut output);
+}
+
+#[test]
+fn keccak_state_try_from_slice() {
+    use std::convert::TryFrom;
+
+    let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    state.update(b"commitment");
+    let checkpoint = state.to_bytes();
+    let resumed = KeccakState::<KeccakF>::try_from(&checkpoint[..]).unwrap();
+    assert!(resumed == state);
+
+    let mut long = checkpoint.to_vec();
+    long.push(0);
+    for bytes in [&checkpoint[..CHECKPOINT_LEN - 1], &long[..], &[][..]].iter() {
+        assert_eq!(
+            KeccakState::<KeccakF>::try_from(*bytes).err(),
+            Some(KeccakError::InvalidCheckpoint)
+        );
+    }
+
+    let mut invalid = checkpoint;
+    invalid[208..216].copy_from_slice(&100u64.to_le_bytes());
+    assert_eq!(
+        KeccakState::<KeccakF>::try_from(&invalid[..]).err(),
+        Some(KeccakError::InvalidRate { rate: 100 })
+    );
+
+    let mut invalid = checkpoint;
+    invalid[200..208].copy_from_slice(&137u64.to_le_bytes());
+    assert_eq!(
+        KeccakState::<KeccakF>::try_from(&invalid[..]).err(),
+        Some(KeccakError::InvalidCheckpoint)
+    );
+
+    // an absorbing state holding a full block
+    let mut invalid = checkpoint;
+    invalid[200..208].copy_from_slice(&136u64.to_le_bytes());
+    assert_eq!(
+        KeccakState::<KeccakF>::try_from(&invalid[..]).err(),
+        Some(KeccakError::InvalidCheckpoint)
+    );
+}
+
+#[test]
+fn keccak_state_switch_rate_at_block_boundary() {
+    // absorb a block at rate 168, then continue at rate 136
+    let mut state = KeccakState::<KeccakF>::with_rate(168, 0x1f);
+    state.update(&[0xa5; 168]);
+    let words = *state.as_words();
+    assert_eq!(state.switch_rate(136), Ok(()));
+    assert_eq!(state.rate(), 136);
+    state.update(b"tail");
+
+    let mut expected = KeccakState::<KeccakF>::from_parts(words, 0, 136, 0x1f);
+    expected.update(b"tail");
+    assert!(state == expected);
+
+    // squeeze at a rate of 72 after padding at 136
+    state.pad();
+    let mut words = *state.as_words();
+    assert_eq!(state.switch_rate(72), Ok(()));
+    let mut output = [0u8; 80];
+    state.squeeze(&mut output);
+
+    let mut stream = Vec::new();
+    for _ in 0..2 {
+        permute(&mut words);
+        stream.extend(words[..9].iter().flat_map(|word| word.to_le_bytes()));
+    }
+    assert_eq!(output[..], stream[..80]);
+}
+
+#[test]
+fn keccak_state_switch_rate_mid_block() {
+    let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    state.update(b"abcd");
+    let before = state.clone();
+    assert_eq!(
+        state.switch_rate(168),
+        Err(KeccakError::NotAtBlockBoundary { offset: 4 })
+    );
+    assert!(state == before);
+
+    let mut output = [0u8; 7];
+    state.squeeze(&mut output);
+    assert_eq!(
+        state.switch_rate(168),
+        Err(KeccakError::NotAtBlockBoundary { offset: 7 })
+    );
+
+    let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    assert_eq!(
+        state.switch_rate(100),
+        Err(KeccakError::InvalidRate { rate: 100 })
+    );
+    assert_eq!(state.rate(), 136);
+}
+
+static PERMUTATION_FAILS: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// `keccak-f[1600]`, panicking while `PERMUTATION_FAILS` is set.
+struct FlakyPermutation;
+
+impl Permutation for FlakyPermutation {
+    fn execute(state: &mut [u64; 25]) {
+        if PERMUTATION_FAILS.load(std::sync::atomic::Ordering::SeqCst) {
+            panic!("permutation failed");
+        }
+        KeccakF::execute(state);
+    }
+}
+
+#[test]
+fn keccak_state_peek_digest_is_panic_safe() {
+    let input: Vec<u8> = (0..300u32).map(|i| i as u8).collect();
+    let mut state = KeccakState::<FlakyPermutation>::with_rate(bits_to_rate(256), 0x01);
+    state.update(&input[..200]);
+
+    PERMUTATION_FAILS.store(true, std::sync::atomic::Ordering::SeqCst);
+    let mut output = [0u8; 32];
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        state.peek_digest(&mut output);
+    }));
+    assert!(result.is_err());
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        state.finalize_into(&mut output);
+    }));
+    assert!(result.is_err());
+    PERMUTATION_FAILS.store(false, std::sync::atomic::Ordering::SeqCst);
+
+    state.peek_digest(&mut output);
+    assert_eq!(output, keccak256(&input[..200]));
+    state.update(&input[200..]);
+    state.finalize(&mut output);
+    assert_eq!(output, keccak256(&input));
+}
+
+#[test]
+fn hash_sink_absorbs_formatted_output() {
+    use std::fmt::Write;
+
+    let mut keccak = Keccak::v256();
+    write!(HashSink(&mut keccak), "{}-{}", 1, 2).unwrap();
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+
+    let mut expected = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(b"1-2");
+    keccak.finalize(&mut expected);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn keccak_xof_clone_forks_the_stream() {
+    let mut xof = Keccak::v256().chain(b"fork").into_xof();
+    let mut skipped = [0u8; 10];
+    xof.squeeze(&mut skipped);
+
+    let mut fork = xof.clone();
+    let mut original = [0u8; 20];
+    let mut forked = [0u8; 20];
+    xof.squeeze(&mut original);
+    fork.squeeze(&mut forked);
+    assert_eq!(original, forked);
+
+    let mut expected = [0u8; 30];
+    Keccak::v256()
+        .chain(b"fork")
+        .into_xof()
+        .squeeze(&mut expected);
+    assert_eq!(expected[10..], original);
+}
+
+#[test]
+fn keccak_small_updates_match_one_update() {
+    let input: Vec<u8> = (0..2000u32).map(|i| (i * 31 + 7) as u8).collect();
+
+    // every size from 1 to 9 bytes, so the fields start at every offset and straddle both the
+    // words and the end of the block
+    for step in 1..=9 {
+        for rate in [72, 136, 168] {
+            let mut whole = KeccakState::<KeccakF>::with_rate(rate, 0x01);
+            whole.update(&input);
+            let mut expected = [0u8; 64];
+            whole.finalize(&mut expected);
+
+            let mut fields = KeccakState::<KeccakF>::with_rate(rate, 0x01);
+            for field in input.chunks(step) {
+                fields.update(field);
+            }
+            let mut output = [0u8; 64];
+            fields.finalize(&mut output);
+            assert_eq!(expected, output, "step {} rate {}", step, rate);
+        }
+    }
+
+    let mut keccak = Keccak::v256();
+    let mut start = 0;
+    for i in 0.. {
+        let end = core::cmp::min(start + 1 + i * 7 % 8, input.len());
+        keccak.update(&input[start..end]);
+        start = end;
+        if start == input.len() {
+            break;
+        }
+    }
+    assert_eq!(keccak256(&input), keccak.finalize_array::<32>());
+}
+
+#[test]
+fn keccak256_pair_matches_concatenation() {
+    let leaves: Vec<[u8; 32]> = (0..8u8).map(|i| keccak256(&[i])).collect();
+    for (left, right) in leaves.iter().zip(leaves.iter().rev()) {
+        assert_eq!(
+            keccak256_pair(left, right),
+            keccak256(&[*left, *right].concat())
+        );
+    }
+
+    // a Merkle root built with the pair helper equals the one built by concatenating
+    let mut level = leaves.clone();
+    let mut concatenated = leaves;
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| keccak256_pair(&pair[0], &pair[1]))
+            .collect();
+        concatenated = concatenated
+            .chunks(2)
+            .map(|pair| keccak256(&pair.concat()))
+            .collect();
+    }
+    assert_eq!(level, concatenated);
+}
+
+const DOMAIN_TAG: [u8; 32] = keccak256_const(b"mydomain");
+const LONG_INPUT: [u8; 300] = [0xa5; 300];
+const LONG_DIGEST: [u8; 32] = keccak256_const(&LONG_INPUT);
+const PERMUTED_ZERO: [u64; 25] = keccakf_const([0; 25]);
+
+#[test]
+fn keccak256_const_matches_runtime() {
+    assert_eq!(DOMAIN_TAG, keccak256(b"mydomain"));
+    assert_eq!(LONG_DIGEST, keccak256(&LONG_INPUT));
+
+    // every padding position, including the exact multiples of the rate
+    let data = [0x5au8; 3 * 136 + 1];
+    for len in 0..data.len() {
+        assert_eq!(keccak256_const(&data[..len]), keccak256(&data[..len]));
+    }
+}
+
+#[test]
+fn keccakf_const_matches_keccakf() {
+    let mut state = [0u64; 25];
+    keccakf(&mut state);
+    assert_eq!(state, PERMUTED_ZERO);
+
+    let mut state: [u64; 25] =
+        core::array::from_fn(|i| (i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    let expected = {
+        let mut state = state;
+        keccakf(&mut state);
+        state
+    };
+    state = keccakf_const(state);
+    assert_eq!(state, expected);
+}
+
+#[test]
+fn keccak_keyed_separates_keys() {
+    let digest = |key: &[u8]| {
+        let mut keyed = Keccak::keyed(key);
+        keyed.update(b"the same message");
+        keyed.finalize_array::<32>()
+    };
+
+    assert_eq!(digest(b"key one"), digest(b"key one"));
+    assert_ne!(digest(b"key one"), digest(b"key two"));
+    assert_ne!(digest(b""), keccak256(b"the same message"));
+    assert_ne!(digest(&[7; 136]), digest(&[7; 135]));
+    assert_ne!(digest(b"key"), digest(b"key\0"));
+}
+
+#[test]
+fn keccak_keyed_is_not_a_prefix_hash() {
+    let mut keyed = Keccak::keyed(b"key one");
+    keyed.update(b"the same message");
+
+    // the zero padded key block, absorbed as a prefix by an unkeyed hasher
+    let mut block = [0u8; 136];
+    block[..7].copy_from_slice(b"key one");
+    let mut prefixed = Keccak::v256();
+    prefixed.update(&block);
+    prefixed.update(b"the same message");
+    assert_ne!(
+        keyed.finalize_array::<32>(),
+        prefixed.finalize_array::<32>()
+    );
+
+    let mut prefixed = Keccak::v256();
+    prefixed.update(&[0; 136]);
+    assert_ne!(
+        Keccak::keyed(b"").finalize_array::<32>(),
+        prefixed.finalize_array::<32>()
+    );
+}
+
+#[test]
+fn keccak_keyed_loses_its_key_on_reset() {
+    let keyed = Keccak::keyed(b"key").chain(b"first");
+    let mut output = [0u8; 32];
+
+    let mut finalized = keyed.clone();
+    finalized.finalize_reset(&mut output);
+    assert_eq!(
+        output,
+        Keccak::keyed(b"key").chain(b"first").finalize_array::<32>()
+    );
+    finalized.update(b"second");
+    assert_eq!(finalized.finalize_array::<32>(), keccak256(b"second"));
+
+    let mut slot = keyed;
+    let taken = slot.take();
+    assert_eq!(taken.finalize_array::<32>(), output);
+    slot.update(b"second");
+    assert_eq!(slot.finalize_array::<32>(), keccak256(b"second"));
+}
+
+#[test]
+#[should_panic(expected = "longer than the 136 byte block")]
+fn keccak_keyed_rejects_long_keys() {
+    Keccak::keyed(&[0; 137]);
+}
+
+#[test]
+fn keccak_with_delim_finalizes_in_another_domain() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"abc");
+
+    let sha3 = keccak.clone().with_delim(0x06);
+    assert_eq!(
+        keccak.with_delim(0x01).finalize_array::<32>(),
+        keccak256(b"abc")
+    );
+    // SHA3-256("abc") from FIPS 202
+    let expected = b"\
+        \x3a\x98\x5d\xa7\x4f\xe2\x25\xb2\x04\x5c\x17\x2d\x6b\xd3\x90\xbd\
+        \x85\x5f\x08\x6e\x3e\x9d\x52\x5b\x46\xbf\xe2\x45\x11\x43\x15\x32\
+    ";
+    assert_eq!(&sha3.finalize_array::<32>(), expected);
+}
+
+#[test]
+#[should_panic(expected = "cannot change the delimiter after padding")]
+fn keccak_state_with_delim_after_padding() {
+    let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    state.pad();
+    state.with_delim(0x06);
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn keccak_into_sha3_and_back() {
+    use tiny_keccak::{sha3_512, Sha3};
+
+    let sha3 = Keccak::v512().chain(b"abc").into_sha3();
+    assert_eq!(sha3.clone().finalize_array::<64>(), sha3_512(b"abc"));
+    assert_eq!(sha3.into_keccak().finalize_array::<64>(), keccak512(b"abc"));
+    assert_eq!(
+        Sha3::v256()
+            .chain(b"abc")
+            .into_keccak()
+            .finalize_array::<32>(),
+        keccak256(b"abc")
+    );
+}
+
+#[test]
+fn keccak_state_squeeze_block_continues_the_stream() {
+    let mut expected = [0u8; 5 * 168 + 10];
+    let mut state = KeccakState::<KeccakF>::with_rate(168, 0x1f);
+    state.update(b"block oriented");
+    state.clone().squeeze(&mut expected);
+
+    let mut output = Vec::new();
+    for _ in 0..5 {
+        output.extend_from_slice(&state.squeeze_block::<168>());
+    }
+    let mut tail = [0u8; 10];
+    state.squeeze(&mut tail);
+    output.extend_from_slice(&tail);
+    assert_eq!(output, expected[..]);
+
+    // and after a squeeze of whole blocks
+    let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+    let mut expected = [0u8; 3 * 136];
+    state.clone().squeeze(&mut expected);
+    state.squeeze(&mut [0u8; 136]);
+    assert_eq!(state.squeeze_block::<136>(), expected[136..272]);
+    assert_eq!(state.squeeze_block::<136>(), expected[272..]);
+}
+
+#[test]
+#[should_panic(expected = "the output is within a block")]
+fn keccak_state_squeeze_block_within_a_block() {
+    let mut state = KeccakState::<KeccakF>::with_rate(168, 0x1f);
+    state.squeeze(&mut [0u8; 10]);
+    state.squeeze_block::<168>();
+}
+
+#[test]
+#[should_panic(expected = "doesn't match the rate")]
+fn keccak_state_squeeze_block_of_another_rate() {
+    let mut state = KeccakState::<KeccakF>::with_rate(168, 0x1f);
+    state.squeeze_block::<136>();
+}
+
+/// A reference sponge over `keccakf`: pads a copy of the whole message with `pad10*1` first,
+/// then absorbs it a block at a time and squeezes `output` with the given rate.
+fn reference_sponge(rate: usize, delim: u8, input: &[u8], output: &mut [u8]) {
+    let mut padded = input.to_vec();
+    padded.push(delim);
+    padded.resize(padded.len().div_ceil(rate) * rate, 0);
+    *padded.last_mut().unwrap() ^= 0x80;
+
+    let mut state = [0u64; 25];
+    for block in padded.chunks(rate) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(bytes);
+            *lane ^= u64::from_le_bytes(word);
+        }
+        keccakf(&mut state);
+    }
+
+    for (i, chunk) in output.chunks_mut(rate).enumerate() {
+        if i > 0 {
+            keccakf(&mut state);
+        }
+        for (j, byte) in chunk.iter_mut().enumerate() {
+            *byte = state[j / 8].to_le_bytes()[j % 8];
+        }
+    }
+}
+
+#[test]
+fn padding_at_the_block_boundary() {
+    // the rates of Keccak/SHA-3 224, 256, 384 and 512 and of SHAKE128/256, with the Keccak,
+    // SHA-3 and SHAKE delimiters
+    for &rate in &[144, 136, 104, 72, 168] {
+        for &delim in &[0x01, 0x06, 0x1f] {
+            let data: Vec<u8> = (0..2 * rate + 1).map(|i| i as u8).collect();
+            for &len in &[
+                rate - 1,
+                rate,
+                rate + 1,
+                2 * rate - 1,
+                2 * rate,
+                2 * rate + 1,
+            ] {
+                let input = &data[..len];
+                // one block, the fast path of `finalize`, and more than one block of output
+                for &out_len in &[32, rate, rate + 1] {
+                    let mut expected = vec![0u8; out_len];
+                    reference_sponge(rate, delim, input, &mut expected);
+
+                    let mut state = KeccakState::<KeccakF>::with_rate(rate, delim);
+                    state.update(input);
+                    let mut output = vec![0u8; out_len];
+                    state.finalize(&mut output);
+                    assert_eq!(output, expected, "rate {} input {} bytes", rate, len);
+
+                    // the input split around the boundary, and squeezed instead of finalized
+                    let mut state = KeccakState::<KeccakF>::with_rate(rate, delim);
+                    let (head, tail) = input.split_at(rate - 1);
+                    state.update(head);
+                    state.update(tail);
+                    let mut output = vec![0u8; out_len];
+                    state.squeeze(&mut output);
+                    assert_eq!(output, expected, "rate {} input {} bytes", rate, len);
+                }
+            }
+        }
+    }
+
+    // states restored at the boundary finalize like the reference, a full block is rejected
+    for &len in &[135, 136, 271, 272] {
+        let input = [0x5au8; 272];
+        let mut expected = [0u8; 32];
+        reference_sponge(136, 0x01, &input[..len], &mut expected);
+
+        let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+        state.update(&input[..len]);
+        let restored = [
+            KeccakState::<KeccakF>::from_parts(*state.as_words(), state.offset(), 136, 0x01),
+            KeccakState::<KeccakF>::from_bytes(&state.to_bytes()).unwrap(),
+            core::convert::TryFrom::try_from(&state.to_bytes()[..]).unwrap(),
+        ];
+        for state in restored.iter() {
+            let mut output = [0u8; 32];
+            state.clone().finalize(&mut output);
+            assert_eq!(output, expected, "input {} bytes", len);
+        }
+
+        let mut full = state.to_bytes();
+        full[200..208].copy_from_slice(&136u64.to_le_bytes());
+        assert_eq!(
+            KeccakState::<KeccakF>::from_bytes(&full).err(),
+            Some(KeccakError::InvalidCheckpoint)
+        );
+    }
+
+    // the digests of messages filling exactly one block
+    let block = [0x5au8; 136];
+    let mut expected = [0u8; 32];
+    reference_sponge(136, 0x01, &block, &mut expected);
+    assert_eq!(keccak256(&block), expected);
+    let block = [0x5au8; 144];
+    let mut expected = [0u8; 28];
+    reference_sponge(144, 0x01, &block, &mut expected);
+    assert_eq!(keccak224(&block), expected);
+    let block = [0x5au8; 104];
+    let mut expected = [0u8; 48];
+    reference_sponge(104, 0x01, &block, &mut expected);
+    assert_eq!(keccak384(&block), expected);
+    let block = [0x5au8; 72];
+    let mut expected = [0u8; 64];
+    reference_sponge(72, 0x01, &block, &mut expected);
+    assert_eq!(keccak512(&block), expected);
+}
+
+#[test]
+fn keccak_builder_matches_v256() {
+    let built = [
+        KeccakBuilder::new().build().unwrap(),
+        KeccakBuilder::new().output_len(32).build().unwrap(),
+        KeccakBuilder::new().rate(136).build().unwrap(),
+        KeccakBuilder::new()
+            .rate(136)
+            .delim(0x01)
+            .output_len(32)
+            .build()
+            .unwrap(),
+    ];
+    let expected = Keccak::v256().chain(b"hello world").finalize_array::<32>();
+    for keccak in built.iter() {
+        assert_eq!(
+            keccak.clone().chain(b"hello world").finalize_array::<32>(),
+            expected
+        );
+    }
+
+    let v512 = KeccakBuilder::new().output_len(64).build().unwrap();
+    assert_eq!(v512.finalize_array::<64>()[..], keccak512(b"")[..]);
+}
+
+#[test]
+fn keccak_builder_rejects_invalid_parameters() {
+    assert_eq!(
+        KeccakBuilder::new().rate(137).build().err(),
+        Some(KeccakError::InvalidRate { rate: 137 })
+    );
+    assert_eq!(
+        KeccakBuilder::new().rate(200).build().err(),
+        Some(KeccakError::InvalidRate { rate: 200 })
+    );
+    // a digest of 30 bytes leaves a rate of 140 bytes, not a whole number of lanes
+    for &len in &[0, 21, 30, 100, usize::MAX] {
+        assert_eq!(
+            KeccakBuilder::new().output_len(len).build().err(),
+            Some(KeccakError::InvalidOutputLen { len })
+        );
+    }
+    assert_eq!(
+        KeccakError::InvalidOutputLen { len: 21 }.to_string(),
+        "no sponge rate has a digest of 21 bytes"
+    );
+    assert_eq!(
+        KeccakBuilder::new().rate(136).output_len(33).build().err(),
+        Some(KeccakError::OutputTooLong { max: 32, len: 33 })
+    );
+    // the digest of the rate would be longer than the configured one
+    assert_eq!(
+        KeccakBuilder::new().rate(136).output_len(20).build().err(),
+        Some(KeccakError::OutputSizeMismatch {
+            expected: 32,
+            len: 20
+        })
+    );
+}
+
+#[test]
+fn keccak_finalize_scattered() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"header fields");
+    let expected = keccak.clone().finalize_array::<32>();
+
+    let mut first = [0u8; 10];
+    let mut second = [0u8; 22];
+    keccak
+        .clone()
+        .finalize_scattered(&mut [&mut first, &mut second]);
+    assert_eq!(first[..], expected[..10]);
+    assert_eq!(second[..], expected[10..]);
+
+    // the sponge squeezes the slices in turn, across the blocks of the output
+    let mut state = KeccakState::<KeccakF>::with_rate(136, 0x1f);
+    state.update(b"header fields");
+    let mut expected = [0u8; 300];
+    state.clone().finalize(&mut expected);
+    let mut output = [0u8; 300];
+    let (a, rest) = output.split_at_mut(10);
+    let (b, rest) = rest.split_at_mut(0);
+    let (c, d) = rest.split_at_mut(130);
+    state.finalize_scattered(&mut [a, b, c, d]);
+    assert_eq!(output[..], expected[..]);
+}
+
+#[test]
+fn keccak_checkpoint_matches_reabsorbing_the_prefix() {
+    // prefixes ending within, and exactly at the end of, a block
+    for prefix_len in [100, 136, 1024].iter() {
+        let prefix = vec![0xa5u8; *prefix_len];
+        let mut keccak = Keccak::v256();
+        keccak.update(&prefix);
+        let checkpoint = keccak.checkpoint();
+
+        let mut state = KeccakState::<KeccakF>::with_rate(136, 0x01);
+        state.update(&prefix);
+        let state_checkpoint = state.checkpoint();
+
+        for nonce in 0u64..1000 {
+            let expected = keccak256(&[&prefix[..], &nonce.to_le_bytes()].concat());
+
+            keccak.restore_from(&checkpoint);
+            keccak.update(&nonce.to_le_bytes());
+            let mut digest = [0u8; 32];
+            keccak.finalize_reset(&mut digest);
+            assert_eq!(digest, expected, "nonce {}", nonce);
+
+            // restoring over a squeezed state
+            state.restore_from(&state_checkpoint);
+            state.update(&nonce.to_le_bytes());
+            state.squeeze(&mut digest);
+            assert_eq!(digest, expected, "nonce {}", nonce);
+        }
+        // the checkpoint itself is untouched by the searches restored from it
+        state.restore_from(&state_checkpoint);
+        assert!(state == state_checkpoint);
+        state.update(&prefix);
+        assert!(state != state_checkpoint);
+    }
+}
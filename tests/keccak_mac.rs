@@ -0,0 +1,47 @@
+use tiny_keccak::{Hasher, Keccak, KeccakMac};
+
+fn key() -> Vec<u8> {
+    (0..32).collect()
+}
+
+#[test]
+fn keccak_mac_known_tag() {
+    let expected = b"\
+        \xee\xf9\x9c\x3e\xe4\x45\x46\x19\xa1\xaa\x9b\x93\x0d\x3d\x0e\x44\
+        \xc8\x5d\x6f\x2f\x5f\x14\x3d\x48\xc7\x99\x46\x03\x8a\x31\x9e\xdd\
+    ";
+
+    let mut mac = KeccakMac::new(&key());
+    mac.update(b"The quick brown fox ");
+    mac.update(b"jumps over the lazy dog");
+    let mut tag = [0u8; 32];
+    mac.clone().finalize(&mut tag);
+    assert_eq!(expected, &tag);
+
+    // the tag is the digest of the key followed by the message
+    let mut digest = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(&key());
+    keccak.update(b"The quick brown fox jumps over the lazy dog");
+    keccak.finalize(&mut digest);
+    assert_eq!(digest, tag);
+
+    assert!(mac.verify(expected));
+}
+
+#[test]
+fn keccak_mac_rejects_wrong_tags() {
+    let mut tag = [0u8; 32];
+    let mut mac = KeccakMac::new(&key());
+    mac.update(b"message");
+    mac.clone().finalize(&mut tag);
+
+    let mut flipped = tag;
+    flipped[17] ^= 0x04;
+    assert!(!mac.clone().verify(&flipped));
+    assert!(!mac.clone().verify(&tag[..31]));
+    assert!(!KeccakMac::new(b"another key")
+        .chain(b"message")
+        .verify(&tag));
+    assert!(mac.verify(&tag));
+}
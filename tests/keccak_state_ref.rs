@@ -0,0 +1,42 @@
+//! The borrowed state doesn't need `std`, the test itself only uses `core`.
+#![no_std]
+
+use tiny_keccak::{bits_to_rate, KeccakF, KeccakStateRef};
+
+#[test]
+fn keccak_state_ref_abc() {
+    let expected = b"\
+        \x4e\x03\x65\x7a\xea\x45\xa9\x4f\xc7\xd4\x7b\xa8\x26\xc8\xd6\x67\
+        \xc0\xd1\xe6\xe3\x3a\x64\xa0\x36\xec\x44\xf5\x8f\xa1\x2d\x6c\x45\
+    ";
+
+    let mut scratch = [0u64; 25];
+    let mut state = KeccakStateRef::<KeccakF>::new(&mut scratch, bits_to_rate(256), 0x01);
+    state.update(b"ab");
+    state.update(b"c");
+    let mut output = [0u8; 32];
+    state.finalize(&mut output);
+    assert_eq!(expected, &output);
+
+    // the permuted state was written to the caller's words
+    assert_ne!(scratch, [0u64; 25]);
+}
+
+#[test]
+fn keccak_state_ref_zeroes_scratch_and_continues_squeezing() {
+    let mut scratch = [0u64; 25];
+    let mut state = KeccakStateRef::<KeccakF>::new(&mut scratch, bits_to_rate(256), 0x1f);
+    state.update(&[0x5a; 300]);
+    let mut output = [0u8; 400];
+    state.finalize(&mut output);
+
+    let mut scratch = [u64::MAX; 25];
+    let mut state = KeccakStateRef::<KeccakF>::new(&mut scratch, bits_to_rate(256), 0x1f);
+    state.update(&[0x5a; 300]);
+    let mut first = [0u8; 100];
+    let mut second = [0u8; 300];
+    state.squeeze(&mut first);
+    state.squeeze(&mut second);
+    assert_eq!(output[..100], first[..]);
+    assert_eq!(output[100..], second[..]);
+}
@@ -0,0 +1,130 @@
+use tiny_keccak::{backend, keccakf, keccakf200, keccakf400, keccakf800, KeccakLane};
+
+#[test]
+fn keccakf_zero_state() {
+    let mut state = [0u64; 25];
+    let expected = [
+        0xf1258f7940e1dde7,
+        0x84d5ccf933c0478a,
+        0xd598261ea65aa9ee,
+        0xbd1547306f80494d,
+        0x8b284e056253d057,
+        0xff97a42d7f8e6fd4,
+        0x90fee5a0a44647c4,
+        0x8c5bda0cd6192e76,
+        0xad30a6f71b19059c,
+        0x30935ab7d08ffc64,
+        0xeb5aa93f2317d635,
+        0xa9a6e6260d712103,
+        0x81a57c16dbcf555f,
+        0x43b831cd0347c826,
+        0x01f22f1a11a5569f,
+        0x05e5635a21d9ae61,
+        0x64befef28cc970f2,
+        0x613670957bc46611,
+        0xb87c5a554fd00ecb,
+        0x8c3ee88a1ccf32c8,
+        0x940c7922ae3a2614,
+        0x1841f924a2c509e4,
+        0x16f53526e70465c2,
+        0x75f644e97f30a13b,
+        0xeaf1ff7b5ceca249,
+    ];
+
+    keccakf(&mut state);
+    assert_eq!(expected, state);
+}
+
+#[test]
+fn keccakf800_zero_state() {
+    let mut state = [0u32; 25];
+    let expected = [
+        0xe531d45d, 0xf404c6fb, 0x23a0bf99, 0xf1f8452f, 0x51ffd042, 0xe539f578, 0xf00b80a7,
+        0xaf973664, 0xbf5af34c, 0x227a2424, 0x88172715, 0x9f685884, 0xb15cd054, 0x1bf4fc0e,
+        0x6166fa91, 0x1a9e599a, 0xa3970a1f, 0xab659687, 0xafab8d68, 0xe74b1015, 0x34001a98,
+        0x4119eff3, 0x930a0e76, 0x87b28070, 0x11efe996,
+    ];
+
+    keccakf800(&mut state);
+    assert_eq!(expected, state);
+}
+
+#[test]
+fn keccakf400_zero_state() {
+    let mut state = [0u16; 25];
+    let expected = [
+        0x09f5, 0x40ac, 0x0fa9, 0x14f5, 0xe89f, 0xeca0, 0x5bd1, 0x7870, 0xeff0, 0xbf8f, 0x0337,
+        0x6052, 0xdc75, 0x0ec9, 0xe776, 0x5246, 0x59a1, 0x5d81, 0x6d95, 0x6e14, 0x633e, 0x58ee,
+        0x71ff, 0x714c, 0xb38e,
+    ];
+
+    keccakf400(&mut state);
+    assert_eq!(expected, state);
+}
+
+#[test]
+fn keccakf200_zero_state() {
+    let mut state = [0u8; 25];
+    let expected = [
+        0x3c, 0x28, 0x26, 0x84, 0x1c, 0xb3, 0x5c, 0x17, 0x1e, 0xaa, 0xe9, 0xb8, 0x11, 0x13, 0x4c,
+        0xea, 0xa3, 0x85, 0x2c, 0x69, 0xd2, 0xc5, 0xab, 0xaf, 0xea,
+    ];
+
+    keccakf200(&mut state);
+    assert_eq!(expected, state);
+}
+
+#[test]
+fn keccak_lane_u64_matches_keccakf() {
+    let mut state = [0u64; 25];
+    for (i, lane) in state.iter_mut().enumerate() {
+        *lane = (i as u64).wrapping_mul(0x9e3779b97f4a7c15);
+    }
+    let mut expected = state;
+
+    keccakf(&mut expected);
+    u64::keccak_f(&mut state);
+    assert_eq!(expected, state);
+}
+
+#[test]
+fn backend_is_known() {
+    let backend = backend();
+    assert!(["avx2", "neon", "scalar"].contains(&backend));
+    if cfg!(not(any(feature = "avx2", feature = "neon"))) {
+        assert_eq!(backend, "scalar");
+    }
+
+    #[cfg(all(feature = "avx2", target_arch = "x86_64"))]
+    assert_eq!(backend == "avx2", std::is_x86_feature_detected!("avx2"));
+}
+
+#[cfg(feature = "unstable-trace")]
+#[test]
+fn keccakf_traced_zero_state() {
+    let mut states = Vec::new();
+    let mut state = [0u64; 25];
+    tiny_keccak::KeccakF::execute_traced(&mut state, |round, state| {
+        assert_eq!(round, states.len());
+        states.push(*state);
+    });
+    assert_eq!(states.len(), 24);
+
+    // the first round of the zero state only applies iota
+    let mut first = [0u64; 25];
+    first[0] = 1;
+    assert_eq!(states[0], first);
+
+    // the states after the second and the last round, computed independently
+    assert_eq!(states[1][0], 0x0000000000008083);
+    assert_eq!(states[1][1], 0x0000100000000000);
+    assert_eq!(states[1][24], 0x0000000000000004);
+    assert_eq!(states[23][0], 0xf1258f7940e1dde7);
+    assert_eq!(states[23][1], 0x84d5ccf933c0478a);
+    assert_eq!(states[23][24], 0xeaf1ff7b5ceca249);
+
+    let mut expected = [0u64; 25];
+    keccakf(&mut expected);
+    assert_eq!(states[23], expected);
+    assert_eq!(state, expected);
+}
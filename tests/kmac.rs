@@ -1,4 +1,5 @@
-use tiny_keccak::{Hasher, IntoXof, Kmac, Xof};
+use tiny_keccak::encode::{bytepad, encode_string, right_encode};
+use tiny_keccak::{CShake, Hasher, IntoXof, KeccakError, Kmac, Xof};
 
 #[test]
 fn test_kmac128_one() {
@@ -331,3 +332,125 @@ fn test_kmac256_xof_three() {
     xof.squeeze(&mut output);
     assert_eq!(expected as &[u8], &output as &[u8]);
 }
+
+#[test]
+fn test_kmac_verify() {
+    let key = b"\
+        \x40\x41\x42\x43\x44\x45\x46\x47\x48\x49\x4A\x4B\x4C\x4D\x4E\x4F\
+        \x50\x51\x52\x53\x54\x55\x56\x57\x58\x59\x5A\x5B\x5C\x5D\x5E\x5F\
+    ";
+    let data = b"\x00\x01\x02\x03";
+    let custom = b"My Tagged Application";
+    let tag = b"\
+        \x3B\x1F\xBA\x96\x3C\xD8\xB0\xB5\x9E\x8C\x1A\x6D\x71\x88\x8B\x71\
+        \x43\x65\x1A\xF8\xBA\x0A\x70\x70\xC0\x97\x9E\x28\x11\x32\x4A\xA5\
+    ";
+    let mut kmac = Kmac::v128(key, custom);
+    kmac.update(data);
+    assert!(kmac.clone().verify(tag));
+    assert!(!kmac.clone().verify(&tag[..31]));
+
+    let mut forged = *tag;
+    forged[31] ^= 1;
+    assert!(!kmac.verify(&forged));
+}
+
+#[test]
+fn test_kmac_verify_rejects_short_tags() {
+    let mut kmac = Kmac::v128(b"key", b"custom");
+    kmac.update(b"hello world");
+    let mut tag = [0u8; 16];
+    kmac.clone().finalize(&mut tag);
+    assert!(kmac.verify(&tag));
+
+    // the empty tag of a zero length KMAC, and tags below the minimum, are rejected
+    let mut short = [0u8; 8];
+    kmac.clone().finalize(&mut short);
+    assert!(!kmac.verify(&[]));
+    assert!(!kmac.verify(&short));
+    assert!(!kmac.verify(&tag[..15]));
+
+    // unless the caller lowers the minimum, which never accepts empty tags
+    assert!(kmac.verify_min_len(&short, 8));
+    assert!(!kmac.verify_min_len(&[], 0));
+}
+
+#[test]
+fn test_kmac_verify_long_tag() {
+    let mut kmac = Kmac::v256(b"key", b"custom");
+    kmac.update(b"hello world");
+    let mut tag = [0u8; 200];
+    kmac.clone().finalize(&mut tag);
+    assert!(kmac.clone().verify(&tag));
+
+    tag[150] ^= 0x80;
+    assert!(!kmac.verify(&tag));
+}
+
+#[test]
+fn test_kmac128_from_encode_helpers() {
+    let key = b"my secret key";
+    let mut expected = [0u8; 32];
+    let mut kmac = Kmac::v128(key, b"");
+    kmac.update(b"hello world");
+    kmac.finalize(&mut expected);
+
+    let mut output = [0u8; 32];
+    let mut cshake = CShake::v128(b"KMAC", b"");
+    bytepad(&mut cshake, &encode_string(key).parts(), 168);
+    cshake.update(b"hello world");
+    cshake.update(right_encode(output.len() * 8).value());
+    cshake.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn test_kmac_finalize_scattered_long_output() {
+    let mut kmac = Kmac::v256(b"key", b"custom");
+    kmac.update(b"hello world");
+    let mut expected = [0u8; 320];
+    kmac.clone().finalize(&mut expected);
+
+    // the slices together are one output of 320 bytes, whose length is encoded
+    let mut key = [0u8; 32];
+    let mut stream = [0u8; 288];
+    kmac.finalize_scattered(&mut [&mut key, &mut stream]);
+    assert_eq!(key[..], expected[..32]);
+    assert_eq!(stream[..], expected[32..]);
+}
+
+#[test]
+fn kmac_xof_into_state() {
+    let mut drained = [0u8; 300];
+    let mut xof = Kmac::v128(b"key", b"custom");
+    xof.update(b"message");
+    let mut xof = xof.into_xof();
+    let mut expected = [0u8; 332];
+    xof.clone().squeeze(&mut expected);
+    xof.squeeze(&mut drained);
+
+    // the state keeps squeezing where the reader stopped
+    let mut state = xof.into_state();
+    let mut next = [0u8; 32];
+    state.squeeze(&mut next);
+    assert_eq!(expected[300..], next);
+}
+
+#[test]
+fn test_kmac_try_new() {
+    let mut expected = [0u8; 32];
+    Kmac::v128(b"key", b"custom")
+        .chain(b"input")
+        .finalize(&mut expected);
+    let mut output = [0u8; 32];
+    Kmac::try_new(b"key", b"custom", 128)
+        .unwrap()
+        .chain(b"input")
+        .finalize(&mut output);
+    assert_eq!(output, expected);
+
+    assert_eq!(
+        Kmac::try_new(b"key", b"custom", 384).err(),
+        Some(KeccakError::InvalidSecurityLevel { bits: 384 })
+    );
+}
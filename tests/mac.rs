@@ -0,0 +1,73 @@
+use tiny_keccak::digest::{KeyInit, Mac};
+use tiny_keccak::{Hasher, HmacSha3, HmacSha3_256, HmacSha3_512, Kmac, Kmac128, Kmac256};
+
+/// Stands in for code generic over the `Mac` trait.
+fn tag<M: Mac + KeyInit>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = <M as Mac>::new_from_slice(key).unwrap();
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn verify<M: Mac + KeyInit>(key: &[u8], message: &[u8], tag: &[u8]) -> bool {
+    let mut mac = <M as Mac>::new_from_slice(key).unwrap();
+    mac.update(message);
+    mac.verify_slice(tag).is_ok()
+}
+
+#[test]
+fn mac_kmac() {
+    let key = b"a key of any length";
+
+    let mut expected = [0u8; 32];
+    Kmac::v128(key, b"")
+        .chain(b"hello world")
+        .finalize(&mut expected);
+    assert_eq!(tag::<Kmac128>(key, b"hello world"), expected);
+
+    let mut expected = [0u8; 64];
+    Kmac::v256(key, b"")
+        .chain(b"hello world")
+        .finalize(&mut expected);
+    assert_eq!(tag::<Kmac256>(key, b"hello world"), expected);
+
+    // the key length is only a recommendation
+    let key = [0x42u8; 32];
+    assert_eq!(
+        tag::<Kmac128>(&key, b""),
+        <Kmac128 as KeyInit>::new(&key.into())
+            .finalize()
+            .into_bytes()
+            .to_vec()
+    );
+}
+
+#[test]
+fn mac_kmac_verify_slice() {
+    let key = b"key";
+    let mut tag = tag::<Kmac128>(key, b"hello world");
+    assert!(verify::<Kmac128>(key, b"hello world", &tag));
+
+    tag[31] ^= 0x01;
+    assert!(!verify::<Kmac128>(key, b"hello world", &tag));
+    assert!(!verify::<Kmac128>(key, b"hello world", &tag[..16]));
+}
+
+#[test]
+fn mac_hmac_sha3() {
+    let key = b"key";
+
+    let mut expected = [0u8; 32];
+    HmacSha3::v256(key)
+        .chain(b"hello world")
+        .finalize(&mut expected);
+    let tag256 = tag::<HmacSha3_256>(key, b"hello world");
+    assert_eq!(tag256, expected);
+    assert!(verify::<HmacSha3_256>(key, b"hello world", &tag256));
+    assert!(!verify::<HmacSha3_256>(key, b"hello world!", &tag256));
+
+    let mut expected = [0u8; 64];
+    HmacSha3::v512(key)
+        .chain(b"hello world")
+        .finalize(&mut expected);
+    assert_eq!(tag::<HmacSha3_512>(key, b"hello world"), expected);
+}
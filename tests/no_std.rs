@@ -0,0 +1,44 @@
+//! The one-shot and incremental APIs using only `core` and stack buffers.
+//!
+//! The library itself is checked for `std` leaks by building it for a target without `std`:
+//! `cargo build --lib --target thumbv7em-none-eabi --features fips202`.
+
+#![no_std]
+
+use tiny_keccak::{keccak256, sha3_256, Hasher, Keccak, Sha3};
+
+#[test]
+fn keccak256_on_the_stack() {
+    let input = [0xa3u8; 200];
+    let mut expected = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(&input[..100]);
+    keccak.update(&input[100..]);
+    keccak.finalize(&mut expected);
+
+    // `Keccak-256` of the 1600-bit message of the NIST examples, computed independently
+    let known = b"\
+        \x3a\x57\x66\x6b\x04\x87\x77\xf2\xc9\x53\xdc\x44\x56\xf4\x5a\x25\
+        \x88\xe1\xcb\x6f\x2d\xa7\x60\x12\x2d\x53\x0a\xc2\xce\x60\x7d\x4a\
+    ";
+    assert_eq!(&expected, known);
+    assert_eq!(keccak256(&input), expected);
+}
+
+#[test]
+fn sha3_256_on_the_stack() {
+    let input = [0xa3u8; 200];
+    let mut expected = [0u8; 32];
+    let mut sha3 = Sha3::v256();
+    sha3.update(&input[..100]);
+    sha3.update(&input[100..]);
+    sha3.finalize(&mut expected);
+
+    // the `SHA3-256` example for the 1600-bit message of the NIST examples
+    let known = b"\
+        \x79\xf3\x8a\xde\xc5\xc2\x03\x07\xa9\x8e\xf7\x6e\x83\x24\xaf\xbf\
+        \xd4\x6c\xfd\x81\xb2\x2e\x39\x73\xc6\x5f\xa1\xbd\x9d\xe3\x17\x87\
+    ";
+    assert_eq!(&expected, known);
+    assert_eq!(sha3_256(&input), expected);
+}
@@ -0,0 +1,52 @@
+use tiny_keccak::{deterministic_nonce, Hasher, Shake, Xof};
+
+const PRIVATE_KEY: [u8; 32] = [0x11; 32];
+const MESSAGE_HASH: [u8; 32] = [0x22; 32];
+
+fn stream(len: usize) -> Vec<u8> {
+    let mut shake = Shake::v256();
+    shake.update(&PRIVATE_KEY);
+    shake.update(&MESSAGE_HASH);
+    let mut output = vec![0u8; len];
+    shake.squeeze(&mut output);
+    output
+}
+
+#[test]
+fn nonce_is_deterministic() {
+    let mut first = [0u8; 32];
+    let mut second = [0u8; 32];
+    deterministic_nonce(&PRIVATE_KEY, &MESSAGE_HASH, 256, &mut first, |_| true);
+    deterministic_nonce(&PRIVATE_KEY, &MESSAGE_HASH, 256, &mut second, |_| true);
+    assert_eq!(first, second);
+    assert_eq!(first[..], stream(32)[..]);
+
+    let mut other = [0u8; 32];
+    deterministic_nonce(&PRIVATE_KEY, &[0x23; 32], 256, &mut other, |_| true);
+    assert_ne!(first, other);
+}
+
+#[test]
+fn nonce_rejected_candidates_are_resqueezed() {
+    let mut candidates = Vec::new();
+    let mut nonce = [0u8; 21];
+    deterministic_nonce(&PRIVATE_KEY, &MESSAGE_HASH, 163, &mut nonce, |candidate| {
+        candidates.push(candidate.to_vec());
+        candidates.len() == 3
+    });
+    assert_eq!(candidates.len(), 3);
+
+    // the candidates are consecutive 21 byte chunks of the stream, masked to 163 bits
+    let stream = stream(63);
+    for (candidate, chunk) in candidates.iter().zip(stream.chunks(21)) {
+        assert_eq!(candidate[0], chunk[0] & 0x07);
+        assert_eq!(candidate[1..], chunk[1..]);
+    }
+    assert_eq!(nonce[..], candidates[2][..]);
+}
+
+#[test]
+#[should_panic(expected = "nonce must be 255 bits long")]
+fn nonce_length_mismatch() {
+    deterministic_nonce(&PRIVATE_KEY, &MESSAGE_HASH, 255, &mut [0u8; 31], |_| true);
+}
@@ -1,4 +1,4 @@
-use tiny_keccak::{Hasher, ParallelHash};
+use tiny_keccak::{CShake, ChainingHasher, Hasher, IntoXof, ParallelHash, ParallelHashXof, Xof};
 
 #[test]
 fn test_parallel_hash128_one() {
@@ -121,3 +121,217 @@ fn test_parallel_hash256_three() {
     phash.finalize(&mut output);
     assert_eq!(expected as &[u8], &output as &[u8]);
 }
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 % 251) as u8).collect()
+}
+
+#[test]
+fn test_parallel_hash128_sample_in_partial_blocks() {
+    // sample #2 of SP 800-185, absorbed in updates that end within the blocks
+    let input = b"\
+        \x00\x01\x02\x03\x04\x05\x06\x07\x10\x11\x12\x13\
+        \x14\x15\x16\x17\x20\x21\x22\x23\x24\x25\x26\x27\
+    ";
+    let expected = b"\
+        \xFC\x48\x4D\xCB\x3F\x84\xDC\xEE\xDC\x35\x34\x38\x15\x1B\xEE\x58\
+        \x15\x7D\x6E\xFE\xD0\x44\x5A\x81\xF1\x65\xE4\x95\x79\x5B\x72\x06\
+    ";
+    let mut output = [0u8; 32];
+    let mut phash = ParallelHash::v128(b"Parallel Data", 8);
+    for chunk in input.chunks(5) {
+        phash.update(chunk);
+    }
+    phash.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+// The samples of SP 800-185 only hash whole blocks. The expected values of the inputs that end
+// within a block are computed with an implementation independent of this crate, which
+// reproduces all the samples.
+
+#[test]
+fn test_parallel_hash128_partial_block() {
+    let input = pattern(1000);
+    let expected = b"\
+        \xf8\x89\x23\x6a\x17\x5f\x02\x34\x77\x07\xc9\xf2\xfa\x84\xe4\xb2\
+        \x57\x56\x02\x0f\x36\xcb\xc2\x98\xac\xba\x90\x64\xce\x30\x74\x00\
+    ";
+
+    let mut output = [0u8; 32];
+    let mut phash = ParallelHash::v128(b"custom", 64);
+    phash.update(&input);
+    phash.finalize(&mut output);
+    assert_eq!(expected, &output);
+
+    let mut output = [0u8; 32];
+    let mut phash = ParallelHash::v128(b"custom", 64);
+    for chunk in input.chunks(7) {
+        phash.update(chunk);
+    }
+    phash.finalize(&mut output);
+    assert_eq!(expected, &output);
+
+    let mut output = [0xffu8; 32];
+    let mut phash = ParallelHash::v128(b"custom", 64);
+    phash.update(&input);
+    phash.finalize_xor(&mut output);
+    for (output, expected) in output.iter().zip(expected.iter()) {
+        assert_eq!(*output, !expected);
+    }
+}
+
+#[test]
+fn test_parallel_hash256_partial_block() {
+    let input = pattern(101);
+    let expected = b"\
+        \xab\x95\x92\x7b\x56\xda\x2d\x8e\x1e\xe0\x46\x64\xa4\x70\x41\xf6\
+        \xe5\xe5\x7c\xae\x7f\x07\xf0\xb6\x97\xe6\x92\x26\xb6\x47\xac\x52\
+        \xd2\x23\x48\x5c\xa1\x3f\xc3\x68\x5d\xb0\x9b\x63\x58\xa1\xdb\x95\
+        \xd2\x02\x39\xe8\xd7\x09\x7d\x2f\xe7\x78\xbe\x62\x20\xaf\xdf\x3b\
+    ";
+
+    let mut output = [0u8; 64];
+    let mut phash = ParallelHash::v256(b"", 8);
+    for chunk in input.chunks(13) {
+        phash.update(chunk);
+    }
+    phash.finalize(&mut output);
+    assert_eq!(&expected[..], &output[..]);
+}
+
+#[test]
+fn test_parallel_hash_is_send_sync() {
+    fn _assert_send_sync<T: Send + Sync>() {}
+    _assert_send_sync::<ParallelHash>();
+    _assert_send_sync::<ParallelHashXof>();
+}
+
+#[test]
+fn test_parallel_hash_large_input() {
+    let block_size = 1024;
+    let input: Vec<u8> = (0..1024 * 1024 + 77).map(|i| (i % 251) as u8).collect();
+
+    // all the blocks in one update, hashed across threads with the `parallel` feature
+    let mut threaded = [0u8; 64];
+    let mut hasher = ParallelHash::v128(b"custom", block_size);
+    hasher.update(&input);
+    hasher.finalize(&mut threaded);
+
+    // a single block per update
+    let mut serial = [0u8; 64];
+    let mut hasher = ParallelHash::v128(b"custom", block_size);
+    for chunk in input.chunks(block_size) {
+        hasher.update(chunk);
+    }
+    hasher.finalize(&mut serial);
+    assert_eq!(threaded, serial);
+
+    // the definition in SP800-185
+    let mut expected = [0u8; 64];
+    let mut cshake = CShake::v128(b"ParallelHash", b"custom");
+    cshake.update(&[2, 4, 0]);
+    for chunk in input.chunks(block_size) {
+        let mut digest = [0u8; 32];
+        let mut block = CShake::v128(b"", b"");
+        block.update(chunk);
+        block.finalize(&mut digest);
+        cshake.update(&digest);
+    }
+    let blocks = input.chunks(block_size).count();
+    assert_eq!(blocks, 1025);
+    cshake.update(&[4, 1, 2]);
+    cshake.update(&[2, 0, 2]);
+    cshake.finalize(&mut expected);
+    assert_eq!(threaded, expected);
+}
+
+#[test]
+fn test_chaining_hasher_three_chunks() {
+    let input = b"\
+        \x00\x01\x02\x03\x04\x05\x06\x07\x10\x11\x12\x13\
+        \x14\x15\x16\x17\x20\x21\x22\x23\x24\x25\x26\x27\
+    ";
+    let expected = b"\
+        \xFC\x48\x4D\xCB\x3F\x84\xDC\xEE\xDC\x35\x34\x38\x15\x1B\xEE\x58\
+        \x15\x7D\x6E\xFE\xD0\x44\x5A\x81\xF1\x65\xE4\x95\x79\x5B\x72\x06\
+    ";
+
+    let tree = ChainingHasher::v128(b"Parallel Data", 8);
+    let chaining_values: Vec<[u8; 32]> = input
+        .chunks(8)
+        .map(|chunk| tree.chaining_value(chunk))
+        .collect();
+    assert_eq!(chaining_values.len(), 3);
+    let mut output = [0u8; 32];
+    tree.combine(&chaining_values, &mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn test_chaining_hasher_on_threads() {
+    let input: Vec<u8> = (0..2500u32).map(|i| (i * 11) as u8).collect();
+    let tree = ChainingHasher::v256(b"tree", 1000);
+
+    // a short last chunk
+    let handles: Vec<_> = input
+        .chunks(1000)
+        .map(|chunk| {
+            let tree = tree.clone();
+            let chunk = chunk.to_vec();
+            std::thread::spawn(move || tree.chaining_value(&chunk))
+        })
+        .collect();
+    let chaining_values: Vec<[u8; 64]> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+    let mut output = [0u8; 100];
+    tree.combine(&chaining_values, &mut output);
+
+    let mut expected = [0u8; 100];
+    ParallelHash::v256(b"tree", 1000)
+        .chain(&input)
+        .finalize(&mut expected);
+    assert_eq!(expected[..], output[..]);
+}
+
+#[test]
+#[should_panic(expected = "chunk is longer than the block size")]
+fn test_chaining_hasher_rejects_long_chunks() {
+    ChainingHasher::v128(b"", 8).chaining_value(&[0u8; 9]);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_hash_update_rayon() {
+    let input: Vec<u8> = (0..16 * 1024 * 1024 + 5).map(|i| (i % 251) as u8).collect();
+
+    let mut expected = [0u8; 32];
+    let mut hasher = ParallelHash::v128(b"", 8192);
+    for chunk in input.chunks(1000) {
+        hasher.update(chunk);
+    }
+    hasher.finalize(&mut expected);
+
+    let mut output = [0u8; 32];
+    let mut hasher = ParallelHash::v128(b"", 8192);
+    hasher.update_rayon(&input);
+    hasher.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn parallel_hash_xof_into_state() {
+    let mut drained = [0u8; 300];
+    let mut xof = ParallelHash::v128(b"custom", 8);
+    xof.update(b"message");
+    let mut xof = xof.into_xof();
+    let mut expected = [0u8; 332];
+    xof.clone().squeeze(&mut expected);
+    xof.squeeze(&mut drained);
+
+    // the state keeps squeezing where the reader stopped
+    let mut state = xof.into_state();
+    let mut next = [0u8; 32];
+    state.squeeze(&mut next);
+    assert_eq!(expected[300..], next);
+}
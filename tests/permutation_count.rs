@@ -0,0 +1,43 @@
+use tiny_keccak::{bits_to_rate, KeccakF, KeccakState};
+
+fn keccak256() -> KeccakState<KeccakF> {
+    KeccakState::with_rate(bits_to_rate(256), 0x01)
+}
+
+#[test]
+fn permutation_count_of_one_block() {
+    let mut state = keccak256();
+    state.update(&[0u8; 136]);
+    assert_eq!(state.permutation_count(), 1);
+
+    // the padding block of an input of a whole block is permuted on its own
+    state.squeeze(&mut [0u8; 32]);
+    assert_eq!(state.permutation_count(), 2);
+}
+
+#[test]
+fn permutation_count_of_sub_block_input() {
+    let mut state = keccak256();
+    for _ in 0..10 {
+        state.update(&[0u8; 13]);
+    }
+    assert_eq!(state.permutation_count(), 0);
+
+    state.squeeze(&mut [0u8; 32]);
+    assert_eq!(state.permutation_count(), 1);
+}
+
+#[test]
+fn permutation_count_of_long_output_and_reset() {
+    let mut state = keccak256();
+    state.update(&[0u8; 200]);
+    // one for the padding block and one for each of the two following blocks of output
+    state.squeeze(&mut [0u8; 300]);
+    assert_eq!(state.permutation_count(), 4);
+    state.squeeze(&mut [0u8; 1]);
+    assert_eq!(state.permutation_count(), 4);
+    assert_eq!(state.clone().permutation_count(), 4);
+
+    state.reset();
+    assert_eq!(state.permutation_count(), 0);
+}
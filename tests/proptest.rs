@@ -0,0 +1,82 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use tiny_keccak::{Hasher, Keccak, Shake, Xof};
+
+/// Splits `input` at the positions in `cuts`, taken modulo the length of `input`.
+fn chunks<'a>(input: &'a [u8], cuts: &[usize]) -> Vec<&'a [u8]> {
+    let mut cuts: Vec<usize> = cuts.iter().map(|cut| cut % (input.len() + 1)).collect();
+    cuts.sort_unstable();
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    for cut in cuts {
+        chunks.push(&input[start..cut]);
+        start = cut;
+    }
+    chunks.push(&input[start..]);
+    chunks
+}
+
+proptest! {
+    #[test]
+    fn keccak_chunked_update(
+        input in vec(any::<u8>(), 0..1024),
+        cuts in vec(any::<usize>(), 0..16),
+    ) {
+        let mut expected = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(&input);
+        keccak.finalize(&mut expected);
+
+        let mut output = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        for chunk in chunks(&input, &cuts) {
+            keccak.update(chunk);
+        }
+        keccak.finalize(&mut output);
+        prop_assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn keccak_empty_updates(
+        input in vec(any::<u8>(), 0..1024),
+        cuts in vec(any::<usize>(), 0..16),
+        empties in vec(any::<bool>(), 0..17),
+    ) {
+        let mut expected = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(&input);
+        keccak.finalize(&mut expected);
+
+        // an empty update before some of the chunks
+        let mut output = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        for (i, chunk) in chunks(&input, &cuts).into_iter().enumerate() {
+            if empties.get(i).copied().unwrap_or(false) {
+                keccak.update(&[]);
+            }
+            keccak.update(chunk);
+        }
+        keccak.update(&[]);
+        keccak.finalize(&mut output);
+        prop_assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn shake_chunked_squeeze(input in vec(any::<u8>(), 0..512), sizes in vec(0..400usize, 0..16)) {
+        let mut shake = Shake::v128();
+        shake.update(&input);
+
+        let total = sizes.iter().sum();
+        let mut expected = vec![0u8; total];
+        shake.clone().finalize(&mut expected);
+
+        let mut output = vec![0u8; total];
+        let mut start = 0;
+        for size in sizes {
+            shake.squeeze(&mut output[start..start + size]);
+            start += size;
+        }
+        prop_assert_eq!(expected, output);
+    }
+}
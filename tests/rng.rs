@@ -0,0 +1,49 @@
+use rand_core::{RngCore, SeedableRng};
+use tiny_keccak::{Hasher, KeccakRng, Shake, Xof};
+
+#[test]
+fn rng_is_deterministic() {
+    let mut a = KeccakRng::from_seed([42u8; 32]);
+    let mut b = KeccakRng::from_seed([42u8; 32]);
+    let mut output_a = [0u8; 1024];
+    let mut output_b = [0u8; 1024];
+    a.fill_bytes(&mut output_a);
+    b.fill_bytes(&mut output_b);
+    assert_eq!(&output_a[..], &output_b[..]);
+
+    let mut c = KeccakRng::from_seed([43u8; 32]);
+    let mut output_c = [0u8; 1024];
+    c.fill_bytes(&mut output_c);
+    assert_ne!(&output_a[..], &output_c[..]);
+}
+
+#[test]
+fn rng_fill_in_parts() {
+    let mut expected = [0u8; 1024];
+    KeccakRng::from_seed([1u8; 32]).fill_bytes(&mut expected);
+
+    let mut output = [0u8; 1024];
+    let mut rng = KeccakRng::from_seed([1u8; 32]);
+    for chunk in output.chunks_mut(13) {
+        rng.fill_bytes(chunk);
+    }
+    assert_eq!(&expected[..], &output[..]);
+
+    let mut rng = KeccakRng::from_seed([1u8; 32]);
+    let mut le = [0u8; 8];
+    le.copy_from_slice(&expected[..8]);
+    assert_eq!(rng.next_u64(), u64::from_le_bytes(le));
+}
+
+#[test]
+fn rng_matches_shake256() {
+    let seed = [9u8; 32];
+    let mut expected = [0u8; 300];
+    let mut shake = Shake::v256();
+    shake.update(&seed);
+    shake.squeeze(&mut expected);
+
+    let mut output = [0u8; 300];
+    KeccakRng::from_seed(seed).fill_bytes(&mut output);
+    assert_eq!(&expected[..], &output[..]);
+}
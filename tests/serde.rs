@@ -0,0 +1,53 @@
+use tiny_keccak::{Hasher, Keccak, KeccakF, KeccakState};
+
+#[test]
+fn keccak_state_round_trip() {
+    let message = [0x5au8; 1000];
+    let mut expected = [0u8; 32];
+    Keccak::v256().chain(&message).finalize(&mut expected);
+
+    let mut keccak = Keccak::v256();
+    keccak.update(&message[..421]);
+    let checkpoint = serde_json::to_string(&keccak.state).unwrap();
+
+    let state: KeccakState<KeccakF> = serde_json::from_str(&checkpoint).unwrap();
+    let mut keccak = Keccak { state };
+    keccak.update(&message[421..]);
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn keccak_state_invalid_rate() {
+    let mut value = serde_json::to_value(&Keccak::v256().state).unwrap();
+    value["rate"] = 100.into();
+    let result = serde_json::from_value::<KeccakState<KeccakF>>(value);
+    assert!(result.is_err());
+}
+
+#[test]
+fn keccak_state_invalid_offset() {
+    let mut value = serde_json::to_value(&Keccak::v256().state).unwrap();
+    value["offset"] = 137.into();
+    let result = serde_json::from_value::<KeccakState<KeccakF>>(value);
+    assert!(result.is_err());
+}
+
+#[test]
+fn keccak_state_full_absorbing_block() {
+    let mut value = serde_json::to_value(&Keccak::v256().state).unwrap();
+    value["offset"] = 136.into();
+    let error = serde_json::from_value::<KeccakState<KeccakF>>(value).unwrap_err();
+    assert!(error.to_string().contains("full block"), "{}", error);
+
+    // a padded state holds an exhausted block, the next squeeze permutes it
+    let mut padded = Keccak::v256().state;
+    padded.pad();
+    let value = serde_json::to_value(&padded).unwrap();
+    assert_eq!(value["offset"], 136);
+    let mut state: KeccakState<KeccakF> = serde_json::from_value(value).unwrap();
+    let mut output = [0u8; 32];
+    state.squeeze(&mut output);
+    assert_eq!(output, tiny_keccak::keccak256(b""));
+}
@@ -1,4 +1,7 @@
-use tiny_keccak::{Hasher, Sha3};
+use tiny_keccak::{
+    constant_time_eq, sha3_224, sha3_256, sha3_384, sha3_512, Hasher, KeccakError, Sha3, Sha3_224,
+    Sha3_256, Sha3_384, Sha3_512,
+};
 
 #[test]
 fn empty_sha3_256() {
@@ -92,3 +95,319 @@ fn long_string_sha3_512_parts() {
     sha3.finalize(&mut output);
     assert_eq!(expected as &[u8], &output as &[u8]);
 }
+
+#[test]
+fn empty_sha3_224() {
+    let sha3 = Sha3::v224();
+    let mut output = [0; 28];
+    let expected = b"\
+        \x6b\x4e\x03\x42\x36\x67\xdb\xb7\x3b\x6e\x15\x45\x4f\x0e\xb1\xab\
+        \xd4\x59\x7f\x9a\x1b\x07\x8e\x3f\x5b\x5a\x6b\xc7\
+    ";
+    sha3.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn empty_sha3_384() {
+    let sha3 = Sha3::v384();
+    let mut output = [0; 48];
+    let expected = b"\
+        \x0c\x63\xa7\x5b\x84\x5e\x4f\x7d\x01\x10\x7d\x85\x2e\x4c\x24\x85\
+        \xc5\x1a\x50\xaa\xaa\x94\xfc\x61\x99\x5e\x71\xbb\xee\x98\x3a\x2a\
+        \xc3\x71\x38\x31\x26\x4a\xdb\x47\xfb\x6b\xd1\xe0\x58\xd5\xf0\x04\
+    ";
+    sha3.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn empty_sha3_512() {
+    let sha3 = Sha3::v512();
+    let mut output = [0; 64];
+    let expected = b"\
+        \xa6\x9f\x73\xcc\xa2\x3a\x9a\xc5\xc8\xb5\x67\xdc\x18\x5a\x75\x6e\
+        \x97\xc9\x82\x16\x4f\xe2\x58\x59\xe0\xd1\xdc\xc1\x47\x5c\x80\xa6\
+        \x15\xb2\x12\x3a\xf1\xf5\xf9\x4c\x11\xe3\xe9\x40\x2c\x3a\xc5\x58\
+        \xf5\x00\x19\x9d\x95\xb6\xd3\xe3\x01\x75\x85\x86\x28\x1d\xcd\x26\
+    ";
+    sha3.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn abc_sha3_224() {
+    let mut sha3 = Sha3::v224();
+    let mut output = [0; 28];
+    let expected = b"\
+        \xe6\x42\x82\x4c\x3f\x8c\xf2\x4a\xd0\x92\x34\xee\x7d\x3c\x76\x6f\
+        \xc9\xa3\xa5\x16\x8d\x0c\x94\xad\x73\xb4\x6f\xdf\
+    ";
+    sha3.update(b"abc");
+    sha3.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn abc_sha3_256() {
+    let mut sha3 = Sha3::v256();
+    let mut output = [0; 32];
+    let expected = b"\
+        \x3a\x98\x5d\xa7\x4f\xe2\x25\xb2\x04\x5c\x17\x2d\x6b\xd3\x90\xbd\
+        \x85\x5f\x08\x6e\x3e\x9d\x52\x5b\x46\xbf\xe2\x45\x11\x43\x15\x32\
+    ";
+    sha3.update(b"abc");
+    sha3.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn abc_sha3_384() {
+    let mut sha3 = Sha3::v384();
+    let mut output = [0; 48];
+    let expected = b"\
+        \xec\x01\x49\x82\x88\x51\x6f\xc9\x26\x45\x9f\x58\xe2\xc6\xad\x8d\
+        \xf9\xb4\x73\xcb\x0f\xc0\x8c\x25\x96\xda\x7c\xf0\xe4\x9b\xe4\xb2\
+        \x98\xd8\x8c\xea\x92\x7a\xc7\xf5\x39\xf1\xed\xf2\x28\x37\x6d\x25\
+    ";
+    sha3.update(b"abc");
+    sha3.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn abc_sha3_512() {
+    let mut sha3 = Sha3::v512();
+    let mut output = [0; 64];
+    let expected = b"\
+        \xb7\x51\x85\x0b\x1a\x57\x16\x8a\x56\x93\xcd\x92\x4b\x6b\x09\x6e\
+        \x08\xf6\x21\x82\x74\x44\xf7\x0d\x88\x4f\x5d\x02\x40\xd2\x71\x2e\
+        \x10\xe1\x16\xe9\x19\x2a\xf3\xc9\x1a\x7e\xc5\x76\x47\xe3\x93\x40\
+        \x57\x34\x0b\x4c\xf4\x08\xd5\xa5\x65\x92\xf8\x27\x4e\xec\x53\xf0\
+    ";
+    sha3.update(b"abc");
+    sha3.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn sha3_reset() {
+    let mut sha3 = Sha3::v256();
+    sha3.update(b"abc");
+    let mut first = [0u8; 32];
+    sha3.clone().finalize(&mut first);
+
+    sha3.reset();
+    sha3.update(b"abc");
+    let mut second = [0u8; 32];
+    sha3.finalize(&mut second);
+    assert_eq!(first, second);
+
+    let mut sha3 = Sha3::v256();
+    sha3.update(b"def");
+    sha3.reset();
+    sha3.update(b"abc");
+    let mut third = [0u8; 32];
+    sha3.finalize(&mut third);
+    assert_eq!(first, third);
+}
+
+#[test]
+fn sha3_finalize_reset() {
+    let mut sha3 = Sha3::v512();
+    for message in [&b"hello"[..], &[0xa3; 200][..], b""].iter() {
+        let mut expected = [0u8; 64];
+        let mut fresh = Sha3::v512();
+        fresh.update(message);
+        fresh.finalize(&mut expected);
+
+        let mut output = [0u8; 64];
+        sha3.update(message);
+        sha3.finalize_reset(&mut output);
+        assert_eq!(&expected[..], &output[..]);
+    }
+}
+
+#[test]
+fn sha3_finalize_into() {
+    let mut sha3 = Sha3::v256();
+    let mut absorbed = Vec::new();
+    for message in [&b"hello"[..], &[0xa3; 200][..]].iter() {
+        sha3.update(message);
+        absorbed.extend_from_slice(message);
+
+        let mut expected = [0u8; 32];
+        let mut fresh = Sha3::v256();
+        fresh.update(&absorbed);
+        fresh.finalize(&mut expected);
+
+        let mut output = [0u8; 32];
+        sha3.finalize_into(&mut output);
+        assert_eq!(expected, output);
+    }
+}
+
+#[test]
+fn sha3_256_finalize_array() {
+    let mut sha3 = Sha3::v256();
+    sha3.update(b"hello");
+    let mut expected = [0u8; 32];
+    sha3.clone().finalize(&mut expected);
+
+    let output: [u8; 32] = sha3.finalize_array();
+    assert_eq!(expected, output);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(
+    expected = "output length exceeds the SHA3 variant, expected at most 32 bytes, got 64"
+)]
+fn sha3_256_finalize_array_wrong_size() {
+    let _: [u8; 64] = Sha3::v256().finalize_array();
+}
+
+#[test]
+fn sha3_verify() {
+    let mut sha3 = Sha3::v256();
+    sha3.update(b"hello");
+    let mut digest = [0u8; 32];
+    sha3.clone().finalize(&mut digest);
+    assert!(sha3.clone().verify(&digest));
+    assert!(!sha3.clone().verify(&digest[..31]));
+
+    for i in 0..digest.len() {
+        let mut near_miss = digest;
+        near_miss[i] ^= 0x01;
+        assert!(!sha3.clone().verify(&near_miss));
+    }
+}
+
+#[test]
+fn test_constant_time_eq() {
+    assert!(constant_time_eq(b"", b""));
+    assert!(constant_time_eq(b"abc", b"abc"));
+    assert!(!constant_time_eq(b"abc", b"abd"));
+    assert!(!constant_time_eq(b"abc", b"bbc"));
+    assert!(!constant_time_eq(b"abc", b"ab"));
+}
+
+#[test]
+fn sha3_chain() {
+    let mut expected = [0u8; 32];
+    let mut sha3 = Sha3::v256();
+    sha3.update(b"hello");
+    sha3.update(b" world");
+    sha3.finalize(&mut expected);
+
+    let mut output = [0u8; 32];
+    Sha3::v256()
+        .chain(b"hello")
+        .chain(b" world")
+        .finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn sha3_default_is_v256() {
+    let mut expected = [0u8; 32];
+    Sha3::v256().chain(b"abc").finalize(&mut expected);
+    let mut output = [0u8; 32];
+    Sha3::default().chain(b"abc").finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn sha3_one_shot() {
+    let input = b"hello world";
+
+    let mut hasher = Sha3::v224();
+    hasher.update(input);
+    assert_eq!(sha3_224(input), hasher.finalize_array::<28>());
+
+    let mut hasher = Sha3::v256();
+    hasher.update(input);
+    assert_eq!(sha3_256(input), hasher.finalize_array::<32>());
+
+    let mut hasher = Sha3::v384();
+    hasher.update(input);
+    assert_eq!(sha3_384(input)[..], hasher.finalize_array::<48>()[..]);
+
+    let mut hasher = Sha3::v512();
+    hasher.update(input);
+    assert_eq!(sha3_512(input)[..], hasher.finalize_array::<64>()[..]);
+}
+
+#[test]
+fn sha3_256_finalize_truncated() {
+    let mut truncated = [0u8; 16];
+    Sha3::v256().finalize(&mut truncated);
+    assert_eq!(truncated, sha3_256(b"")[..16]);
+}
+
+#[test]
+fn sha3_update_vectored() {
+    let (a, b, c) = (&b"hello"[..], &[0xa3; 200][..], &b"world"[..]);
+
+    let mut expected = [0u8; 32];
+    let mut sha3 = Sha3::v256();
+    sha3.update(a);
+    sha3.update(b);
+    sha3.update(c);
+    sha3.finalize(&mut expected);
+
+    let mut output = [0u8; 32];
+    let mut sha3 = Sha3::v256();
+    sha3.update(&[a, b, c].concat());
+    sha3.finalize(&mut output);
+    assert_eq!(expected, output);
+
+    let mut sha3 = Sha3::v256();
+    sha3.update_vectored(&[a, b, c]);
+    sha3.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn sha3_typed_output() {
+    let mut hasher = Sha3_256::new();
+    hasher.update(b"hello world");
+    let digest: [u8; 32] = hasher.finalize();
+    assert_eq!(digest, sha3_256(b"hello world"));
+
+    assert_eq!(Sha3_224::new().finalize(), sha3_224(b""));
+    assert_eq!(Sha3_384::new().finalize(), sha3_384(b""));
+    assert_eq!(Sha3_512::new().finalize(), sha3_512(b""));
+    assert_eq!(Sha3_224::OUTPUT, 28);
+
+    // usable in const contexts, e.g. to size block buffers
+    const BLOCK: [u8; Sha3_512::RATE] = [0; Sha3_512::RATE];
+    assert_eq!(BLOCK.len(), 72);
+    assert_eq!(Sha3_256::RATE, 136);
+}
+
+#[test]
+fn sha3_try_finalize() {
+    let mut output = [0u8; 64];
+    assert_eq!(Sha3::v512().try_finalize(&mut output), Ok(()));
+    assert_eq!(output, sha3_512(b""));
+    assert_eq!(
+        Sha3::v384().try_finalize(&mut output),
+        Err(KeccakError::OutputSizeMismatch {
+            expected: 48,
+            len: 64
+        })
+    );
+
+    let mut sizes = [(Sha3::v224(), 28), (Sha3::v256(), 32), (Sha3::v384(), 48)];
+    for (sha3, len) in sizes.iter_mut() {
+        assert_eq!(sha3.clone().try_finalize(&mut output[..*len]), Ok(()));
+        assert_eq!(
+            sha3.clone().try_finalize(&mut output[..*len - 1]),
+            Err(KeccakError::OutputSizeMismatch {
+                expected: *len,
+                len: *len - 1
+            })
+        );
+    }
+}
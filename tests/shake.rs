@@ -35,3 +35,332 @@ fn shake_xof_two() {
 
     assert_eq!(expected, &output);
 }
+
+#[test]
+fn empty_shake_128() {
+    let shake = Shake::v128();
+    let mut output = [0; 32];
+    let expected = b"\
+        \x7f\x9c\x2b\xa4\xe8\x8f\x82\x7d\x61\x60\x45\x50\x76\x05\x85\x3e\
+        \xd7\x3b\x80\x93\xf6\xef\xbc\x88\xeb\x1a\x6e\xac\xfa\x66\xef\x26\
+    ";
+
+    shake.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn empty_shake_256() {
+    let shake = Shake::v256();
+    let mut output = [0; 64];
+    let expected = b"\
+        \x46\xb9\xdd\x2b\x0b\xa8\x8d\x13\x23\x3b\x3f\xeb\x74\x3e\xeb\x24\
+        \x3f\xcd\x52\xea\x62\xb8\x1b\x82\xb5\x0c\x27\x64\x6e\xd5\x76\x2f\
+        \xd7\x5d\xc4\xdd\xd8\xc0\xf2\x00\xcb\x05\x01\x9d\x67\xb5\x92\xf6\
+        \xfc\x82\x1c\x49\x47\x9a\xb4\x86\x40\x29\x2e\xac\xb3\xb7\xc4\xbe\
+    ";
+
+    shake.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn shake_xof_squeeze_in_parts() {
+    let mut expected = [0; 512];
+    Shake::v128().squeeze(&mut expected);
+
+    let mut output = [0; 512];
+    let mut shake = Shake::v128();
+    for chunk in output.chunks_mut(7) {
+        shake.squeeze(chunk);
+    }
+
+    assert_eq!(&expected[..], &output[..]);
+    assert_eq!(
+        b"\xDD\xA2\x52\x98\x33\x46\x2B\x71\xA4\x1A\x45\xBE\x97\x29\x0B\x6F",
+        &output[496..]
+    );
+}
+
+#[test]
+fn shake_finalize_array() {
+    let mut shake = Shake::v256();
+    shake.update(b"hello");
+    let mut expected = [0u8; 200];
+    shake.clone().finalize(&mut expected);
+
+    let output: [u8; 200] = shake.clone().finalize_array();
+    assert_eq!(&expected[..], &output[..]);
+
+    let output: [u8; 16] = shake.finalize_array();
+    assert_eq!(&expected[..16], &output[..]);
+}
+
+#[test]
+fn shake_default_is_v128() {
+    let mut expected = [0u8; 64];
+    Shake::v128().chain(b"abc").finalize(&mut expected);
+    let mut output = [0u8; 64];
+    Shake::default().chain(b"abc").finalize(&mut output);
+    assert_eq!(&expected[..], &output[..]);
+}
+
+#[test]
+fn shake_squeeze_empty() {
+    let mut expected = [0u8; 64];
+    let mut shake = Shake::v128();
+    shake.update(b"hello world");
+    shake.squeeze(&mut expected);
+
+    let mut output = [0u8; 64];
+    let mut shake = Shake::v128();
+    shake.update(b"hello");
+    // an empty squeeze neither pads nor switches to squeezing, so more input can be absorbed
+    shake.squeeze(&mut []);
+    shake.update(b" world");
+    shake.squeeze(&mut []);
+    shake.squeeze(&mut output[..10]);
+    shake.squeeze(&mut []);
+    shake.squeeze(&mut output[10..]);
+    assert_eq!(expected[..], output[..]);
+}
+
+#[test]
+fn shake_squeeze_10_mib() {
+    let expected_tail = b"\
+        \xe0\x7c\x12\x59\xc2\x28\xf8\xbb\xb1\x42\x1a\x7a\x1c\x02\xa2\x96\
+        \x97\x2a\x17\x5d\x91\xa2\xbf\xa4\xb6\xb2\x87\xc8\x33\x4d\x60\x4c\
+    ";
+
+    let mut expected = vec![0u8; 10 * 1024 * 1024];
+    Shake::v128().squeeze(&mut expected);
+    assert_eq!(expected_tail[..], expected[expected.len() - 32..]);
+
+    let mut output = vec![0u8; expected.len()];
+    let mut shake = Shake::v128();
+    for chunk in output.chunks_mut(1 << 20 | 7) {
+        shake.squeeze(chunk);
+    }
+    assert!(expected == output);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn shake_vec() {
+    let mut expected = [0u8; 100];
+    let mut shake = Shake::v128();
+    shake.update(b"hello world");
+    shake.finalize(&mut expected);
+    assert_eq!(tiny_keccak::shake128_vec(b"hello world", 100), expected);
+
+    let mut expected = [0u8; 300];
+    let mut shake = Shake::v256();
+    shake.update(b"hello world");
+    shake.finalize(&mut expected);
+    assert_eq!(
+        tiny_keccak::shake256_vec(b"hello world", 300)[..],
+        expected[..]
+    );
+    assert!(tiny_keccak::shake128_vec(b"", 0).is_empty());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn shake_squeeze_to_vec() {
+    use tiny_keccak::XofExt;
+
+    let mut shake = Shake::v256();
+    shake.update(b"hello world");
+    let mut reference = shake.clone();
+
+    let mut expected = [0u8; 500];
+    reference.squeeze(&mut expected[..100]);
+    reference.squeeze(&mut expected[100..]);
+
+    let first = shake.squeeze_to_vec(100);
+    let second = shake.squeeze_to_vec(400);
+    assert_eq!(first.capacity(), first.len());
+    assert_eq!(second.capacity(), 400);
+    assert_eq!(first[..], expected[..100]);
+    assert_eq!(second[..], expected[100..]);
+    assert!(shake.squeeze_to_vec(0).is_empty());
+}
+
+#[test]
+fn shake_clone_forks_the_stream() {
+    let mut shake = Shake::v128();
+    shake.update(b"fork");
+    let mut skipped = [0u8; 10];
+    shake.squeeze(&mut skipped);
+
+    let mut fork = shake.clone();
+    let mut original = [0u8; 20];
+    let mut forked = [0u8; 20];
+    shake.squeeze(&mut original);
+    fork.squeeze(&mut forked);
+    assert_eq!(original, forked);
+}
+
+#[test]
+fn shake_squeeze_reference_offsets() {
+    // windows of the SHAKE128("abc") stream, within, across and past the 168 byte blocks
+    let windows = [
+        (
+            0,
+            "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc8",
+        ),
+        (
+            160,
+            "cc29082f5647584e6aa01b3f5af057805f973ff8ecb8b226ac32ada6f01c1fcd",
+        ),
+        (
+            168,
+            "6aa01b3f5af057805f973ff8ecb8b226ac32ada6f01c1fcd4818cb006aa5b4cd",
+        ),
+        (
+            335,
+            "854cedd50d30a223e7d54e9a24f0a2526b31002afbd1b4ebea69c8400c3deb4c",
+        ),
+        (
+            4000,
+            "f090a4cab7cf1c30285dd7d631f52a2ebfdb17ab14d2939c594397187f8b0172",
+        ),
+        (
+            4168,
+            "f2530731d4e9475da5bf71a73d50e3d212b0685024a000a6bba04d7a22de5068",
+        ),
+    ];
+
+    // squeezed at once, and in parts starting in the middle of blocks or on their boundaries
+    for &part in &[4200, 1, 7, 168, 169, 336, 1000] {
+        let mut shake = Shake::v128();
+        shake.update(b"abc");
+        let mut stream = [0u8; 4200];
+        for chunk in stream.chunks_mut(part) {
+            shake.squeeze(chunk);
+        }
+
+        for &(offset, expected) in windows.iter() {
+            let window: String = stream[offset..offset + 32]
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            assert_eq!(
+                window, expected,
+                "parts of {} bytes, offset {}",
+                part, offset
+            );
+        }
+    }
+}
+
+#[test]
+fn shake_verify_rejects_empty_output() {
+    for shake in [Shake::v128(), Shake::v256()].iter() {
+        let mut output = [0u8; 32];
+        shake.clone().finalize(&mut output);
+        assert!(shake.clone().verify(&output));
+        assert!(!shake.clone().verify(&output[..0]));
+    }
+}
+
+#[test]
+fn shake_verify() {
+    let mut shake = Shake::v256();
+    shake.update(b"tag input");
+    let mut expected = [0u8; 300];
+    shake.clone().squeeze(&mut expected);
+
+    assert!(shake.clone().verify(&expected));
+    // an empty tag never verifies, a minimum length rejects shorter tags
+    assert!(!shake.clone().verify(&[]));
+    assert!(!Xof::verify(&mut shake.clone(), &[]));
+    assert!(Xof::verify_min_len(&mut shake.clone(), &expected[..16], 16));
+    assert!(!Xof::verify_min_len(
+        &mut shake.clone(),
+        &expected[..15],
+        16
+    ));
+
+    // a single flipped byte anywhere fails, the last one included
+    for &position in &[0, 63, 64, 299] {
+        let mut tampered = expected;
+        tampered[position] ^= 0x01;
+        assert!(!shake.clone().verify(&tampered), "byte {}", position);
+    }
+
+    // verifying through the Xof trait borrows the reader, the stream continues after it
+    let mut rest = [0u8; 32];
+    assert!(Xof::verify(&mut shake, &expected[..100]));
+    assert!(Xof::verify(&mut shake, &expected[100..101]));
+    shake.squeeze(&mut rest);
+    assert_eq!(rest, expected[101..133]);
+}
+
+#[test]
+fn shake_seed_from() {
+    let parent = || {
+        let mut parent = Shake::v256();
+        parent.update(b"root key");
+        parent
+    };
+
+    // the child absorbed the first 32 bytes of the parent, and the parent continues after them
+    let mut reader = parent();
+    let mut child = Shake::seed_from(&mut reader, 32);
+    let mut stream = [0u8; 64];
+    parent().squeeze(&mut stream);
+    let mut next = [0u8; 32];
+    reader.squeeze(&mut next);
+    assert_eq!(next, stream[32..]);
+
+    let mut expected = Shake::v256();
+    expected.update(&stream[..32]);
+    expected.update(b"label");
+    child.update(b"label");
+    let mut output = [0u8; 32];
+    let mut expected_output = [0u8; 32];
+    child.clone().squeeze(&mut output);
+    expected.squeeze(&mut expected_output);
+    assert_eq!(output, expected_output);
+
+    // identically seeded children agree, other seed lengths don't
+    let mut twin = Shake::seed_from(&mut parent(), 32);
+    twin.update(b"label");
+    let mut twin_output = [0u8; 32];
+    twin.squeeze(&mut twin_output);
+    assert_eq!(output, twin_output);
+
+    for &n in &[0, 31, 33, 200] {
+        let mut other = Shake::seed_from(&mut parent(), n);
+        other.update(b"label");
+        let mut other_output = [0u8; 32];
+        other.squeeze(&mut other_output);
+        assert_ne!(output, other_output, "{} bytes", n);
+    }
+}
+
+#[test]
+#[should_panic(expected = "cannot absorb after squeezing")]
+fn shake_update_after_squeeze() {
+    let mut shake = Shake::v128();
+    shake.update(b"seed");
+    shake.squeeze(&mut [0u8; 32]);
+    shake.update(b"more");
+}
+
+#[test]
+fn shake_finalize_scattered_fields() {
+    let mut shake = Shake::v128();
+    shake.update(b"seed");
+    let mut expected = [0u8; 256];
+    shake.clone().finalize(&mut expected);
+
+    let mut nonce = [0u8; 24];
+    let mut key = [0u8; 32];
+    let mut pad = [0u8; 200];
+    shake.finalize_scattered(&mut [&mut nonce, &mut key, &mut pad]);
+    assert_eq!(nonce[..], expected[..24]);
+    assert_eq!(key[..], expected[24..56]);
+    assert_eq!(pad[..], expected[56..]);
+}
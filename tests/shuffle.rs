@@ -0,0 +1,55 @@
+use tiny_keccak::shuffle;
+
+const SEED: &[u8] = b"tiny-keccak shuffle";
+
+fn shuffled(seed: &[u8]) -> Vec<u32> {
+    let mut items: Vec<u32> = (0..100).collect();
+    shuffle(seed, &mut items);
+    items
+}
+
+#[test]
+fn shuffle_is_reproducible() {
+    // computed independently from the SHAKE256 stream of the seed
+    let expected = [
+        14, 83, 9, 60, 31, 50, 30, 6, 85, 13, 43, 90, 21, 16, 71, 18, 10, 44, 77, 45, 99, 57, 73,
+        1, 72, 66, 79, 80, 52, 34, 87, 68, 82, 86, 96, 59, 94, 53, 48, 12, 33, 78, 65, 27, 28, 32,
+        24, 38, 20, 76, 19, 95, 29, 2, 39, 84, 67, 42, 63, 93, 62, 47, 17, 22, 37, 40, 0, 41, 88,
+        74, 26, 64, 4, 92, 5, 61, 35, 49, 25, 46, 58, 51, 91, 8, 11, 89, 56, 54, 70, 75, 81, 69,
+        36, 98, 97, 55, 3, 23, 15, 7,
+    ];
+    assert_eq!(shuffled(SEED), expected);
+    assert_eq!(shuffled(SEED), shuffled(SEED));
+}
+
+#[test]
+fn shuffle_depends_on_the_seed() {
+    let first = shuffled(SEED);
+    let second = shuffled(b"another seed");
+    assert_ne!(first, second);
+
+    // both are permutations of the items
+    let mut sorted = second.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, (0..100).collect::<Vec<u32>>());
+}
+
+#[test]
+fn shuffle_short_slices() {
+    let mut empty: [u8; 0] = [];
+    shuffle(SEED, &mut empty);
+    let mut single = [7];
+    shuffle(SEED, &mut single);
+    assert_eq!(single, [7]);
+
+    // both orders of a pair are drawn
+    let orders: Vec<[u8; 2]> = (0..32u8)
+        .map(|seed| {
+            let mut pair = [0, 1];
+            shuffle(&[seed], &mut pair);
+            pair
+        })
+        .collect();
+    assert!(orders.contains(&[0, 1]));
+    assert!(orders.contains(&[1, 0]));
+}
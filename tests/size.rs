@@ -0,0 +1,38 @@
+//! Prints the sizes of the sponge and the hashers, run with `cargo test --features fips202,sp800
+//! --test size -- --nocapture`. The crate checks the same bounds at compile time.
+
+use std::mem::size_of;
+use tiny_keccak::{
+    CShake, Keccak, Keccak256, KeccakF, KeccakState, Kmac, Sha3, Sha3_256, Shake, TupleHash,
+};
+
+#[test]
+fn sponge_sizes() {
+    let sizes = [
+        ("KeccakState<KeccakF>", size_of::<KeccakState<KeccakF>>()),
+        ("Keccak", size_of::<Keccak>()),
+        ("Keccak256", size_of::<Keccak256>()),
+        ("Sha3", size_of::<Sha3>()),
+        ("Sha3_256", size_of::<Sha3_256>()),
+        ("Shake", size_of::<Shake>()),
+        ("CShake", size_of::<CShake>()),
+        ("Kmac", size_of::<Kmac>()),
+        ("TupleHash", size_of::<TupleHash>()),
+    ];
+
+    // the fixed budget of the target, and the counter of the `count-permutations` feature
+    let budget = if cfg!(target_pointer_width = "64") {
+        224
+    } else {
+        216
+    };
+    let budget = if cfg!(feature = "count-permutations") {
+        budget + 8
+    } else {
+        budget
+    };
+    for &(name, size) in sizes.iter() {
+        println!("{:<24} {} bytes", name, size);
+        assert!(size <= budget, "{} takes {} bytes", name, size);
+    }
+}
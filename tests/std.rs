@@ -0,0 +1,138 @@
+use std::io::{self, IoSlice, Read, Write};
+use tiny_keccak::{Hasher, HasherIoExt, IntoXof, Keccak, Xof, XofReader};
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|j| (j % 251) as u8).collect()
+}
+
+#[test]
+fn write_keccak_in_odd_chunks() {
+    let input = pattern(3 * 1024 * 1024 + 17);
+    let mut expected = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(&input);
+    keccak.finalize(&mut expected);
+
+    let mut keccak = Keccak::v256();
+    for chunk in input.chunks(1337) {
+        keccak.write_all(chunk).unwrap();
+    }
+    keccak.flush().unwrap();
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn copy_into_keccak() {
+    let input = pattern(3 * 1024 * 1024 + 17);
+    let mut expected = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(&input);
+    keccak.finalize(&mut expected);
+
+    let mut keccak = Keccak::v256();
+    let copied = io::copy(&mut &input[..], &mut keccak).unwrap();
+    assert_eq!(copied, input.len() as u64);
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn write_vectored_into_keccak() {
+    let input = pattern(1000);
+    let mut expected = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(&input);
+    keccak.finalize(&mut expected);
+
+    let slices: Vec<IoSlice> = input.chunks(137).map(IoSlice::new).collect();
+    let mut keccak = Keccak::v256();
+    assert_eq!(keccak.write_vectored(&slices).unwrap(), input.len());
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn update_reader_keccak() {
+    let input = pattern(3 * 1024 * 1024 + 17);
+    let mut expected = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(&input);
+    keccak.finalize(&mut expected);
+
+    let mut keccak = Keccak::v256();
+    let len = keccak.update_reader(&mut io::Cursor::new(&input)).unwrap();
+    assert_eq!(len, input.len() as u64);
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+/// Yields `data` in short reads, interrupted every other call, then fails.
+struct FlakyReader<'a> {
+    data: &'a [u8],
+    calls: usize,
+}
+
+impl io::Read for FlakyReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.calls += 1;
+        if self.calls & 1 == 0 {
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        if self.data.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"));
+        }
+        let len = self.data.len().min(buf.len()).min(100);
+        buf[..len].copy_from_slice(&self.data[..len]);
+        self.data = &self.data[len..];
+        Ok(len)
+    }
+}
+
+#[test]
+fn update_reader_propagates_errors() {
+    let input = pattern(1000);
+    let mut keccak = Keccak::v256();
+    let mut reader = FlakyReader {
+        data: &input,
+        calls: 0,
+    };
+    let err = keccak.update_reader(&mut reader).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+
+    // the bytes read before the error are absorbed
+    let mut expected = [0u8; 32];
+    let mut keccak_expected = Keccak::v256();
+    keccak_expected.update(&input);
+    keccak_expected.finalize(&mut expected);
+    let mut output = [0u8; 32];
+    keccak.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn copy_from_xof_reader() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"stream");
+    let mut expected = vec![0u8; 100 * 1024 + 3];
+    keccak.clone().into_xof().squeeze(&mut expected);
+
+    let mut reader = XofReader::new(keccak.clone().into_xof()).take(expected.len() as u64);
+    let mut output = Vec::new();
+    let len = io::copy(&mut reader, &mut output).unwrap();
+    assert_eq!(len, expected.len() as u64);
+    assert_eq!(expected, output);
+
+    // every read fills the buffer and the stream goes on after it
+    let mut reader = XofReader::new(keccak.into_xof());
+    let mut buf = [0u8; 1000];
+    assert_eq!(reader.read(&mut buf).unwrap(), buf.len());
+    assert_eq!(&buf[..], &expected[..1000]);
+    let mut rest = [0u8; 24];
+    reader.into_inner().squeeze(&mut rest);
+    assert_eq!(&rest[..], &expected[1000..1024]);
+}
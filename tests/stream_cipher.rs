@@ -0,0 +1,45 @@
+use tiny_keccak::{Hasher, KeccakStreamCipher, Shake, Xof};
+
+const KEY: [u8; 32] = [0x42; 32];
+const NONCE: [u8; 16] = [0x24; 16];
+
+fn plaintext() -> Vec<u8> {
+    (0..1000).map(|i| (i * 7) as u8).collect()
+}
+
+#[test]
+fn stream_cipher_chunked_round_trip() {
+    let plaintext = plaintext();
+
+    let mut data = plaintext.clone();
+    let mut cipher = KeccakStreamCipher::new(&KEY, &NONCE);
+    let (first, rest) = data.split_at_mut(1);
+    let (second, third) = rest.split_at_mut(135);
+    cipher.apply_keystream(first);
+    cipher.apply_keystream(second);
+    cipher.apply_keystream(third);
+    assert_ne!(plaintext, data);
+
+    let mut cipher = KeccakStreamCipher::new(&KEY, &NONCE);
+    let (first, second) = data.split_at_mut(777);
+    cipher.apply_keystream(first);
+    cipher.apply_keystream(second);
+    assert_eq!(plaintext, data);
+}
+
+#[test]
+fn stream_cipher_keystream_is_shake256() {
+    let mut keystream = vec![0u8; 300];
+    let mut shake = Shake::v256();
+    shake.update(&KEY);
+    shake.update(&NONCE);
+    shake.squeeze(&mut keystream);
+
+    let mut data = vec![0u8; 300];
+    KeccakStreamCipher::new(&KEY, &NONCE).apply_keystream(&mut data);
+    assert_eq!(keystream, data);
+
+    let mut other = vec![0u8; 300];
+    KeccakStreamCipher::new(&KEY, &[0x25; 16]).apply_keystream(&mut other);
+    assert_ne!(data, other);
+}
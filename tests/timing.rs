@@ -0,0 +1,152 @@
+//! `dudect` style timing tests of the permutation and of `Xof::verify`, run with
+//! `cargo test --release --features timing-test --test timing -- --nocapture`.
+//!
+//! The permutation is timed on two classes of inputs, a fixed all-zero state and random states,
+//! in a random order, and `Xof::verify` on tags differing from the stream in the first or the
+//! last byte. Welch's t-test then compares the two timing distributions, a constant time
+//! implementation has no statistically significant difference between them. `dudect` treats
+//! `|t| > 10` as a certain leak, smaller values below `4.5` are expected from noise.
+//!
+//! See "Dude, is my code constant time?", Reparaz, Balasch and Verbauwhede, DATE 2017.
+
+use std::hint::black_box;
+use std::time::Instant;
+use tiny_keccak::{permute, KeccakF, KeccakState, Xof};
+
+const MEASUREMENTS: usize = 200_000;
+
+/// Measurements slower than this percentile are dropped, they are dominated by interrupts and
+/// context switches rather than by the permutation.
+const CROP_PERCENTILE: f64 = 0.9;
+
+const T_THRESHOLD: f64 = 10.0;
+
+/// xorshift64*, the inputs only have to be unpredictable to the branch predictor.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+/// The running mean and variance of one class, with Welford's algorithm.
+#[derive(Default)]
+struct Stats {
+    n: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Stats {
+    fn push(&mut self, x: f64) {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        self.m2 / (self.n - 1.0)
+    }
+}
+
+fn welch_t(a: &Stats, b: &Stats) -> f64 {
+    (a.mean - b.mean) / (a.variance() / a.n + b.variance() / b.n).sqrt()
+}
+
+/// Crops the slowest measurements and compares the timings of the two classes.
+fn t_statistic(classes: &[usize], timings: &[f64]) -> f64 {
+    let mut sorted = timings.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let crop = sorted[(timings.len() as f64 * CROP_PERCENTILE) as usize];
+
+    let mut stats = [Stats::default(), Stats::default()];
+    for (&class, &timing) in classes.iter().zip(timings.iter()) {
+        if timing <= crop {
+            stats[class].push(timing);
+        }
+    }
+
+    let t = welch_t(&stats[0], &stats[1]);
+    println!(
+        "class 0: {:.1} ns, class 1: {:.1} ns, t = {:.2}",
+        stats[0].mean, stats[1].mean, t
+    );
+    t
+}
+
+#[test]
+fn permutation_is_constant_time() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+    // the inputs are prepared up front, so only the permutation is timed
+    let mut classes = Vec::with_capacity(MEASUREMENTS);
+    let mut states = Vec::with_capacity(MEASUREMENTS);
+    for _ in 0..MEASUREMENTS {
+        let class = (rng.next() & 1) as usize;
+        let mut state = [0u64; 25];
+        if class == 1 {
+            for word in state.iter_mut() {
+                *word = rng.next();
+            }
+        }
+        classes.push(class);
+        states.push(state);
+    }
+
+    let mut timings = Vec::with_capacity(MEASUREMENTS);
+    for state in states.iter_mut() {
+        let start = Instant::now();
+        permute(black_box(state));
+        timings.push(start.elapsed().as_nanos() as f64);
+    }
+
+    let t = t_statistic(&classes, &timings);
+    assert!(
+        t.abs() < T_THRESHOLD,
+        "the permutation time depends on the state, t = {:.2}",
+        t
+    );
+}
+
+#[test]
+fn xof_verify_is_constant_time() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+    let mut xof = KeccakState::<KeccakF>::with_rate(168, 0x1f);
+    xof.update(b"tag input");
+    let mut expected = [0u8; 256];
+    xof.clone().squeeze(&mut expected);
+
+    // the tags differ from the stream in the first or in the last byte
+    let mut classes = Vec::with_capacity(MEASUREMENTS);
+    let mut tags = Vec::with_capacity(MEASUREMENTS);
+    for _ in 0..MEASUREMENTS {
+        let class = (rng.next() & 1) as usize;
+        let mut tag = expected;
+        let position = if class == 0 { 0 } else { tag.len() - 1 };
+        tag[position] ^= 1 << (rng.next() & 7);
+        classes.push(class);
+        tags.push(tag);
+    }
+
+    let mut timings = Vec::with_capacity(MEASUREMENTS);
+    for tag in tags.iter() {
+        let mut xof = xof.clone();
+        let start = Instant::now();
+        let valid = Xof::verify(&mut xof, black_box(tag));
+        timings.push(start.elapsed().as_nanos() as f64);
+        assert!(!valid);
+    }
+
+    let t = t_statistic(&classes, &timings);
+    assert!(
+        t.abs() < T_THRESHOLD,
+        "the verification time depends on the position of the difference, t = {:.2}",
+        t
+    );
+}
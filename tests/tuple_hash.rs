@@ -1,4 +1,4 @@
-use tiny_keccak::{Hasher, TupleHash};
+use tiny_keccak::{Hasher, IntoXof, TupleHash, Xof};
 
 #[test]
 fn test_tuple_hash128_one() {
@@ -111,3 +111,95 @@ fn test_tuple_hash256_three() {
     hasher.finalize(&mut output);
     assert_eq!(expected as &[u8], &output as &[u8]);
 }
+
+#[test]
+fn test_tuple_hash_is_unambiguous() {
+    let mut first = [0u8; 32];
+    let mut hasher = TupleHash::v128(b"");
+    hasher.update(b"abc");
+    hasher.update(b"d");
+    hasher.finalize(&mut first);
+
+    let mut second = [0u8; 32];
+    let mut hasher = TupleHash::v128(b"");
+    hasher.update(b"ab");
+    hasher.update(b"cd");
+    hasher.finalize(&mut second);
+
+    assert_ne!(first, second);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_tuple_hash_write() {
+    use std::io::Write;
+
+    let mut expected = [0u8; 32];
+    let mut hasher = TupleHash::v128(b"");
+    hasher.update(b"\x00\x01\x02");
+    hasher.update(b"\x10\x11\x12\x13\x14\x15");
+    hasher.finalize(&mut expected);
+
+    // every write is one element of the tuple
+    let mut hasher = TupleHash::v128(b"");
+    hasher.write_all(b"\x00\x01\x02").unwrap();
+    hasher.write_all(b"\x10\x11\x12\x13\x14\x15").unwrap();
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn tuple_hash_xof_into_state() {
+    let mut drained = [0u8; 300];
+    let mut xof = TupleHash::v128(b"custom");
+    xof.update(b"message");
+    let mut xof = xof.into_xof();
+    let mut expected = [0u8; 332];
+    xof.clone().squeeze(&mut expected);
+    xof.squeeze(&mut drained);
+
+    // the state keeps squeezing where the reader stopped
+    let mut state = xof.into_state();
+    let mut next = [0u8; 32];
+    state.squeeze(&mut next);
+    assert_eq!(expected[300..], next);
+}
+
+#[test]
+fn test_tuple_hash_update_vectored_is_one_element() {
+    let mut expected = [0u8; 32];
+    TupleHash::v128(b"").chain(b"abc").finalize(&mut expected);
+
+    let mut output = [0u8; 32];
+    let mut hasher = TupleHash::v128(b"");
+    hasher.update_vectored(&[b"ab", b"c"]);
+    hasher.finalize(&mut output);
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_tuple_hash_update_iter_is_one_element() {
+    let data: Vec<u8> = (0..100).collect();
+    for &len in &[0, 63, 64, 65, 100] {
+        let mut expected = [0u8; 32];
+        TupleHash::v128(b"")
+            .chain(&data[..len])
+            .finalize(&mut expected);
+
+        let mut output = [0u8; 32];
+        let mut hasher = TupleHash::v128(b"");
+        hasher.update_iter(data[..len].iter().copied());
+        hasher.finalize(&mut output);
+        assert_eq!(output, expected, "len {}", len);
+
+        // without an exact size hint the bytes are collected first
+        #[cfg(feature = "alloc")]
+        {
+            let mut hasher = TupleHash::v128(b"");
+            hasher.update_iter(data[..len].iter().copied().filter(|_| true));
+            hasher.finalize(&mut output);
+            assert_eq!(output, expected, "len {}", len);
+        }
+    }
+}
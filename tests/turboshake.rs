@@ -0,0 +1,206 @@
+use tiny_keccak::{bits_to_rate, Hasher, KeccakP, KeccakState, TurboShake, Xof};
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|j| (j % 251) as u8).collect()
+}
+
+fn test_turboshake<M: AsRef<[u8]>>(
+    mut turboshake: TurboShake,
+    message: M,
+    output_len: usize,
+    expected: &[u8],
+) {
+    turboshake.update(message.as_ref());
+    let mut res = vec![0; output_len];
+    turboshake.finalize(&mut res);
+    assert_eq!(&res[output_len - expected.len()..], expected);
+}
+
+#[test]
+fn turboshake128_empty() {
+    let expected = b"\
+        \x1e\x41\x5f\x1c\x59\x83\xaf\xf2\x16\x92\x17\x27\x7d\x17\xbb\x53\
+        \x8c\xd9\x45\xa3\x97\xdd\xec\x54\x1f\x1c\xe4\x1a\xf2\xc1\xb7\x4c\
+    ";
+    test_turboshake(TurboShake::v128(0x1f), "", 32, expected);
+}
+
+#[test]
+fn turboshake128_empty_64() {
+    let expected = b"\
+        \x1e\x41\x5f\x1c\x59\x83\xaf\xf2\x16\x92\x17\x27\x7d\x17\xbb\x53\
+        \x8c\xd9\x45\xa3\x97\xdd\xec\x54\x1f\x1c\xe4\x1a\xf2\xc1\xb7\x4c\
+        \x3e\x8c\xca\xe2\xa4\xda\xe5\x6c\x84\xa0\x4c\x23\x85\xc0\x3c\x15\
+        \xe8\x19\x3b\xdf\x58\x73\x73\x63\x32\x16\x91\xc0\x54\x62\xc8\xdf\
+    ";
+    test_turboshake(TurboShake::v128(0x1f), "", 64, expected);
+}
+
+#[test]
+fn turboshake128_empty_long() {
+    let expected = b"\
+        \xa3\xb9\xb0\x38\x59\x00\xce\x76\x1f\x22\xae\xd5\x48\xe7\x54\xda\
+        \x10\xa5\x24\x2d\x62\xe8\xc6\x58\xe3\xf3\xa9\x23\xa7\x55\x56\x07\
+    ";
+    test_turboshake(TurboShake::v128(0x1f), "", 10032, expected);
+}
+
+#[test]
+fn turboshake128_pattern_17() {
+    let expected = b"\
+        \x9c\x97\xd0\x36\xa3\xba\xc8\x19\xdb\x70\xed\xe0\xca\x55\x4e\xc6\
+        \xe4\xc2\xa1\xa4\xff\xbf\xd9\xec\x26\x9c\xa6\xa1\x11\x16\x12\x33\
+    ";
+    test_turboshake(TurboShake::v128(0x1f), pattern(17), 32, expected);
+}
+
+#[test]
+fn turboshake128_pattern_17_pow_3() {
+    let expected = b"\
+        \xd4\x97\x6e\xb5\x6b\xcf\x11\x85\x20\x58\x2b\x70\x9f\x73\xe1\xd6\
+        \x85\x3e\x00\x1f\xda\xf8\x0e\x1b\x13\xe0\xd0\x59\x9d\x5f\xb3\x72\
+    ";
+    test_turboshake(TurboShake::v128(0x1f), pattern(4913), 32, expected);
+}
+
+#[test]
+fn turboshake128_domain_01() {
+    let expected = b"\
+        \xbf\x32\x3f\x94\x04\x94\xe8\x8e\xe1\xc5\x40\xfe\x66\x0b\xe8\xa0\
+        \xc9\x3f\x43\xd1\x5e\xc0\x06\x99\x84\x62\xfa\x99\x4e\xed\x5d\xab\
+    ";
+    test_turboshake(TurboShake::v128(0x01), [0xff, 0xff, 0xff], 32, expected);
+}
+
+#[test]
+fn turboshake128_domain_06() {
+    let expected = b"\
+        \x8e\xc9\xc6\x64\x65\xed\x0d\x4a\x6c\x35\xd1\x35\x06\x71\x8d\x68\
+        \x7a\x25\xcb\x05\xc7\x4c\xca\x1e\x42\x50\x1a\xbd\x83\x87\x4a\x67\
+    ";
+    test_turboshake(TurboShake::v128(0x06), [0xff], 32, expected);
+}
+
+#[test]
+fn turboshake128_domain_07() {
+    let expected = b"\
+        \xb6\x58\x57\x60\x01\xca\xd9\xb1\xe5\xf3\x99\xa9\xf7\x77\x23\xbb\
+        \xa0\x54\x58\x04\x2d\x68\x20\x6f\x72\x52\x68\x2d\xba\x36\x63\xed\
+    ";
+    test_turboshake(TurboShake::v128(0x07), [0xff, 0xff, 0xff], 32, expected);
+}
+
+#[test]
+fn turboshake128_domain_0b() {
+    let expected = b"\
+        \x8d\xee\xaa\x1a\xec\x47\xcc\xee\x56\x9f\x65\x9c\x21\xdf\xa8\xe1\
+        \x12\xdb\x3c\xee\x37\xb1\x81\x78\xb2\xac\xd8\x05\xb7\x99\xcc\x37\
+    ";
+    test_turboshake(TurboShake::v128(0x0b), [0xff; 7], 32, expected);
+}
+
+#[test]
+fn turboshake128_domain_30() {
+    let expected = b"\
+        \x55\x31\x22\xe2\x13\x5e\x36\x3c\x32\x92\xbe\xd2\xc6\x42\x1f\xa2\
+        \x32\xba\xb0\x3d\xaa\x07\xc7\xd6\x63\x66\x03\x28\x65\x06\x32\x5b\
+    ";
+    test_turboshake(TurboShake::v128(0x30), [0xff], 32, expected);
+}
+
+#[test]
+fn turboshake128_domain_7f() {
+    let expected = b"\
+        \x16\x27\x4c\xc6\x56\xd4\x4c\xef\xd4\x22\x39\x5d\x0f\x90\x53\xbd\
+        \xa6\xd2\x8e\x12\x2a\xba\x15\xc7\x65\xe5\xad\x0e\x6e\xaf\x26\xf9\
+    ";
+    test_turboshake(TurboShake::v128(0x7f), [0xff, 0xff, 0xff], 32, expected);
+}
+
+#[test]
+fn turboshake256_empty() {
+    let expected = b"\
+        \x36\x7a\x32\x9d\xaf\xea\x87\x1c\x78\x02\xec\x67\xf9\x05\xae\x13\
+        \xc5\x76\x95\xdc\x2c\x66\x63\xc6\x10\x35\xf5\x9a\x18\xf8\xe7\xdb\
+        \x11\xed\xc0\xe1\x2e\x91\xea\x60\xeb\x6b\x32\xdf\x06\xdd\x7f\x00\
+        \x2f\xba\xfa\xbb\x6e\x13\xec\x1c\xc2\x0d\x99\x55\x47\x60\x0d\xb0\
+    ";
+    test_turboshake(TurboShake::v256(0x1f), "", 64, expected);
+}
+
+#[test]
+fn turboshake256_empty_long() {
+    let expected = b"\
+        \xab\xef\xa1\x16\x30\xc6\x61\x26\x92\x49\x74\x26\x85\xec\x08\x2f\
+        \x20\x72\x65\xdc\xcf\x2f\x43\x53\x4e\x9c\x61\xba\x0c\x9d\x1d\x75\
+    ";
+    test_turboshake(TurboShake::v256(0x1f), "", 10032, expected);
+}
+
+#[test]
+fn turboshake256_pattern_17() {
+    let expected = b"\
+        \xb3\xba\xb0\x30\x0e\x6a\x19\x1f\xbe\x61\x37\x93\x98\x35\x92\x35\
+        \x78\x79\x4e\xa5\x48\x43\xf5\x01\x10\x90\xfa\x2f\x37\x80\xa9\xe5\
+        \xcb\x22\xc5\x9d\x78\xb4\x0a\x0f\xbf\xf9\xe6\x72\xc0\xfb\xe0\x97\
+        \x0b\xd2\xc8\x45\x09\x1c\x60\x44\xd6\x87\x05\x4d\xa5\xd8\xe9\xc7\
+    ";
+    test_turboshake(TurboShake::v256(0x1f), pattern(17), 64, expected);
+}
+
+#[test]
+fn turboshake256_domain_01() {
+    let expected = b"\
+        \xd2\x1c\x6f\xbb\xf5\x87\xfa\x22\x82\xf2\x9a\xea\x62\x01\x75\xfb\
+        \x02\x57\x41\x3a\xf7\x8a\x0b\x1b\x2a\x87\x41\x9c\xe0\x31\xd9\x33\
+        \xae\x7a\x4d\x38\x33\x27\xa8\xa1\x76\x41\xa3\x4f\x8a\x1d\x10\x03\
+        \xad\x7d\xa6\xb7\x2d\xba\x84\xbb\x62\xfe\xf2\x8f\x62\xf1\x24\x24\
+    ";
+    test_turboshake(TurboShake::v256(0x01), [0xff, 0xff, 0xff], 64, expected);
+}
+
+#[test]
+fn turboshake_squeeze_in_parts() {
+    let mut expected = [0u8; 500];
+    let mut turboshake = TurboShake::v128(TurboShake::DEFAULT_DOMAIN);
+    turboshake.update(b"hello");
+    turboshake.clone().finalize(&mut expected);
+
+    let mut output = [0u8; 500];
+    for chunk in output.chunks_mut(7) {
+        turboshake.squeeze(chunk);
+    }
+    assert_eq!(&expected[..], &output[..]);
+}
+
+#[test]
+#[should_panic(expected = "0x01..=0x7f")]
+fn turboshake_domain_zero() {
+    TurboShake::v128(0x00);
+}
+
+#[test]
+#[should_panic(expected = "0x01..=0x7f")]
+fn turboshake_domain_too_large() {
+    TurboShake::v256(0x80);
+}
+
+#[test]
+fn turboshake_is_a_12_round_sponge() {
+    let message = pattern(300);
+    for &(bits, domain) in &[(128, TurboShake::DEFAULT_DOMAIN), (256, 0x0b)] {
+        let mut state = KeccakState::<KeccakP<12>>::with_rate(bits_to_rate(bits), domain);
+        state.update(&message);
+        let mut expected = [0u8; 200];
+        state.finalize(&mut expected);
+
+        let mut turboshake = match bits {
+            128 => TurboShake::v128(domain),
+            _ => TurboShake::v256(domain),
+        };
+        turboshake.update(&message);
+        let mut output = [0u8; 200];
+        turboshake.squeeze(&mut output);
+        assert_eq!(output, expected, "TurboSHAKE{}", bits);
+    }
+}
@@ -0,0 +1,27 @@
+use tiny_keccak::{Hasher, IntoXof, Keccak, Kmac, Xof};
+
+#[test]
+fn keccak_with_zeroize() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"secret key material");
+    let mut output = [0u8; 32];
+    keccak.clone().finalize(&mut output);
+    drop(keccak);
+
+    let mut keccak = Keccak::v256();
+    keccak.update(b"secret key material");
+    let mut expected = [0u8; 32];
+    keccak.finalize(&mut expected);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn kmac_xof_with_zeroize() {
+    let mut kmac = Kmac::v256(b"key", b"");
+    kmac.update(b"input");
+    let mut xof = kmac.into_xof();
+    let mut output = [0u8; 64];
+    xof.squeeze(&mut output);
+    drop(xof);
+    assert_ne!(output, [0u8; 64]);
+}